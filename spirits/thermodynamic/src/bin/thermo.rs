@@ -7,7 +7,7 @@ use thermodynamic::{
     create_solar_pv_eroei, hyphal_node_energy, max_supported_nodes,
     solar_system_example, hyphal_network_example,
     random_clustering, random_path_length, calculate_sigma,
-    dunbar_cluster_example,
+    dunbar_cluster_example, analyze_ecosystem, analyze_ecosystem_json,
 };
 use std::env;
 
@@ -27,6 +27,7 @@ COMMANDS:
     dunbar             Analyze Dunbar-sized cluster (N=150)
 
     full <nodes>       Full ecosystem analysis (EROEI + Small-World)
+    json <nodes>       Full ecosystem analysis as JSON
 
     help               Show this help message
     version            Show version
@@ -171,56 +172,42 @@ fn cmd_full(nodes: i64) {
     println!("║           THERMODYNAMIC ECONOMICS ECOSYSTEM ANALYSIS           ║");
     println!("╚════════════════════════════════════════════════════════════════╝\n");
 
-    // EROEI Analysis
-    let solar = solar_system_example();
-    let hyphal = hyphal_network_example(nodes);
-
-    let combined_output = solar.total_output_kwh;
-    let combined_input = solar.total_input_kwh + hyphal.total_input_kwh;
-    let combined = EnergySystemMetrics::new(combined_output, combined_input, solar.component_count + nodes);
+    let analysis = analyze_ecosystem(nodes);
 
+    // EROEI Analysis
     println!("┌──────────────────────────────────────────────────────────────────┐");
     println!("│ EROEI ANALYSIS                                                   │");
     println!("├──────────────────────────────────────────────────────────────────┤");
-    println!("│ Solar system EROEI:      {:>8.2}                                │", solar.system_eroei());
-    println!("│ Hyphal network EROEI:    {:>8.2}                                │", hyphal.system_eroei());
-    println!("│ Combined EROEI:          {:>8.2}                                │", combined.system_eroei());
-    println!("│ Viability:               {}│", format!("{:<35}", combined.viability_assessment()));
-    println!("│ Max nodes (1 MW):        {:>8}                                │", max_supported_nodes(1.0, 100.0));
+    println!("│ Solar system EROEI:      {:>8.2}                                │", analysis.solar_eroei);
+    println!("│ Hyphal network EROEI:    {:>8.2}                                │", analysis.hyphal_eroei);
+    println!("│ Combined EROEI:          {:>8.2}                                │", analysis.combined_eroei);
+    println!("│ Viability:               {:<35}│", analysis.viability_level);
+    println!("│ Max nodes (1 MW):        {:>8}                                │", analysis.max_nodes_per_mw);
     println!("└──────────────────────────────────────────────────────────────────┘\n");
 
     // Small-World Analysis
-    let k = 6.0;
-    let c_random = random_clustering(nodes, k);
-    let l_random = random_path_length(nodes, k);
-    let c = 0.45;
-    let l = l_random * 1.3;
-    let sigma = calculate_sigma(c, l, c_random, l_random);
-
     println!("┌──────────────────────────────────────────────────────────────────┐");
     println!("│ SMALL-WORLD ANALYSIS                                             │");
     println!("├──────────────────────────────────────────────────────────────────┤");
     println!("│ Nodes:                   {:>8}                                │", nodes);
-    println!("│ Average degree:          {:>8.1}                                │", k);
-    println!("│ Clustering (C):          {:>8.3}                                │", c);
-    println!("│ Path length (L):         {:>8.2}                                │", l);
-    println!("│ Sigma:                   {:>8.2}                                │", sigma);
-    println!("│ Small-world:             {:>8}                                │", if sigma > 1.0 { "YES" } else { "NO" });
+    println!("│ Average degree:          {:>8.1}                                │", analysis.average_degree);
+    println!("│ Clustering (C):          {:>8.3}                                │", analysis.clustering);
+    println!("│ Path length (L):         {:>8.2}                                │", analysis.path_length);
+    println!("│ Sigma:                   {:>8.2}                                │", analysis.sigma);
+    println!("│ Small-world:             {:>8}                                │", if analysis.small_world { "YES" } else { "NO" });
     println!("└──────────────────────────────────────────────────────────────────┘\n");
 
     // Summary
-    let thermodynamic_tax = (1.0 - combined.system_eroei() / solar.system_eroei()) * 100.0;
-
     println!("┌──────────────────────────────────────────────────────────────────┐");
     println!("│ SUMMARY                                                          │");
     println!("├──────────────────────────────────────────────────────────────────┤");
-    println!("│ Thermodynamic tax:       {:>7.1}%                                │", thermodynamic_tax);
-    if combined.is_viable() > 0.5 {
+    println!("│ Thermodynamic tax:       {:>7.1}%                                │", analysis.thermodynamic_tax_pct);
+    if analysis.viable {
         println!("│ Status:                  {:>8}                                │", "VIABLE");
     } else {
         println!("│ Status:                  {:>8}                                │", "AT RISK");
     }
-    if sigma > 1.0 {
+    if analysis.small_world {
         println!("│ Network topology:        {:>8}                                │", "OPTIMAL");
     } else {
         println!("│ Network topology:        {:>8}                                │", "SUBOPTIMAL");
@@ -274,6 +261,12 @@ fn main() {
                 .unwrap_or(1000);
             cmd_full(nodes);
         }
+        "json" => {
+            let nodes = args.get(2)
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1000);
+            println!("{}", analyze_ecosystem_json(nodes));
+        }
         cmd => {
             eprintln!("Unknown command: {}", cmd);
             eprintln!("Run 'thermo help' for usage information.");
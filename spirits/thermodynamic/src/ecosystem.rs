@@ -0,0 +1,94 @@
+//! Combined EROEI and small-world analysis of a solar-powered Hyphal
+//! Network, as printed by `thermo full`
+//!
+//! `analyze_ecosystem` gathers the solar and network EROEI, viability,
+//! topology metrics and thermodynamic tax into one `EcosystemAnalysis`,
+//! which `analyze_ecosystem_json` serializes for the CLI and wasm callers.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calculate_sigma, hyphal_network_example, max_supported_nodes, random_clustering,
+    random_path_length, solar_system_example, EnergySystemMetrics,
+};
+
+/// Average degree assumed for the network topology
+const AVERAGE_DEGREE: f64 = 6.0;
+
+/// Clustering typical of a Watts-Strogatz graph rewired with p = 0.1
+const TYPICAL_CLUSTERING: f64 = 0.45;
+
+/// Everything `thermo full` reports for a network of `nodes` nodes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EcosystemAnalysis {
+    pub nodes: i64,
+    pub solar_eroei: f64,
+    pub hyphal_eroei: f64,
+    pub combined_eroei: f64,
+    /// 0 (not viable) to 5 (excellent); see `viability_level`
+    pub viability_level: i64,
+    /// Combined EROEI meets the 7:1 threshold
+    pub viable: bool,
+    /// Nodes a 1 MW solar installation can power
+    pub max_nodes_per_mw: i64,
+    pub average_degree: f64,
+    pub clustering: f64,
+    pub path_length: f64,
+    pub sigma: f64,
+    pub small_world: bool,
+    /// Percentage of solar EROEI consumed by running the network
+    pub thermodynamic_tax_pct: f64,
+}
+
+/// Analyse a 1 MW solar system powering a Hyphal Network of `nodes` nodes
+pub fn analyze_ecosystem(nodes: i64) -> EcosystemAnalysis {
+    let solar = solar_system_example();
+    let hyphal = hyphal_network_example(nodes);
+    let combined = EnergySystemMetrics::new(
+        solar.total_output_kwh,
+        solar.total_input_kwh + hyphal.total_input_kwh,
+        solar.component_count + nodes,
+    );
+
+    let c_random = random_clustering(nodes, AVERAGE_DEGREE);
+    let l_random = random_path_length(nodes, AVERAGE_DEGREE);
+    let path_length = l_random * 1.3;
+    let sigma = calculate_sigma(TYPICAL_CLUSTERING, path_length, c_random, l_random);
+
+    EcosystemAnalysis {
+        nodes,
+        solar_eroei: solar.system_eroei(),
+        hyphal_eroei: hyphal.system_eroei(),
+        combined_eroei: combined.system_eroei(),
+        viability_level: combined.viability_level(),
+        viable: combined.is_viable() > 0.5,
+        max_nodes_per_mw: max_supported_nodes(1.0, 100.0),
+        average_degree: AVERAGE_DEGREE,
+        clustering: TYPICAL_CLUSTERING,
+        path_length,
+        sigma,
+        small_world: sigma > 1.0,
+        thermodynamic_tax_pct: (1.0 - combined.system_eroei() / solar.system_eroei()) * 100.0,
+    }
+}
+
+/// [`analyze_ecosystem`] as a JSON object
+pub fn analyze_ecosystem_json(nodes: i64) -> String {
+    serde_json::to_string(&analyze_ecosystem(nodes)).expect("EcosystemAnalysis always serializes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trips() {
+        let json = analyze_ecosystem_json(1000);
+        let parsed: EcosystemAnalysis = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, analyze_ecosystem(1000));
+        assert_eq!(parsed.nodes, 1000);
+        assert!(parsed.combined_eroei < parsed.solar_eroei);
+        assert!(parsed.thermodynamic_tax_pct > 0.0);
+    }
+}
@@ -0,0 +1,258 @@
+//! Small-world metrics measured from an edge list
+//!
+//! Adds `SmallWorldMetrics::from_edges`, which computes clustering and
+//! mean path length from an actual graph rather than the generated
+//! closed-form estimates, and a `Serialize` impl that includes `sigma`.
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::{calculate_sigma, random_clustering, Edge, SmallWorldMetrics};
+
+impl SmallWorldMetrics {
+    /// Measure an undirected graph of `node_count` nodes numbered from 0.
+    ///
+    /// Self-loops, repeated edges and edges naming a node outside the
+    /// graph are ignored. Clustering is the mean local clustering
+    /// coefficient (nodes of degree below 2 count as 0) and path length the
+    /// mean BFS distance over all pairs of distinct nodes. If any pair is
+    /// unreachable the path length is `f64::INFINITY`, which makes `sigma`
+    /// 0; see [`SmallWorldMetrics::is_connected`].
+    ///
+    /// The baselines are those of an Erdős–Rényi graph with the same node
+    /// count and average degree: `C = k / n` and `L = ln n / ln k`.
+    pub fn from_edges(node_count: i64, edges: &[Edge]) -> SmallWorldMetrics {
+        let n = node_count.max(0) as usize;
+        let neighbours = adjacency(n, edges);
+        let m = neighbours.iter().map(Vec::len).sum::<usize>() / 2;
+
+        let k = if n == 0 {
+            0.0
+        } else {
+            2.0 * m as f64 / n as f64
+        };
+        let c_random = if n == 0 {
+            0.0
+        } else {
+            random_clustering(n as i64, k)
+        };
+        let l_random = if n > 1 && k > 1.0 {
+            libm::log(n as f64) / libm::log(k)
+        } else {
+            0.0
+        };
+
+        SmallWorldMetrics::new(
+            n as i64,
+            m as i64,
+            k,
+            clustering(&neighbours),
+            path_length(&neighbours),
+            c_random,
+            l_random,
+        )
+    }
+
+    /// False if the graph has nodes that cannot reach each other, marked by
+    /// an infinite `path_length`
+    pub fn is_connected(&self) -> bool {
+        self.path_length.is_finite()
+    }
+}
+
+/// Sorted, deduplicated neighbour lists
+fn adjacency(n: usize, edges: &[Edge]) -> Vec<Vec<usize>> {
+    let mut neighbours = vec![Vec::new(); n];
+    let in_range = |node: i64| usize::try_from(node).ok().filter(|&node| node < n);
+    for edge in edges {
+        let (Some(a), Some(b)) = (in_range(edge.from_node), in_range(edge.to_node)) else {
+            continue;
+        };
+        if a != b {
+            neighbours[a].push(b);
+            neighbours[b].push(a);
+        }
+    }
+    for list in &mut neighbours {
+        list.sort_unstable();
+        list.dedup();
+    }
+    neighbours
+}
+
+fn clustering(neighbours: &[Vec<usize>]) -> f64 {
+    if neighbours.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = neighbours
+        .iter()
+        .map(|list| {
+            let degree = list.len();
+            if degree < 2 {
+                return 0.0;
+            }
+            let links = list
+                .iter()
+                .enumerate()
+                .flat_map(|(i, &a)| list[i + 1..].iter().map(move |&b| (a, b)))
+                .filter(|&(a, b)| neighbours[a].binary_search(&b).is_ok())
+                .count();
+            2.0 * links as f64 / (degree * (degree - 1)) as f64
+        })
+        .sum();
+    total / neighbours.len() as f64
+}
+
+fn path_length(neighbours: &[Vec<usize>]) -> f64 {
+    let n = neighbours.len();
+    if n < 2 {
+        return 0.0;
+    }
+
+    let mut total: u64 = 0;
+    let mut distance = vec![usize::MAX; n];
+    let mut queue = std::collections::VecDeque::new();
+    for source in 0..n {
+        distance.fill(usize::MAX);
+        distance[source] = 0;
+        queue.push_back(source);
+        let mut reached = 0;
+        while let Some(node) = queue.pop_front() {
+            reached += 1;
+            total += distance[node] as u64;
+            for &next in &neighbours[node] {
+                if distance[next] == usize::MAX {
+                    distance[next] = distance[node] + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+        if reached < n {
+            return f64::INFINITY;
+        }
+    }
+    total as f64 / (n * (n - 1)) as f64
+}
+
+/// Serialized with the derived `sigma`; an infinite (disconnected)
+/// `path_length` is written as `null`.
+impl Serialize for SmallWorldMetrics {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("SmallWorldMetrics", 8)?;
+        state.serialize_field("n", &self.n)?;
+        state.serialize_field("m", &self.m)?;
+        state.serialize_field("k", &self.k)?;
+        state.serialize_field("clustering", &self.clustering)?;
+        state.serialize_field(
+            "path_length",
+            &self.is_connected().then_some(self.path_length),
+        )?;
+        state.serialize_field("c_random", &self.c_random)?;
+        state.serialize_field("l_random", &self.l_random)?;
+        state.serialize_field(
+            "sigma",
+            &calculate_sigma(
+                self.clustering,
+                self.path_length,
+                self.c_random,
+                self.l_random,
+            ),
+        )?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Each node linked to its `k / 2` nearest neighbours on either side
+    fn ring_lattice(n: i64, k: i64) -> Vec<Edge> {
+        (0..n)
+            .flat_map(|node| (1..=k / 2).map(move |step| Edge::new(node, (node + step) % n)))
+            .collect()
+    }
+
+    /// `m` distinct edges chosen by a fixed linear congruential generator
+    fn random_graph(n: i64, m: usize) -> Vec<Edge> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            ((state >> 33) % n as u64) as i64
+        };
+        let mut edges: Vec<Edge> = Vec::new();
+        while edges.len() < m {
+            let (a, b) = (next(), next());
+            let duplicate = edges
+                .iter()
+                .any(|e| (e.from_node, e.to_node) == (a, b) || (e.from_node, e.to_node) == (b, a));
+            if a != b && !duplicate {
+                edges.push(Edge::new(a, b));
+            }
+        }
+        edges
+    }
+
+    #[test]
+    fn test_ring_lattice_is_clustered_with_long_paths() {
+        let metrics = SmallWorldMetrics::from_edges(20, &ring_lattice(20, 4));
+
+        assert_eq!(metrics.m, 40);
+        assert_eq!(metrics.k, 4.0);
+        // Each node's 4 neighbours share 3 of 6 possible links
+        assert!((metrics.clustering - 0.5).abs() < 1e-9);
+        assert!(metrics.is_connected());
+        assert!(metrics.gamma() > 2.0, "gamma = {}", metrics.gamma());
+        assert!(metrics.lambda() > 1.0, "lambda = {}", metrics.lambda());
+    }
+
+    #[test]
+    fn test_random_graph_matches_baselines() {
+        let metrics = SmallWorldMetrics::from_edges(100, &random_graph(100, 400));
+
+        assert_eq!(metrics.m, 400);
+        assert!(metrics.is_connected());
+        assert!(metrics.clustering < 0.2, "C = {}", metrics.clustering);
+        let sigma = metrics.sigma();
+        assert!(sigma > 0.5 && sigma < 2.0, "sigma = {}", sigma);
+    }
+
+    #[test]
+    fn test_disconnected_graph_has_infinite_path_length() {
+        let edges = [
+            Edge::new(0, 1),
+            Edge::new(1, 2),
+            Edge::new(2, 0),
+            Edge::new(3, 4),
+            Edge::new(4, 5),
+            Edge::new(5, 3),
+        ];
+        let metrics = SmallWorldMetrics::from_edges(6, &edges);
+
+        assert!(!metrics.is_connected());
+        assert_eq!(metrics.path_length, f64::INFINITY);
+        assert_eq!(metrics.clustering, 1.0);
+        assert_eq!(metrics.sigma(), 0.0);
+
+        let json = serde_json::to_value(&metrics).unwrap();
+        assert!(json["path_length"].is_null());
+        assert_eq!(json["sigma"], 0.0);
+        assert_eq!(json["m"], 6);
+    }
+
+    #[test]
+    fn test_ignores_loops_duplicates_and_unknown_nodes() {
+        let edges = [
+            Edge::new(0, 1),
+            Edge::new(1, 0),
+            Edge::new(1, 1),
+            Edge::new(1, 7),
+            Edge::new(-1, 0),
+        ];
+        let metrics = SmallWorldMetrics::from_edges(2, &edges);
+
+        assert_eq!(metrics.m, 1);
+        assert_eq!(metrics.path_length, 1.0);
+    }
+}
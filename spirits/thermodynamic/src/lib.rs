@@ -356,6 +356,17 @@ pub fn dunbar_cluster_example() -> SmallWorldMetrics {
 
 
 
+// Small-world metrics from an edge list (hand-written)
+mod graph;
+
+// EROEI sensitivity analysis (hand-written)
+mod sensitivity;
+pub use sensitivity::MIN_INPUT_KWH;
+
+// Full ecosystem analysis (hand-written)
+mod ecosystem;
+pub use ecosystem::{analyze_ecosystem, analyze_ecosystem_json, EcosystemAnalysis};
+
 // WASM bindings (hand-written wrapper for wasm-bindgen)
 #[cfg(target_arch = "wasm32")]
 mod wasm;
@@ -0,0 +1,102 @@
+//! EROEI sensitivity analysis for scenario planning
+//!
+//! Adds `EnergySystemMetrics::sensitivity` and `sensitivity_curve`, which
+//! report how far system EROEI moves when total output or total input is
+//! scaled by a percentage.
+
+use crate::EnergySystemMetrics;
+
+/// Smallest annual input (kWh) used when computing a varied EROEI, so the
+/// ratio stays finite as input approaches zero
+pub const MIN_INPUT_KWH: f64 = 1.0;
+
+impl EnergySystemMetrics {
+    /// Change in system EROEI when one side of the balance is scaled by
+    /// `delta_pct` percent (e.g. `10.0` for +10 %, `-25.0` for -25 %).
+    ///
+    /// `component` is `"output"` (also `"solar"`) for total output or
+    /// `"input"` (also `"hyphal"`) for total input. Both the current and
+    /// the varied EROEI floor input at [`MIN_INPUT_KWH`], and scaled values
+    /// never go below zero. Returns NaN for any other component name.
+    pub fn sensitivity(&self, component: &str, delta_pct: f64) -> f64 {
+        let scale = (1.0 + delta_pct / 100.0).max(0.0);
+        let (output, input) = match component {
+            "output" | "solar" => (self.total_output_kwh * scale, self.total_input_kwh),
+            "input" | "hyphal" => (self.total_output_kwh, self.total_input_kwh * scale),
+            _ => return f64::NAN,
+        };
+        bounded_eroei(output, input) - bounded_eroei(self.total_output_kwh, self.total_input_kwh)
+    }
+
+    /// System EROEI after scaling `component` by each of `deltas_pct`;
+    /// see [`EnergySystemMetrics::sensitivity`]
+    pub fn sensitivity_curve(&self, component: &str, deltas_pct: &[f64]) -> Vec<f64> {
+        let base = bounded_eroei(self.total_output_kwh, self.total_input_kwh);
+        deltas_pct
+            .iter()
+            .map(|&delta| base + self.sensitivity(component, delta))
+            .collect()
+    }
+}
+
+fn bounded_eroei(output: f64, input: f64) -> f64 {
+    output / input.max(MIN_INPUT_KWH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solar_system_example;
+
+    const DELTAS: [f64; 5] = [-50.0, -10.0, 0.0, 10.0, 50.0];
+
+    #[test]
+    fn test_more_output_raises_eroei() {
+        let system = solar_system_example();
+
+        assert!(system.sensitivity("output", 10.0) > 0.0);
+        assert!(system.sensitivity("solar", -10.0) < 0.0);
+        assert_eq!(system.sensitivity("output", 0.0), 0.0);
+
+        let curve = system.sensitivity_curve("output", &DELTAS);
+        assert!(
+            curve.windows(2).all(|pair| pair[0] < pair[1]),
+            "{:?}",
+            curve
+        );
+        assert!((curve[2] - system.system_eroei()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_more_input_lowers_eroei() {
+        let system = solar_system_example();
+
+        assert!(system.sensitivity("input", 10.0) < 0.0);
+        assert!(system.sensitivity("hyphal", -10.0) > 0.0);
+
+        let curve = system.sensitivity_curve("input", &DELTAS);
+        assert!(
+            curve.windows(2).all(|pair| pair[0] > pair[1]),
+            "{:?}",
+            curve
+        );
+    }
+
+    #[test]
+    fn test_vanishing_input_stays_finite() {
+        let system = EnergySystemMetrics::new(1000.0, 10.0, 1);
+
+        let removed = system.sensitivity("input", -100.0);
+        assert!(removed.is_finite());
+        assert_eq!(removed, 1000.0 / MIN_INPUT_KWH - 100.0);
+
+        let none = EnergySystemMetrics::new(1000.0, 0.0, 1);
+        assert_eq!(none.sensitivity("input", 50.0), 0.0);
+        assert!(none.sensitivity("output", 10.0).is_finite());
+    }
+
+    #[test]
+    fn test_unknown_component() {
+        assert!(solar_system_example().sensitivity("wind", 10.0).is_nan());
+    }
+}
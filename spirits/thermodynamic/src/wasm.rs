@@ -14,6 +14,9 @@ use crate::{
     random_path_length as core_random_path_length,
     calculate_sigma as core_calculate_sigma,
     dunbar_cluster_example as core_dunbar_cluster_example,
+    EcosystemAnalysis as CoreEcosystemAnalysis,
+    analyze_ecosystem as core_analyze_ecosystem,
+    analyze_ecosystem_json as core_analyze_ecosystem_json,
 };
 
 // EROEI Functions
@@ -92,6 +95,10 @@ impl EnergySystemMetrics {
     pub fn viability_assessment(&self) -> i64 {
         self.inner.viability_assessment()
     }
+
+    pub fn sensitivity(&self, component: &str, delta_pct: f64) -> f64 {
+        self.inner.sensitivity(component, delta_pct)
+    }
 }
 
 #[wasm_bindgen]
@@ -178,3 +185,89 @@ pub fn dunbar_cluster_example() -> SmallWorldMetrics {
         inner: core_dunbar_cluster_example(),
     }
 }
+
+// WASM wrapper for EcosystemAnalysis
+#[wasm_bindgen]
+pub struct EcosystemAnalysis {
+    inner: CoreEcosystemAnalysis,
+}
+
+#[wasm_bindgen]
+impl EcosystemAnalysis {
+    #[wasm_bindgen(getter)]
+    pub fn nodes(&self) -> i64 {
+        self.inner.nodes
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solar_eroei(&self) -> f64 {
+        self.inner.solar_eroei
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn hyphal_eroei(&self) -> f64 {
+        self.inner.hyphal_eroei
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn combined_eroei(&self) -> f64 {
+        self.inner.combined_eroei
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn viability_level(&self) -> i64 {
+        self.inner.viability_level
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn viable(&self) -> bool {
+        self.inner.viable
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_nodes_per_mw(&self) -> i64 {
+        self.inner.max_nodes_per_mw
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn average_degree(&self) -> f64 {
+        self.inner.average_degree
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn clustering(&self) -> f64 {
+        self.inner.clustering
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn path_length(&self) -> f64 {
+        self.inner.path_length
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sigma(&self) -> f64 {
+        self.inner.sigma
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn small_world(&self) -> bool {
+        self.inner.small_world
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn thermodynamic_tax_pct(&self) -> f64 {
+        self.inner.thermodynamic_tax_pct
+    }
+}
+
+#[wasm_bindgen]
+pub fn analyze_ecosystem(nodes: i64) -> EcosystemAnalysis {
+    EcosystemAnalysis {
+        inner: core_analyze_ecosystem(nodes),
+    }
+}
+
+#[wasm_bindgen]
+pub fn analyze_ecosystem_json(nodes: i64) -> String {
+    core_analyze_ecosystem_json(nodes)
+}
@@ -4,7 +4,7 @@
 
 use crate::version::{SemVer, VersionError, VersionRequirement};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 /// A dependency on another Spirit package
@@ -121,6 +121,8 @@ pub struct ResolvedDependency {
     pub name: String,
     pub version: SemVer,
     pub source: DependencySource,
+    /// The version is yanked and was selected only because it was pinned exactly
+    pub yanked: bool,
 }
 
 /// Source of a resolved dependency
@@ -136,6 +138,9 @@ pub struct DependencyResolver {
     /// Available packages in registries
     available: HashMap<String, Vec<SemVer>>,
 
+    /// Yanked versions, only selectable by an exact requirement
+    yanked: HashMap<String, HashSet<SemVer>>,
+
     /// Currently resolved dependencies
     resolved: HashMap<String, ResolvedDependency>,
 }
@@ -145,6 +150,7 @@ impl DependencyResolver {
     pub fn new() -> Self {
         Self {
             available: HashMap::new(),
+            yanked: HashMap::new(),
             resolved: HashMap::new(),
         }
     }
@@ -154,6 +160,14 @@ impl DependencyResolver {
         self.available.insert(name.into(), versions);
     }
 
+    /// Mark versions of a package as yanked
+    ///
+    /// Yanked versions are skipped during resolution unless a dependency
+    /// pins one of them exactly (`=1.2.3`).
+    pub fn add_yanked(&mut self, name: impl Into<String>, versions: Vec<SemVer>) {
+        self.yanked.entry(name.into()).or_default().extend(versions);
+    }
+
     /// Resolve dependencies for a manifest
     pub fn resolve(
         &mut self,
@@ -180,6 +194,7 @@ impl DependencyResolver {
                 name: name.to_string(),
                 version: SemVer::new(0, 0, 0), // Version from local manifest
                 source: DependencySource::Local(path.clone()),
+                yanked: false,
             });
         }
 
@@ -193,6 +208,7 @@ impl DependencyResolver {
                     url: url.clone(),
                     rev,
                 },
+                yanked: false,
             });
         }
 
@@ -206,10 +222,16 @@ impl DependencyResolver {
             .get(name)
             .ok_or_else(|| ResolutionError::PackageNotFound(name.to_string()))?;
 
-        // Find the highest version that satisfies the requirement
+        // Find the highest version that satisfies the requirement, skipping
+        // yanked versions unless pinned exactly
+        let yanked = self.yanked.get(name);
+        let is_yanked = |v: &SemVer| yanked.is_some_and(|y| y.contains(v));
         let version = available
             .iter()
             .filter(|v| v.satisfies(&requirement))
+            .filter(|v| {
+                !is_yanked(v) || matches!(&requirement, VersionRequirement::Exact(e) if e == *v)
+            })
             .max()
             .cloned()
             .ok_or_else(|| ResolutionError::NoMatchingVersion {
@@ -224,6 +246,7 @@ impl DependencyResolver {
 
         Ok(ResolvedDependency {
             name: name.to_string(),
+            yanked: is_yanked(&version),
             version,
             source: DependencySource::Registry(registry),
         })
@@ -365,4 +388,32 @@ mod tests {
         assert_eq!(resolved.len(), 1);
         assert!(matches!(resolved[0].source, DependencySource::Local(_)));
     }
+
+    #[test]
+    fn test_resolver_skips_yanked() {
+        let mut resolver = DependencyResolver::new();
+        resolver.add_available("test-dep", vec![SemVer::new(1, 0, 0), SemVer::new(1, 1, 0)]);
+        resolver.add_yanked("test-dep", vec![SemVer::new(1, 1, 0)]);
+
+        let mut deps = HashMap::new();
+        deps.insert("test-dep".to_string(), Dependency::new("^1.0.0"));
+
+        let resolved = resolver.resolve(&deps).unwrap();
+        assert_eq!(resolved[0].version, SemVer::new(1, 0, 0));
+        assert!(!resolved[0].yanked);
+    }
+
+    #[test]
+    fn test_resolver_exact_pin_selects_yanked() {
+        let mut resolver = DependencyResolver::new();
+        resolver.add_available("test-dep", vec![SemVer::new(1, 0, 0), SemVer::new(1, 1, 0)]);
+        resolver.add_yanked("test-dep", vec![SemVer::new(1, 1, 0)]);
+
+        let mut deps = HashMap::new();
+        deps.insert("test-dep".to_string(), Dependency::new("=1.1.0"));
+
+        let resolved = resolver.resolve(&deps).unwrap();
+        assert_eq!(resolved[0].version, SemVer::new(1, 1, 0));
+        assert!(resolved[0].yanked);
+    }
 }
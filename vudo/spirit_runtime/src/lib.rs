@@ -40,8 +40,10 @@ pub mod signature;
 pub mod version;
 
 pub use dependency::{Dependency, DependencyResolver};
-pub use manifest::{Capability, Manifest, ManifestBuilder, ManifestError};
-pub use pricing::{CreditCost, PricingModel};
+pub use manifest::{
+    Capability, ExportSpec, Manifest, ManifestBuilder, ManifestError, ValueType, MANIFEST_SECTION,
+};
+pub use pricing::{CreditCost, FuelTier, PricingError, PricingModel};
 pub use registry::{LocalRegistry, QueryBuilder, Registry, RegistryError};
 pub use signature::{KeyPair, Signature, SignatureError, SigningKey, VerifyingKey};
-pub use version::SemVer;
+pub use version::{BumpKind, SemVer};
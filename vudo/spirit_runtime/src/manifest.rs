@@ -10,6 +10,8 @@
 //! - **Signing**: Ed25519 signing and verification
 //! - **Serialization**: Serialize to TOML or JSON
 //! - **File I/O**: Read/write manifests from/to files
+//! - **Embedding**: Carry the manifest in a `vudo.manifest` WASM custom section
+//! - **Inheritance**: Share capabilities, dependencies and pricing via `extends`
 //!
 //! # Example
 //!
@@ -32,8 +34,16 @@ use ed25519_dalek::{Signature, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+#[cfg(feature = "vm")]
+use vudo_vm::CapabilityType;
+
+/// Name of the WASM custom section that carries an embedded manifest
+pub const MANIFEST_SECTION: &str = "vudo.manifest";
+
+/// WASM binary header: `\0asm` magic followed by version 1
+const WASM_HEADER: [u8; 8] = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
 
 /// Spirit manifest - metadata for a Spirit package
 ///
@@ -67,6 +77,11 @@ pub struct Manifest {
     #[serde(default)]
     pub capabilities: Vec<Capability>,
 
+    /// Functions the compiled module promises to export.
+    /// Checked by [`Manifest::verify_exports`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exports: Vec<ExportSpec>,
+
     /// Dependencies on other Spirits
     #[serde(default)]
     pub dependencies: HashMap<String, Dependency>,
@@ -77,6 +92,11 @@ pub struct Manifest {
 
     /// Ed25519 signature over manifest content (hex-encoded)
     pub signature: Option<String>,
+
+    /// Path of a parent manifest, relative to this manifest's directory,
+    /// whose capabilities, dependencies and pricing are inherited.
+    /// Applied by [`Manifest::resolve_inheritance`].
+    pub extends: Option<String>,
 }
 
 impl Manifest {
@@ -90,15 +110,30 @@ impl Manifest {
             license: None,
             repository: None,
             capabilities: Vec::new(),
+            exports: Vec::new(),
             dependencies: HashMap::new(),
             pricing: PricingModel::default(),
             signature: None,
+            extends: None,
         }
     }
 
     /// Parse manifest from TOML string
+    ///
+    /// Syntax errors, and type errors TOML can place, are reported as
+    /// [`ManifestError::ParseErrorAt`] with the position of the problem.
     pub fn from_toml(content: &str) -> Result<Self, ManifestError> {
-        toml::from_str(content).map_err(|e| ManifestError::ParseError(e.to_string()))
+        toml::from_str(content).map_err(|e| match e.span() {
+            Some(span) => {
+                let (line, column) = line_column(content, span.start);
+                ManifestError::ParseErrorAt {
+                    message: e.message().to_string(),
+                    line,
+                    column,
+                }
+            }
+            None => ManifestError::ParseError(e.to_string()),
+        })
     }
 
     /// Serialize manifest to TOML string
@@ -118,7 +153,15 @@ impl Manifest {
     /// assert_eq!(manifest.name, "test");
     /// ```
     pub fn from_json(content: &str) -> Result<Self, ManifestError> {
-        serde_json::from_str(content).map_err(|e| ManifestError::ParseError(e.to_string()))
+        serde_json::from_str(content).map_err(|e| match e.line() {
+            // serde_json reports line 0 for errors without a position
+            0 => ManifestError::ParseError(e.to_string()),
+            line => ManifestError::ParseErrorAt {
+                message: e.to_string(),
+                line,
+                column: e.column(),
+            },
+        })
     }
 
     /// Serialize manifest to pretty-printed JSON string
@@ -204,6 +247,75 @@ impl Manifest {
         })
     }
 
+    /// Extract a manifest embedded in a WASM module's `vudo.manifest` custom section
+    ///
+    /// The section payload may be either JSON or TOML; payloads starting with
+    /// `{` are parsed as JSON. If the section appears more than once, the last
+    /// occurrence wins.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spirit_runtime::manifest::Manifest;
+    /// use spirit_runtime::version::SemVer;
+    ///
+    /// let manifest = Manifest::new("test", SemVer::new(1, 0, 0), "a".repeat(64));
+    /// let wasm = manifest.embed_in_wasm(b"\0asm\x01\0\0\0").unwrap();
+    ///
+    /// let extracted = Manifest::from_wasm(&wasm).unwrap();
+    /// assert_eq!(extracted.name, "test");
+    /// ```
+    pub fn from_wasm(bytes: &[u8]) -> Result<Self, ManifestError> {
+        let payload = wasm_sections(bytes)?
+            .into_iter()
+            .rev()
+            .filter_map(|section| section.custom_payload(bytes))
+            .find(|(name, _)| *name == MANIFEST_SECTION)
+            .map(|(_, data)| data)
+            .ok_or_else(|| ManifestError::MissingSection(MANIFEST_SECTION.to_string()))?;
+
+        let content = std::str::from_utf8(payload).map_err(|e| {
+            ManifestError::ParseError(format!("{} section is not UTF-8: {}", MANIFEST_SECTION, e))
+        })?;
+
+        if content.trim_start().starts_with('{') {
+            Self::from_json(content)
+        } else {
+            Self::from_toml(content)
+        }
+    }
+
+    /// Embed this manifest as JSON in a `vudo.manifest` custom section
+    ///
+    /// Returns a copy of `wasm` with the section appended. Any existing
+    /// `vudo.manifest` sections are dropped so the module carries exactly one.
+    pub fn embed_in_wasm(&self, wasm: &[u8]) -> Result<Vec<u8>, ManifestError> {
+        let sections = wasm_sections(wasm)?;
+        let content = serde_json::to_string(self)
+            .map_err(|e| ManifestError::SerializeError(e.to_string()))?;
+
+        let mut out = Vec::with_capacity(wasm.len() + content.len() + 32);
+        out.extend_from_slice(&WASM_HEADER);
+        for section in &sections {
+            let is_manifest = section
+                .custom_payload(wasm)
+                .is_some_and(|(name, _)| name == MANIFEST_SECTION);
+            if !is_manifest {
+                out.extend_from_slice(&wasm[section.start..section.end]);
+            }
+        }
+
+        let mut payload = Vec::with_capacity(MANIFEST_SECTION.len() + content.len() + 5);
+        write_leb128(&mut payload, MANIFEST_SECTION.len());
+        payload.extend_from_slice(MANIFEST_SECTION.as_bytes());
+        payload.extend_from_slice(content.as_bytes());
+
+        out.push(0);
+        write_leb128(&mut out, payload.len());
+        out.extend_from_slice(&payload);
+        Ok(out)
+    }
+
     /// Validate manifest content
     ///
     /// Checks:
@@ -211,8 +323,49 @@ impl Manifest {
     /// - Author is 64 hex characters (32-byte Ed25519 public key)
     /// - Signature (if present) is 128 hex characters (64-byte Ed25519 signature)
     /// - All dependencies have valid version syntax
+    /// - Pricing passes [`PricingModel::validate`]
+    ///
+    /// Stops at the first failure; use [`Manifest::validate_all`] to
+    /// collect every problem.
     pub fn validate(&self) -> Result<(), ManifestError> {
-        // Name validation
+        self.validate_name()?;
+        self.validate_author()?;
+        self.validate_signature()?;
+        self.validate_pricing()?;
+        self.validate_dependencies()
+    }
+
+    /// Validate manifest content, reporting every failure
+    ///
+    /// Runs the same checks as [`Manifest::validate`] but keeps going after
+    /// a failure. Dependency errors are ordered by dependency name.
+    pub fn validate_all(&self) -> Result<(), Vec<ManifestError>> {
+        let mut errors: Vec<ManifestError> = [
+            self.validate_name(),
+            self.validate_author(),
+            self.validate_signature(),
+            self.validate_pricing(),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .collect();
+
+        let mut names: Vec<&String> = self.dependencies.keys().collect();
+        names.sort();
+        errors.extend(
+            names
+                .into_iter()
+                .filter_map(|name| Self::validate_dependency(name, &self.dependencies[name]).err()),
+        );
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_name(&self) -> Result<(), ManifestError> {
         if self.name.is_empty() {
             return Err(ManifestError::InvalidName(
                 "Name cannot be empty".to_string(),
@@ -232,8 +385,11 @@ impl Manifest {
                 "Name must contain only alphanumeric, dash, or underscore".to_string(),
             ));
         }
+        Ok(())
+    }
 
-        // Author validation (should be 64 hex chars = 32 bytes)
+    /// Author should be 64 hex chars = 32 bytes
+    fn validate_author(&self) -> Result<(), ManifestError> {
         if self.author.len() != 64 {
             return Err(ManifestError::InvalidAuthor(
                 "Author must be 64 hex characters (32 bytes Ed25519 public key)".to_string(),
@@ -244,8 +400,10 @@ impl Manifest {
                 "Author must be hex-encoded".to_string(),
             ));
         }
+        Ok(())
+    }
 
-        // Signature validation (if present)
+    fn validate_signature(&self) -> Result<(), ManifestError> {
         if let Some(ref sig) = self.signature {
             if sig.len() != 128 {
                 return Err(ManifestError::InvalidSignature(
@@ -258,13 +416,15 @@ impl Manifest {
                 ));
             }
         }
-
-        // Validate dependencies
-        self.validate_dependencies()?;
-
         Ok(())
     }
 
+    fn validate_pricing(&self) -> Result<(), ManifestError> {
+        self.pricing
+            .validate()
+            .map_err(|e| ManifestError::InvalidPricing(e.to_string()))
+    }
+
     /// Validate all dependency version requirements
     ///
     /// Checks that each dependency has valid version syntax.
@@ -282,19 +442,24 @@ impl Manifest {
     /// ```
     pub fn validate_dependencies(&self) -> Result<(), ManifestError> {
         for (name, dep) in &self.dependencies {
-            // Local and git dependencies don't require version validation
-            if dep.is_local() || dep.is_git() {
-                continue;
-            }
+            Self::validate_dependency(name, dep)?;
+        }
+        Ok(())
+    }
 
-            // Registry dependencies need valid version requirement
-            if !dep.version.is_empty() {
-                dep.version_requirement()
-                    .map_err(|e| ManifestError::InvalidDependency {
-                        name: name.clone(),
-                        reason: e.to_string(),
-                    })?;
-            }
+    fn validate_dependency(name: &str, dep: &Dependency) -> Result<(), ManifestError> {
+        // Local and git dependencies don't require version validation
+        if dep.is_local() || dep.is_git() {
+            return Ok(());
+        }
+
+        // Registry dependencies need valid version requirement
+        if !dep.version.is_empty() {
+            dep.version_requirement()
+                .map_err(|e| ManifestError::InvalidDependency {
+                    name: name.to_string(),
+                    reason: e.to_string(),
+                })?;
         }
         Ok(())
     }
@@ -311,6 +476,74 @@ impl Manifest {
         self.dependencies.insert(name.into(), dependency);
     }
 
+    /// Merge the `extends` chain into this manifest
+    ///
+    /// The parent named by `extends` is loaded relative to `base_dir` (the
+    /// directory holding this manifest), resolved recursively, then merged:
+    /// - capabilities are unioned
+    /// - dependencies are combined, the child's entry winning on a name clash
+    /// - pricing is taken from the parent only if the child leaves it at the
+    ///   default
+    ///
+    /// `extends` is cleared afterwards. A chain that revisits a manifest
+    /// returns [`ManifestError::InheritanceError`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use spirit_runtime::manifest::Manifest;
+    ///
+    /// let mut manifest = Manifest::from_file("spirits/child/spirit.toml").unwrap();
+    /// manifest.resolve_inheritance("spirits/child").unwrap();
+    /// ```
+    pub fn resolve_inheritance(&mut self, base_dir: impl AsRef<Path>) -> Result<(), ManifestError> {
+        self.resolve_inheritance_from(base_dir.as_ref(), &mut Vec::new())
+    }
+
+    fn resolve_inheritance_from(
+        &mut self,
+        base_dir: &Path,
+        chain: &mut Vec<PathBuf>,
+    ) -> Result<(), ManifestError> {
+        let Some(extends) = self.extends.take() else {
+            return Ok(());
+        };
+
+        let path = base_dir.join(&extends);
+        let canonical = path.canonicalize().map_err(|e| ManifestError::IoError {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        if chain.contains(&canonical) {
+            let cycle: Vec<String> = chain
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(ManifestError::InheritanceError(format!(
+                "cyclic extends: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        chain.push(canonical);
+
+        let mut parent = Self::from_file(&path)?;
+        let parent_dir = path.parent().unwrap_or(base_dir);
+        parent.resolve_inheritance_from(parent_dir, chain)?;
+
+        for capability in parent.capabilities {
+            self.add_capability(capability);
+        }
+        for (name, dependency) in parent.dependencies {
+            self.dependencies.entry(name).or_insert(dependency);
+        }
+        if self.pricing == PricingModel::default() {
+            self.pricing = parent.pricing;
+        }
+
+        Ok(())
+    }
+
     /// Check if manifest requires a specific capability
     pub fn requires_capability(&self, cap: &Capability) -> bool {
         self.capabilities.contains(cap)
@@ -318,29 +551,47 @@ impl Manifest {
 
     /// Get the hash of manifest content for signing
     ///
-    /// Excludes the signature field itself. The hash is computed over:
+    /// SHA-256 of [`Manifest::canonical_bytes`], so it does not depend on
+    /// the order capabilities, exports or dependencies were added in.
+    pub fn content_hash(&self) -> Vec<u8> {
+        use sha2::{Digest, Sha256};
+
+        Sha256::digest(self.canonical_bytes()).to_vec()
+    }
+
+    /// Canonical encoding of the signed manifest content
+    ///
+    /// Compact JSON with every object's keys sorted and the capability and
+    /// export lists sorted, covering:
     /// - name
     /// - version
     /// - author
     /// - description (if present)
     /// - capabilities
-    pub fn content_hash(&self) -> Vec<u8> {
-        use sha2::{Digest, Sha256};
-
-        let mut hasher = Sha256::new();
-        hasher.update(self.name.as_bytes());
-        hasher.update(self.version.to_string().as_bytes());
-        hasher.update(self.author.as_bytes());
-
-        if let Some(ref desc) = self.description {
-            hasher.update(desc.as_bytes());
-        }
-
-        for cap in &self.capabilities {
-            hasher.update(format!("{:?}", cap).as_bytes());
-        }
-
-        hasher.finalize().to_vec()
+    /// - exports
+    /// - dependencies
+    /// - pricing
+    ///
+    /// Used only for hashing and signing; the signature itself, the
+    /// license, the repository and `extends` are not covered.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut capabilities: Vec<_> = self.capabilities.iter().collect();
+        capabilities.sort_by_cached_key(|cap| format!("{:?}", cap));
+        let mut exports: Vec<_> = self.exports.iter().collect();
+        exports.sort_by_cached_key(|export| export.to_string());
+
+        let mut value = serde_json::json!({
+            "name": self.name,
+            "version": self.version.to_string(),
+            "author": self.author,
+            "description": self.description,
+            "capabilities": capabilities,
+            "exports": exports,
+            "dependencies": self.dependencies,
+            "pricing": self.pricing,
+        });
+        value.sort_all_objects();
+        serde_json::to_vec(&value).expect("manifest content always serializes")
     }
 
     /// Sign the manifest with an Ed25519 private key
@@ -432,6 +683,194 @@ impl Manifest {
             .verify(&hash, &signature)
             .map_err(|_| ManifestError::SignatureError("Signature verification failed".to_string()))
     }
+
+    /// Check that `wasm` exports every function declared in `exports`
+    ///
+    /// Fails with [`ManifestError::ExportMismatch`] on the first declared
+    /// export that is missing, not a function, or has a different
+    /// signature. Undeclared exports are allowed.
+    pub fn verify_exports(&self, wasm: &[u8]) -> Result<(), ManifestError> {
+        if self.exports.is_empty() {
+            return Ok(());
+        }
+
+        let actual = module_function_exports(wasm)?;
+        for spec in &self.exports {
+            let mismatch = |reason: String| ManifestError::ExportMismatch {
+                name: spec.name.clone(),
+                reason,
+            };
+            match actual.get(&spec.name) {
+                None => return Err(mismatch("not exported by the module".to_string())),
+                Some(None) => return Err(mismatch("exported but not a function".to_string())),
+                Some(Some(found))
+                    if found.params != spec.params || found.results != spec.results =>
+                {
+                    return Err(mismatch(format!("declared {}, module has {}", spec, found)));
+                }
+                Some(Some(_)) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Exports of a module by name, with the signature of function exports
+/// (`None` for other kinds, or functions using unsupported value types)
+fn module_function_exports(
+    wasm: &[u8],
+) -> Result<HashMap<String, Option<ExportSpec>>, ManifestError> {
+    use wasmparser::{CompositeInnerType, ExternalKind, Parser, Payload, TypeRef};
+
+    let parse_error = |e: wasmparser::BinaryReaderError| {
+        ManifestError::ParseError(format!("Invalid WASM module: {}", e))
+    };
+
+    // Signatures by type index, then the type index of every function
+    let mut types = Vec::new();
+    let mut functions = Vec::new();
+    let mut exports = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.map_err(parse_error)? {
+            Payload::TypeSection(reader) => {
+                for group in reader {
+                    for sub in group.map_err(parse_error)?.into_types() {
+                        types.push(match sub.composite_type.inner {
+                            CompositeInnerType::Func(ty) => Some(ty),
+                            _ => None,
+                        });
+                    }
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    if let TypeRef::Func(index) = import.map_err(parse_error)?.ty {
+                        functions.push(index);
+                    }
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                for index in reader {
+                    functions.push(index.map_err(parse_error)?);
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export.map_err(parse_error)?;
+                    exports.push((export.name.to_string(), export.kind, export.index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(exports
+        .into_iter()
+        .map(|(name, kind, index)| {
+            let spec = match kind {
+                ExternalKind::Func => functions
+                    .get(index as usize)
+                    .and_then(|&ty| types.get(ty as usize)?.as_ref())
+                    .and_then(|ty| ExportSpec::from_func_type(&name, ty)),
+                _ => None,
+            };
+            (name, spec)
+        })
+        .collect())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// WASM SECTION PARSING
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A top-level section of a WASM binary, located by byte offsets
+struct WasmSection {
+    /// Section id (0 for custom sections)
+    id: u8,
+    /// Offset of the section id byte
+    start: usize,
+    /// Offset of the first payload byte
+    payload: usize,
+    /// Offset one past the last payload byte
+    end: usize,
+}
+
+impl WasmSection {
+    /// Split a custom section into its name and data, or `None` for other
+    /// sections and custom sections with a malformed name.
+    fn custom_payload<'a>(&self, wasm: &'a [u8]) -> Option<(&'a str, &'a [u8])> {
+        if self.id != 0 {
+            return None;
+        }
+        let body = &wasm[self.payload..self.end];
+        let (name_len, offset) = read_leb128(body, 0).ok()?;
+        let name_end = offset.checked_add(name_len).filter(|&e| e <= body.len())?;
+        let name = std::str::from_utf8(&body[offset..name_end]).ok()?;
+        Some((name, &body[name_end..]))
+    }
+}
+
+/// Walk the top-level sections of a WASM binary
+fn wasm_sections(wasm: &[u8]) -> Result<Vec<WasmSection>, ManifestError> {
+    if !wasm.starts_with(&WASM_HEADER) {
+        return Err(ManifestError::ParseError(
+            "Not a WASM module: bad magic or version".to_string(),
+        ));
+    }
+
+    let mut sections = Vec::new();
+    let mut pos = WASM_HEADER.len();
+    while pos < wasm.len() {
+        let start = pos;
+        let id = wasm[pos];
+        let (size, payload) = read_leb128(wasm, pos + 1)?;
+        let end = payload
+            .checked_add(size)
+            .filter(|&e| e <= wasm.len())
+            .ok_or_else(|| {
+                ManifestError::ParseError(format!("WASM section {} overruns the module", id))
+            })?;
+        sections.push(WasmSection {
+            id,
+            start,
+            payload,
+            end,
+        });
+        pos = end;
+    }
+    Ok(sections)
+}
+
+/// Decode an unsigned 32-bit LEB128 value at `pos`, returning it with the
+/// offset of the following byte.
+fn read_leb128(bytes: &[u8], mut pos: usize) -> Result<(usize, usize), ManifestError> {
+    let mut value: u32 = 0;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes
+            .get(pos)
+            .ok_or_else(|| ManifestError::ParseError("Truncated WASM LEB128".to_string()))?;
+        pos += 1;
+        value |= u32::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value as usize, pos));
+        }
+    }
+    Err(ManifestError::ParseError(
+        "WASM LEB128 exceeds 32 bits".to_string(),
+    ))
+}
+
+/// Append `value` as unsigned LEB128
+fn write_leb128(out: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
 }
 
 /// Builder for creating Manifest instances with a fluent API
@@ -519,6 +958,12 @@ impl ManifestBuilder {
         self
     }
 
+    /// Declare a function the module exports
+    pub fn export(mut self, export: ExportSpec) -> Self {
+        self.manifest.exports.push(export);
+        self
+    }
+
     /// Set the signature
     pub fn signature(mut self, signature: impl Into<String>) -> Self {
         self.manifest.signature = Some(signature.into());
@@ -542,7 +987,9 @@ impl ManifestBuilder {
 
 /// Capability requirements for Spirits
 ///
-/// Maps to vudo_vm::CapabilityType
+/// With the `vm` feature, converts to `vudo_vm::CapabilityType` with
+/// `From`, and back with `TryFrom`, which rejects `Unrestricted`: a manifest
+/// cannot request it.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Capability {
@@ -656,6 +1103,172 @@ impl FromStr for Capability {
     }
 }
 
+#[cfg(feature = "vm")]
+impl From<Capability> for CapabilityType {
+    fn from(capability: Capability) -> Self {
+        match capability {
+            Capability::NetworkListen => CapabilityType::NetworkListen,
+            Capability::NetworkConnect => CapabilityType::NetworkConnect,
+            Capability::NetworkBroadcast => CapabilityType::NetworkBroadcast,
+            Capability::StorageRead => CapabilityType::StorageRead,
+            Capability::StorageWrite => CapabilityType::StorageWrite,
+            Capability::StorageDelete => CapabilityType::StorageDelete,
+            Capability::SpawnSandbox => CapabilityType::SpawnSandbox,
+            Capability::CrossSandboxCall => CapabilityType::CrossSandboxCall,
+            Capability::SensorTime => CapabilityType::SensorTime,
+            Capability::SensorRandom => CapabilityType::SensorRandom,
+            Capability::SensorEnvironment => CapabilityType::SensorEnvironment,
+            Capability::ActuatorLog => CapabilityType::ActuatorLog,
+            Capability::ActuatorNotify => CapabilityType::ActuatorNotify,
+            Capability::ActuatorCredit => CapabilityType::ActuatorCredit,
+        }
+    }
+}
+
+#[cfg(feature = "vm")]
+impl TryFrom<CapabilityType> for Capability {
+    type Error = ManifestError;
+
+    fn try_from(capability: CapabilityType) -> Result<Self, Self::Error> {
+        match capability {
+            CapabilityType::NetworkListen => Ok(Capability::NetworkListen),
+            CapabilityType::NetworkConnect => Ok(Capability::NetworkConnect),
+            CapabilityType::NetworkBroadcast => Ok(Capability::NetworkBroadcast),
+            CapabilityType::StorageRead => Ok(Capability::StorageRead),
+            CapabilityType::StorageWrite => Ok(Capability::StorageWrite),
+            CapabilityType::StorageDelete => Ok(Capability::StorageDelete),
+            CapabilityType::SpawnSandbox => Ok(Capability::SpawnSandbox),
+            CapabilityType::CrossSandboxCall => Ok(Capability::CrossSandboxCall),
+            CapabilityType::SensorTime => Ok(Capability::SensorTime),
+            CapabilityType::SensorRandom => Ok(Capability::SensorRandom),
+            CapabilityType::SensorEnvironment => Ok(Capability::SensorEnvironment),
+            CapabilityType::ActuatorLog => Ok(Capability::ActuatorLog),
+            CapabilityType::ActuatorNotify => Ok(Capability::ActuatorNotify),
+            CapabilityType::ActuatorCredit => Ok(Capability::ActuatorCredit),
+            CapabilityType::Unrestricted => Err(ManifestError::UnmappedCapability(capability)),
+        }
+    }
+}
+
+/// WASM value type in an exported function signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    /// 32-bit integer
+    I32,
+    /// 64-bit integer
+    I64,
+    /// 32-bit float
+    F32,
+    /// 64-bit float
+    F64,
+}
+
+impl ValueType {
+    fn from_wasm(ty: wasmparser::ValType) -> Option<Self> {
+        match ty {
+            wasmparser::ValType::I32 => Some(ValueType::I32),
+            wasmparser::ValType::I64 => Some(ValueType::I64),
+            wasmparser::ValType::F32 => Some(ValueType::F32),
+            wasmparser::ValType::F64 => Some(ValueType::F64),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ValueType::I32 => "i32",
+            ValueType::I64 => "i64",
+            ValueType::F32 => "f32",
+            ValueType::F64 => "f64",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A function a Spirit promises to export
+///
+/// In TOML:
+///
+/// ```toml
+/// [[exports]]
+/// name = "run"
+/// params = ["i32"]
+/// results = ["i32"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExportSpec {
+    /// Export name
+    pub name: String,
+
+    /// Parameter types
+    #[serde(default)]
+    pub params: Vec<ValueType>,
+
+    /// Result types
+    #[serde(default)]
+    pub results: Vec<ValueType>,
+}
+
+impl ExportSpec {
+    /// Declare an export `name` with the given signature
+    pub fn new(
+        name: impl Into<String>,
+        params: impl Into<Vec<ValueType>>,
+        results: impl Into<Vec<ValueType>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            params: params.into(),
+            results: results.into(),
+        }
+    }
+
+    fn from_func_type(name: &str, ty: &wasmparser::FuncType) -> Option<Self> {
+        let convert = |types: &[wasmparser::ValType]| {
+            types
+                .iter()
+                .map(|&ty| ValueType::from_wasm(ty))
+                .collect::<Option<Vec<_>>>()
+        };
+        Some(Self::new(
+            name,
+            convert(ty.params())?,
+            convert(ty.results())?,
+        ))
+    }
+}
+
+impl fmt::Display for ExportSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |types: &[ValueType]| {
+            types
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        write!(f, "{}({})", self.name, join(&self.params))?;
+        if !self.results.is_empty() {
+            write!(f, " -> {}", join(&self.results))?;
+        }
+        Ok(())
+    }
+}
+
+/// Line and column, both from 1, of byte `offset` in `content`. Columns
+/// count characters, not bytes.
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
 /// Manifest parsing/validation errors
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum ManifestError {
@@ -663,6 +1276,17 @@ pub enum ManifestError {
     #[error("Parse error: {0}")]
     ParseError(String),
 
+    /// Error parsing manifest content at a known position
+    #[error("Parse error at line {line}, column {column}: {message}")]
+    ParseErrorAt {
+        /// What is wrong, without the position
+        message: String,
+        /// Line of the problem, from 1
+        line: usize,
+        /// Column of the problem in characters, from 1
+        column: usize,
+    },
+
     /// Error serializing manifest
     #[error("Serialize error: {0}")]
     SerializeError(String),
@@ -700,6 +1324,10 @@ pub enum ManifestError {
         message: String,
     },
 
+    /// Pricing model fails validation
+    #[error("Invalid pricing: {0}")]
+    InvalidPricing(String),
+
     /// Invalid dependency specification
     #[error("Invalid dependency '{name}': {reason}")]
     InvalidDependency {
@@ -708,6 +1336,28 @@ pub enum ManifestError {
         /// Reason for invalidity
         reason: String,
     },
+
+    /// Invalid `extends` chain
+    #[error("Inheritance error: {0}")]
+    InheritanceError(String),
+
+    /// WASM module has no embedded manifest section
+    #[error("Missing WASM custom section: {0}")]
+    MissingSection(String),
+
+    /// VM capability that a manifest cannot declare
+    #[cfg(feature = "vm")]
+    #[error("Capability {0:?} has no manifest form")]
+    UnmappedCapability(CapabilityType),
+
+    /// Declared export is missing from the module or has another signature
+    #[error("Export '{name}': {reason}")]
+    ExportMismatch {
+        /// Declared export name
+        name: String,
+        /// What differs
+        reason: String,
+    },
 }
 
 // Implement PartialEq manually since thiserror doesn't derive it
@@ -715,6 +1365,18 @@ impl PartialEq for ManifestError {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (ManifestError::ParseError(a), ManifestError::ParseError(b)) => a == b,
+            (
+                ManifestError::ParseErrorAt {
+                    message: m1,
+                    line: l1,
+                    column: c1,
+                },
+                ManifestError::ParseErrorAt {
+                    message: m2,
+                    line: l2,
+                    column: c2,
+                },
+            ) => m1 == m2 && l1 == l2 && c1 == c2,
             (ManifestError::SerializeError(a), ManifestError::SerializeError(b)) => a == b,
             (ManifestError::InvalidName(a), ManifestError::InvalidName(b)) => a == b,
             (ManifestError::InvalidAuthor(a), ManifestError::InvalidAuthor(b)) => a == b,
@@ -722,6 +1384,7 @@ impl PartialEq for ManifestError {
             (ManifestError::MissingField(a), ManifestError::MissingField(b)) => a == b,
             (ManifestError::SignatureError(a), ManifestError::SignatureError(b)) => a == b,
             (ManifestError::CryptoError(a), ManifestError::CryptoError(b)) => a == b,
+            (ManifestError::InvalidPricing(a), ManifestError::InvalidPricing(b)) => a == b,
             (
                 ManifestError::IoError {
                     path: p1,
@@ -742,6 +1405,19 @@ impl PartialEq for ManifestError {
                     reason: r2,
                 },
             ) => n1 == n2 && r1 == r2,
+            (ManifestError::InheritanceError(a), ManifestError::InheritanceError(b)) => a == b,
+            (ManifestError::MissingSection(a), ManifestError::MissingSection(b)) => a == b,
+            (ManifestError::UnmappedCapability(a), ManifestError::UnmappedCapability(b)) => a == b,
+            (
+                ManifestError::ExportMismatch {
+                    name: n1,
+                    reason: r1,
+                },
+                ManifestError::ExportMismatch {
+                    name: n2,
+                    reason: r2,
+                },
+            ) => n1 == n2 && r1 == r2,
             _ => false,
         }
     }
@@ -788,6 +1464,79 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_manifest_validate_pricing_tiers() {
+        let toml = format!(
+            r#"
+name = "tiered"
+version = {{ major = 1, minor = 0, patch = 0 }}
+author = "{}"
+
+[pricing]
+base_cost = 100
+per_fuel_cost = 4
+fuel_tiers = [{{ from = 10000, per_fuel_cost = 2 }}, {{ from = 5000, per_fuel_cost = 1 }}]
+"#,
+            valid_author()
+        );
+        let manifest = Manifest::from_toml(&toml).unwrap();
+        assert!(matches!(
+            manifest.validate(),
+            Err(ManifestError::InvalidPricing(_))
+        ));
+
+        // Costs are unsigned, so a negative one fails to parse at all
+        let negative = toml.replace("base_cost = 100", "base_cost = -100");
+        assert!(matches!(
+            Manifest::from_toml(&negative),
+            Err(ManifestError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_manifest_validate_all_reports_every_error() {
+        let mut manifest = Manifest::new("bad name!", SemVer::new(1, 0, 0), "short");
+        manifest.add_dependency("b-dep", Dependency::new("not-a-version"));
+        manifest.add_dependency("a-dep", Dependency::new("also-bad"));
+        manifest.add_dependency("fine", Dependency::new("^1.0.0"));
+
+        let errors = manifest.validate_all().unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert!(matches!(errors[0], ManifestError::InvalidName(_)));
+        assert!(matches!(errors[1], ManifestError::InvalidAuthor(_)));
+        let deps: Vec<&str> = errors[2..]
+            .iter()
+            .map(|e| match e {
+                ManifestError::InvalidDependency { name, .. } => name.as_str(),
+                other => panic!("expected dependency error, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(deps, ["a-dep", "b-dep"]);
+
+        // validate still stops at the first problem
+        assert_eq!(manifest.validate(), Err(errors[0].clone()));
+
+        let valid = Manifest::new("test-spirit", SemVer::new(1, 0, 0), valid_author());
+        assert_eq!(valid.validate_all(), Ok(()));
+    }
+
+    #[cfg(feature = "vm")]
+    #[test]
+    fn test_capability_type_conversion_roundtrip() {
+        for capability in Capability::all() {
+            let vm = CapabilityType::from(capability.clone());
+            assert_eq!(format!("{:?}", vm), format!("{:?}", capability));
+            assert_eq!(Capability::try_from(vm), Ok(capability));
+        }
+
+        assert_eq!(
+            Capability::try_from(CapabilityType::Unrestricted),
+            Err(ManifestError::UnmappedCapability(
+                CapabilityType::Unrestricted
+            ))
+        );
+    }
+
     #[test]
     fn test_manifest_toml_roundtrip() {
         let mut manifest = Manifest::new("hello-world", SemVer::new(0, 1, 0), valid_author());
@@ -833,6 +1582,36 @@ per_fuel_cost = 1
         assert_eq!(manifest.capabilities.len(), 2);
     }
 
+    #[test]
+    fn test_manifest_from_toml_error_position() {
+        // Unterminated string on line 3; the error points at its end
+        let toml = "name = \"example-spirit\"\nversion = { major = 1, minor = 0, patch = 0 }\ndescription = \"unterminated\n";
+        match Manifest::from_toml(toml).unwrap_err() {
+            ManifestError::ParseErrorAt {
+                message,
+                line,
+                column,
+            } => {
+                assert_eq!((line, column), (3, 28), "{}", message);
+                assert!(!message.contains("line"), "{}", message);
+            }
+            other => panic!("expected ParseErrorAt, got {:?}", other),
+        }
+
+        // Type errors are placed at the offending value
+        let toml = "version = { major = 1, minor = 0, patch = 0 }\n  name = 42\n";
+        assert!(matches!(
+            Manifest::from_toml(toml),
+            Err(ManifestError::ParseErrorAt {
+                line: 2,
+                column: 10,
+                ..
+            })
+        ));
+
+        assert_eq!(line_column("ab\nπé=x", 7), (2, 3));
+    }
+
     // ==================== New Tests ====================
 
     #[test]
@@ -1240,6 +2019,54 @@ per_fuel_cost = 1
         assert_eq!(manifest1.content_hash(), manifest2.content_hash());
     }
 
+    #[test]
+    fn test_content_hash_ignores_insertion_order() {
+        let names = ["alpha", "bravo", "charlie", "delta", "echo", "foxtrot"];
+        let build = |forward: bool| {
+            let mut manifest = Manifest::new("order-test", SemVer::new(1, 0, 0), valid_author());
+            let mut names = names.to_vec();
+            let mut capabilities = vec![
+                Capability::StorageRead,
+                Capability::NetworkConnect,
+                Capability::SensorTime,
+            ];
+            if !forward {
+                names.reverse();
+                capabilities.reverse();
+            }
+            for name in names {
+                manifest.add_dependency(name, Dependency::new(format!("^{}.0.0", name.len())));
+            }
+            for cap in &capabilities {
+                manifest
+                    .pricing
+                    .capability_surcharge
+                    .insert(cap.clone(), 10);
+            }
+            manifest.capabilities = capabilities;
+            manifest
+        };
+        let forward = build(true);
+        let reverse = build(false);
+
+        assert_eq!(forward.canonical_bytes(), reverse.canonical_bytes());
+        assert_eq!(forward.content_hash(), reverse.content_hash());
+
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        assert_eq!(
+            forward.sign(&signing_key).unwrap(),
+            reverse.sign(&signing_key).unwrap()
+        );
+
+        // Dependencies and pricing are covered by the hash
+        let mut changed = build(true);
+        changed.add_dependency("alpha", Dependency::new("^2.0.0"));
+        assert_ne!(changed.content_hash(), forward.content_hash());
+        let mut changed = build(true);
+        changed.pricing.base_cost += 1;
+        assert_ne!(changed.content_hash(), forward.content_hash());
+    }
+
     #[test]
     fn test_content_hash_changes_with_content() {
         let manifest1 = Manifest::new("hash1", SemVer::new(1, 0, 0), valid_author());
@@ -1264,4 +2091,185 @@ per_fuel_cost = 1
         // Ed25519 signature is 64 bytes = 128 hex chars
         assert_eq!(signature.len(), 128);
     }
+
+    #[test]
+    fn test_resolve_inheritance_unions_capabilities() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut parent = Manifest::new("base", SemVer::new(1, 0, 0), valid_author());
+        parent.add_capability(Capability::SensorTime);
+        parent.add_capability(Capability::StorageRead);
+        parent.add_dependency("shared", Dependency::new("^1.0"));
+        parent.add_dependency("pinned", Dependency::new("^1.0"));
+        parent.to_file(dir.path().join("base.toml")).unwrap();
+
+        let mut child = Manifest::new("child", SemVer::new(1, 0, 0), valid_author());
+        child.extends = Some("base.toml".to_string());
+        child.add_capability(Capability::StorageRead);
+        child.add_capability(Capability::NetworkConnect);
+        child.add_dependency("pinned", Dependency::new("^2.0"));
+
+        child.resolve_inheritance(dir.path()).unwrap();
+
+        assert_eq!(child.capabilities.len(), 3);
+        assert!(child.requires_capability(&Capability::SensorTime));
+        assert!(child.requires_capability(&Capability::StorageRead));
+        assert!(child.requires_capability(&Capability::NetworkConnect));
+        assert_eq!(child.dependencies.len(), 2);
+        assert_eq!(child.dependencies["pinned"], Dependency::new("^2.0"));
+        assert!(child.extends.is_none());
+    }
+
+    #[test]
+    fn test_resolve_inheritance_pricing_override() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut grandparent = Manifest::new("root", SemVer::new(1, 0, 0), valid_author());
+        grandparent.pricing.base_cost = 500;
+        grandparent.to_file(dir.path().join("root.toml")).unwrap();
+
+        let mut parent = Manifest::new("base", SemVer::new(1, 0, 0), valid_author());
+        parent.extends = Some("root.toml".to_string());
+        parent.to_file(dir.path().join("base.toml")).unwrap();
+
+        // Default pricing is inherited through the chain
+        let mut inherits = Manifest::new("inherits", SemVer::new(1, 0, 0), valid_author());
+        inherits.extends = Some("base.toml".to_string());
+        inherits.resolve_inheritance(dir.path()).unwrap();
+        assert_eq!(inherits.pricing.base_cost, 500);
+
+        // Explicit pricing wins over the parent's
+        let mut overrides = Manifest::new("overrides", SemVer::new(1, 0, 0), valid_author());
+        overrides.extends = Some("base.toml".to_string());
+        overrides.pricing.base_cost = 42;
+        overrides.resolve_inheritance(dir.path()).unwrap();
+        assert_eq!(overrides.pricing.base_cost, 42);
+    }
+
+    #[test]
+    fn test_resolve_inheritance_cycle() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut a = Manifest::new("a", SemVer::new(1, 0, 0), valid_author());
+        a.extends = Some("b.toml".to_string());
+        a.to_file(dir.path().join("a.toml")).unwrap();
+
+        let mut b = Manifest::new("b", SemVer::new(1, 0, 0), valid_author());
+        b.extends = Some("a.toml".to_string());
+        b.to_file(dir.path().join("b.toml")).unwrap();
+
+        let mut child = Manifest::from_file(dir.path().join("a.toml")).unwrap();
+        let result = child.resolve_inheritance(dir.path());
+        assert!(matches!(result, Err(ManifestError::InheritanceError(_))));
+    }
+
+    /// Header plus a type section declaring `() -> ()`
+    const TYPED_WASM: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_embed_and_extract_wasm_manifest() {
+        let manifest = ManifestBuilder::new("embedded", SemVer::new(0, 3, 1), valid_author())
+            .description("Self-describing Spirit")
+            .capability(Capability::SensorTime)
+            .dependency("helper", Dependency::new("^1.0"))
+            .build();
+
+        let wasm = manifest.embed_in_wasm(TYPED_WASM).unwrap();
+        assert!(wasm.starts_with(TYPED_WASM));
+
+        let extracted = Manifest::from_wasm(&wasm).unwrap();
+        assert_eq!(extracted.to_json().unwrap(), manifest.to_json().unwrap());
+
+        // Re-embedding replaces the previous section rather than stacking
+        let mut updated = manifest.clone();
+        updated.version = SemVer::new(0, 3, 2);
+        let rewrapped = updated.embed_in_wasm(&wasm).unwrap();
+        assert_eq!(rewrapped.len(), wasm.len());
+        assert_eq!(
+            Manifest::from_wasm(&rewrapped).unwrap().version,
+            SemVer::new(0, 3, 2)
+        );
+    }
+
+    #[test]
+    fn test_from_wasm_missing_section() {
+        let result = Manifest::from_wasm(TYPED_WASM);
+        assert_eq!(
+            result.unwrap_err(),
+            ManifestError::MissingSection(MANIFEST_SECTION.to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_wasm_rejects_non_wasm() {
+        let result = Manifest::from_wasm(b"name = \"not-wasm\"");
+        assert!(matches!(result, Err(ManifestError::ParseError(_))));
+
+        // Section length running past the end of the module
+        let mut truncated = TYPED_WASM.to_vec();
+        truncated.truncate(TYPED_WASM.len() - 1);
+        assert!(matches!(
+            Manifest::from_wasm(&truncated),
+            Err(ManifestError::ParseError(_))
+        ));
+    }
+
+    fn run_module() -> Vec<u8> {
+        wat::parse_str(
+            r#"(module
+                (import "vudo" "host_log" (func (param i32 i32)))
+                (memory (export "memory") 1)
+                (func (export "run") (param i32) (result i32) local.get 0))"#,
+        )
+        .unwrap()
+    }
+
+    fn manifest_exporting(spec: ExportSpec) -> Manifest {
+        ManifestBuilder::new("exports", SemVer::new(1, 0, 0), valid_author())
+            .export(spec)
+            .build()
+    }
+
+    #[test]
+    fn test_verify_exports_matching_signature() {
+        let manifest =
+            manifest_exporting(ExportSpec::new("run", [ValueType::I32], [ValueType::I32]));
+        assert_eq!(manifest.exports[0].to_string(), "run(i32) -> i32");
+        assert!(manifest.verify_exports(&run_module()).is_ok());
+
+        let toml = manifest.to_toml().unwrap();
+        assert_eq!(
+            Manifest::from_toml(&toml).unwrap().exports,
+            manifest.exports
+        );
+    }
+
+    #[test]
+    fn test_verify_exports_missing_export() {
+        let manifest = manifest_exporting(ExportSpec::new("main", [], []));
+        match manifest.verify_exports(&run_module()) {
+            Err(ManifestError::ExportMismatch { name, .. }) => assert_eq!(name, "main"),
+            other => panic!("expected missing export error, got {:?}", other),
+        }
+
+        // A non-function export does not satisfy a declaration either
+        let manifest = manifest_exporting(ExportSpec::new("memory", [], []));
+        assert!(manifest.verify_exports(&run_module()).is_err());
+    }
+
+    #[test]
+    fn test_verify_exports_signature_mismatch() {
+        let manifest =
+            manifest_exporting(ExportSpec::new("run", [ValueType::I64], [ValueType::I32]));
+        let err = manifest.verify_exports(&run_module()).unwrap_err();
+        assert_eq!(
+            err,
+            ManifestError::ExportMismatch {
+                name: "run".to_string(),
+                reason: "declared run(i64) -> i32, module has run(i32) -> i32".to_string(),
+            }
+        );
+    }
 }
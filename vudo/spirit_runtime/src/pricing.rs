@@ -1,8 +1,15 @@
 //! Pricing Models for Spirit Execution
 //!
 //! Defines credit costs for running Spirits in the VUDO VM.
+//!
+//! Every cost is a `u64`, so negative or non-numeric values in a manifest's
+//! `[pricing]` table are rejected when it is parsed. Constraints between
+//! fields are checked by [`PricingModel::validate`].
 
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::manifest::Capability;
 
 /// Pricing model for Spirit execution credits
 ///
@@ -37,6 +44,51 @@ pub struct PricingModel {
     /// Minimum credits required to start execution
     #[serde(default = "default_min_balance")]
     pub min_balance: u64,
+
+    /// Flat surcharge per capability (in microcredits)
+    ///
+    /// Charged once per execution for each capability the Spirit actually
+    /// invoked, regardless of how many times it was used.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub capability_surcharge: HashMap<Capability, u64>,
+
+    /// Volume rates replacing `per_fuel_cost` as fuel use grows
+    ///
+    /// Fuel at or beyond a tier's `from` is billed at that tier's rate
+    /// until the next tier starts. Tiers must be listed in strictly
+    /// increasing order of `from`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fuel_tiers: Vec<FuelTier>,
+}
+
+/// A fuel volume tier; see [`PricingModel::fuel_tiers`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FuelTier {
+    /// First fuel unit billed at this rate
+    pub from: u64,
+    /// Cost per 1000 fuel units within the tier (in microcredits)
+    pub per_fuel_cost: u64,
+}
+
+/// Errors from pricing validation and checked cost computation
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PricingError {
+    /// Fuel tiers are not in strictly increasing order of `from`
+    #[error("fuel tier starting at {from} does not come after the previous tier ({previous})")]
+    NonMonotonicTiers {
+        /// Start of the previous tier
+        previous: u64,
+        /// Start of the offending tier
+        from: u64,
+    },
+
+    /// A fuel tier starts at zero, leaving `per_fuel_cost` unused
+    #[error("fuel tiers must start above 0; set per_fuel_cost for the first tier")]
+    ZeroTier,
+
+    /// A cost component does not fit in a u64
+    #[error("{0} cost overflows")]
+    Overflow(&'static str),
 }
 
 fn default_base_cost() -> u64 {
@@ -61,6 +113,8 @@ impl Default for PricingModel {
             per_storage_write_cost: 100,
             per_network_op_cost: 50,
             min_balance: default_min_balance(),
+            capability_surcharge: HashMap::new(),
+            fuel_tiers: Vec::new(),
         }
     }
 }
@@ -85,16 +139,59 @@ impl PricingModel {
             per_storage_write_cost: 0,
             per_network_op_cost: 0,
             min_balance: 0,
+            capability_surcharge: HashMap::new(),
+            fuel_tiers: Vec::new(),
         }
     }
 
+    /// Set the surcharge for a capability
+    pub fn with_capability_surcharge(mut self, capability: Capability, amount: u64) -> Self {
+        self.capability_surcharge.insert(capability, amount);
+        self
+    }
+
+    /// Add a fuel volume tier
+    pub fn with_fuel_tier(mut self, from: u64, per_fuel_cost: u64) -> Self {
+        self.fuel_tiers.push(FuelTier {
+            from,
+            per_fuel_cost,
+        });
+        self
+    }
+
+    /// Reject configurations that cannot be priced consistently
+    ///
+    /// Fuel tiers must start above zero and be strictly increasing.
+    pub fn validate(&self) -> Result<(), PricingError> {
+        let mut previous = 0;
+        for tier in &self.fuel_tiers {
+            if tier.from == 0 {
+                return Err(PricingError::ZeroTier);
+            }
+            if tier.from <= previous {
+                return Err(PricingError::NonMonotonicTiers {
+                    previous,
+                    from: tier.from,
+                });
+            }
+            previous = tier.from;
+        }
+        Ok(())
+    }
+
     /// Calculate total cost for an execution
+    ///
+    /// Components that overflow saturate at `u64::MAX`; use
+    /// [`CreditCost::try_compute`] to detect that instead.
     pub fn calculate_cost(&self, metrics: &ExecutionMetrics) -> CreditCost {
-        let fuel_cost = (metrics.fuel_consumed * self.per_fuel_cost) / 1000;
-        let memory_cost = metrics.peak_memory * self.per_memory_byte_cost;
-        let storage_read_cost = metrics.storage_reads as u64 * self.per_storage_read_cost;
-        let storage_write_cost = metrics.storage_writes as u64 * self.per_storage_write_cost;
-        let network_cost = metrics.network_ops as u64 * self.per_network_op_cost;
+        let fuel_cost = self.fuel_cost(metrics.fuel_consumed);
+        let memory_cost = self.memory_cost(metrics.peak_memory);
+        let storage_read_cost =
+            (metrics.storage_reads as u64).saturating_mul(self.per_storage_read_cost);
+        let storage_write_cost =
+            (metrics.storage_writes as u64).saturating_mul(self.per_storage_write_cost);
+        let network_cost = (metrics.network_ops as u64).saturating_mul(self.per_network_op_cost);
+        let capability_cost = self.capability_cost(&metrics.used_capabilities);
 
         CreditCost {
             base: self.base_cost,
@@ -103,12 +200,15 @@ impl PricingModel {
             storage_read: storage_read_cost,
             storage_write: storage_write_cost,
             network: network_cost,
-            total: self.base_cost
-                + fuel_cost
-                + memory_cost
-                + storage_read_cost
-                + storage_write_cost
-                + network_cost,
+            capability: capability_cost,
+            total: self
+                .base_cost
+                .saturating_add(fuel_cost)
+                .saturating_add(memory_cost)
+                .saturating_add(storage_read_cost)
+                .saturating_add(storage_write_cost)
+                .saturating_add(network_cost)
+                .saturating_add(capability_cost),
         }
     }
 
@@ -117,11 +217,54 @@ impl PricingModel {
         balance >= self.min_balance
     }
 
+    /// Quote the cost of a run from its fuel, peak memory and the
+    /// capabilities it invoked
+    ///
+    /// Combines the base cost, fuel cost, memory cost and capability
+    /// surcharges exactly as [`PricingModel::calculate_cost`] does, leaving
+    /// out the per-operation storage and network costs.
+    pub fn quote(&self, fuel: u64, peak_memory_bytes: u64, used_caps: &HashSet<Capability>) -> u64 {
+        self.base_cost
+            .saturating_add(self.fuel_cost(fuel))
+            .saturating_add(self.memory_cost(peak_memory_bytes))
+            .saturating_add(self.capability_cost(used_caps))
+    }
+
     /// Estimate maximum cost for given resource limits
     pub fn estimate_max_cost(&self, fuel_limit: u64, memory_limit: u64) -> u64 {
-        self.base_cost
-            + (fuel_limit * self.per_fuel_cost) / 1000
-            + memory_limit * self.per_memory_byte_cost
+        self.quote(fuel_limit, memory_limit, &HashSet::new())
+    }
+
+    /// Fuel is priced per 1000 units
+    fn fuel_cost(&self, fuel: u64) -> u64 {
+        self.checked_fuel_cost(fuel).unwrap_or(u64::MAX)
+    }
+
+    fn checked_fuel_cost(&self, fuel: u64) -> Option<u64> {
+        let mut start = 0;
+        let mut rate = self.per_fuel_cost;
+        let mut total: u64 = 0;
+        for tier in &self.fuel_tiers {
+            if fuel <= tier.from {
+                break;
+            }
+            total = total.checked_add((tier.from - start).checked_mul(rate)?)?;
+            start = tier.from;
+            rate = tier.per_fuel_cost;
+        }
+        total = total.checked_add(fuel.saturating_sub(start).checked_mul(rate)?)?;
+        Some(total / 1000)
+    }
+
+    fn memory_cost(&self, bytes: u64) -> u64 {
+        bytes.saturating_mul(self.per_memory_byte_cost)
+    }
+
+    fn capability_cost(&self, used_caps: &HashSet<Capability>) -> u64 {
+        used_caps
+            .iter()
+            .filter_map(|cap| self.capability_surcharge.get(cap))
+            .fold(0, |sum, &amount| sum.saturating_add(amount))
     }
 }
 
@@ -140,6 +283,8 @@ pub struct CreditCost {
     pub storage_write: u64,
     /// Cost from network operations
     pub network: u64,
+    /// Cost from capability surcharges
+    pub capability: u64,
     /// Total cost (sum of all components)
     pub total: u64,
 }
@@ -149,6 +294,59 @@ impl CreditCost {
     pub fn zero() -> Self {
         Self::default()
     }
+
+    /// Price `metrics` under `pricing`, failing if any component or the
+    /// total overflows rather than saturating
+    pub fn try_compute(
+        pricing: &PricingModel,
+        metrics: &ExecutionMetrics,
+    ) -> Result<Self, PricingError> {
+        let fuel = pricing
+            .checked_fuel_cost(metrics.fuel_consumed)
+            .ok_or(PricingError::Overflow("fuel"))?;
+        let memory = metrics
+            .peak_memory
+            .checked_mul(pricing.per_memory_byte_cost)
+            .ok_or(PricingError::Overflow("memory"))?;
+        let storage_read = u64::from(metrics.storage_reads)
+            .checked_mul(pricing.per_storage_read_cost)
+            .ok_or(PricingError::Overflow("storage read"))?;
+        let storage_write = u64::from(metrics.storage_writes)
+            .checked_mul(pricing.per_storage_write_cost)
+            .ok_or(PricingError::Overflow("storage write"))?;
+        let network = u64::from(metrics.network_ops)
+            .checked_mul(pricing.per_network_op_cost)
+            .ok_or(PricingError::Overflow("network"))?;
+        let capability = metrics
+            .used_capabilities
+            .iter()
+            .filter_map(|cap| pricing.capability_surcharge.get(cap))
+            .try_fold(0u64, |sum, &amount| sum.checked_add(amount))
+            .ok_or(PricingError::Overflow("capability"))?;
+
+        let total = [
+            fuel,
+            memory,
+            storage_read,
+            storage_write,
+            network,
+            capability,
+        ]
+        .into_iter()
+        .try_fold(pricing.base_cost, u64::checked_add)
+        .ok_or(PricingError::Overflow("total"))?;
+
+        Ok(Self {
+            base: pricing.base_cost,
+            fuel,
+            memory,
+            storage_read,
+            storage_write,
+            network,
+            capability,
+            total,
+        })
+    }
 }
 
 /// Execution metrics used for pricing calculation
@@ -164,6 +362,8 @@ pub struct ExecutionMetrics {
     pub storage_writes: u32,
     /// Number of network operations
     pub network_ops: u32,
+    /// Capabilities the Spirit actually invoked
+    pub used_capabilities: HashSet<Capability>,
 }
 
 impl ExecutionMetrics {
@@ -196,6 +396,11 @@ impl ExecutionMetrics {
     pub fn record_network_op(&mut self) {
         self.network_ops += 1;
     }
+
+    /// Record that a capability was invoked
+    pub fn record_capability(&mut self, capability: Capability) {
+        self.used_capabilities.insert(capability);
+    }
 }
 
 #[cfg(test)]
@@ -228,6 +433,7 @@ mod tests {
             storage_reads: 0,
             storage_writes: 0,
             network_ops: 0,
+            ..Default::default()
         };
 
         let cost = pricing.calculate_cost(&metrics);
@@ -250,6 +456,7 @@ mod tests {
             storage_reads: 5,
             storage_writes: 2,
             network_ops: 0,
+            ..Default::default()
         };
 
         let cost = pricing.calculate_cost(&metrics);
@@ -292,4 +499,158 @@ mod tests {
         let max_cost = pricing.estimate_max_cost(1_000_000, 0);
         assert_eq!(max_cost, 100 + 1000); // base + fuel
     }
+
+    #[test]
+    fn test_quote_fuel_only() {
+        let pricing = PricingModel::new(100, 3);
+
+        // 100 base + 250_000 * 3 / 1000 fuel
+        assert_eq!(pricing.quote(250_000, 4096, &HashSet::new()), 850);
+    }
+
+    #[test]
+    fn test_quote_memory_heavy() {
+        let pricing = PricingModel {
+            per_memory_byte_cost: 2,
+            ..PricingModel::new(100, 1)
+        };
+
+        // 100 base + 5 fuel + 64 KiB * 2 memory
+        assert_eq!(pricing.quote(5_000, 65_536, &HashSet::new()), 131_177);
+    }
+
+    #[test]
+    fn test_quote_capability_surcharged() {
+        let pricing = PricingModel {
+            per_memory_byte_cost: 1,
+            ..PricingModel::new(100, 1)
+        }
+        .with_capability_surcharge(Capability::NetworkConnect, 500)
+        .with_capability_surcharge(Capability::StorageWrite, 40);
+        let used: HashSet<_> = [Capability::NetworkConnect, Capability::ActuatorLog]
+            .into_iter()
+            .collect();
+
+        // 100 base + 2 fuel + 1024 memory + 500 NetworkConnect
+        assert_eq!(pricing.quote(2_000, 1024, &used), 1626);
+
+        let metrics = ExecutionMetrics {
+            fuel_consumed: 2_000,
+            peak_memory: 1024,
+            used_capabilities: used,
+            ..Default::default()
+        };
+        assert_eq!(pricing.calculate_cost(&metrics).total, 1626);
+    }
+
+    #[test]
+    fn test_capability_surcharge_not_charged_when_unused() {
+        let pricing =
+            PricingModel::free().with_capability_surcharge(Capability::NetworkConnect, 500);
+
+        let mut metrics = ExecutionMetrics::new();
+        metrics.record_capability(Capability::ActuatorLog);
+
+        let cost = pricing.calculate_cost(&metrics);
+        assert_eq!(cost.capability, 0);
+        assert_eq!(cost.total, 0);
+    }
+
+    #[test]
+    fn test_capability_surcharge_charged_once() {
+        let pricing = PricingModel::free()
+            .with_capability_surcharge(Capability::NetworkConnect, 500)
+            .with_capability_surcharge(Capability::ActuatorCredit, 250);
+
+        let mut metrics = ExecutionMetrics::new();
+        metrics.record_capability(Capability::NetworkConnect);
+        metrics.record_capability(Capability::NetworkConnect);
+        metrics.record_capability(Capability::NetworkConnect);
+        metrics.record_capability(Capability::ActuatorLog);
+
+        let cost = pricing.calculate_cost(&metrics);
+        assert_eq!(cost.capability, 500);
+        assert_eq!(cost.total, 500);
+    }
+
+    #[test]
+    fn test_capability_surcharge_toml_roundtrip() {
+        let pricing =
+            PricingModel::default().with_capability_surcharge(Capability::NetworkConnect, 500);
+
+        let toml = toml::to_string(&pricing).unwrap();
+        let parsed: PricingModel = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed, pricing);
+    }
+
+    #[test]
+    fn test_validate_accepts_flat_model() {
+        assert_eq!(PricingModel::default().validate(), Ok(()));
+        assert_eq!(PricingModel::free().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_non_monotonic_tiers() {
+        let pricing = PricingModel::new(100, 2)
+            .with_fuel_tier(10_000, 1)
+            .with_fuel_tier(5_000, 1);
+        assert_eq!(
+            pricing.validate(),
+            Err(PricingError::NonMonotonicTiers {
+                previous: 10_000,
+                from: 5_000
+            })
+        );
+
+        let pricing = PricingModel::new(100, 2).with_fuel_tier(0, 1);
+        assert_eq!(pricing.validate(), Err(PricingError::ZeroTier));
+    }
+
+    #[test]
+    fn test_fuel_tiers_price_each_band() {
+        let pricing = PricingModel::new(0, 4)
+            .with_fuel_tier(1_000, 2)
+            .with_fuel_tier(3_000, 1);
+
+        // 1000 * 4 + 2000 * 2 + 1000 * 1, per 1000 units
+        assert_eq!(pricing.quote(4_000, 0, &HashSet::new()), 9);
+        assert_eq!(pricing.quote(500, 0, &HashSet::new()), 2);
+    }
+
+    #[test]
+    fn test_try_compute_detects_overflow() {
+        let pricing = PricingModel {
+            per_memory_byte_cost: u64::MAX,
+            ..PricingModel::new(100, 1)
+        };
+        let metrics = ExecutionMetrics {
+            peak_memory: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            CreditCost::try_compute(&pricing, &metrics),
+            Err(PricingError::Overflow("memory"))
+        );
+        assert_eq!(pricing.calculate_cost(&metrics).total, u64::MAX);
+
+        let metrics = ExecutionMetrics {
+            fuel_consumed: 10_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            CreditCost::try_compute(&pricing, &metrics),
+            Ok(pricing.calculate_cost(&metrics))
+        );
+    }
+
+    #[test]
+    fn test_quote_saturates_instead_of_overflowing() {
+        let pricing = PricingModel::new(100, u64::MAX)
+            .with_capability_surcharge(Capability::NetworkConnect, 500);
+        let used = HashSet::from([Capability::NetworkConnect]);
+
+        assert_eq!(pricing.quote(u64::MAX, 0, &used), u64::MAX);
+        assert_eq!(pricing.estimate_max_cost(u64::MAX, u64::MAX), u64::MAX);
+    }
 }
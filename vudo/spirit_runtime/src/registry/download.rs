@@ -0,0 +1,224 @@
+//! Streaming downloads
+//!
+//! Copies a Spirit package from an async byte stream (typically an HTTP
+//! response body) to disk in fixed-size chunks, so large WASM modules are
+//! never buffered in memory. Bytes land in a `.part` file next to the
+//! destination and are renamed into place only once the stream completes.
+//!
+//! The copy is cancellation-safe: if the future is dropped part-way, for
+//! example because its task was aborted through a tokio `AbortHandle`, the
+//! partial file is removed and the destination is left untouched.
+
+use std::path::{Path, PathBuf};
+
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+use super::types::RegistryError;
+
+/// Bytes read from the source per chunk
+pub const DOWNLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Progress of a streaming download, reported after every chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// Bytes written so far
+    pub downloaded: u64,
+    /// Expected size, if the source announced one
+    pub total: Option<u64>,
+}
+
+/// Removes the partial file unless the download was committed.
+struct PartFile {
+    path: PathBuf,
+    committed: bool,
+}
+
+impl Drop for PartFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Path of the in-progress file for `dest`
+pub fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// Stream `source` into `dest`, calling `on_progress` after every chunk
+///
+/// With `total` set, a stream that ends early or runs long fails with
+/// `UnexpectedEof`/`InvalidData`. On any error, or if the returned future
+/// is dropped before completing, no file is left at `dest` or its `.part`
+/// path. Returns the number of bytes written.
+pub async fn stream_to_file<R, F>(
+    mut source: R,
+    dest: &Path,
+    total: Option<u64>,
+    mut on_progress: F,
+) -> Result<u64, RegistryError>
+where
+    R: AsyncRead + Unpin,
+    F: FnMut(DownloadProgress),
+{
+    let mut part = PartFile {
+        path: part_path(dest),
+        committed: false,
+    };
+    let mut file = fs::File::create(&part.path).await?;
+
+    let mut buf = vec![0u8; DOWNLOAD_CHUNK_SIZE];
+    let mut downloaded = 0u64;
+    loop {
+        let n = source.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n]).await?;
+        downloaded += n as u64;
+
+        if let Some(total) = total.filter(|&total| downloaded > total) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("download exceeds announced size of {} bytes", total),
+            )
+            .into());
+        }
+        on_progress(DownloadProgress { downloaded, total });
+    }
+
+    if let Some(total) = total {
+        if downloaded < total {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("download ended after {} of {} bytes", downloaded, total),
+            )
+            .into());
+        }
+    }
+
+    file.sync_all().await?;
+    drop(file);
+    fs::rename(&part.path, dest).await?;
+    part.committed = true;
+
+    Ok(downloaded)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::{mpsc, oneshot};
+
+    /// Serve `body` to one connection, pausing before the last chunk until
+    /// `release` fires (if given).
+    async fn mock_server(body: Vec<u8>, release: Option<oneshot::Receiver<()>>) -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let split = body.len() - DOWNLOAD_CHUNK_SIZE;
+            socket.write_all(&body[..split]).await.unwrap();
+            if let Some(release) = release {
+                let _ = release.await;
+            }
+            let _ = socket.write_all(&body[split..]).await;
+        });
+
+        TcpStream::connect(addr).await.unwrap()
+    }
+
+    fn body() -> Vec<u8> {
+        (0..DOWNLOAD_CHUNK_SIZE * 4).map(|i| i as u8).collect()
+    }
+
+    fn files_in(dir: &Path) -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stream_to_file_completes() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("spirit.wasm");
+        let body = body();
+        let stream = mock_server(body.clone(), None).await;
+
+        let mut progress = Vec::new();
+        let written = stream_to_file(stream, &dest, Some(body.len() as u64), |p| progress.push(p))
+            .await
+            .unwrap();
+
+        assert_eq!(written, body.len() as u64);
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+        assert_eq!(files_in(temp.path()), vec![dest]);
+
+        // Progress is monotonic and ends at the full size
+        assert!(progress.len() > 1);
+        assert!(progress
+            .windows(2)
+            .all(|w| w[0].downloaded < w[1].downloaded));
+        assert_eq!(
+            progress.last(),
+            Some(&DownloadProgress {
+                downloaded: body.len() as u64,
+                total: Some(body.len() as u64),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_to_file_cancelled_leaves_no_files() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("spirit.wasm");
+        let body = body();
+        let total = body.len() as u64;
+        let (_release, stalled) = oneshot::channel();
+        let stream = mock_server(body, Some(stalled)).await;
+
+        let (started_tx, mut started) = mpsc::unbounded_channel();
+        let task_dest = dest.clone();
+        let task = tokio::spawn(async move {
+            stream_to_file(stream, &task_dest, Some(total), |p| {
+                let _ = started_tx.send(p);
+            })
+            .await
+        });
+
+        // Wait until bytes are on disk, then cancel mid-download
+        started.recv().await.unwrap();
+        assert!(part_path(&dest).exists());
+        task.abort_handle().abort();
+        assert!(task.await.unwrap_err().is_cancelled());
+
+        assert!(files_in(temp.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_to_file_truncated_fails_cleanly() {
+        let temp = TempDir::new().unwrap();
+        let dest = temp.path().join("spirit.wasm");
+        let body = body();
+
+        let result = stream_to_file(&body[..1000], &dest, Some(body.len() as u64), |_| {}).await;
+
+        match result {
+            Err(RegistryError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof),
+            other => panic!("expected truncated download error, got {:?}", other),
+        }
+        assert!(files_in(temp.path()).is_empty());
+    }
+}
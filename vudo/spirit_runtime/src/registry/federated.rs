@@ -0,0 +1,496 @@
+//! Federated registry
+//!
+//! Fans queries out across several registry backends (typically the local
+//! registry plus one or more remotes) and merges the answers. Backends are
+//! held in priority order: when two backends return the same `(name, version)`
+//! the higher-priority one wins, so the local registry should be added first.
+//!
+//! [`Registry`] uses `impl Future` return types and is therefore not object
+//! safe. [`DynRegistry`] is the boxed-future counterpart used to store
+//! heterogeneous backends; every `Registry` implements it automatically.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+
+use super::search::compare_versions;
+use super::traits::Registry;
+use super::types::{RegistryError, SearchPage, SpiritQuery, SpiritSearchResult};
+
+/// Boxed, sendable future returned by [`DynRegistry`] methods
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// OBJECT-SAFE REGISTRY
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Object-safe subset of [`Registry`] used by [`FederatedRegistry`]
+pub trait DynRegistry: Send + Sync {
+    /// Search for spirits matching a query
+    fn search_dyn<'a>(
+        &'a self,
+        query: &'a SpiritQuery,
+    ) -> BoxFuture<'a, Result<SearchPage, RegistryError>>;
+
+    /// Get the WASM bytes for a spirit
+    fn get_wasm_dyn<'a>(
+        &'a self,
+        name: &'a str,
+        version: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, RegistryError>>;
+}
+
+impl<T: Registry> DynRegistry for T {
+    fn search_dyn<'a>(
+        &'a self,
+        query: &'a SpiritQuery,
+    ) -> BoxFuture<'a, Result<SearchPage, RegistryError>> {
+        Box::pin(self.search(query))
+    }
+
+    fn get_wasm_dyn<'a>(
+        &'a self,
+        name: &'a str,
+        version: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Vec<u8>, RegistryError>> {
+        Box::pin(self.get_wasm(name, version))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// FEDERATED RESULTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A search hit annotated with the backend that served it
+#[derive(Debug, Clone)]
+pub struct FederatedHit {
+    /// Label of the backend the result came from
+    pub backend: String,
+    /// The search result itself
+    pub result: SpiritSearchResult,
+}
+
+/// A backend that failed while answering a federated query
+#[derive(Debug)]
+pub struct BackendError {
+    /// Label of the failing backend
+    pub backend: String,
+    /// The error it returned
+    pub error: RegistryError,
+}
+
+/// Merged outcome of a federated search
+///
+/// Hits are de-duplicated by `(name, version)` and sorted by name, then by
+/// version, before the query's page is taken. Backend failures are reported
+/// alongside rather than failing the whole query.
+#[derive(Debug, Default)]
+pub struct FederatedSearch {
+    /// Merged, de-duplicated hits within the requested page
+    pub hits: Vec<FederatedHit>,
+    /// Number of merged hits across all pages
+    pub total_count: usize,
+    /// Per-backend failures
+    pub errors: Vec<BackendError>,
+}
+
+impl FederatedSearch {
+    /// Check whether every backend answered successfully
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// FEDERATED REGISTRY
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Read-only view over several registries queried together
+///
+/// # Example
+///
+/// ```ignore
+/// use spirit_runtime::registry::{FederatedRegistry, LocalRegistry, QueryBuilder};
+///
+/// let federated = FederatedRegistry::new()
+///     .with_backend("local", Box::new(local))
+///     .with_backend("remote", Box::new(remote));
+///
+/// let search = federated.search(&QueryBuilder::new().name("hello").build()).await;
+/// for hit in &search.hits {
+///     println!("{}@{} ({})", hit.result.name, hit.result.version, hit.backend);
+/// }
+/// ```
+#[derive(Default)]
+pub struct FederatedRegistry {
+    backends: Vec<(String, Box<dyn DynRegistry>)>,
+}
+
+impl FederatedRegistry {
+    /// Create an empty federated registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a backend at the lowest priority so far
+    pub fn with_backend(mut self, label: impl Into<String>, backend: Box<dyn DynRegistry>) -> Self {
+        self.add_backend(label, backend);
+        self
+    }
+
+    /// Append a backend at the lowest priority so far
+    pub fn add_backend(&mut self, label: impl Into<String>, backend: Box<dyn DynRegistry>) {
+        self.backends.push((label.into(), backend));
+    }
+
+    /// Backend labels in priority order
+    pub fn backends(&self) -> Vec<&str> {
+        self.backends
+            .iter()
+            .map(|(label, _)| label.as_str())
+            .collect()
+    }
+
+    /// Search all backends concurrently and merge the results
+    ///
+    /// Backends are asked for every match; the page is taken from the
+    /// merged set.
+    pub async fn search(&self, query: &SpiritQuery) -> FederatedSearch {
+        let unpaged = SpiritQuery {
+            offset: 0,
+            limit: None,
+            ..query.clone()
+        };
+        let futures = self
+            .backends
+            .iter()
+            .map(|(_, backend)| backend.search_dyn(&unpaged))
+            .collect();
+        let outcomes = join_all(futures).await;
+
+        let mut merged = FederatedSearch::default();
+        let mut seen = HashSet::new();
+
+        // Outcomes are in priority order, so the first (name, version) wins
+        for ((label, _), outcome) in self.backends.iter().zip(outcomes) {
+            match outcome {
+                Ok(page) => {
+                    for result in page.results {
+                        if seen.insert((result.name.clone(), result.version.clone())) {
+                            merged.hits.push(FederatedHit {
+                                backend: label.clone(),
+                                result,
+                            });
+                        }
+                    }
+                }
+                Err(error) => merged.errors.push(BackendError {
+                    backend: label.clone(),
+                    error,
+                }),
+            }
+        }
+
+        merged.hits.sort_by(|a, b| {
+            a.result
+                .name
+                .cmp(&b.result.name)
+                .then_with(|| compare_versions(&a.result.version, &b.result.version))
+        });
+
+        merged.total_count = merged.hits.len();
+        merged.hits = std::mem::take(&mut merged.hits)
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect();
+
+        merged
+    }
+
+    /// Fetch WASM bytes from the first backend that has the spirit
+    ///
+    /// Backends are tried in priority order. If none succeed, the first
+    /// error other than "not found" is returned, or `NotFound` otherwise.
+    pub async fn get_wasm(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Vec<u8>, RegistryError> {
+        let mut first_error = None;
+
+        for (_, backend) in &self.backends {
+            match backend.get_wasm_dyn(name, version).await {
+                Ok(bytes) => return Ok(bytes),
+                Err(RegistryError::NotFound(_) | RegistryError::VersionNotFound { .. }) => {}
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        Err(first_error.unwrap_or_else(|| match version {
+            Some(version) => RegistryError::VersionNotFound {
+                name: name.to_string(),
+                version: version.to_string(),
+            },
+            None => RegistryError::NotFound(name.to_string()),
+        }))
+    }
+}
+
+/// Drive a set of futures concurrently, returning their outputs in order
+async fn join_all<T>(mut futures: Vec<BoxFuture<'_, T>>) -> Vec<T> {
+    let mut outputs: Vec<Option<T>> = futures.iter().map(|_| None).collect();
+
+    std::future::poll_fn(|cx| {
+        let mut pending = false;
+        for (future, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if output.is_none() {
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(value) => *output = Some(value),
+                    Poll::Pending => pending = true,
+                }
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+
+    outputs.into_iter().flatten().collect()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::Manifest;
+    use crate::registry::InstalledSpirit;
+    use std::path::{Path, PathBuf};
+
+    /// In-memory registry serving a fixed set of `(name, version)` entries
+    struct MockRegistry {
+        entries: Vec<(&'static str, &'static str)>,
+        wasm: Vec<u8>,
+        fail: bool,
+    }
+
+    impl MockRegistry {
+        fn new(entries: Vec<(&'static str, &'static str)>, wasm: Vec<u8>) -> Self {
+            Self {
+                entries,
+                wasm,
+                fail: false,
+            }
+        }
+
+        fn failing() -> Self {
+            Self {
+                entries: Vec::new(),
+                wasm: Vec::new(),
+                fail: true,
+            }
+        }
+
+        fn result(name: &str, version: &str) -> SpiritSearchResult {
+            SpiritSearchResult {
+                name: name.to_string(),
+                version: version.to_string(),
+                manifest: Manifest::new(name, version.parse().unwrap(), "a".repeat(64)),
+                path: PathBuf::new(),
+            }
+        }
+
+        fn check(&self) -> Result<(), RegistryError> {
+            if self.fail {
+                Err(RegistryError::Io(std::io::Error::other("backend offline")))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl Registry for MockRegistry {
+        async fn init(&mut self) -> Result<(), RegistryError> {
+            Ok(())
+        }
+
+        async fn install(&mut self, source: &str) -> Result<InstalledSpirit, RegistryError> {
+            Err(RegistryError::InvalidSource(source.to_string()))
+        }
+
+        async fn uninstall(&mut self, name: &str) -> Result<(), RegistryError> {
+            Err(RegistryError::NotFound(name.to_string()))
+        }
+
+        async fn uninstall_version(&mut self, name: &str, _: &str) -> Result<(), RegistryError> {
+            Err(RegistryError::NotFound(name.to_string()))
+        }
+
+        async fn get(&self, name: &str) -> Result<SpiritSearchResult, RegistryError> {
+            Err(RegistryError::NotFound(name.to_string()))
+        }
+
+        async fn get_version(
+            &self,
+            name: &str,
+            _: &str,
+        ) -> Result<SpiritSearchResult, RegistryError> {
+            Err(RegistryError::NotFound(name.to_string()))
+        }
+
+        async fn search(&self, query: &SpiritQuery) -> Result<SearchPage, RegistryError> {
+            self.check()?;
+            let matches = self
+                .entries
+                .iter()
+                .filter(|(name, _)| query.name.as_deref().is_none_or(|n| name.contains(n)))
+                .map(|(name, version)| Self::result(name, version))
+                .collect();
+            Ok(SearchPage::paginate(matches, query))
+        }
+
+        async fn list(&self) -> Result<Vec<InstalledSpirit>, RegistryError> {
+            Ok(Vec::new())
+        }
+
+        async fn get_wasm(&self, name: &str, _: Option<&str>) -> Result<Vec<u8>, RegistryError> {
+            self.check()?;
+            if self.entries.iter().any(|(n, _)| *n == name) {
+                Ok(self.wasm.clone())
+            } else {
+                Err(RegistryError::NotFound(name.to_string()))
+            }
+        }
+
+        async fn get_manifest(
+            &self,
+            name: &str,
+            _: Option<&str>,
+        ) -> Result<Manifest, RegistryError> {
+            Err(RegistryError::NotFound(name.to_string()))
+        }
+
+        fn is_installed(&self, name: &str) -> bool {
+            self.entries.iter().any(|(n, _)| *n == name)
+        }
+
+        fn is_version_installed(&self, name: &str, version: &str) -> bool {
+            self.entries.contains(&(name, version))
+        }
+
+        fn root(&self) -> &Path {
+            Path::new("")
+        }
+    }
+
+    fn overlapping() -> FederatedRegistry {
+        let local = MockRegistry::new(vec![("hello", "0.2.0"), ("echo", "1.0.0")], vec![1]);
+        let remote = MockRegistry::new(
+            vec![("hello", "0.2.0"), ("hello", "0.1.0"), ("zeta", "0.1.0")],
+            vec![2],
+        );
+
+        FederatedRegistry::new()
+            .with_backend("local", Box::new(local))
+            .with_backend("remote", Box::new(remote))
+    }
+
+    #[tokio::test]
+    async fn test_search_dedupes_and_orders() {
+        let search = overlapping().search(&SpiritQuery::new()).await;
+
+        assert!(search.is_complete());
+        let hits: Vec<(&str, &str, &str)> = search
+            .hits
+            .iter()
+            .map(|h| {
+                (
+                    h.result.name.as_str(),
+                    h.result.version.as_str(),
+                    h.backend.as_str(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            hits,
+            vec![
+                ("echo", "1.0.0", "local"),
+                ("hello", "0.1.0", "remote"),
+                ("hello", "0.2.0", "local"),
+                ("zeta", "0.1.0", "remote"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_pages_merged_hits() {
+        let federated = overlapping();
+
+        // Paging applies to the merged set, not to each backend
+        let search = federated.search(&SpiritQuery::new().with_page(1, 2)).await;
+        let hits: Vec<(&str, &str)> = search
+            .hits
+            .iter()
+            .map(|h| (h.result.name.as_str(), h.result.version.as_str()))
+            .collect();
+        assert_eq!(hits, vec![("hello", "0.1.0"), ("hello", "0.2.0")]);
+        assert_eq!(search.total_count, 4);
+
+        let search = federated.search(&SpiritQuery::new().with_page(10, 2)).await;
+        assert!(search.hits.is_empty());
+        assert_eq!(search.total_count, 4);
+    }
+
+    #[tokio::test]
+    async fn test_search_reports_backend_errors() {
+        let federated = overlapping().with_backend("broken", Box::new(MockRegistry::failing()));
+
+        let search = federated
+            .search(&SpiritQuery::new().with_name("hello"))
+            .await;
+
+        assert_eq!(search.hits.len(), 2);
+        assert_eq!(search.errors.len(), 1);
+        assert_eq!(search.errors[0].backend, "broken");
+    }
+
+    #[tokio::test]
+    async fn test_get_wasm_priority_order() {
+        let federated = overlapping();
+
+        // Present in both: local wins
+        assert_eq!(federated.get_wasm("hello", None).await.unwrap(), vec![1]);
+        // Only remote has it
+        assert_eq!(federated.get_wasm("zeta", None).await.unwrap(), vec![2]);
+        assert!(matches!(
+            federated.get_wasm("missing", None).await,
+            Err(RegistryError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_wasm_skips_failing_backend() {
+        let federated = FederatedRegistry::new()
+            .with_backend("broken", Box::new(MockRegistry::failing()))
+            .with_backend(
+                "remote",
+                Box::new(MockRegistry::new(vec![("hello", "0.1.0")], vec![7])),
+            );
+
+        assert_eq!(federated.get_wasm("hello", None).await.unwrap(), vec![7]);
+        assert!(matches!(
+            federated.get_wasm("missing", None).await,
+            Err(RegistryError::Io(_))
+        ));
+    }
+}
@@ -6,7 +6,7 @@
 //!
 //! ```text
 //! ~/.vudo/registry/
-//! ├── index.json           # Registry index
+//! ├── index.json           # Registry index (index.bin in the binary format)
 //! ├── spirits/             # Installed spirits
 //! │   ├── my-spirit/
 //! │   │   ├── 0.1.0/
@@ -21,13 +21,16 @@ use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::fs;
 
+use crate::dependency::DependencyResolver;
 use crate::manifest::Manifest;
 use crate::signature::VerifyingKey;
+use crate::version::SemVer;
 
+use super::search::{compare_versions, sort_results, SortBy, SortOrder};
 use super::traits::Registry;
 use super::types::{
-    InstallSource, InstalledSpirit, RegistryConfig, RegistryError, RegistryIndex, SpiritQuery,
-    SpiritSearchResult,
+    IndexFormat, InstallPlan, InstallSource, InstalledSpirit, RegistryConfig, RegistryError,
+    RegistryEvent, RegistryIndex, RegistryObserver, SearchPage, SpiritQuery, SpiritSearchResult,
 };
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -47,6 +50,8 @@ pub struct LocalRegistry {
     initialized: bool,
     /// Registry configuration for signature verification
     config: RegistryConfig,
+    /// Callbacks notified of persisted changes
+    observers: Vec<RegistryObserver>,
 }
 
 impl LocalRegistry {
@@ -61,6 +66,7 @@ impl LocalRegistry {
             index: RegistryIndex::default(),
             initialized: false,
             config: RegistryConfig::default(),
+            observers: Vec::new(),
         }
     }
 
@@ -71,6 +77,7 @@ impl LocalRegistry {
             index: RegistryIndex::default(),
             initialized: false,
             config: RegistryConfig::default(),
+            observers: Vec::new(),
         }
     }
 
@@ -81,6 +88,7 @@ impl LocalRegistry {
             index: RegistryIndex::default(),
             initialized: false,
             config,
+            observers: Vec::new(),
         }
     }
 
@@ -94,9 +102,27 @@ impl LocalRegistry {
         self.config = config;
     }
 
-    /// Get path to index file
+    /// Register a callback for installs, updates, uninstalls and yanks
+    ///
+    /// Callbacks run in registration order, only after the operation has
+    /// succeeded and the index has been saved.
+    pub fn add_observer(&mut self, observer: impl Fn(&RegistryEvent) + Send + Sync + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    fn notify(&self, event: RegistryEvent) {
+        for observer in &self.observers {
+            observer(&event);
+        }
+    }
+
+    /// Get path to the index file in the configured format
     fn index_path(&self) -> PathBuf {
-        self.root.join("index.json")
+        self.index_path_for(self.config.index_format)
+    }
+
+    fn index_path_for(&self, format: IndexFormat) -> PathBuf {
+        self.root.join(format.file_name())
     }
 
     /// Get path to spirits directory
@@ -120,20 +146,191 @@ impl LocalRegistry {
     }
 
     /// Load index from disk
-    async fn load_index(&mut self) -> Result<(), RegistryError> {
-        let path = self.index_path();
-        if path.exists() {
-            let content = fs::read_to_string(&path).await?;
-            self.index = serde_json::from_str(&content)?;
-        } else {
+    ///
+    /// Reads the index in the configured format, falling back to the other
+    /// format if only that file exists. Returns the format that was read.
+    async fn load_index(&mut self) -> Result<Option<IndexFormat>, RegistryError> {
+        let configured = self.config.index_format;
+        for format in [configured, configured.fallback()] {
+            let path = self.index_path_for(format);
+            if !path.exists() {
+                continue;
+            }
+            self.index = match format {
+                IndexFormat::Json => serde_json::from_str(&fs::read_to_string(&path).await?)?,
+                IndexFormat::Binary => RegistryIndex::from_binary(&fs::read(&path).await?)?,
+            };
+            return Ok(Some(format));
+        }
+        self.index = RegistryIndex::new();
+        Ok(None)
+    }
+
+    /// Load the index without touching the filesystem
+    ///
+    /// Read-only alternative to [`Registry::init`] for callers that must not
+    /// create directories or rewrite the index (e.g. dry runs). A missing or
+    /// unreadable index yields an empty registry.
+    pub async fn load(&mut self) -> Result<(), RegistryError> {
+        if self.load_index().await.is_err() {
             self.index = RegistryIndex::new();
         }
+        self.initialized = true;
         Ok(())
     }
 
+    /// Regenerate the index by scanning the `spirits/` directory
+    ///
+    /// Every `{name}/{version}/manifest.json` that parses is rediscovered;
+    /// unreadable entries are skipped. The `latest` symlink of each spirit is
+    /// repointed at its highest non-yanked version and the fresh index is
+    /// written to disk. Returns the number of spirits found.
+    pub async fn rebuild_index(&mut self) -> Result<usize, RegistryError> {
+        let mut index = RegistryIndex::new();
+        let spirits_dir = self.spirits_dir();
+
+        if spirits_dir.exists() {
+            let mut spirit_entries = fs::read_dir(&spirits_dir).await?;
+            while let Some(spirit_entry) = spirit_entries.next_entry().await? {
+                if !spirit_entry.file_type().await?.is_dir() {
+                    continue;
+                }
+                let name = spirit_entry.file_name().to_string_lossy().into_owned();
+
+                let mut versions = Vec::new();
+                let mut yanked = Vec::new();
+                let mut version_entries = fs::read_dir(spirit_entry.path()).await?;
+                while let Some(version_entry) = version_entries.next_entry().await? {
+                    // Skip the `latest` symlink and stray files
+                    if !version_entry.file_type().await?.is_dir() {
+                        continue;
+                    }
+                    let manifest_path = version_entry.path().join("manifest.json");
+                    let Ok(content) = fs::read_to_string(&manifest_path).await else {
+                        continue;
+                    };
+                    if serde_json::from_str::<Manifest>(&content).is_ok() {
+                        let version = version_entry.file_name().to_string_lossy().into_owned();
+                        if version_entry.path().join(YANKED_MARKER).exists() {
+                            yanked.push(version.clone());
+                        }
+                        versions.push(version);
+                    }
+                }
+
+                if versions.is_empty() {
+                    continue;
+                }
+                versions.sort_by(|a, b| compare_versions(a, b));
+
+                let mut spirit = InstalledSpirit {
+                    name,
+                    latest: versions.last().cloned().unwrap_or_default(),
+                    versions,
+                    installed_at: Self::now(),
+                    source: InstallSource::Local {
+                        path: spirit_entry.path(),
+                    },
+                    yanked,
+                };
+                if let Some(latest) = spirit.latest_unyanked() {
+                    spirit.latest = latest.to_string();
+                }
+                index.spirits.push(spirit);
+            }
+        }
+
+        index.spirits.sort_by(|a, b| a.name.cmp(&b.name));
+        for spirit in &index.spirits {
+            self.update_latest_symlink(&spirit.name, &spirit.latest)
+                .await;
+        }
+
+        let count = index.spirits.len();
+        self.index = index;
+        self.save_index().await?;
+
+        Ok(count)
+    }
+
+    /// Mark an installed version as yanked
+    ///
+    /// Yanked versions stay on disk and remain reachable by exact version,
+    /// but are skipped by `get`, `search` and dependency resolution.
+    pub async fn yank(&mut self, name: &str, version: &str) -> Result<(), RegistryError> {
+        self.set_yanked(name, version, true).await
+    }
+
+    /// Restore a previously yanked version
+    pub async fn unyank(&mut self, name: &str, version: &str) -> Result<(), RegistryError> {
+        self.set_yanked(name, version, false).await
+    }
+
+    async fn set_yanked(
+        &mut self,
+        name: &str,
+        version: &str,
+        yanked: bool,
+    ) -> Result<(), RegistryError> {
+        let spirit = self
+            .index
+            .find_mut(name)
+            .filter(|s| s.has_version(version))
+            .ok_or_else(|| RegistryError::VersionNotFound {
+                name: name.to_string(),
+                version: version.to_string(),
+            })?;
+        let changed = spirit.is_yanked(version) != yanked;
+        spirit.set_yanked(version, yanked);
+        let latest = spirit.latest_unyanked().map(str::to_string);
+
+        // Keep a marker beside the version so rebuild_index preserves it
+        let marker = self.spirit_version_dir(name, version).join(YANKED_MARKER);
+        if yanked {
+            fs::write(&marker, b"").await?;
+        } else if marker.exists() {
+            fs::remove_file(&marker).await?;
+        }
+
+        if let Some(latest) = latest {
+            self.update_latest_symlink(name, &latest).await;
+        }
+
+        self.save_index().await?;
+
+        if changed {
+            let (name, version) = (name.to_string(), version.to_string());
+            self.notify(if yanked {
+                RegistryEvent::Yanked { name, version }
+            } else {
+                RegistryEvent::Unyanked { name, version }
+            });
+        }
+        Ok(())
+    }
+
+    /// Build a dependency resolver seeded with the installed versions
+    ///
+    /// Yanked versions are registered as such, so they resolve only when
+    /// pinned exactly.
+    pub fn dependency_resolver(&self) -> DependencyResolver {
+        let mut resolver = DependencyResolver::new();
+        for spirit in &self.index.spirits {
+            let parse = |versions: &[String]| -> Vec<SemVer> {
+                versions.iter().filter_map(|v| v.parse().ok()).collect()
+            };
+            resolver.add_available(spirit.name.clone(), parse(&spirit.versions));
+            resolver.add_yanked(spirit.name.clone(), parse(&spirit.yanked));
+        }
+        resolver
+    }
+
     /// Save index to disk
     async fn save_index(&self) -> Result<(), RegistryError> {
-        let content = serde_json::to_string_pretty(&self.index)?;
+        let content = match self.config.index_format {
+            IndexFormat::Json => serde_json::to_vec_pretty(&self.index)?,
+            IndexFormat::Binary => self.index.to_binary()?,
+        };
         fs::write(self.index_path(), content).await?;
         Ok(())
     }
@@ -232,11 +429,14 @@ impl LocalRegistry {
         Ok(())
     }
 
-    /// Install from a local directory containing manifest and wasm
-    async fn install_from_dir(
-        &mut self,
-        source_path: &Path,
-    ) -> Result<InstalledSpirit, RegistryError> {
+    /// Work out what installing the spirit in `source_path` would write
+    ///
+    /// Runs every check `install` does, except that an already installed
+    /// version is reported in [`InstallPlan::already_installed`] rather than
+    /// failing. `manifest.json` is sized as the normalized JSON `install`
+    /// writes. Dependencies are not part of the plan: `install` does not
+    /// install them.
+    pub async fn plan_install(&self, source_path: &Path) -> Result<InstallPlan, RegistryError> {
         // Validate source path exists
         if !source_path.exists() {
             return Err(RegistryError::InvalidSource(format!(
@@ -260,36 +460,61 @@ impl LocalRegistry {
         // Verify signature before proceeding with installation
         self.verify_manifest_signature(&manifest).await?;
 
-        let name = manifest.name.clone();
         let version = manifest.version.to_string();
-
-        // Check if already installed
-        if self.index.contains_version(&name, &version) {
-            return Err(RegistryError::AlreadyInstalled { name, version });
+        let mut files = vec![
+            (
+                PathBuf::from("spirit.wasm"),
+                fs::metadata(&wasm_source).await?.len(),
+            ),
+            (
+                PathBuf::from("manifest.json"),
+                serde_json::to_string_pretty(&manifest)?.len() as u64,
+            ),
+        ];
+        if source_path.join("assets").is_dir() {
+            collect_files(source_path, Path::new("assets"), &mut files).await?;
         }
 
-        // Create target directory
-        let target_dir = self.spirit_version_dir(&name, &version);
-        fs::create_dir_all(&target_dir).await?;
+        Ok(InstallPlan {
+            target_dir: self.spirit_version_dir(&manifest.name, &version),
+            already_installed: self.index.contains_version(&manifest.name, &version),
+            source: source_path.to_path_buf(),
+            manifest,
+            files,
+        })
+    }
 
-        // Copy WASM file
-        let wasm_target = target_dir.join("spirit.wasm");
-        fs::copy(&wasm_source, &wasm_target).await?;
+    /// Install from a local directory containing manifest and wasm
+    async fn install_from_dir(
+        &mut self,
+        source_path: &Path,
+    ) -> Result<InstalledSpirit, RegistryError> {
+        let plan = self.plan_install(source_path).await?;
+        let name = plan.manifest.name.clone();
+        let version = plan.manifest.version.to_string();
 
-        // Write manifest as JSON (normalized format)
-        let manifest_target = target_dir.join("manifest.json");
-        let manifest_json = serde_json::to_string_pretty(&manifest)?;
-        fs::write(&manifest_target, manifest_json).await?;
+        // Check if already installed
+        if plan.already_installed {
+            return Err(RegistryError::AlreadyInstalled { name, version });
+        }
 
-        // Copy assets directory if present
-        let assets_source = source_path.join("assets");
-        if assets_source.exists() && assets_source.is_dir() {
-            let assets_target = target_dir.join("assets");
-            copy_dir_recursive(&assets_source, &assets_target).await?;
+        // Write the planned files; the manifest as normalized JSON
+        for (file, _) in &plan.files {
+            let target = plan.target_dir.join(file);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            if file == Path::new("manifest.json") {
+                let manifest_json = serde_json::to_string_pretty(&plan.manifest)?;
+                fs::write(&target, manifest_json).await?;
+            } else {
+                fs::copy(source_path.join(file), &target).await?;
+            }
         }
 
         // Update index
         let now = Self::now();
+        let update = self.index.contains(&name);
         let installed = if let Some(existing) = self.index.find_mut(&name) {
             existing.add_version(version.clone());
             existing.clone()
@@ -302,6 +527,7 @@ impl LocalRegistry {
                 source: InstallSource::Local {
                     path: source_path.to_path_buf(),
                 },
+                yanked: Vec::new(),
             };
             self.index.spirits.push(new_spirit.clone());
             new_spirit
@@ -313,6 +539,12 @@ impl LocalRegistry {
         // Persist index
         self.save_index().await?;
 
+        self.notify(if update {
+            RegistryEvent::Updated { name, version }
+        } else {
+            RegistryEvent::Installed { name, version }
+        });
+
         Ok(installed)
     }
 
@@ -365,6 +597,9 @@ impl LocalRegistry {
     }
 }
 
+/// File placed in a version directory to record that it is yanked
+const YANKED_MARKER: &str = ".yanked";
+
 /// Manifest format enumeration
 #[derive(Debug, Clone, Copy)]
 enum ManifestFormat {
@@ -389,13 +624,18 @@ impl Registry for LocalRegistry {
         fs::create_dir_all(self.spirits_dir()).await?;
         fs::create_dir_all(self.cache_dir()).await?;
 
-        // Load or create index
-        let index_exists = self.index_path().exists();
-        self.load_index().await?;
-
-        // Save index if it didn't exist (creates the file)
-        if !index_exists {
-            self.save_index().await?;
+        // Load the index, rebuilding it from disk if missing or corrupt
+        match self.load_index().await {
+            Ok(Some(format)) if format == self.config.index_format => {}
+            Ok(Some(format)) => {
+                // Migrate to the configured format, dropping the old file so
+                // it cannot go stale
+                self.save_index().await?;
+                fs::remove_file(self.index_path_for(format)).await?;
+            }
+            Ok(None) | Err(_) => {
+                self.rebuild_index().await?;
+            }
         }
 
         self.initialized = true;
@@ -426,9 +666,21 @@ impl Registry for LocalRegistry {
             fs::remove_dir_all(&dir).await?;
         }
 
+        let versions = self
+            .index
+            .find(name)
+            .map(|s| s.versions.clone())
+            .unwrap_or_default();
         self.index.spirits.retain(|s| s.name != name);
         self.save_index().await?;
 
+        for version in versions {
+            self.notify(RegistryEvent::Uninstalled {
+                name: name.to_string(),
+                version,
+            });
+        }
+
         Ok(())
     }
 
@@ -438,6 +690,7 @@ impl Registry for LocalRegistry {
             fs::remove_dir_all(&dir).await?;
         }
 
+        let removed = self.index.contains_version(name, version);
         let new_latest = if let Some(spirit) = self.index.find_mut(name) {
             spirit.remove_version(version);
             if spirit.versions.is_empty() {
@@ -460,6 +713,13 @@ impl Registry for LocalRegistry {
 
         self.save_index().await?;
 
+        if removed {
+            self.notify(RegistryEvent::Uninstalled {
+                name: name.to_string(),
+                version: version.to_string(),
+            });
+        }
+
         Ok(())
     }
 
@@ -468,7 +728,14 @@ impl Registry for LocalRegistry {
             .index
             .find(name)
             .ok_or_else(|| RegistryError::NotFound(name.to_string()))?;
-        self.get_version(name, &spirit.latest).await
+        let version = if spirit.is_yanked(&spirit.latest) {
+            spirit
+                .latest_unyanked()
+                .ok_or_else(|| RegistryError::Yanked(name.to_string()))?
+        } else {
+            &spirit.latest
+        };
+        self.get_version(name, version).await
     }
 
     async fn get_version(
@@ -497,19 +764,23 @@ impl Registry for LocalRegistry {
         })
     }
 
-    async fn search(&self, query: &SpiritQuery) -> Result<Vec<SpiritSearchResult>, RegistryError> {
+    async fn search(&self, query: &SpiritQuery) -> Result<SearchPage, RegistryError> {
         let mut results = Vec::new();
 
         for spirit in &self.index.spirits {
             // Name filter
-            if let Some(ref pattern) = query.name {
-                if !spirit.name.to_lowercase().contains(&pattern.to_lowercase()) {
-                    continue;
-                }
+            if !query.matches_name(&spirit.name) {
+                continue;
             }
 
+            // An exact version request may reach a yanked version
+            let found = match query.version.as_deref() {
+                Some(v) if spirit.has_version(v) => self.get_version(&spirit.name, v).await,
+                _ => self.get(&spirit.name).await,
+            };
+
             // Get full manifest for detailed filtering
-            if let Ok(result) = self.get(&spirit.name).await {
+            if let Ok(result) = found {
                 // Author filter
                 if let Some(ref author) = query.author {
                     if !result
@@ -546,7 +817,8 @@ impl Registry for LocalRegistry {
             }
         }
 
-        Ok(results)
+        sort_results(&mut results, SortBy::Name, SortOrder::Ascending);
+        Ok(SearchPage::paginate(results, query))
     }
 
     async fn list(&self) -> Result<Vec<InstalledSpirit>, RegistryError> {
@@ -592,19 +864,27 @@ impl Registry for LocalRegistry {
 // HELPERS
 // ═══════════════════════════════════════════════════════════════════════════
 
-/// Recursively copy a directory
-async fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), RegistryError> {
-    fs::create_dir_all(dst).await?;
-
-    let mut entries = fs::read_dir(src).await?;
+/// Append every file under `root/dir` to `files` as a path relative to
+/// `root` with its size, in name order
+async fn collect_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<(PathBuf, u64)>,
+) -> Result<(), RegistryError> {
+    let mut names = Vec::new();
+    let mut entries = fs::read_dir(root.join(dir)).await?;
     while let Some(entry) = entries.next_entry().await? {
-        let path = entry.path();
-        let dest_path = dst.join(entry.file_name());
+        names.push(entry.file_name());
+    }
+    names.sort();
 
-        if path.is_dir() {
-            Box::pin(copy_dir_recursive(&path, &dest_path)).await?;
+    for name in names {
+        let relative = dir.join(name);
+        let metadata = fs::metadata(root.join(&relative)).await?;
+        if metadata.is_dir() {
+            Box::pin(collect_files(root, &relative, files)).await?;
         } else {
-            fs::copy(&path, &dest_path).await?;
+            files.push((relative, metadata.len()));
         }
     }
 
@@ -703,6 +983,75 @@ mod tests {
         ));
     }
 
+    /// Every file under `dir`, relative to it, with its size
+    fn files_under(dir: &Path) -> Vec<(PathBuf, u64)> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let prefix = PathBuf::from(entry.file_name());
+                files.extend(
+                    files_under(&path)
+                        .into_iter()
+                        .map(|(file, size)| (prefix.join(file), size)),
+                );
+            } else {
+                files.push((
+                    PathBuf::from(entry.file_name()),
+                    entry.metadata().unwrap().len(),
+                ));
+            }
+        }
+        files.sort();
+        files
+    }
+
+    #[tokio::test]
+    async fn test_plan_install_matches_install() {
+        let temp = TempDir::new().unwrap();
+        let spirit_dir = temp.path().join("planned");
+        fs::create_dir_all(spirit_dir.join("assets/icons"))
+            .await
+            .unwrap();
+        create_test_spirit(&spirit_dir, "planned", "0.3.0")
+            .await
+            .unwrap();
+        fs::write(spirit_dir.join("assets/readme.txt"), b"hello")
+            .await
+            .unwrap();
+        fs::write(spirit_dir.join("assets/icons/logo.svg"), b"<svg/>")
+            .await
+            .unwrap();
+        // Not copied by install, so not planned either
+        fs::write(spirit_dir.join("notes.md"), b"scratch")
+            .await
+            .unwrap();
+
+        let registry_dir = temp.path().join("registry");
+        let mut registry = LocalRegistry::with_root(&registry_dir);
+        registry.init().await.unwrap();
+
+        let plan = registry.plan_install(&spirit_dir).await.unwrap();
+        assert!(!plan.already_installed);
+        assert!(!plan.target_dir.exists());
+
+        registry
+            .install(spirit_dir.to_str().unwrap())
+            .await
+            .unwrap();
+
+        let mut planned = plan.files.clone();
+        planned.sort();
+        assert_eq!(files_under(&plan.target_dir), planned);
+        assert_eq!(
+            plan.size(),
+            planned.iter().map(|(_, size)| size).sum::<u64>()
+        );
+
+        let plan = registry.plan_install(&spirit_dir).await.unwrap();
+        assert!(plan.already_installed);
+    }
+
     #[tokio::test]
     async fn test_get_installed_spirit() {
         let temp = TempDir::new().unwrap();
@@ -822,9 +1171,70 @@ mod tests {
             .unwrap();
 
         let query = SpiritQuery::new().with_name("searchable");
-        let results = registry.search(&query).await.unwrap();
+        let results = registry.search(&query).await.unwrap().results;
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "searchable-spirit");
+
+        // Names are substring-matched unless another mode is requested
+        let query = SpiritQuery::new().with_name("spirit");
+        assert_eq!(registry.search(&query).await.unwrap().len(), 1);
+        let query = query.with_name_match(crate::registry::MatchMode::Prefix);
+        assert!(registry.search(&query).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_pagination() {
+        let temp = TempDir::new().unwrap();
+        let registry_dir = temp.path().join("registry");
+        let mut registry = LocalRegistry::with_root(&registry_dir);
+        registry.init().await.unwrap();
+
+        // Installed out of order; pages follow name order
+        for name in ["spirit-d", "spirit-b", "spirit-e", "spirit-a", "spirit-c"] {
+            let src = temp.path().join(name);
+            fs::create_dir_all(&src).await.unwrap();
+            create_test_spirit(&src, name, "0.1.0").await.unwrap();
+            registry.install(src.to_str().unwrap()).await.unwrap();
+        }
+
+        let names = |page: &SearchPage| -> Vec<String> {
+            page.results.iter().map(|r| r.name.clone()).collect()
+        };
+
+        let page = registry
+            .search(&SpiritQuery::new().with_page(0, 2))
+            .await
+            .unwrap();
+        assert_eq!(names(&page), vec!["spirit-a", "spirit-b"]);
+        assert_eq!(page.total_count, 5);
+
+        // Last page is short
+        let page = registry
+            .search(&SpiritQuery::new().with_page(4, 2))
+            .await
+            .unwrap();
+        assert_eq!(names(&page), vec!["spirit-e"]);
+        assert_eq!(page.total_count, 5);
+
+        // Offset past the end is empty but still reports the total
+        let page = registry
+            .search(&SpiritQuery::new().with_page(5, 2))
+            .await
+            .unwrap();
+        assert!(page.is_empty());
+        assert_eq!(page.total_count, 5);
+
+        // total_count covers the filtered match set, not the page
+        let query = crate::registry::QueryBuilder::new()
+            .name("spirit-c")
+            .page(0, 10)
+            .build();
+        let page = registry.search(&query).await.unwrap();
+        assert_eq!(names(&page), vec!["spirit-c"]);
+        assert_eq!(page.total_count, 1);
+
+        // Without a page every match is returned
+        assert_eq!(registry.search(&SpiritQuery::new()).await.unwrap().len(), 5);
     }
 
     // ═══════════════════════════════════════════════════════════════════════════
@@ -989,6 +1399,7 @@ mod tests {
             require_signatures: true,
             trusted_keys_dir: None,
             unsigned_allowed_authors: vec![],
+            ..RegistryConfig::default()
         };
         let mut registry = LocalRegistry::with_config(&registry_dir, config);
         registry.init().await.unwrap();
@@ -1018,6 +1429,7 @@ mod tests {
             require_signatures: true,
             trusted_keys_dir: None,
             unsigned_allowed_authors: vec!["a".repeat(64)],
+            ..RegistryConfig::default()
         };
         let mut registry = LocalRegistry::with_config(&registry_dir, config);
         registry.init().await.unwrap();
@@ -1033,4 +1445,251 @@ mod tests {
         let installed = result.unwrap();
         assert_eq!(installed.name, "allowed-unsigned");
     }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // INDEX REBUILD TESTS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Install `hello@0.2.0`, `hello@0.1.0` (in that order) and `echo@1.0.0`
+    async fn populated_registry(temp: &TempDir) -> PathBuf {
+        let registry_dir = temp.path().join("registry");
+        let mut registry = LocalRegistry::with_root(&registry_dir);
+        registry.init().await.unwrap();
+
+        for (name, version) in [("hello", "0.2.0"), ("hello", "0.1.0"), ("echo", "1.0.0")] {
+            let src = temp.path().join(format!("{}-{}", name, version));
+            fs::create_dir_all(&src).await.unwrap();
+            create_test_spirit(&src, name, version).await.unwrap();
+            registry.install(src.to_str().unwrap()).await.unwrap();
+        }
+
+        registry_dir
+    }
+
+    fn assert_rediscovered(registry: &LocalRegistry) {
+        let hello = registry.index.find("hello").expect("hello rediscovered");
+        assert_eq!(hello.versions, vec!["0.1.0", "0.2.0"]);
+        assert_eq!(hello.latest, "0.2.0");
+        assert!(registry.is_version_installed("echo", "1.0.0"));
+        assert_eq!(registry.index.spirits.len(), 2);
+
+        #[cfg(unix)]
+        assert_eq!(
+            std::fs::read_link(registry.spirit_dir("hello").join("latest")).unwrap(),
+            PathBuf::from("0.2.0")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_init_rebuilds_missing_index() {
+        let temp = TempDir::new().unwrap();
+        let registry_dir = populated_registry(&temp).await;
+        fs::remove_file(registry_dir.join("index.json"))
+            .await
+            .unwrap();
+
+        let mut registry = LocalRegistry::with_root(&registry_dir);
+        registry.init().await.unwrap();
+
+        assert_rediscovered(&registry);
+        assert!(registry_dir.join("index.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_init_migrates_json_index_to_binary() {
+        let temp = TempDir::new().unwrap();
+        let registry_dir = populated_registry(&temp).await;
+
+        let config = RegistryConfig {
+            index_format: IndexFormat::Binary,
+            ..RegistryConfig::default()
+        };
+        let mut registry = LocalRegistry::with_config(&registry_dir, config);
+        registry.init().await.unwrap();
+
+        assert_eq!(registry.index.spirits.len(), 2);
+        assert!(registry_dir.join("index.bin").exists());
+        assert!(!registry_dir.join("index.json").exists());
+
+        // The binary index is found again even without the config
+        let mut reopened = LocalRegistry::with_root(&registry_dir);
+        reopened.load().await.unwrap();
+        assert_eq!(reopened.index.spirits.len(), 2);
+        assert!(reopened.is_version_installed("hello", "0.1.0"));
+        assert!(reopened.is_version_installed("echo", "1.0.0"));
+    }
+
+    #[tokio::test]
+    async fn test_init_rebuilds_corrupt_index() {
+        let temp = TempDir::new().unwrap();
+        let registry_dir = populated_registry(&temp).await;
+        fs::write(registry_dir.join("index.json"), "{ not json")
+            .await
+            .unwrap();
+
+        let mut registry = LocalRegistry::with_root(&registry_dir);
+        registry.init().await.unwrap();
+
+        assert_rediscovered(&registry);
+        assert_eq!(registry.get("hello").await.unwrap().version, "0.2.0");
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_index_repairs_latest() {
+        let temp = TempDir::new().unwrap();
+        let registry_dir = populated_registry(&temp).await;
+
+        let mut registry = LocalRegistry::with_root(&registry_dir);
+        registry.init().await.unwrap();
+        // Installing 0.1.0 last left `latest` pointing at the older version
+        assert_eq!(registry.index.find("hello").unwrap().latest, "0.1.0");
+
+        let count = registry.rebuild_index().await.unwrap();
+
+        assert_eq!(count, 2);
+        assert_rediscovered(&registry);
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // YANK TESTS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    #[tokio::test]
+    async fn test_yank_latest_falls_back_to_prior() {
+        let temp = TempDir::new().unwrap();
+        let registry_dir = populated_registry(&temp).await;
+        let mut registry = LocalRegistry::with_root(&registry_dir);
+        registry.init().await.unwrap();
+        registry.rebuild_index().await.unwrap();
+
+        registry.yank("hello", "0.2.0").await.unwrap();
+
+        assert_eq!(registry.get("hello").await.unwrap().version, "0.1.0");
+        let results = registry
+            .search(&SpiritQuery::new().with_name("hello"))
+            .await
+            .unwrap()
+            .results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].version, "0.1.0");
+
+        let deps = std::collections::HashMap::from([(
+            "hello".to_string(),
+            crate::Dependency::new("^0.1.0"),
+        )]);
+        let resolved = registry.dependency_resolver().resolve(&deps).unwrap();
+        assert_eq!(resolved[0].version.to_string(), "0.1.0");
+
+        #[cfg(unix)]
+        assert_eq!(
+            std::fs::read_link(registry.spirit_dir("hello").join("latest")).unwrap(),
+            PathBuf::from("0.1.0")
+        );
+
+        registry.unyank("hello", "0.2.0").await.unwrap();
+        assert_eq!(registry.get("hello").await.unwrap().version, "0.2.0");
+    }
+
+    #[tokio::test]
+    async fn test_yanked_version_reachable_by_exact_version() {
+        let temp = TempDir::new().unwrap();
+        let registry_dir = populated_registry(&temp).await;
+        let mut registry = LocalRegistry::with_root(&registry_dir);
+        registry.init().await.unwrap();
+
+        registry.yank("hello", "0.2.0").await.unwrap();
+
+        assert!(registry.get_version("hello", "0.2.0").await.is_ok());
+        assert!(registry.get_wasm("hello", Some("0.2.0")).await.is_ok());
+        let results = registry
+            .search(&SpiritQuery::new().with_name("hello").with_version("0.2.0"))
+            .await
+            .unwrap()
+            .results;
+        assert_eq!(results[0].version, "0.2.0");
+
+        let deps = std::collections::HashMap::from([(
+            "hello".to_string(),
+            crate::Dependency::new("=0.2.0"),
+        )]);
+        let resolved = registry.dependency_resolver().resolve(&deps).unwrap();
+        assert_eq!(resolved[0].version.to_string(), "0.2.0");
+        assert!(resolved[0].yanked);
+    }
+
+    #[tokio::test]
+    async fn test_yank_survives_rebuild() {
+        let temp = TempDir::new().unwrap();
+        let registry_dir = populated_registry(&temp).await;
+        let mut registry = LocalRegistry::with_root(&registry_dir);
+        registry.init().await.unwrap();
+
+        registry.yank("hello", "0.2.0").await.unwrap();
+        assert!(matches!(
+            registry.yank("hello", "9.9.9").await,
+            Err(RegistryError::VersionNotFound { .. })
+        ));
+
+        registry.rebuild_index().await.unwrap();
+
+        let hello = registry.index.find("hello").unwrap();
+        assert!(hello.is_yanked("0.2.0"));
+        assert_eq!(hello.latest, "0.1.0");
+
+        registry.yank("hello", "0.1.0").await.unwrap();
+        assert!(matches!(
+            registry.get("hello").await,
+            Err(RegistryError::Yanked(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_observer_receives_install_and_uninstall() {
+        use std::sync::{Arc, Mutex};
+
+        let temp = TempDir::new().unwrap();
+        let spirit_dir = temp.path().join("watched");
+        fs::create_dir_all(&spirit_dir).await.unwrap();
+        create_test_spirit(&spirit_dir, "watched", "0.1.0")
+            .await
+            .unwrap();
+
+        let registry_dir = temp.path().join("registry");
+        let mut registry = LocalRegistry::with_root(&registry_dir);
+        registry.init().await.unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let index_path = registry.index_path();
+        registry.add_observer(move |event| {
+            // The index is already on disk when observers run
+            let index = std::fs::read_to_string(&index_path).unwrap();
+            let persisted = index.contains("\"watched\"");
+            sink.lock().unwrap().push((event.clone(), persisted));
+        });
+
+        registry
+            .install(spirit_dir.to_str().unwrap())
+            .await
+            .unwrap();
+        // A failed install notifies nobody
+        assert!(registry
+            .install(spirit_dir.to_str().unwrap())
+            .await
+            .is_err());
+        registry.uninstall("watched").await.unwrap();
+
+        let installed = RegistryEvent::Installed {
+            name: "watched".to_string(),
+            version: "0.1.0".to_string(),
+        };
+        let uninstalled = RegistryEvent::Uninstalled {
+            name: "watched".to_string(),
+            version: "0.1.0".to_string(),
+        };
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![(installed, true), (uninstalled, false)]
+        );
+    }
 }
@@ -9,6 +9,8 @@
 //!
 //! - [`Registry`] - Core trait defining registry operations
 //! - [`LocalRegistry`] - Filesystem-based implementation (default)
+//! - [`FederatedRegistry`] - Merges searches across several backends
+//! - [`stream_to_file`] - Chunked, cancellation-safe download to disk
 //!
 //! # Directory Structure
 //!
@@ -45,7 +47,8 @@
 //!         .name("hello")
 //!         .capability("SensorTime")
 //!         .build();
-//!     let results = registry.search(&query).await?;
+//!     let page = registry.search(&query).await?;
+//!     println!("{} of {} matches", page.len(), page.total_count);
 //!
 //!     // Get WASM bytes for execution
 //!     let wasm = registry.get_wasm("hello-world", None).await?;
@@ -54,17 +57,23 @@
 //! }
 //! ```
 
+mod download;
+mod federated;
 mod local;
 mod search;
 mod traits;
 mod types;
 
 // Re-export primary types
+pub use download::{part_path, stream_to_file, DownloadProgress, DOWNLOAD_CHUNK_SIZE};
+pub use federated::{
+    BackendError, BoxFuture, DynRegistry, FederatedHit, FederatedRegistry, FederatedSearch,
+};
 pub use local::LocalRegistry;
 pub use search::{compare_versions, filter_by_capability, matches_name_pattern, sort_results};
-pub use search::{QueryBuilder, SortBy, SortOrder};
+pub use search::{MatchMode, QueryBuilder, SortBy, SortOrder};
 pub use traits::{Registry, RegistryExt};
 pub use types::{
-    InstallSource, InstalledSpirit, RegistryConfig, RegistryError, RegistryIndex, SpiritQuery,
-    SpiritSearchResult,
+    IndexFormat, InstallPlan, InstallSource, InstalledSpirit, RegistryConfig, RegistryError,
+    RegistryEvent, RegistryIndex, RegistryObserver, SearchPage, SpiritQuery, SpiritSearchResult,
 };
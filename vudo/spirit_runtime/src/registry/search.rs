@@ -35,12 +35,18 @@ impl QueryBuilder {
         Self::default()
     }
 
-    /// Filter by name pattern (partial match)
+    /// Filter by name pattern, compared according to the match mode
     pub fn name(mut self, name: impl Into<String>) -> Self {
         self.query.name = Some(name.into());
         self
     }
 
+    /// How the name pattern is compared (substring by default)
+    pub fn match_mode(mut self, mode: MatchMode) -> Self {
+        self.query.name_match = mode;
+        self
+    }
+
     /// Filter by author
     pub fn author(mut self, author: impl Into<String>) -> Self {
         self.query.author = Some(author.into());
@@ -53,6 +59,13 @@ impl QueryBuilder {
         self
     }
 
+    /// Return at most `limit` matches, skipping the first `offset`
+    pub fn page(mut self, offset: usize, limit: usize) -> Self {
+        self.query.offset = offset;
+        self.query.limit = Some(limit);
+        self
+    }
+
     /// Require a typed Capability
     pub fn with_capability(mut self, cap: Capability) -> Self {
         self.query.capabilities.push(format!("{:?}", cap));
@@ -71,6 +84,75 @@ impl QueryBuilder {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// MATCH MODE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// How a query's name pattern is compared against Spirit names
+///
+/// All modes ignore case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchMode {
+    /// The name equals the pattern
+    Exact,
+    /// The name starts with the pattern
+    Prefix,
+    /// The name contains the pattern anywhere (default)
+    #[default]
+    Substring,
+    /// The pattern is a glob: `*` matches any run of characters, `?` one
+    Glob,
+}
+
+impl MatchMode {
+    /// Returns true if `name` matches `pattern` in this mode
+    pub fn matches(&self, name: &str, pattern: &str) -> bool {
+        let name = name.to_lowercase();
+        let pattern = pattern.to_lowercase();
+
+        match self {
+            MatchMode::Exact => name == pattern,
+            MatchMode::Prefix => name.starts_with(&pattern),
+            MatchMode::Substring => name.contains(&pattern),
+            MatchMode::Glob => glob_match(&name, &pattern),
+        }
+    }
+}
+
+/// Match `name` against a glob with `*` and `?` wildcards
+fn glob_match(name: &str, pattern: &str) -> bool {
+    let name: Vec<char> = name.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+
+    let (mut n, mut p) = (0, 0);
+    // Position of the last `*` seen and the name index it is matched up to
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                n += 1;
+                p += 1;
+            }
+            _ => match star {
+                // Let the last `*` swallow one more character and retry
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // SORT OPTIONS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -254,6 +336,43 @@ mod tests {
         assert!(matches_name_pattern("Hello-World", "hello*"));
     }
 
+    #[test]
+    fn test_match_modes() {
+        let names = ["hello-world", "say-hello"];
+        let matching = |mode: MatchMode, pattern: &str| -> Vec<&str> {
+            names
+                .iter()
+                .copied()
+                .filter(|name| mode.matches(name, pattern))
+                .collect()
+        };
+
+        assert_eq!(matching(MatchMode::Prefix, "hel"), ["hello-world"]);
+        assert_eq!(
+            matching(MatchMode::Substring, "hel"),
+            ["hello-world", "say-hello"]
+        );
+        assert!(matching(MatchMode::Exact, "hel").is_empty());
+        assert_eq!(matching(MatchMode::Exact, "Say-Hello"), ["say-hello"]);
+        assert_eq!(matching(MatchMode::Glob, "hel*"), ["hello-world"]);
+        assert_eq!(matching(MatchMode::Glob, "*hello"), ["say-hello"]);
+        assert_eq!(matching(MatchMode::Glob, "?ay-*l?"), ["say-hello"]);
+        assert!(matching(MatchMode::Glob, "hel").is_empty());
+    }
+
+    #[test]
+    fn test_query_builder_match_mode() {
+        assert_eq!(QueryBuilder::new().build().name_match, MatchMode::Substring);
+
+        let query = QueryBuilder::new()
+            .name("hel*")
+            .match_mode(MatchMode::Glob)
+            .build();
+        assert_eq!(query.name_match, MatchMode::Glob);
+        assert!(query.matches_name("hello-world"));
+        assert!(!query.matches_name("say-hello"));
+    }
+
     #[test]
     fn test_sort_order() {
         assert_eq!(SortBy::default(), SortBy::Name);
@@ -6,7 +6,7 @@
 
 use crate::manifest::Manifest;
 
-use super::types::{InstalledSpirit, RegistryError, SpiritQuery, SpiritSearchResult};
+use super::types::{InstalledSpirit, RegistryError, SearchPage, SpiritQuery, SpiritSearchResult};
 
 // ═══════════════════════════════════════════════════════════════════════════
 // REGISTRY TRAIT
@@ -91,10 +91,12 @@ pub trait Registry: Send + Sync {
     /// Search for spirits matching a query
     ///
     /// Searches by name, author, and capabilities based on the query.
+    /// Matches are sorted by name before the query's `offset`/`limit`
+    /// page is taken; `total_count` counts every match.
     fn search(
         &self,
         query: &SpiritQuery,
-    ) -> impl std::future::Future<Output = Result<Vec<SpiritSearchResult>, RegistryError>> + Send;
+    ) -> impl std::future::Future<Output = Result<SearchPage, RegistryError>> + Send;
 
     /// List all installed spirits
     ///
@@ -7,10 +7,12 @@
 //! - Error types
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::manifest::Manifest;
 
+use super::search::MatchMode;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // REGISTRY CONFIGURATION
 // ═══════════════════════════════════════════════════════════════════════════
@@ -26,6 +28,36 @@ pub struct RegistryConfig {
     pub trusted_keys_dir: Option<PathBuf>,
     /// Allow unsigned spirits from these authors
     pub unsigned_allowed_authors: Vec<String>,
+    /// On-disk format the index is written in
+    pub index_format: IndexFormat,
+}
+
+/// On-disk format of the registry index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndexFormat {
+    /// Human-readable `index.json`
+    #[default]
+    Json,
+    /// Compact `index.bin`, faster to load for large registries
+    Binary,
+}
+
+impl IndexFormat {
+    /// File name of the index in the registry root
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            IndexFormat::Json => "index.json",
+            IndexFormat::Binary => "index.bin",
+        }
+    }
+
+    /// The other format, tried when this one's file is absent
+    pub fn fallback(&self) -> Self {
+        match self {
+            IndexFormat::Json => IndexFormat::Binary,
+            IndexFormat::Binary => IndexFormat::Json,
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -34,8 +66,9 @@ pub struct RegistryConfig {
 
 /// Registry index containing all installed spirits
 ///
-/// The index is persisted to `index.json` in the registry root directory.
-/// It provides fast lookup of installed spirits without scanning the filesystem.
+/// The index is persisted to `index.json` (or `index.bin`, see
+/// [`IndexFormat`]) in the registry root directory. It provides fast lookup
+/// of installed spirits without scanning the filesystem.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RegistryIndex {
     /// Schema version for forward compatibility
@@ -76,6 +109,124 @@ impl RegistryIndex {
             .map(|s| s.versions.contains(&version.to_string()))
             .unwrap_or(false)
     }
+
+    /// Encode the index in the binary format
+    ///
+    /// The encoding is deterministic: equal indexes produce equal bytes.
+    pub fn to_binary(&self) -> Result<Vec<u8>, RegistryError> {
+        let wire = BinaryIndex::from(self);
+        let mut bytes = BINARY_INDEX_MAGIC.to_vec();
+        bytes.extend(postcard::to_stdvec(&wire).map_err(|e| RegistryError::Binary(e.to_string()))?);
+        Ok(bytes)
+    }
+
+    /// Decode an index produced by [`RegistryIndex::to_binary`]
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, RegistryError> {
+        let payload = bytes
+            .strip_prefix(BINARY_INDEX_MAGIC)
+            .ok_or_else(|| RegistryError::Binary("missing index header".to_string()))?;
+        let wire: BinaryIndex =
+            postcard::from_bytes(payload).map_err(|e| RegistryError::Binary(e.to_string()))?;
+        Ok(wire.into())
+    }
+
+    /// Write the index to `path` in the binary format
+    pub fn save_binary(&self, path: impl AsRef<Path>) -> Result<(), RegistryError> {
+        std::fs::write(path, self.to_binary()?)?;
+        Ok(())
+    }
+
+    /// Read an index written by [`RegistryIndex::save_binary`]
+    pub fn load_binary(path: impl AsRef<Path>) -> Result<Self, RegistryError> {
+        Self::from_binary(&std::fs::read(path)?)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// BINARY INDEX FORMAT
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Leads every binary index so other files are rejected up front
+const BINARY_INDEX_MAGIC: &[u8] = b"VIDX";
+
+// postcard is not self-describing, so the binary format uses mirror types
+// without the internally tagged enum and skipped fields of the JSON form.
+
+#[derive(Serialize, Deserialize)]
+struct BinaryIndex {
+    schema_version: u32,
+    spirits: Vec<BinarySpirit>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinarySpirit {
+    name: String,
+    versions: Vec<String>,
+    latest: String,
+    installed_at: u64,
+    source: BinarySource,
+    yanked: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum BinarySource {
+    Local(PathBuf),
+    Remote(String),
+    Built(PathBuf),
+}
+
+impl From<&RegistryIndex> for BinaryIndex {
+    fn from(index: &RegistryIndex) -> Self {
+        let spirits = index
+            .spirits
+            .iter()
+            .map(|spirit| BinarySpirit {
+                name: spirit.name.clone(),
+                versions: spirit.versions.clone(),
+                latest: spirit.latest.clone(),
+                installed_at: spirit.installed_at,
+                source: match &spirit.source {
+                    InstallSource::Local { path } => BinarySource::Local(path.clone()),
+                    InstallSource::Remote { url } => BinarySource::Remote(url.clone()),
+                    InstallSource::Built { source_path } => {
+                        BinarySource::Built(source_path.clone())
+                    }
+                },
+                yanked: spirit.yanked.clone(),
+            })
+            .collect();
+
+        Self {
+            schema_version: index.schema_version,
+            spirits,
+        }
+    }
+}
+
+impl From<BinaryIndex> for RegistryIndex {
+    fn from(wire: BinaryIndex) -> Self {
+        let spirits = wire
+            .spirits
+            .into_iter()
+            .map(|spirit| InstalledSpirit {
+                name: spirit.name,
+                versions: spirit.versions,
+                latest: spirit.latest,
+                installed_at: spirit.installed_at,
+                source: match spirit.source {
+                    BinarySource::Local(path) => InstallSource::Local { path },
+                    BinarySource::Remote(url) => InstallSource::Remote { url },
+                    BinarySource::Built(source_path) => InstallSource::Built { source_path },
+                },
+                yanked: spirit.yanked,
+            })
+            .collect();
+
+        Self {
+            schema_version: wire.schema_version,
+            spirits,
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -95,6 +246,9 @@ pub struct InstalledSpirit {
     pub installed_at: u64,
     /// Installation source
     pub source: InstallSource,
+    /// Yanked versions: skipped by resolution unless pinned exactly
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub yanked: Vec<String>,
 }
 
 impl InstalledSpirit {
@@ -111,8 +265,31 @@ impl InstalledSpirit {
         }
     }
 
+    /// Check if a version has been yanked
+    pub fn is_yanked(&self, version: &str) -> bool {
+        self.yanked.iter().any(|v| v == version)
+    }
+
+    /// Mark a version as yanked or restore it
+    pub fn set_yanked(&mut self, version: &str, yanked: bool) {
+        self.yanked.retain(|v| v != version);
+        if yanked {
+            self.yanked.push(version.to_string());
+        }
+    }
+
+    /// Highest installed version that has not been yanked
+    pub fn latest_unyanked(&self) -> Option<&str> {
+        self.versions
+            .iter()
+            .filter(|v| !self.is_yanked(v))
+            .max_by(|a, b| super::search::compare_versions(a, b))
+            .map(String::as_str)
+    }
+
     /// Remove a version from the installed list
     pub fn remove_version(&mut self, version: &str) {
+        self.yanked.retain(|v| v != version);
         self.versions.retain(|v| v != version);
         if self.latest == version && !self.versions.is_empty() {
             self.latest = self.versions.last().cloned().unwrap_or_default();
@@ -120,6 +297,35 @@ impl InstalledSpirit {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// INSTALL PLAN
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// What installing a spirit from a source directory writes
+///
+/// Produced by `LocalRegistry::plan_install` and carried out file by file
+/// by `install`, so a dry run reports exactly what an install does.
+#[derive(Debug, Clone)]
+pub struct InstallPlan {
+    /// Manifest read from the source
+    pub manifest: Manifest,
+    /// Source directory
+    pub source: PathBuf,
+    /// Version directory the files are written to
+    pub target_dir: PathBuf,
+    /// Files written, relative to `target_dir`, with their sizes in bytes
+    pub files: Vec<(PathBuf, u64)>,
+    /// This version is already installed, so installing it fails
+    pub already_installed: bool,
+}
+
+impl InstallPlan {
+    /// Total bytes written
+    pub fn size(&self) -> u64 {
+        self.files.iter().map(|(_, size)| size).sum()
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // INSTALL SOURCE
 // ═══════════════════════════════════════════════════════════════════════════
@@ -151,14 +357,20 @@ impl Default for InstallSource {
 /// Query for searching spirits
 #[derive(Debug, Clone, Default)]
 pub struct SpiritQuery {
-    /// Name pattern (supports partial matching)
+    /// Name pattern, compared according to `name_match`
     pub name: Option<String>,
+    /// How `name` is compared against Spirit names
+    pub name_match: MatchMode,
     /// Required capabilities
     pub capabilities: Vec<String>,
     /// Author filter
     pub author: Option<String>,
     /// Version constraint
     pub version: Option<String>,
+    /// Number of leading matches to skip
+    pub offset: usize,
+    /// Maximum number of matches to return (all, without one)
+    pub limit: Option<usize>,
 }
 
 impl SpiritQuery {
@@ -173,6 +385,12 @@ impl SpiritQuery {
         self
     }
 
+    /// Compare the name pattern using `mode`
+    pub fn with_name_match(mut self, mode: MatchMode) -> Self {
+        self.name_match = mode;
+        self
+    }
+
     /// Filter by author
     pub fn with_author(mut self, author: impl Into<String>) -> Self {
         self.author = Some(author.into());
@@ -191,6 +409,20 @@ impl SpiritQuery {
         self
     }
 
+    /// Return only `limit` matches, starting after the first `offset`
+    pub fn with_page(mut self, offset: usize, limit: usize) -> Self {
+        self.offset = offset;
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Returns true if `name` passes the name filter (always, without one)
+    pub fn matches_name(&self, name: &str) -> bool {
+        self.name
+            .as_deref()
+            .is_none_or(|pattern| self.name_match.matches(name, pattern))
+    }
+
     /// Check if query is empty (matches everything)
     pub fn is_empty(&self) -> bool {
         self.name.is_none()
@@ -200,6 +432,41 @@ impl SpiritQuery {
     }
 }
 
+/// One page of search results
+#[derive(Debug, Clone, Default)]
+pub struct SearchPage {
+    /// Matches within the requested page, in sorted order
+    pub results: Vec<SpiritSearchResult>,
+    /// Number of matches across all pages
+    pub total_count: usize,
+}
+
+impl SearchPage {
+    /// Cut the page `query` asks for out of the full, sorted match set
+    pub fn paginate(matches: Vec<SpiritSearchResult>, query: &SpiritQuery) -> Self {
+        let total_count = matches.len();
+        let results = matches
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect();
+        Self {
+            results,
+            total_count,
+        }
+    }
+
+    /// Check if the page holds no results
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Number of results on this page
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+}
+
 /// Result of a spirit search
 #[derive(Debug, Clone)]
 pub struct SpiritSearchResult {
@@ -213,6 +480,28 @@ pub struct SpiritSearchResult {
     pub path: PathBuf,
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// EVENTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A change to the registry, delivered to observers once it is persisted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryEvent {
+    /// First version of a spirit installed
+    Installed { name: String, version: String },
+    /// Another version of an already installed spirit installed
+    Updated { name: String, version: String },
+    /// A version removed; uninstalling a spirit reports each of its versions
+    Uninstalled { name: String, version: String },
+    /// A version marked as yanked
+    Yanked { name: String, version: String },
+    /// A yanked version restored
+    Unyanked { name: String, version: String },
+}
+
+/// Callback registered with `LocalRegistry::add_observer`
+pub type RegistryObserver = Box<dyn Fn(&RegistryEvent) + Send + Sync>;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // ERRORS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -255,6 +544,12 @@ pub enum RegistryError {
 
     #[error("Author key not found: {author}")]
     AuthorKeyNotFound { author: String },
+
+    #[error("All versions of '{0}' are yanked")]
+    Yanked(String),
+
+    #[error("Binary index error: {0}")]
+    Binary(String),
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -281,12 +576,112 @@ mod tests {
             latest: "0.1.0".to_string(),
             installed_at: 0,
             source: InstallSource::default(),
+            yanked: Vec::new(),
         });
 
         assert!(index.find("test-spirit").is_some());
         assert!(index.find("nonexistent").is_none());
     }
 
+    fn synthetic_index(count: usize) -> RegistryIndex {
+        let mut index = RegistryIndex::new();
+        for i in 0..count {
+            index.spirits.push(InstalledSpirit {
+                name: format!("spirit-{:05}", i),
+                versions: vec![
+                    "0.1.0".to_string(),
+                    "0.2.0".to_string(),
+                    "1.0.0".to_string(),
+                ],
+                latest: "1.0.0".to_string(),
+                installed_at: 1_700_000_000 + i as u64,
+                source: match i % 3 {
+                    0 => InstallSource::Local {
+                        path: PathBuf::from(format!("/spirits/spirit-{}", i)),
+                    },
+                    1 => InstallSource::Remote {
+                        url: format!("https://example.com/spirit-{}.spirit", i),
+                    },
+                    _ => InstallSource::Built {
+                        source_path: PathBuf::from(format!("/src/spirit-{}", i)),
+                    },
+                },
+                yanked: if i % 10 == 0 {
+                    vec!["0.1.0".to_string()]
+                } else {
+                    Vec::new()
+                },
+            });
+        }
+        index
+    }
+
+    #[test]
+    fn test_registry_index_binary_roundtrip() {
+        let index = synthetic_index(5_000);
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("index.bin");
+
+        index.save_binary(&path).unwrap();
+        let loaded = RegistryIndex::load_binary(&path).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&loaded).unwrap(),
+            serde_json::to_string(&index).unwrap()
+        );
+        // Byte-stable: re-encoding the decoded index reproduces the file
+        assert_eq!(loaded.to_binary().unwrap(), std::fs::read(&path).unwrap());
+        assert_eq!(index.to_binary().unwrap(), index.to_binary().unwrap());
+    }
+
+    #[test]
+    fn test_registry_index_binary_rejects_json() {
+        let json = serde_json::to_vec(&RegistryIndex::new()).unwrap();
+        assert!(matches!(
+            RegistryIndex::from_binary(&json),
+            Err(RegistryError::Binary(_))
+        ));
+    }
+
+    #[test]
+    fn test_registry_index_binary_is_smaller_than_json() {
+        let index = synthetic_index(5_000);
+        let json = serde_json::to_vec(&index).unwrap();
+        let binary = index.to_binary().unwrap();
+        assert!(binary.len() < json.len());
+    }
+
+    /// Wall-clock comparison, too noisy for shared CI runners; run with
+    /// `cargo test --release -p spirit_runtime -- --ignored`.
+    #[test]
+    #[ignore = "timing benchmark"]
+    fn test_registry_index_binary_loads_faster_than_json() {
+        use std::time::Instant;
+
+        let index = synthetic_index(5_000);
+        let json = serde_json::to_vec(&index).unwrap();
+        let binary = index.to_binary().unwrap();
+
+        let start = Instant::now();
+        for _ in 0..5 {
+            serde_json::from_slice::<RegistryIndex>(&json).unwrap();
+        }
+        let json_time = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..5 {
+            RegistryIndex::from_binary(&binary).unwrap();
+        }
+        let binary_time = start.elapsed();
+
+        assert!(
+            binary_time < json_time,
+            "binary {:?} vs json {:?}",
+            binary_time,
+            json_time
+        );
+    }
+
     #[test]
     fn test_installed_spirit_versions() {
         let mut spirit = InstalledSpirit {
@@ -295,6 +690,7 @@ mod tests {
             latest: "0.1.0".to_string(),
             installed_at: 0,
             source: InstallSource::default(),
+            yanked: Vec::new(),
         };
 
         assert!(spirit.has_version("0.1.0"));
@@ -309,6 +705,30 @@ mod tests {
         assert_eq!(spirit.latest, "0.1.0");
     }
 
+    #[test]
+    fn test_installed_spirit_yanked() {
+        let mut spirit = InstalledSpirit {
+            name: "test".to_string(),
+            versions: vec!["0.1.0".to_string(), "0.2.0".to_string()],
+            latest: "0.2.0".to_string(),
+            installed_at: 0,
+            source: InstallSource::default(),
+            yanked: Vec::new(),
+        };
+
+        assert_eq!(spirit.latest_unyanked(), Some("0.2.0"));
+
+        spirit.set_yanked("0.2.0", true);
+        assert!(spirit.is_yanked("0.2.0"));
+        assert_eq!(spirit.latest_unyanked(), Some("0.1.0"));
+
+        spirit.set_yanked("0.1.0", true);
+        assert_eq!(spirit.latest_unyanked(), None);
+
+        spirit.set_yanked("0.2.0", false);
+        assert_eq!(spirit.latest_unyanked(), Some("0.2.0"));
+    }
+
     #[test]
     fn test_spirit_query_builder() {
         let query = SpiritQuery::new()
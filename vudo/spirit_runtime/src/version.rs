@@ -92,6 +92,88 @@ impl SemVer {
     pub fn bump_patch(&self) -> Self {
         Self::new(self.major, self.minor, self.patch + 1)
     }
+
+    /// Advance the prerelease tag
+    ///
+    /// A trailing numeric identifier is incremented (`alpha.1` -> `alpha.2`),
+    /// otherwise `.1` is appended (`alpha` -> `alpha.1`). A stable version
+    /// moves to the first prerelease of the next patch (`1.2.3` -> `1.2.4-0`).
+    pub fn bump_prerelease(&self) -> Self {
+        match &self.prerelease {
+            None => self.bump_patch().with_prerelease("0"),
+            Some(pre) => {
+                let next = match pre.rsplit_once('.') {
+                    Some((head, n)) => match n.parse::<u64>() {
+                        Ok(n) => format!("{}.{}", head, n + 1),
+                        Err(_) => format!("{}.1", pre),
+                    },
+                    None => match pre.parse::<u64>() {
+                        Ok(n) => (n + 1).to_string(),
+                        Err(_) => format!("{}.1", pre),
+                    },
+                };
+                Self::new(self.major, self.minor, self.patch).with_prerelease(next)
+            }
+        }
+    }
+
+    /// Apply a bump of the given kind
+    ///
+    /// `BumpKind::Downgrade` is not a bump and returns the version unchanged.
+    pub fn bump(&self, kind: BumpKind) -> Self {
+        match kind {
+            BumpKind::Major => self.bump_major(),
+            BumpKind::Minor => self.bump_minor(),
+            BumpKind::Patch => self.bump_patch(),
+            BumpKind::PreRelease => self.bump_prerelease(),
+            BumpKind::Downgrade => self.clone(),
+        }
+    }
+
+    /// Classify the change from this version to `newer`
+    ///
+    /// Returns the most significant component that increased, `PreRelease`
+    /// if only the prerelease tag moved forward, `Downgrade` if `newer` has
+    /// lower precedence, and `None` if both have equal precedence (build
+    /// metadata is ignored).
+    pub fn bump_kind(&self, newer: &SemVer) -> Option<BumpKind> {
+        match newer.cmp(self) {
+            Ordering::Equal => None,
+            Ordering::Less => Some(BumpKind::Downgrade),
+            Ordering::Greater if newer.major != self.major => Some(BumpKind::Major),
+            Ordering::Greater if newer.minor != self.minor => Some(BumpKind::Minor),
+            Ordering::Greater if newer.patch != self.patch => Some(BumpKind::Patch),
+            Ordering::Greater => Some(BumpKind::PreRelease),
+        }
+    }
+}
+
+/// Kind of change between two versions, see [`SemVer::bump_kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BumpKind {
+    /// Major version increased (breaking change)
+    Major,
+    /// Minor version increased (new functionality)
+    Minor,
+    /// Patch version increased (bug fixes)
+    Patch,
+    /// Only the prerelease tag advanced
+    PreRelease,
+    /// The newer version has lower precedence
+    Downgrade,
+}
+
+impl fmt::Display for BumpKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BumpKind::Major => "major",
+            BumpKind::Minor => "minor",
+            BumpKind::Patch => "patch",
+            BumpKind::PreRelease => "prerelease",
+            BumpKind::Downgrade => "downgrade",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 impl Default for SemVer {
@@ -353,6 +435,75 @@ mod tests {
         assert!(!v1.is_compatible_with(&v4)); // Different major
     }
 
+    #[test]
+    fn test_bump_kind() {
+        let v = SemVer::new(1, 2, 3);
+        assert_eq!(v.bump_kind(&SemVer::new(2, 0, 0)), Some(BumpKind::Major));
+        assert_eq!(v.bump_kind(&SemVer::new(1, 3, 0)), Some(BumpKind::Minor));
+        assert_eq!(v.bump_kind(&SemVer::new(1, 2, 4)), Some(BumpKind::Patch));
+        assert_eq!(
+            v.bump_kind(&SemVer::new(1, 3, 0).with_prerelease("beta")),
+            Some(BumpKind::Minor)
+        );
+        assert_eq!(v.bump_kind(&v.clone().with_build("ci.7")), None);
+
+        let alpha = SemVer::new(2, 0, 0).with_prerelease("alpha");
+        assert_eq!(
+            alpha.bump_kind(&SemVer::new(2, 0, 0).with_prerelease("beta")),
+            Some(BumpKind::PreRelease)
+        );
+        assert_eq!(
+            alpha.bump_kind(&SemVer::new(2, 0, 0)),
+            Some(BumpKind::PreRelease)
+        );
+    }
+
+    #[test]
+    fn test_bump_kind_downgrade() {
+        let v = SemVer::new(1, 2, 3);
+        assert_eq!(
+            v.bump_kind(&SemVer::new(1, 2, 2)),
+            Some(BumpKind::Downgrade)
+        );
+        assert_eq!(
+            v.bump_kind(&SemVer::new(0, 9, 9)),
+            Some(BumpKind::Downgrade)
+        );
+        assert_eq!(
+            v.bump_kind(&v.clone().with_prerelease("rc.1")),
+            Some(BumpKind::Downgrade)
+        );
+        assert_eq!(v.bump(BumpKind::Downgrade), v);
+    }
+
+    #[test]
+    fn test_bump_by_kind() {
+        let v = SemVer::new(1, 2, 3);
+        for kind in [BumpKind::Major, BumpKind::Minor, BumpKind::Patch] {
+            assert_eq!(v.bump_kind(&v.bump(kind)), Some(kind));
+        }
+        assert_eq!(
+            v.bump(BumpKind::PreRelease),
+            SemVer::new(1, 2, 4).with_prerelease("0")
+        );
+
+        let rc = SemVer::new(1, 0, 0).with_prerelease("rc.1");
+        assert_eq!(
+            rc.bump(BumpKind::PreRelease),
+            SemVer::new(1, 0, 0).with_prerelease("rc.2")
+        );
+        assert_eq!(
+            rc.bump_kind(&rc.bump(BumpKind::PreRelease)),
+            Some(BumpKind::PreRelease)
+        );
+        assert_eq!(
+            SemVer::new(1, 0, 0)
+                .with_prerelease("rc")
+                .bump(BumpKind::PreRelease),
+            SemVer::new(1, 0, 0).with_prerelease("rc.1")
+        );
+    }
+
     #[test]
     fn test_bump_versions() {
         let v = SemVer::new(1, 2, 3);
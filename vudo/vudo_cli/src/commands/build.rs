@@ -33,6 +33,10 @@ pub struct BuildArgs {
     /// Output file path
     #[arg(short, long)]
     pub output: Option<PathBuf>,
+
+    /// Also list the code size of every function in the module
+    #[arg(long)]
+    pub size_report: bool,
 }
 
 pub async fn execute(args: BuildArgs, _config: &VudoConfig) -> Result<()> {
@@ -66,7 +70,7 @@ pub async fn execute(args: BuildArgs, _config: &VudoConfig) -> Result<()> {
     let src_path = project_path.join("src");
     let dol_files = find_dol_files(&src_path)?;
 
-    println!("\n{} DOL source files:", "Compiling".green().bold());
+    println!("\n{} DOL source files:", "[1/3] Compiling".green().bold());
     for file in &dol_files {
         println!("  - {:?}", file.strip_prefix(&project_path).unwrap_or(file));
     }
@@ -79,16 +83,28 @@ pub async fn execute(args: BuildArgs, _config: &VudoConfig) -> Result<()> {
 
     // Create a minimal valid WASM module as placeholder
     let wasm_module = create_placeholder_wasm(&manifest);
+    manifest
+        .verify_exports(&wasm_module)
+        .map_err(|e| anyhow::anyhow!("Module does not match manifest exports: {}", e))?;
 
-    fs::write(&output_path, wasm_module)
+    println!("{} {:?}", "[2/3] Writing".green().bold(), output_path);
+    fs::write(&output_path, &wasm_module)
         .with_context(|| format!("Failed to write output to {:?}", output_path))?;
 
+    println!("{} module", "[3/3] Analyzing".green().bold());
+    let report = WasmReport::analyze(&wasm_module)?;
+
     println!(
         "\n{} Built Spirit package: {:?}",
         "✓".green().bold(),
         output_path
     );
 
+    report.print_summary(args.target.starts_with("wasm"));
+    if args.size_report {
+        report.print_function_sizes();
+    }
+
     // If emit flag is set, show intermediate representation
     if let Some(emit_type) = args.emit {
         println!(
@@ -108,6 +124,141 @@ pub async fn execute(args: BuildArgs, _config: &VudoConfig) -> Result<()> {
     Ok(())
 }
 
+/// Size and shape of a compiled module, shown after a build
+struct WasmReport {
+    size: usize,
+    exports: u32,
+    memory_pages: u64,
+    data_bytes: usize,
+    /// (section name, content bytes) in module order
+    sections: Vec<(String, usize)>,
+    /// (function index, export name, body bytes) for defined functions
+    functions: Vec<(u32, Option<String>, usize)>,
+}
+
+impl WasmReport {
+    fn analyze(wasm: &[u8]) -> Result<Self> {
+        use wasmparser::{ExternalKind, Parser, Payload, TypeRef};
+
+        let mut report = WasmReport {
+            size: wasm.len(),
+            exports: 0,
+            memory_pages: 0,
+            data_bytes: 0,
+            sections: Vec::new(),
+            functions: Vec::new(),
+        };
+        let mut imported_functions = 0;
+        let mut export_names = std::collections::HashMap::new();
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            let payload = payload.context("Failed to parse built module")?;
+            if let Payload::CustomSection(reader) = &payload {
+                report.sections.push((
+                    format!("custom \"{}\"", reader.name()),
+                    reader.range().len(),
+                ));
+            } else if let Some((id, range)) = payload.as_section() {
+                report
+                    .sections
+                    .push((section_name(id).to_string(), range.len()));
+            }
+
+            match payload {
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        match import?.ty {
+                            TypeRef::Func(_) => imported_functions += 1,
+                            TypeRef::Memory(memory) => {
+                                report.memory_pages = report.memory_pages.max(memory.initial)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Payload::MemorySection(reader) => {
+                    for memory in reader {
+                        report.memory_pages = report.memory_pages.max(memory?.initial);
+                    }
+                }
+                Payload::ExportSection(reader) => {
+                    report.exports = reader.count();
+                    for export in reader {
+                        let export = export?;
+                        if export.kind == ExternalKind::Func {
+                            export_names.insert(export.index, export.name.to_string());
+                        }
+                    }
+                }
+                Payload::DataSection(reader) => {
+                    for data in reader {
+                        report.data_bytes += data?.data.len();
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let index = imported_functions + report.functions.len() as u32;
+                    let name = export_names.get(&index).cloned();
+                    report.functions.push((index, name, body.range().len()));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn print_summary(&self, show_sections: bool) {
+        println!("\n{}", "Build summary".cyan().bold());
+        println!("  {:<16}{} bytes", "WASM size", self.size);
+        println!("  {:<16}{}", "Exports", self.exports);
+        println!("  {:<16}{}", "Functions", self.functions.len());
+        println!(
+            "  {:<16}{} memory pages ({} KiB), {} data bytes",
+            "Instantiation",
+            self.memory_pages,
+            self.memory_pages * 64,
+            self.data_bytes
+        );
+
+        if show_sections {
+            println!("  Sections:");
+            for (name, size) in &self.sections {
+                println!("    {:<14}{:>8} bytes", name, size);
+            }
+        }
+    }
+
+    fn print_function_sizes(&self) {
+        println!("\n{}", "Function sizes".cyan().bold());
+        for (index, name, size) in &self.functions {
+            let label = match name {
+                Some(name) => format!("#{} {}", index, name),
+                None => format!("#{}", index),
+            };
+            println!("    {:<24}{:>8} bytes", label, size);
+        }
+    }
+}
+
+fn section_name(id: u8) -> &'static str {
+    match id {
+        1 => "type",
+        2 => "import",
+        3 => "function",
+        4 => "table",
+        5 => "memory",
+        6 => "global",
+        7 => "export",
+        8 => "start",
+        9 => "element",
+        10 => "code",
+        11 => "data",
+        12 => "datacount",
+        13 => "tag",
+        _ => "unknown",
+    }
+}
+
 fn find_dol_files(dir: &PathBuf) -> Result<Vec<PathBuf>> {
     let mut dol_files = Vec::new();
 
@@ -3,7 +3,8 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::Colorize;
-use std::path::PathBuf;
+use spirit_runtime::manifest::{Capability, Manifest, ManifestError};
+use std::path::{Path, PathBuf};
 
 use crate::config::VudoConfig;
 
@@ -20,6 +21,11 @@ pub struct CheckArgs {
     /// Output format
     #[arg(long, value_name = "FORMAT", default_value = "pretty")]
     pub format: OutputFormat,
+
+    /// Rewrite the manifest's capability list: add capabilities the built
+    /// module imports, drop duplicates and sort it
+    #[arg(long)]
+    pub fix: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, clap::ValueEnum)]
@@ -32,15 +38,28 @@ pub enum OutputFormat {
 
 pub async fn execute(args: CheckArgs, _config: &VudoConfig) -> Result<()> {
     let path = args.path.unwrap_or_else(|| PathBuf::from("."));
+    let fixes = if args.fix {
+        fix_manifest(&path)?
+    } else {
+        Vec::new()
+    };
 
     if args.format == OutputFormat::Json {
-        run_json_check(&path, args.strict).await
+        run_json_check(&path, args.strict, &fixes).await
     } else {
-        run_pretty_check(&path, args.strict).await
+        run_pretty_check(&path, args.strict, &fixes).await
     }
 }
 
-async fn run_pretty_check(path: &PathBuf, strict: bool) -> Result<()> {
+async fn run_pretty_check(path: &PathBuf, strict: bool, fixes: &[String]) -> Result<()> {
+    if !fixes.is_empty() {
+        println!("{}", "Fixing manifest...".cyan().bold());
+        for fix in fixes {
+            println!("  {} {}", "fixed".green(), fix);
+        }
+        println!();
+    }
+
     println!("{}", "Checking DOL files...".cyan().bold());
     println!();
 
@@ -60,8 +79,9 @@ async fn run_pretty_check(path: &PathBuf, strict: bool) -> Result<()> {
 
     // Collect .dol files
     let dol_files = collect_dol_files(path)?;
+    let manifest_check = check_manifest(path)?;
 
-    if dol_files.is_empty() {
+    if dol_files.is_empty() && manifest_check.is_none() {
         println!("{}", "No .dol files found.".yellow());
         return Ok(());
     }
@@ -97,6 +117,22 @@ async fn run_pretty_check(path: &PathBuf, strict: bool) -> Result<()> {
         }
     }
 
+    if let Some((manifest, problems)) = &manifest_check {
+        let relative_path = manifest
+            .strip_prefix(std::env::current_dir()?)
+            .unwrap_or(manifest);
+        print!("  {} {}... ", "Checking".cyan(), relative_path.display());
+        if problems.is_empty() {
+            println!("{}", "OK".green());
+        } else {
+            println!("{}", "ERROR".red());
+            for problem in problems {
+                println!("    {} {}", "→".red(), problem);
+            }
+            errors += problems.len();
+        }
+    }
+
     println!();
     println!("{}", "─".repeat(60).dimmed());
 
@@ -126,7 +162,7 @@ async fn run_pretty_check(path: &PathBuf, strict: bool) -> Result<()> {
     Ok(())
 }
 
-async fn run_json_check(path: &PathBuf, strict: bool) -> Result<()> {
+async fn run_json_check(path: &PathBuf, strict: bool, fixes: &[String]) -> Result<()> {
     // Check if path exists
     if !path.exists() {
         let output = serde_json::json!({
@@ -174,6 +210,18 @@ async fn run_json_check(path: &PathBuf, strict: bool) -> Result<()> {
         }
     }
 
+    if let Some((manifest, problems)) = check_manifest(path)? {
+        let relative_path = manifest
+            .strip_prefix(std::env::current_dir()?)
+            .unwrap_or(&manifest);
+        total_errors += problems.len();
+        file_results.push(serde_json::json!({
+            "file": relative_path.to_string_lossy(),
+            "status": if problems.is_empty() { "ok" } else { "error" },
+            "messages": problems,
+        }));
+    }
+
     let output = serde_json::json!({
         "success": total_errors == 0,
         "mode": if strict { "strict" } else { "standard" },
@@ -182,6 +230,7 @@ async fn run_json_check(path: &PathBuf, strict: bool) -> Result<()> {
         "errors": total_errors,
         "warnings": total_warnings,
         "results": file_results,
+        "fixes": fixes,
         "note": "Full DOL parser integration coming soon",
     });
 
@@ -194,6 +243,201 @@ async fn run_json_check(path: &PathBuf, strict: bool) -> Result<()> {
     Ok(())
 }
 
+/// Validate a project's manifest, and its built module against the
+/// exports and capabilities the manifest declares
+///
+/// Returns the manifest path with every problem found, or `None` unless
+/// `path` is a project directory with a runtime-format manifest or one
+/// that is not valid TOML. Exports and imports are only checked once
+/// `<name>.spirit` has been built.
+fn check_manifest(path: &Path) -> Result<Option<(PathBuf, Vec<String>)>> {
+    let manifest_path = path.join("manifest.toml");
+    if let Some(error) = syntax_error(&manifest_path)? {
+        return Ok(Some((manifest_path, vec![error])));
+    }
+
+    let Some((manifest_path, manifest)) = load_manifest(path)? else {
+        return Ok(None);
+    };
+    let mut problems: Vec<String> = match manifest.validate_all() {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors.iter().map(ToString::to_string).collect(),
+    };
+
+    let wasm = read_module(path, &manifest)?;
+    if let Some(wasm) = wasm.as_deref().filter(|_| !manifest.exports.is_empty()) {
+        if let Err(e) = manifest.verify_exports(wasm) {
+            problems.push(e.to_string());
+        }
+    }
+
+    match fix_capabilities(&manifest.capabilities, wasm.as_deref()) {
+        Ok((_, changes)) => problems.extend(changes.iter().filter_map(CapabilityChange::problem)),
+        Err(e) => problems.push(e.to_string()),
+    }
+
+    Ok(Some((manifest_path, problems)))
+}
+
+/// Apply the capability fixes to a project's manifest and rewrite it
+///
+/// Returns a description of each change; the file is left untouched when
+/// there are none.
+fn fix_manifest(path: &Path) -> Result<Vec<String>> {
+    let Some((manifest_path, mut manifest)) = load_manifest(path)? else {
+        return Ok(Vec::new());
+    };
+    let wasm = read_module(path, &manifest)?;
+    let (capabilities, changes) = fix_capabilities(&manifest.capabilities, wasm.as_deref())?;
+    if changes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    manifest.capabilities = capabilities;
+    let content = manifest
+        .to_toml()
+        .map_err(|e| anyhow::anyhow!("Failed to serialize manifest: {}", e))?;
+    std::fs::write(&manifest_path, content)
+        .with_context(|| format!("Failed to write manifest at {:?}", manifest_path))?;
+
+    Ok(changes.iter().map(ToString::to_string).collect())
+}
+
+/// Parse `path/manifest.toml`, or `None` if there is no runtime-format
+/// manifest there
+fn load_manifest(path: &Path) -> Result<Option<(PathBuf, Manifest)>> {
+    let manifest_path = path.join("manifest.toml");
+    if !manifest_path.is_file() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read manifest at {:?}", manifest_path))?;
+    // Manifests in the older `vudo new` layout are not checked
+    Ok(toml::from_str::<Manifest>(&content)
+        .ok()
+        .map(|manifest| (manifest_path, manifest)))
+}
+
+/// The TOML syntax error in the manifest at `manifest_path`, prefixed with
+/// `manifest.toml:<line>:<column>:` when its position is known
+///
+/// Manifests in either layout must be valid TOML, so unlike a manifest in
+/// the older layout this is reported.
+fn syntax_error(manifest_path: &Path) -> Result<Option<String>> {
+    if !manifest_path.is_file() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read manifest at {:?}", manifest_path))?;
+    if content.parse::<toml::Table>().is_ok() {
+        return Ok(None);
+    }
+    Ok(Manifest::from_toml(&content).err().map(|e| match e {
+        ManifestError::ParseErrorAt {
+            message,
+            line,
+            column,
+        } => format!("manifest.toml:{}:{}: {}", line, column, message),
+        other => other.to_string(),
+    }))
+}
+
+/// The built `<name>.spirit` module next to the manifest, if any
+fn read_module(path: &Path, manifest: &Manifest) -> Result<Option<Vec<u8>>> {
+    let module_path = path.join(format!("{}.spirit", manifest.name));
+    if !module_path.is_file() {
+        return Ok(None);
+    }
+    std::fs::read(&module_path)
+        .map(Some)
+        .with_context(|| format!("Failed to read module at {:?}", module_path))
+}
+
+/// One mechanical correction to a manifest's capability list
+#[derive(Debug, Clone, PartialEq)]
+enum CapabilityChange {
+    RemovedDuplicate(Capability),
+    AddedImported(Capability),
+    Sorted,
+}
+
+impl CapabilityChange {
+    /// The problem `vudo check` reports without `--fix`; an unsorted list
+    /// is not one
+    fn problem(&self) -> Option<String> {
+        match self {
+            CapabilityChange::RemovedDuplicate(capability) => Some(format!(
+                "Capability {} is declared more than once (fix with --fix)",
+                capability
+            )),
+            CapabilityChange::AddedImported(capability) => Some(format!(
+                "Module imports need undeclared capability {} (fix with --fix)",
+                capability
+            )),
+            CapabilityChange::Sorted => None,
+        }
+    }
+}
+
+impl std::fmt::Display for CapabilityChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CapabilityChange::RemovedDuplicate(capability) => {
+                write!(f, "Removed duplicate capability {}", capability)
+            }
+            CapabilityChange::AddedImported(capability) => {
+                write!(
+                    f,
+                    "Added capability {} needed by the module's imports",
+                    capability
+                )
+            }
+            CapabilityChange::Sorted => write!(f, "Sorted capabilities"),
+        }
+    }
+}
+
+/// The canonical capability list for `declared`: deduplicated, extended
+/// with whatever `wasm` imports need, in [`Capability::all`] order
+fn fix_capabilities(
+    declared: &[Capability],
+    wasm: Option<&[u8]>,
+) -> Result<(Vec<Capability>, Vec<CapabilityChange>)> {
+    let mut changes = Vec::new();
+    let mut capabilities: Vec<Capability> = Vec::new();
+    for capability in declared {
+        if capabilities.contains(capability) {
+            changes.push(CapabilityChange::RemovedDuplicate(capability.clone()));
+        } else {
+            capabilities.push(capability.clone());
+        }
+    }
+
+    if let Some(wasm) = wasm {
+        let required = vudo_vm::sandbox::required_capabilities(wasm)
+            .map_err(|e| anyhow::anyhow!("Failed to read module imports: {}", e))?;
+        for required in required {
+            let capability = Capability::try_from(required)
+                .map_err(|e| anyhow::anyhow!("Module imports need {:?}: {}", required, e))?;
+            if !capabilities.contains(&capability) {
+                changes.push(CapabilityChange::AddedImported(capability.clone()));
+                capabilities.push(capability);
+            }
+        }
+    }
+
+    let canonical = Capability::all();
+    let rank = |capability: &Capability| canonical.iter().position(|c| c == capability);
+    if !capabilities.is_sorted_by_key(rank) {
+        capabilities.sort_by_key(rank);
+        changes.push(CapabilityChange::Sorted);
+    }
+
+    Ok((capabilities, changes))
+}
+
 fn collect_dol_files(path: &PathBuf) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
 
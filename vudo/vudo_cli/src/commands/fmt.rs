@@ -13,11 +13,31 @@ pub struct FmtArgs {
     #[arg(value_name = "PATH")]
     pub path: Option<PathBuf>,
 
-    /// Check formatting without modifying files (CI mode)
+    /// Check formatting without modifying files (CI mode). Prints a diff of
+    /// each file that would change and exits with status 2 if any would.
     #[arg(long)]
     pub check: bool,
 }
 
+/// Exit status of `vudo fmt --check` when files need formatting, distinct
+/// from the status 1 used for other failures
+pub const UNFORMATTED_EXIT_CODE: i32 = 2;
+
+/// Returned by `--check` when some files would be reformatted
+#[derive(Debug)]
+pub struct Unformatted {
+    /// Number of files that would change
+    pub count: usize,
+}
+
+impl std::fmt::Display for Unformatted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} file(s) need formatting", self.count)
+    }
+}
+
+impl std::error::Error for Unformatted {}
+
 pub async fn execute(args: FmtArgs, _config: &VudoConfig) -> Result<()> {
     run(args).await
 }
@@ -156,6 +176,9 @@ async fn run_check_mode(path: &PathBuf) -> Result<()> {
 
                 if formatted != content {
                     println!("{}", "NEEDS FORMATTING".yellow());
+                    for line in diff_lines(&content, &formatted) {
+                        println!("    {}", line);
+                    }
                     needs_formatting.push(relative_path.to_path_buf());
                 } else {
                     println!("{}", "OK".green());
@@ -201,13 +224,45 @@ async fn run_check_mode(path: &PathBuf) -> Result<()> {
             .italic()
     );
 
-    if !needs_formatting.is_empty() || errors > 0 {
-        anyhow::bail!("Formatting check failed");
+    if errors > 0 {
+        anyhow::bail!("Formatting check failed with {} error(s)", errors);
+    }
+    if !needs_formatting.is_empty() {
+        return Err(Unformatted {
+            count: needs_formatting.len(),
+        }
+        .into());
     }
 
     Ok(())
 }
 
+/// Describe how `formatted` differs from `original`, one entry per changed line.
+///
+/// The formatter only edits within lines and drops trailing blank lines, so
+/// lines are compared by position. Lines are quoted so whitespace changes
+/// are visible.
+fn diff_lines(original: &str, formatted: &str) -> Vec<String> {
+    let before: Vec<&str> = original.split('\n').collect();
+    let after: Vec<&str> = formatted.split('\n').collect();
+
+    let mut diff = Vec::new();
+    for i in 0..before.len().max(after.len()) {
+        let (old, new) = (before.get(i), after.get(i));
+        if old == new {
+            continue;
+        }
+        diff.push(format!("{}:", format!("line {}", i + 1).dimmed()));
+        if let Some(old) = old {
+            diff.push(format!("{}", format!("- {:?}", old).red()));
+        }
+        if let Some(new) = new {
+            diff.push(format!("{}", format!("+ {:?}", new).green()));
+        }
+    }
+    diff
+}
+
 fn format_dol_content(content: &str) -> String {
     // Basic formatting for now:
     // 1. Trim trailing whitespace from each line
@@ -3,10 +3,12 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::*;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use crate::config::VudoConfig;
 use spirit_runtime::registry::{LocalRegistry, Registry};
+use spirit_runtime::Manifest;
 
 #[derive(Args, Debug)]
 pub struct InstallArgs {
@@ -16,9 +18,17 @@ pub struct InstallArgs {
     /// Force reinstall if already installed
     #[arg(short, long)]
     pub force: bool,
+
+    /// Show what would be installed without modifying the registry
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 pub async fn execute(args: InstallArgs, _config: &VudoConfig) -> Result<()> {
+    if args.dry_run {
+        return dry_run(&args).await;
+    }
+
     println!(
         "{} Spirit from: {}",
         "Installing".green().bold(),
@@ -76,6 +86,10 @@ pub async fn execute(args: InstallArgs, _config: &VudoConfig) -> Result<()> {
         }
     }
 
+    if let Ok(manifest) = read_source_manifest(&args.source) {
+        warn_yanked_dependencies(&registry, &manifest);
+    }
+
     // Install spirit
     let source_str = args
         .source
@@ -100,3 +114,114 @@ pub async fn execute(args: InstallArgs, _config: &VudoConfig) -> Result<()> {
 
     Ok(())
 }
+
+/// Print what installing `args.source` would write, without touching the
+/// registry
+///
+/// The plan comes from [`LocalRegistry::plan_install`], which `install`
+/// itself carries out, so it lists exactly the files an install copies.
+async fn dry_run(args: &InstallArgs) -> Result<()> {
+    println!(
+        "{} install plan for: {}",
+        "Dry run".yellow().bold(),
+        args.source.display().to_string().cyan()
+    );
+
+    let mut registry = LocalRegistry::new();
+    registry.load().await.context("Failed to read registry")?;
+
+    let plan = registry
+        .plan_install(&args.source)
+        .await
+        .context("Failed to install Spirit")?;
+    let name = &plan.manifest.name;
+    let version = plan.manifest.version.to_string();
+    let replaces = args.force && registry.is_installed(name);
+    if plan.already_installed && !args.force {
+        anyhow::bail!(
+            "Failed to install Spirit: {}@{} is already installed (use --force to reinstall)",
+            name,
+            version
+        );
+    }
+
+    warn_yanked_dependencies(&registry, &plan.manifest);
+
+    println!("  {}@{} -> {}", name, version, plan.target_dir.display());
+    for (file, size) in &plan.files {
+        println!("    {} ({} bytes)", file.display(), size);
+    }
+    println!("  {} {} bytes", "Total size:".cyan(), plan.size());
+    if replaces {
+        println!(
+            "  {} existing installation of {}",
+            "Replaces:".yellow(),
+            name
+        );
+    }
+
+    let new_capabilities: Vec<String> = match registry.get_manifest(name, None).await {
+        Ok(current) => plan
+            .manifest
+            .capabilities
+            .iter()
+            .filter(|c| !current.capabilities.contains(c))
+            .map(|c| format!("{:?}", c))
+            .collect(),
+        Err(_) => plan
+            .manifest
+            .capabilities
+            .iter()
+            .map(|c| format!("{:?}", c))
+            .collect(),
+    };
+    if new_capabilities.is_empty() {
+        println!("  {} none", "New capabilities:".cyan());
+    } else {
+        println!(
+            "  {} {}",
+            "New capabilities:".cyan(),
+            new_capabilities.join(", ")
+        );
+    }
+
+    println!("\n{} No changes made (dry run)", "✓".green().bold());
+
+    Ok(())
+}
+
+/// Warn about registry dependencies of `manifest` that resolve to a yanked
+/// version
+///
+/// Install does not fetch dependencies, so ones that do not resolve are
+/// not an error here.
+fn warn_yanked_dependencies(registry: &LocalRegistry, manifest: &Manifest) {
+    for (name, dep) in &manifest.dependencies {
+        if !dep.is_registry() {
+            continue;
+        }
+        let request = HashMap::from([(name.clone(), dep.clone())]);
+        let Ok(resolved) = registry.dependency_resolver().resolve(&request) else {
+            continue;
+        };
+        for dep in resolved.iter().filter(|d| d.yanked) {
+            println!(
+                "  {} {}@{} is yanked (pinned exactly)",
+                "Warning:".yellow(),
+                dep.name,
+                dep.version
+            );
+        }
+    }
+}
+
+/// Read `manifest.json` or `manifest.toml` from a spirit source directory
+fn read_source_manifest(dir: &Path) -> Result<Manifest> {
+    let json_path = dir.join("manifest.json");
+    let path = if json_path.exists() {
+        json_path
+    } else {
+        dir.join("manifest.toml")
+    };
+    Manifest::from_file(&path).with_context(|| format!("Failed to read manifest: {:?}", path))
+}
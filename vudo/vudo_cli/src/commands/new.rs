@@ -7,6 +7,7 @@ use std::fs;
 use std::path::PathBuf;
 
 use crate::config::VudoConfig;
+use vudo_vm::CapabilityProfile;
 
 #[derive(Args, Debug)]
 pub struct NewArgs {
@@ -20,6 +21,10 @@ pub struct NewArgs {
     /// Path where to create the project (defaults to current directory)
     #[arg(short, long)]
     pub path: Option<PathBuf>,
+
+    /// Capability profile to declare (minimal, network, storage, system, custom:A,B)
+    #[arg(long)]
+    pub profile: Option<CapabilityProfile>,
 }
 
 pub async fn execute(args: NewArgs, _config: &VudoConfig) -> Result<()> {
@@ -43,7 +48,7 @@ pub async fn execute(args: NewArgs, _config: &VudoConfig) -> Result<()> {
     fs::create_dir_all(project_path.join("tests"))?;
 
     // Create manifest.toml
-    let manifest_content = create_manifest(&args.name, template);
+    let manifest_content = create_manifest(&args.name, template, args.profile.as_ref());
     fs::write(project_path.join("manifest.toml"), manifest_content)
         .context("Failed to write manifest.toml")?;
 
@@ -76,7 +81,12 @@ pub async fn execute(args: NewArgs, _config: &VudoConfig) -> Result<()> {
     Ok(())
 }
 
-fn create_manifest(name: &str, template: &str) -> String {
+fn create_manifest(name: &str, template: &str, profile: Option<&CapabilityProfile>) -> String {
+    let profile_line = match profile {
+        Some(profile) => format!("profile = \"{}\"\n", profile),
+        None => String::new(),
+    };
+
     format!(
         r#"[spirit]
 name = "{}"
@@ -85,7 +95,7 @@ description = "A VUDO Spirit created from {} template"
 author = "Your Name <you@example.com>"
 
 [capabilities]
-# Capabilities required by this Spirit
+{}# Capabilities required by this Spirit
 # compute = true
 # memory = true
 # network = false
@@ -99,7 +109,7 @@ author = "Your Name <you@example.com>"
 target = "wasm32"
 optimization = "release"
 "#,
-        name, template
+        name, template, profile_line
     )
 }
 
@@ -7,6 +7,8 @@ use std::fs;
 use std::path::PathBuf;
 
 use crate::config::VudoConfig;
+use spirit_runtime::Manifest;
+use vudo_vm::CapabilityType;
 
 #[derive(Args, Debug)]
 pub struct PublishArgs {
@@ -33,6 +35,10 @@ pub struct PublishArgs {
     /// Registry URL (defaults to config default)
     #[arg(long)]
     pub registry: Option<String>,
+
+    /// Refuse to publish unless the manifest carries a valid signature
+    #[arg(long)]
+    pub require_signed: bool,
 }
 
 pub async fn execute(args: PublishArgs, config: &VudoConfig) -> Result<()> {
@@ -64,6 +70,9 @@ pub async fn execute(args: PublishArgs, config: &VudoConfig) -> Result<()> {
         );
     }
 
+    let (manifest, wasm) = unpack_package(&package_data)?;
+    check_package(&manifest, &wasm, args.require_signed)?;
+
     // Determine visibility
     let visibility = if args.public {
         "public"
@@ -121,6 +130,101 @@ pub async fn execute(args: PublishArgs, config: &VudoConfig) -> Result<()> {
     Ok(())
 }
 
+/// Split a package into its manifest and WASM module, skipping the
+/// `vudo sign` envelope if present.
+pub(crate) fn unpack_package(package_data: &[u8]) -> Result<(Manifest, Vec<u8>)> {
+    // SIGNED\n<32-byte key>\n<64-byte signature>\n<package>
+    const ENVELOPE_LEN: usize = 7 + 32 + 1 + 64 + 1;
+
+    let package = if package_data.starts_with(b"SIGNED\n") {
+        package_data
+            .get(ENVELOPE_LEN..)
+            .context("Malformed package: truncated signature envelope")?
+    } else {
+        package_data
+    };
+
+    let body = package
+        .strip_prefix(b"MANIFEST\n")
+        .context("Malformed package: missing MANIFEST section")?;
+    let wasm_start =
+        find(body, b"\n\nWASM\n").context("Malformed package: missing WASM section")?;
+
+    let manifest_toml = std::str::from_utf8(&body[..wasm_start])
+        .context("Malformed package: manifest is not UTF-8")?;
+    let manifest = Manifest::from_toml(manifest_toml)
+        .map_err(|e| anyhow::anyhow!("Malformed package: {}", e))?;
+
+    let wasm = &body[wasm_start + b"\n\nWASM\n".len()..];
+    let wasm_end = find(wasm, b"\n\nFILE\n").unwrap_or(wasm.len());
+
+    Ok((manifest, wasm[..wasm_end].to_vec()))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Pre-publish checks; each failure names the check that failed.
+fn check_package(manifest: &Manifest, wasm: &[u8], require_signed: bool) -> Result<()> {
+    println!("\n{} package", "Checking".green().bold());
+
+    manifest
+        .validate()
+        .map_err(|e| anyhow::anyhow!("Manifest validation failed: {}", e))?;
+    println!("  {} manifest is valid", "✓".green());
+
+    match &manifest.signature {
+        Some(_) => {
+            manifest
+                .verify()
+                .map_err(|e| anyhow::anyhow!("Manifest signature is invalid: {}", e))?;
+            println!("  {} manifest signature verified", "✓".green());
+        }
+        None if require_signed => {
+            anyhow::bail!(
+                "Manifest is unsigned and --require-signed was given. Run 'vudo sign' first."
+            );
+        }
+        None => println!("  {} manifest is unsigned", "!".yellow()),
+    }
+
+    let mut sandbox = vudo_vm::sandbox::Sandbox::new_with_defaults(
+        wasm,
+        [0u8; 32],
+        vudo_vm::sandbox::ResourceLimits::default(),
+    )
+    .map_err(|e| anyhow::anyhow!("WASM module failed to compile: {}", e))?;
+    sandbox
+        .initialize()
+        .map_err(|e| anyhow::anyhow!("WASM module failed to compile: {}", e))?;
+    println!("  {} WASM module compiles", "✓".green());
+
+    let declared: Vec<CapabilityType> = manifest
+        .capabilities
+        .iter()
+        .cloned()
+        .map(CapabilityType::from)
+        .collect();
+    let undeclared: Vec<String> = vudo_vm::sandbox::required_capabilities(wasm)
+        .map_err(|e| anyhow::anyhow!("WASM module failed to compile: {}", e))?
+        .into_iter()
+        .filter(|required| !declared.contains(required))
+        .map(|required| format!("{:?}", required))
+        .collect();
+    if !undeclared.is_empty() {
+        anyhow::bail!(
+            "WASM imports need capabilities missing from the manifest: {}",
+            undeclared.join(", ")
+        );
+    }
+    println!("  {} declared capabilities cover imports", "✓".green());
+
+    Ok(())
+}
+
 fn find_spirit_package(dir: &str) -> Result<PathBuf> {
     let dir_path = PathBuf::from(dir);
 
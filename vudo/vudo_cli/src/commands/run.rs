@@ -3,17 +3,33 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::*;
+use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use wasmtime::Val;
 
 use crate::config::VudoConfig;
-use vudo_vm::{CapabilitySet, ResourceLimits};
+use spirit_runtime::{Capability, PricingModel};
+use vudo_vm::host::PublicKey;
+use vudo_vm::sandbox::{ExecutionOutcome, ResourceLimits, Sandbox, SandboxError, WASM_PAGE_SIZE};
+use vudo_vm::trace::DEFAULT_TRACE_CAPACITY;
+use vudo_vm::{
+    public_key_from_hex, public_key_to_hex, CapabilityGrant, CapabilityProfile, CapabilitySet,
+    CapabilityType,
+};
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
 
 #[derive(Args, Debug)]
 pub struct RunArgs {
-    /// Path to Spirit package or project
+    /// Path to Spirit package, project, or `.wat` source file
     pub spirit: Option<PathBuf>,
 
+    /// Function to call when running a `.wat` file (default: _start, then main)
+    #[arg(long, value_name = "NAME")]
+    pub entry: Option<String>,
+
     /// Fuel limit for execution (default: 1,000,000)
     #[arg(long, default_value = "1000000")]
     pub fuel: u64,
@@ -26,6 +42,10 @@ pub struct RunArgs {
     #[arg(long)]
     pub capabilities: Option<Vec<String>>,
 
+    /// Capability profile to grant (minimal, network, storage, system, custom:A,B)
+    #[arg(long)]
+    pub profile: Option<CapabilityProfile>,
+
     /// Sandbox isolation level (strict, normal, permissive)
     #[arg(long, default_value = "normal")]
     pub sandbox: String,
@@ -34,18 +54,99 @@ pub struct RunArgs {
     #[arg(long)]
     pub trace: bool,
 
+    /// Owner public key as 64 hex characters (default: an ephemeral key)
+    #[arg(long, value_name = "HEX", value_parser = parse_owner)]
+    pub owner: Option<PublicKey>,
+
+    #[command(flatten)]
+    pub limits: LimitOverrides,
+
+    #[command(flatten)]
+    pub pricing: PricingOverrides,
+
     /// Arguments to pass to the Spirit
     #[arg(last = true)]
     pub args: Vec<String>,
 }
 
+/// Resource limit overrides applied on top of the sandbox defaults.
+///
+/// The result is checked with `ResourceLimits::validate`, so values stay
+/// within the sandbox maxima.
+#[derive(Args, Debug, Default)]
+#[command(next_help_heading = "Limit overrides")]
+pub struct LimitOverrides {
+    /// Fuel available to the Spirit (replaces --fuel)
+    #[arg(long, conflicts_with = "fuel")]
+    pub max_fuel: Option<u64>,
+
+    /// Linear memory limit in megabytes (replaces --memory)
+    #[arg(long, conflicts_with = "memory")]
+    pub max_memory_mb: Option<u64>,
+
+    /// Wall-clock limit in seconds
+    #[arg(long)]
+    pub timeout_secs: Option<u64>,
+
+    /// Maximum WASM table elements
+    #[arg(long)]
+    pub max_table_elements: Option<u32>,
+}
+
+/// Pricing overrides applied on top of the manifest's `[pricing]` table,
+/// or the default pricing when there is no manifest.
+#[derive(Args, Debug, Default)]
+#[command(next_help_heading = "Pricing overrides")]
+pub struct PricingOverrides {
+    /// Base cost of the run in microcredits
+    #[arg(
+        long,
+        value_name = "MICROCREDITS",
+        value_parser = parse_price,
+        allow_negative_numbers = true
+    )]
+    pub pricing_base: Option<u64>,
+
+    /// Cost per 1000 fuel units in microcredits
+    #[arg(
+        long,
+        value_name = "MICROCREDITS",
+        value_parser = parse_price,
+        allow_negative_numbers = true
+    )]
+    pub pricing_per_fuel: Option<u64>,
+
+    /// Charge nothing for the run, zeroing every price
+    #[arg(long, conflicts_with_all = ["pricing_base", "pricing_per_fuel"])]
+    pub free: bool,
+}
+
+impl PricingOverrides {
+    /// Apply the overrides to `pricing`
+    fn apply(&self, pricing: PricingModel) -> PricingModel {
+        if self.free {
+            return PricingModel::free();
+        }
+        PricingModel {
+            base_cost: self.pricing_base.unwrap_or(pricing.base_cost),
+            per_fuel_cost: self.pricing_per_fuel.unwrap_or(pricing.per_fuel_cost),
+            ..pricing
+        }
+    }
+}
+
 pub async fn execute(args: RunArgs, _config: &VudoConfig) -> Result<()> {
-    let spirit_path = args.spirit.unwrap_or_else(|| {
+    let spirit_path = args.spirit.clone().unwrap_or_else(|| {
         // Look for built Spirit in current directory
         PathBuf::from(".")
     });
 
+    if spirit_path.extension().and_then(|s| s.to_str()) == Some("wat") {
+        return execute_wat(&spirit_path, &args).await;
+    }
+
     // Determine the WASM file to execute
+    let mut manifest = None;
     let wasm_file = if spirit_path.is_file()
         && spirit_path.extension().and_then(|s| s.to_str()) == Some("spirit")
     {
@@ -56,9 +157,11 @@ pub async fn execute(args: RunArgs, _config: &VudoConfig) -> Result<()> {
         if manifest_path.exists() {
             let manifest_content =
                 fs::read_to_string(&manifest_path).context("Failed to read manifest.toml")?;
-            let manifest: spirit_runtime::Manifest =
+            let parsed: spirit_runtime::Manifest =
                 toml::from_str(&manifest_content).context("Failed to parse manifest.toml")?;
-            spirit_path.join(format!("{}.spirit", manifest.name))
+            let wasm_file = spirit_path.join(format!("{}.spirit", parsed.name));
+            manifest = Some(parsed);
+            wasm_file
         } else {
             anyhow::bail!("Could not find Spirit package or manifest.toml");
         }
@@ -74,22 +177,41 @@ pub async fn execute(args: RunArgs, _config: &VudoConfig) -> Result<()> {
     println!("{} Spirit: {:?}", "Running".green().bold(), wasm_file);
 
     // Configure resource limits
-    let memory_bytes = parse_memory_limit(args.memory.as_deref())?;
-    let limits = ResourceLimits {
-        max_fuel: args.fuel,
-        cpu_quota: args.fuel,
-        memory_bytes: memory_bytes.unwrap_or(ResourceLimits::default().memory_bytes),
-        ..Default::default()
-    };
+    let limits = resolve_limits(args.fuel, args.memory.as_deref(), &args.limits)?;
 
-    println!("  {} {}", "Fuel:".cyan(), args.fuel);
-    if let Some(mem) = memory_bytes {
-        println!("  {} {} bytes", "Memory:".cyan(), mem);
-    }
+    println!("  {} {}", "Fuel:".cyan(), limits.max_fuel);
+    println!("  {} {} bytes", "Memory:".cyan(), limits.memory_bytes);
+    println!("  {} {:?}", "Timeout:".cyan(), limits.max_duration);
     println!("  {} {}", "Sandbox:".cyan(), args.sandbox);
 
+    // Local runs are granted by an ephemeral key, which also owns the
+    // sandbox unless --owner names another key
+    let owner_key = ephemeral_signing_key();
+    let owner = args
+        .owner
+        .unwrap_or_else(|| owner_key.verifying_key().to_bytes());
+    println!("  {} {}", "Owner:".cyan(), public_key_to_hex(&owner));
+
     // Configure capabilities
-    let capabilities = CapabilitySet::default();
+    let capabilities = match &args.profile {
+        Some(profile) => {
+            println!("  {} {}", "Profile:".cyan(), profile);
+            for cap in profile.to_capability_types() {
+                println!("  {} {:?}", "Capability:".cyan(), cap);
+            }
+            profile.to_capability_set(&owner_key, owner, None)
+        }
+        // Without a profile, a project gets what its manifest declares
+        None => match &manifest {
+            Some(manifest) => {
+                for cap in &manifest.capabilities {
+                    println!("  {} {:?}", "Capability:".cyan(), cap);
+                }
+                manifest_capability_set(manifest, &owner_key, owner)
+            }
+            None => CapabilitySet::default(),
+        },
+    };
     if let Some(caps) = &args.capabilities {
         for cap in caps {
             match cap.as_str() {
@@ -110,6 +232,13 @@ pub async fn execute(args: RunArgs, _config: &VudoConfig) -> Result<()> {
         println!("  {} Enabled", "Trace:".cyan());
     }
 
+    let pricing = args.pricing.apply(
+        manifest
+            .as_ref()
+            .map(|m| m.pricing.clone())
+            .unwrap_or_default(),
+    );
+
     // Load WASM module
     let wasm_bytes = fs::read(&wasm_file)
         .with_context(|| format!("Failed to read WASM file: {:?}", wasm_file))?;
@@ -117,14 +246,128 @@ pub async fn execute(args: RunArgs, _config: &VudoConfig) -> Result<()> {
     println!("\n{} Spirit execution...", "Starting".green().bold());
 
     // Execute in sandbox
-    // For now, this is a placeholder - the actual execution would use the VUDO VM
-    execute_in_sandbox(&wasm_bytes, limits, capabilities, args.trace).await?;
+    execute_in_sandbox(
+        &wasm_bytes,
+        owner,
+        limits,
+        capabilities,
+        &pricing,
+        None,
+        args.trace,
+    )
+    .await?;
 
     println!("\n{} Execution completed successfully", "✓".green().bold());
 
     Ok(())
 }
 
+/// Grant `owner` each capability `manifest` declares, signed by `granter`.
+fn manifest_capability_set(
+    manifest: &spirit_runtime::Manifest,
+    granter: &ed25519_dalek::SigningKey,
+    owner: [u8; 32],
+) -> CapabilitySet {
+    let grants = manifest
+        .capabilities
+        .iter()
+        .enumerate()
+        .map(|(i, cap)| {
+            CapabilityGrant::builder(CapabilityType::from(cap.clone()))
+                .id(i as u64 + 1)
+                .grantee(owner)
+                .sign(granter)
+        })
+        .collect();
+    CapabilitySet::from_grants(grants)
+}
+
+/// Run a WAT source file directly, without a manifest or package.
+///
+/// The module gets `--profile` capabilities if given, otherwise
+/// `Unrestricted`, and `--entry` (or the usual entrypoint) is called.
+async fn execute_wat(path: &Path, args: &RunArgs) -> Result<()> {
+    println!("{} WAT: {:?}", "Running".green().bold(), path);
+
+    let wasm_bytes = wat::parse_file(path)
+        .map_err(|e| anyhow::anyhow!("Failed to parse WAT file {:?}: {}", path, e))?;
+
+    let limits = resolve_limits(args.fuel, args.memory.as_deref(), &args.limits)?;
+
+    let owner_key = ephemeral_signing_key();
+    let owner = args
+        .owner
+        .unwrap_or_else(|| owner_key.verifying_key().to_bytes());
+
+    let capabilities = match &args.profile {
+        Some(profile) => {
+            println!("  {} {}", "Profile:".cyan(), profile);
+            profile.to_capability_set(&owner_key, owner, None)
+        }
+        None => {
+            println!("  {} Unrestricted", "Capability:".cyan());
+            let grant = CapabilityGrant::builder(CapabilityType::Unrestricted)
+                .grantee(owner)
+                .sign(&owner_key);
+            CapabilitySet::from_grants(vec![grant])
+        }
+    };
+
+    let pricing = args.pricing.apply(PricingModel::default());
+
+    execute_in_sandbox(
+        &wasm_bytes,
+        owner,
+        limits,
+        capabilities,
+        &pricing,
+        args.entry.as_deref(),
+        args.trace,
+    )
+    .await?;
+
+    println!("\n{} Execution completed successfully", "✓".green().bold());
+
+    Ok(())
+}
+
+/// Build the sandbox limits from `--fuel`, `--memory` and the overrides.
+fn resolve_limits(
+    fuel: u64,
+    memory: Option<&str>,
+    overrides: &LimitOverrides,
+) -> Result<ResourceLimits> {
+    let mut limits = ResourceLimits {
+        max_fuel: overrides.max_fuel.unwrap_or(fuel),
+        ..Default::default()
+    };
+
+    if let Some(bytes) = parse_memory_limit(memory)? {
+        limits.memory_bytes = bytes as u64;
+    }
+    if let Some(mb) = overrides.max_memory_mb {
+        limits.memory_bytes = mb.saturating_mul(BYTES_PER_MB);
+    }
+    // Let the module declare as much memory as it may grow to
+    limits.max_memory_pages = limits.memory_bytes / WASM_PAGE_SIZE;
+
+    if let Some(secs) = overrides.timeout_secs {
+        limits.max_duration = Duration::from_secs(secs);
+    }
+    if let Some(elements) = overrides.max_table_elements {
+        limits.max_table_elements = elements;
+    }
+
+    limits.validate().map_err(|e| match e {
+        SandboxError::InvalidModule(reason) => {
+            anyhow::anyhow!("Invalid resource limits: {}", reason)
+        }
+        other => anyhow::anyhow!("Invalid resource limits: {}", other),
+    })?;
+
+    Ok(limits)
+}
+
 fn parse_memory_limit(limit: Option<&str>) -> Result<Option<usize>> {
     match limit {
         None => Ok(None),
@@ -149,10 +392,31 @@ fn parse_memory_limit(limit: Option<&str>) -> Result<Option<usize>> {
     }
 }
 
+fn parse_owner(hex: &str) -> Result<PublicKey, String> {
+    public_key_from_hex(hex).map_err(|e| e.to_string())
+}
+
+fn parse_price(value: &str) -> Result<u64, String> {
+    if value.starts_with('-') {
+        return Err(format!("Price must be non-negative, got {}", value));
+    }
+    value
+        .parse()
+        .map_err(|_| format!("Invalid price: {}", value))
+}
+
+fn ephemeral_signing_key() -> ed25519_dalek::SigningKey {
+    let secret_bytes: [u8; 32] = rand::Rng::gen(&mut rand::rngs::OsRng);
+    ed25519_dalek::SigningKey::from_bytes(&secret_bytes)
+}
+
 async fn execute_in_sandbox(
     wasm_bytes: &[u8],
-    _limits: ResourceLimits,
-    _capabilities: CapabilitySet,
+    owner: [u8; 32],
+    limits: ResourceLimits,
+    capabilities: CapabilitySet,
+    pricing: &PricingModel,
+    entry: Option<&str>,
     trace: bool,
 ) -> Result<()> {
     // Validate WASM module
@@ -164,6 +428,19 @@ async fn execute_in_sandbox(
         anyhow::bail!("Invalid WASM module: missing magic number");
     }
 
+    let max_fuel = limits.max_fuel;
+    let memory_bytes = limits.memory_bytes;
+    let max_duration = limits.max_duration;
+
+    let mut sandbox = Sandbox::builder(wasm_bytes, owner)
+        .limits(limits)
+        .capabilities(capabilities)
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create sandbox: {}", e))?;
+    sandbox
+        .initialize()
+        .map_err(|e| anyhow::anyhow!("Invalid WASM module: {}", e))?;
+
     println!(
         "  {} WASM module ({} bytes)",
         "Validated".green(),
@@ -172,17 +449,80 @@ async fn execute_in_sandbox(
 
     if trace {
         println!("  {} Execution trace enabled", "Debug:".yellow());
+        sandbox.enable_trace(DEFAULT_TRACE_CAPACITY);
     }
 
-    // In a real implementation, this would:
-    // 1. Create a Wasmtime engine with the configured limits
-    // 2. Load and validate the WASM module
-    // 3. Instantiate with host functions
-    // 4. Call the main function
-    // 5. Handle results and errors
+    let result = match entry {
+        Some(name) => {
+            println!("  {} {}", "Calling".cyan(), name);
+            sandbox.run_entry(name)
+        }
+        None => {
+            println!("  {} Spirit main function", "Calling".cyan());
+            sandbox.run_start()
+        }
+    }
+    .map_err(|e| anyhow::anyhow!("Spirit failed to run: {}", e))?;
+
+    for entry in sandbox.trace() {
+        println!(
+            "  {} {}({:?}) -> {}",
+            "Trace:".yellow(),
+            entry.function,
+            entry.args,
+            entry.result
+        );
+    }
 
-    println!("  {} Spirit main function", "Calling".cyan());
-    println!("  {} Spirit returned successfully", "Result:".green());
+    match result.outcome {
+        ExecutionOutcome::Completed => {
+            println!(
+                "  {} Spirit returned successfully ({} fuel)",
+                "Result:".green(),
+                result.fuel_consumed
+            );
+            let used_capabilities: HashSet<Capability> = sandbox
+                .capability_usage()
+                .into_iter()
+                .filter_map(|cap| Capability::try_from(cap).ok())
+                .collect();
+            let charge =
+                pricing.quote(result.fuel_consumed, result.memory_used, &used_capabilities);
+            println!("  {} {} microcredits", "Charge:".cyan(), charge);
+
+            let values = result.return_value.unwrap_or_default();
+            if !values.is_empty() {
+                let values: Vec<String> = values.iter().map(format_value).collect();
+                println!("  {} {}", "Returned:".green(), values.join(", "));
+            }
+            Ok(())
+        }
+        ExecutionOutcome::OutOfFuel => anyhow::bail!(
+            "Spirit paused: out of fuel after {} units (raise --max-fuel)",
+            max_fuel
+        ),
+        ExecutionOutcome::Timeout => anyhow::bail!(
+            "Spirit timed out after {:?} (raise --timeout-secs)",
+            max_duration
+        ),
+        ExecutionOutcome::MemoryLimit => anyhow::bail!(
+            "Spirit exceeded the memory limit of {} bytes (raise --max-memory-mb)",
+            memory_bytes
+        ),
+        ExecutionOutcome::Trap(_) => anyhow::bail!(
+            "Spirit trapped: {}",
+            result.error.unwrap_or_else(|| "unknown trap".to_string())
+        ),
+    }
+}
 
-    Ok(())
+/// Render a WASM return value for display
+fn format_value(value: &Val) -> String {
+    match value {
+        Val::I32(v) => v.to_string(),
+        Val::I64(v) => v.to_string(),
+        Val::F32(bits) => f32::from_bits(*bits).to_string(),
+        Val::F64(bits) => f64::from_bits(*bits).to_string(),
+        other => format!("{:?}", other),
+    }
 }
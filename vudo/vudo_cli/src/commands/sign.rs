@@ -6,7 +6,7 @@ use colored::*;
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use sha2::{Digest, Sha256};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::config::VudoConfig;
 
@@ -22,10 +22,23 @@ pub struct SignArgs {
     /// Verify signature instead of signing
     #[arg(long)]
     pub verify: bool,
+
+    /// Rotate the manifest's author key (PACKAGE is a manifest or project directory)
+    #[arg(long, requires = "new_key", conflicts_with = "verify")]
+    pub rotate: bool,
+
+    /// Path to the new signing key used with --rotate
+    #[arg(long)]
+    pub new_key: Option<PathBuf>,
 }
 
 pub async fn execute(args: SignArgs, config: &VudoConfig) -> Result<()> {
-    if args.verify {
+    if args.rotate {
+        let new_key = args
+            .new_key
+            .context("--rotate requires --new-key <keyfile>")?;
+        rotate_manifest(&args.package, &new_key)?;
+    } else if args.verify {
         verify_package(&args.package)?;
     } else {
         sign_package(&args.package, args.key, config)?;
@@ -202,6 +215,51 @@ fn verify_package(package_path: &PathBuf) -> Result<()> {
     Ok(())
 }
 
+fn rotate_manifest(manifest_path: &Path, new_key_path: &PathBuf) -> Result<()> {
+    let manifest_file = if manifest_path.is_dir() {
+        manifest_path.join("manifest.toml")
+    } else {
+        manifest_path.to_path_buf()
+    };
+
+    println!(
+        "{} author key: {:?}",
+        "Rotating".green().bold(),
+        manifest_file
+    );
+
+    let mut manifest = spirit_runtime::Manifest::from_file(&manifest_file)
+        .with_context(|| format!("Failed to load manifest: {:?}", manifest_file))?;
+
+    // Only the holder of a valid signature may hand authorship to a new key
+    manifest
+        .verify()
+        .context("Refusing to rotate: current signature does not verify")?;
+
+    println!("  {} {}", "Old author:".cyan(), manifest.author);
+
+    let new_key = load_signing_key(new_key_path)?;
+    manifest.author = hex::encode(new_key.verifying_key().as_bytes());
+    let signature = manifest
+        .sign(&new_key)
+        .context("Failed to re-sign manifest")?;
+    manifest.signature = Some(signature);
+
+    println!("  {} {}", "New author:".cyan(), manifest.author);
+
+    manifest
+        .to_file(&manifest_file)
+        .with_context(|| format!("Failed to write manifest: {:?}", manifest_file))?;
+
+    println!(
+        "\n{} Rotated and re-signed: {:?}",
+        "✓".green().bold(),
+        manifest_file
+    );
+
+    Ok(())
+}
+
 fn generate_signing_key() -> SigningKey {
     use ed25519_dalek::SigningKey;
     use rand::rngs::OsRng;
@@ -1,12 +1,15 @@
 //! `vudo summon` - Download Spirit from Imaginarium
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Args;
 use colored::*;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::config::VudoConfig;
+use spirit_runtime::SemVer;
 
 #[derive(Args, Debug)]
 pub struct SummonArgs {
@@ -24,6 +27,10 @@ pub struct SummonArgs {
     /// Registry URL (defaults to config default)
     #[arg(long)]
     pub registry: Option<String>,
+
+    /// Download and verify the Spirit's signatures without installing it
+    #[arg(long, conflicts_with = "run")]
+    pub verify_only: bool,
 }
 
 pub async fn execute(args: SummonArgs, config: &VudoConfig) -> Result<()> {
@@ -54,6 +61,10 @@ pub async fn execute(args: SummonArgs, config: &VudoConfig) -> Result<()> {
 
     println!("  {} {}", "Registry:".cyan(), registry);
 
+    if args.verify_only {
+        return verify_only(&registry, &name, version.as_deref());
+    }
+
     // Determine output path
     let spirit_dir = args.output.unwrap_or_else(|| {
         let vudo_dir = config.vudo_dir();
@@ -93,6 +104,106 @@ pub async fn execute(args: SummonArgs, config: &VudoConfig) -> Result<()> {
     Ok(())
 }
 
+/// Fetch the package and check both of its signatures, writing nothing
+fn verify_only(registry: &str, name: &str, version: Option<&str>) -> Result<()> {
+    println!("\n{} signatures...", "Verifying".green().bold());
+
+    let package = fetch_package(registry, name, version)?;
+    let (manifest, _wasm) = super::publish::unpack_package(&package)?;
+
+    println!("  {} {}", "Author:".cyan(), manifest.author);
+    println!("  {} {}", "Version:".cyan(), manifest.version);
+
+    let manifest_result = match &manifest.signature {
+        Some(_) => manifest.verify().map_err(|e| anyhow::anyhow!("{}", e)),
+        None => Err(anyhow::anyhow!("manifest is unsigned")),
+    };
+    report("manifest signature", &manifest_result);
+
+    let artifact_result = verify_artifact(&package, &manifest.author);
+    report("artifact signature", &artifact_result);
+
+    if manifest_result.is_err() || artifact_result.is_err() {
+        anyhow::bail!("Verification failed for {}; nothing was installed", name);
+    }
+
+    println!(
+        "\n{} {} {} verified; nothing was installed",
+        "✓".green().bold(),
+        name,
+        manifest.version
+    );
+    Ok(())
+}
+
+fn report(what: &str, result: &Result<()>) {
+    match result {
+        Ok(()) => println!("  {} {} verified", "✓".green(), what),
+        Err(e) => println!("  {} {} invalid: {}", "✗".red(), what, e),
+    }
+}
+
+/// Check the `vudo sign` envelope: a signature over the SHA-256 of the
+/// package by the manifest's author.
+fn verify_artifact(package: &[u8], author: &str) -> Result<()> {
+    // SIGNED\n<32-byte key>\n<64-byte signature>\n<package>
+    let envelope = package
+        .strip_prefix(b"SIGNED\n")
+        .context("package is unsigned")?;
+    if envelope.len() < 32 + 1 + 64 + 1 {
+        anyhow::bail!("truncated signature envelope");
+    }
+    let (key, rest) = envelope.split_at(32);
+    let (signature, body) = rest[1..].split_at(64);
+    let body = &body[1..];
+
+    if hex::encode(key) != author {
+        anyhow::bail!("signed by {}, not the manifest author", hex::encode(key));
+    }
+
+    let key = VerifyingKey::from_bytes(key.try_into()?).context("invalid public key")?;
+    let signature = Signature::from_bytes(signature.try_into()?);
+    key.verify(&Sha256::digest(body), &signature)
+        .context("signature does not match package contents")
+}
+
+/// Read a package from the registry.
+///
+/// Only local registries (a directory path or `file://` URL holding
+/// `vudo pack` output named `<name>-<version>.spirit`) are supported so
+/// far. Without a version the highest one present is used.
+fn fetch_package(registry: &str, name: &str, version: Option<&str>) -> Result<Vec<u8>> {
+    let dir = match registry.strip_prefix("file://") {
+        Some(path) => Path::new(path),
+        None if !registry.contains("://") => Path::new(registry),
+        None => {
+            anyhow::bail!("Remote registries are not supported yet; use a local registry directory")
+        }
+    };
+
+    let file = match version {
+        Some(version) => format!("{}-{}.spirit", name, version),
+        None => {
+            let prefix = format!("{}-", name);
+            let latest = fs::read_dir(dir)
+                .with_context(|| format!("Failed to read registry: {:?}", dir))?
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .filter_map(|file| {
+                    let version = file.strip_prefix(&prefix)?.strip_suffix(".spirit")?;
+                    Some((version.parse::<SemVer>().ok()?, file))
+                })
+                .max_by(|a, b| a.0.cmp(&b.0));
+            match latest {
+                Some((_, file)) => file,
+                None => anyhow::bail!("Spirit {} not found in registry {:?}", name, dir),
+            }
+        }
+    };
+
+    let path = dir.join(file);
+    fs::read(&path).with_context(|| format!("Failed to download {:?}", path))
+}
+
 fn parse_spirit_name(name: &str) -> Result<(Option<String>, String, Option<String>)> {
     // Handle @creator/name@version format
     let (creator, rest) = if let Some(stripped) = name.strip_prefix('@') {
@@ -3,6 +3,7 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::*;
+use std::path::Path;
 
 use crate::config::VudoConfig;
 use spirit_runtime::registry::{LocalRegistry, Registry};
@@ -19,21 +20,33 @@ pub struct UninstallArgs {
     /// Skip confirmation prompt
     #[arg(short = 'y', long)]
     pub yes: bool,
+
+    /// Show what would be removed without modifying the registry
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 pub async fn execute(args: UninstallArgs, _config: &VudoConfig) -> Result<()> {
-    // Initialize registry
+    // Initialize registry (read-only for dry runs)
     let mut registry = LocalRegistry::new();
-    registry
-        .init()
-        .await
-        .context("Failed to initialize registry")?;
+    if args.dry_run {
+        registry.load().await.context("Failed to read registry")?;
+    } else {
+        registry
+            .init()
+            .await
+            .context("Failed to initialize registry")?;
+    }
 
     // Check if spirit is installed
     if !registry.is_installed(&args.name) {
         anyhow::bail!("Spirit '{}' is not installed", args.name);
     }
 
+    if args.dry_run {
+        return dry_run(&registry, &args).await;
+    }
+
     if let Some(ref version) = args.spirit_version {
         // Uninstall specific version
         if !registry.is_version_installed(&args.name, version) {
@@ -113,3 +126,52 @@ pub async fn execute(args: UninstallArgs, _config: &VudoConfig) -> Result<()> {
 
     Ok(())
 }
+
+/// Print the versions that would be removed and the space freed
+async fn dry_run(registry: &LocalRegistry, args: &UninstallArgs) -> Result<()> {
+    let versions = match args.spirit_version {
+        Some(ref version) => {
+            if !registry.is_version_installed(&args.name, version) {
+                anyhow::bail!("Version {}@{} is not installed", args.name, version);
+            }
+            vec![version.clone()]
+        }
+        None => registry
+            .list()
+            .await?
+            .into_iter()
+            .find(|s| s.name == args.name)
+            .map(|s| s.versions)
+            .unwrap_or_default(),
+    };
+
+    println!(
+        "{} uninstall plan for: {}",
+        "Dry run".yellow().bold(),
+        args.name.cyan()
+    );
+
+    let mut total = 0;
+    for (i, version) in versions.iter().enumerate() {
+        let spirit = registry.get_version(&args.name, version).await?;
+        let size = dir_size(&spirit.path);
+        total += size;
+        println!("  {}. {}@{} ({} bytes)", i + 1, args.name, version, size);
+    }
+    println!("  {} {} bytes", "Total size:".cyan(), total);
+
+    println!("\n{} No changes made (dry run)", "✓".green().bold());
+
+    Ok(())
+}
+
+/// Total size of all files under `path`
+fn dir_size(path: &Path) -> u64 {
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.is_dir() => std::fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|e| dir_size(&e.path())).sum())
+            .unwrap_or(0),
+        Ok(meta) => meta.len(),
+        Err(_) => 0,
+    }
+}
@@ -156,6 +156,9 @@ async fn main() -> Result<()> {
         }
         Err(e) => {
             eprintln!("{} {}", "Error:".red().bold(), e);
+            if e.downcast_ref::<commands::fmt::Unformatted>().is_some() {
+                std::process::exit(commands::fmt::UNFORMATTED_EXIT_CODE);
+            }
             std::process::exit(1);
         }
     }
@@ -17,6 +17,9 @@
 //! root level with a SemVer object. Tests that require full lifecycle integration
 //! use a helper to create compatible manifests.
 
+use ed25519_dalek::SigningKey;
+use spirit_runtime::registry::{LocalRegistry, Registry};
+use spirit_runtime::{Capability, Dependency, Manifest, SemVer};
 use std::fs;
 use std::path::Path;
 use std::process::{Command, Output};
@@ -404,6 +407,32 @@ fn test_check_json_output() {
     );
 }
 
+#[test]
+fn test_check_reports_manifest_syntax_error_position() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+
+    let output = run_vudo(&["new", "syntax-check-test"], temp_path);
+    assert_success(&output, "vudo new syntax-check-test");
+
+    let project_path = temp_path.join("syntax-check-test");
+    fs::write(
+        project_path.join("manifest.toml"),
+        "name = \"syntax-check-test\"\ndescription = \"unterminated\n",
+    )
+    .expect("Failed to write manifest.toml");
+
+    let output = run_vudo(&["check", "--format", "json"], &project_path);
+    assert_failure(&output, "vudo check (manifest syntax error)");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("manifest.toml:2:28: invalid basic string"),
+        "Check should locate the syntax error: {}",
+        stdout
+    );
+}
+
 #[test]
 fn test_check_warns_on_empty_file() {
     let temp_dir = TempDir::new().expect("Failed to create temp directory");
@@ -670,6 +699,106 @@ fn test_verify_fails_on_unsigned_package() {
     assert_failure(&output, "vudo sign --verify (unsigned package)");
 }
 
+// =============================================================================
+// Test 5b: vudo sign --rotate key rotation
+// =============================================================================
+
+/// Writes a manifest authored and signed by `key`, plus hex key files for
+/// `key` and `next`. Returns (manifest path, new key path).
+fn write_signed_manifest(
+    base_path: &Path,
+    key: &SigningKey,
+    next: &SigningKey,
+) -> (std::path::PathBuf, std::path::PathBuf) {
+    let mut manifest = Manifest::new(
+        "rotate-test",
+        SemVer::new(0, 1, 0),
+        hex::encode(key.verifying_key().as_bytes()),
+    );
+    manifest.signature = Some(manifest.sign(key).expect("Failed to sign manifest"));
+
+    let manifest_path = base_path.join("manifest.toml");
+    manifest
+        .to_file(&manifest_path)
+        .expect("Failed to write manifest.toml");
+
+    let new_key_path = base_path.join("new.key");
+    fs::write(&new_key_path, hex::encode(next.to_bytes())).expect("Failed to write key");
+
+    (manifest_path, new_key_path)
+}
+
+#[test]
+fn test_sign_rotate_resigns_with_new_key() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+
+    let old_key = SigningKey::from_bytes(&[1u8; 32]);
+    let new_key = SigningKey::from_bytes(&[2u8; 32]);
+    let (manifest_path, new_key_path) = write_signed_manifest(temp_path, &old_key, &new_key);
+
+    let output = run_vudo(
+        &[
+            "sign",
+            "--rotate",
+            "--new-key",
+            new_key_path.to_str().unwrap(),
+            manifest_path.to_str().unwrap(),
+        ],
+        temp_path,
+    );
+    assert_success(&output, "vudo sign --rotate");
+
+    let rotated = Manifest::from_file(&manifest_path).expect("Failed to reload manifest");
+    assert_eq!(
+        rotated.author,
+        hex::encode(new_key.verifying_key().as_bytes())
+    );
+    rotated
+        .verify()
+        .expect("Rotated manifest should verify under the new key");
+}
+
+#[test]
+fn test_sign_rotate_refuses_invalid_signature() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+
+    let old_key = SigningKey::from_bytes(&[1u8; 32]);
+    let new_key = SigningKey::from_bytes(&[2u8; 32]);
+    let (manifest_path, new_key_path) = write_signed_manifest(temp_path, &old_key, &new_key);
+
+    // Tamper with signed content so the existing signature no longer verifies
+    let mut manifest = Manifest::from_file(&manifest_path).unwrap();
+    manifest.description = Some("tampered".to_string());
+    manifest.to_file(&manifest_path).unwrap();
+    let before = fs::read_to_string(&manifest_path).unwrap();
+
+    let output = run_vudo(
+        &[
+            "sign",
+            "--rotate",
+            "--new-key",
+            new_key_path.to_str().unwrap(),
+            manifest_path.to_str().unwrap(),
+        ],
+        temp_path,
+    );
+    assert_failure(&output, "vudo sign --rotate (invalid signature)");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Refusing to rotate"),
+        "Expected refusal message: {}",
+        stderr
+    );
+    assert_eq!(
+        fs::read_to_string(&manifest_path).unwrap(),
+        before,
+        "Manifest must be left untouched"
+    );
+}
+
 // =============================================================================
 // Test 6: Build with various options
 // =============================================================================
@@ -734,6 +863,49 @@ fn test_build_with_emit_option() {
     );
 }
 
+#[test]
+fn test_build_reports_wasm_size() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+
+    let project_path = create_compatible_spirit_project(temp_path, "size-test");
+
+    let output = run_vudo(&["build"], &project_path);
+    assert_success(&output, "vudo build");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let reported: u64 = stdout
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("WASM size"))
+        .and_then(|rest| rest.trim().strip_suffix(" bytes"))
+        .and_then(|n| n.parse().ok())
+        .unwrap_or_else(|| panic!("Build output should report WASM size: {}", stdout));
+
+    let actual = fs::metadata(project_path.join("size-test.spirit"))
+        .expect("Built Spirit should exist")
+        .len();
+    assert_eq!(reported, actual);
+    assert!(stdout.contains("Exports"), "Summary should list exports");
+}
+
+#[test]
+fn test_build_size_report_lists_functions() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+
+    let project_path = create_compatible_spirit_project(temp_path, "size-report-test");
+
+    let output = run_vudo(&["build", "--size-report"], &project_path);
+    assert_success(&output, "vudo build --size-report");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Function sizes") && stdout.contains("#0 main"),
+        "Size report should list per-function sizes: {}",
+        stdout
+    );
+}
+
 // =============================================================================
 // Test 7: Run with various options
 // =============================================================================
@@ -917,6 +1089,32 @@ fn test_manifest_content_from_new() {
     );
 }
 
+#[test]
+fn test_new_with_capability_profile() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+
+    let output = run_vudo(
+        &["new", "profile-test", "--profile", "custom:NetworkConnect"],
+        temp_path,
+    );
+    assert_success(&output, "vudo new --profile");
+
+    let manifest_content = fs::read_to_string(temp_path.join("profile-test").join("manifest.toml"))
+        .expect("Failed to read manifest");
+    assert!(
+        manifest_content.contains("profile = \"custom:NetworkConnect\""),
+        "Manifest should declare the profile: {}",
+        manifest_content
+    );
+
+    let output = run_vudo(
+        &["new", "bad-profile", "--profile", "everything"],
+        temp_path,
+    );
+    assert_failure(&output, "vudo new --profile everything");
+}
+
 #[test]
 fn test_project_structure() {
     let temp_dir = TempDir::new().expect("Failed to create temp directory");
@@ -1053,3 +1251,1028 @@ fn test_new_then_build_integration() {
     let output = run_vudo(&["build"], &project_path);
     assert_success(&output, "vudo build after vudo new");
 }
+
+// =============================================================================
+// Test 13: install/uninstall --dry-run
+// =============================================================================
+
+/// Writes an installable spirit (manifest.json + spirit.wasm) into `base/name`
+fn write_installable_spirit(
+    base_path: &Path,
+    name: &str,
+    version: SemVer,
+    capabilities: &[Capability],
+    path_deps: &[&str],
+) -> std::path::PathBuf {
+    let dir = base_path.join(name);
+    fs::create_dir_all(&dir).expect("Failed to create spirit directory");
+
+    let mut manifest = Manifest::new(name, version, "a".repeat(64));
+    for cap in capabilities {
+        manifest.add_capability(cap.clone());
+    }
+    for dep in path_deps {
+        manifest.add_dependency(*dep, Dependency::from_path(format!("../{}", dep)));
+    }
+    manifest
+        .to_file(dir.join("manifest.json"))
+        .expect("Failed to write manifest.json");
+    fs::write(dir.join("spirit.wasm"), EMPTY_WASM).expect("Failed to write wasm");
+
+    dir
+}
+
+/// Sorted list of every path under `root`, for before/after comparisons
+fn snapshot_tree(root: &Path) -> Vec<std::path::PathBuf> {
+    let mut paths = Vec::new();
+    if let Ok(entries) = fs::read_dir(root) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            paths.push(path.clone());
+            if path.is_dir() && !path.is_symlink() {
+                paths.extend(snapshot_tree(&path));
+            }
+        }
+    }
+    paths.sort();
+    paths
+}
+
+/// `(file, size)` lines of a `vudo install --dry-run` plan, sorted
+fn planned_files(stdout: &str) -> Vec<(String, u64)> {
+    let mut files: Vec<(String, u64)> = stdout
+        .lines()
+        .filter_map(|l| l.strip_prefix("    "))
+        .filter_map(|l| l.strip_suffix(" bytes)"))
+        .filter_map(|l| l.rsplit_once(" ("))
+        .map(|(file, size)| (file.to_string(), size.parse().unwrap()))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Every file under `dir`, relative to it with `/` separators, with its size
+fn installed_files(dir: &Path) -> Vec<(String, u64)> {
+    let mut files = Vec::new();
+    for path in snapshot_tree(dir) {
+        if path.is_file() {
+            let relative = path.strip_prefix(dir).unwrap();
+            let relative: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            files.push((relative.join("/"), fs::metadata(&path).unwrap().len()));
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+fn test_install_dry_run_matches_install() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+    let home = temp_path.join("home");
+    fs::create_dir_all(&home).unwrap();
+    let env = [("HOME", home.to_str().unwrap())];
+
+    write_installable_spirit(temp_path, "dep-a", SemVer::new(0, 1, 0), &[], &[]);
+    let root = write_installable_spirit(
+        temp_path,
+        "root-spirit",
+        SemVer::new(1, 0, 0),
+        &[Capability::NetworkConnect],
+        &["dep-a"],
+    );
+    fs::create_dir_all(root.join("assets/icons")).unwrap();
+    fs::write(root.join("assets/readme.txt"), "hello").unwrap();
+    fs::write(root.join("assets/icons/logo.svg"), "<svg/>").unwrap();
+    fs::write(root.join("notes.md"), "not installed").unwrap();
+
+    let output = run_vudo_with_env(
+        &["install", "--dry-run", root.to_str().unwrap()],
+        temp_path,
+        &env,
+    );
+    assert_success(&output, "vudo install --dry-run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("New capabilities: NetworkConnect"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(
+        !home.join(".vudo").exists(),
+        "Dry run must not create the registry"
+    );
+    let planned = planned_files(&stdout);
+    let total: u64 = planned.iter().map(|(_, size)| size).sum();
+    assert!(
+        stdout.contains(&format!("Total size: {} bytes", total)),
+        "stdout: {}",
+        stdout
+    );
+
+    let output = run_vudo_with_env(&["install", root.to_str().unwrap()], temp_path, &env);
+    assert_success(&output, "vudo install");
+
+    // The plan lists exactly what was written, and nothing for dep-a
+    let spirits = home.join(".vudo").join("registry").join("spirits");
+    assert_eq!(installed_files(&spirits.join("root-spirit/1.0.0")), planned);
+    assert!(!spirits.join("dep-a").exists());
+
+    // Planning an installed version fails like installing it does
+    let output = run_vudo_with_env(
+        &["install", "--dry-run", root.to_str().unwrap()],
+        temp_path,
+        &env,
+    );
+    assert_failure(&output, "vudo install --dry-run (already installed)");
+}
+
+#[test]
+fn test_install_does_not_require_resolvable_dependencies() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+    let home = temp_path.join("home");
+    fs::create_dir_all(&home).unwrap();
+    let env = [("HOME", home.to_str().unwrap())];
+
+    write_installable_spirit(temp_path, "path-dep", SemVer::new(0, 1, 0), &[], &[]);
+    let app = temp_path.join("loose-app");
+    fs::create_dir_all(&app).unwrap();
+    let mut manifest = Manifest::new("loose-app", SemVer::new(1, 0, 0), "a".repeat(64));
+    manifest.add_dependency("missing", Dependency::new("^3.0.0"));
+    manifest.add_dependency(
+        "remote",
+        Dependency::from_git("https://example.com/remote.git", None),
+    );
+    manifest.add_dependency("path-dep", Dependency::from_path("../path-dep"));
+    manifest.to_file(app.join("manifest.json")).unwrap();
+    fs::write(app.join("spirit.wasm"), EMPTY_WASM).unwrap();
+
+    // Install neither resolves dependencies nor installs path dependencies,
+    // and neither does its plan
+    let output = run_vudo_with_env(
+        &["install", "--dry-run", app.to_str().unwrap()],
+        temp_path,
+        &env,
+    );
+    assert_success(&output, "vudo install --dry-run (unresolved dependencies)");
+    let output = run_vudo_with_env(&["install", app.to_str().unwrap()], temp_path, &env);
+    assert_success(&output, "vudo install (unresolved dependencies)");
+
+    let output = run_vudo_with_env(&["uninstall", "--dry-run", "path-dep"], temp_path, &env);
+    assert_failure(&output, "vudo uninstall --dry-run path-dep");
+}
+
+#[test]
+fn test_uninstall_dry_run_leaves_registry_untouched() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+    let home = temp_path.join("home");
+    fs::create_dir_all(&home).unwrap();
+    let env = [("HOME", home.to_str().unwrap())];
+
+    let spirit = write_installable_spirit(temp_path, "removable", SemVer::new(0, 1, 0), &[], &[]);
+    let output = run_vudo_with_env(&["install", spirit.to_str().unwrap()], temp_path, &env);
+    assert_success(&output, "vudo install");
+
+    let registry = home.join(".vudo").join("registry");
+    let before = snapshot_tree(&registry);
+    let index_before = fs::read_to_string(registry.join("index.json")).unwrap();
+
+    let output = run_vudo_with_env(&["uninstall", "--dry-run", "removable"], temp_path, &env);
+    assert_success(&output, "vudo uninstall --dry-run");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("removable@0.1.0"), "stdout: {}", stdout);
+    assert!(stdout.contains("No changes made"), "stdout: {}", stdout);
+
+    assert_eq!(snapshot_tree(&registry), before);
+    assert_eq!(
+        fs::read_to_string(registry.join("index.json")).unwrap(),
+        index_before
+    );
+}
+
+// =============================================================================
+// Test 14: Yanked dependencies
+// =============================================================================
+
+/// Installs `name@version` for each entry into the registry under `home`
+fn install_into_home(temp_path: &Path, home: &Path, spirits: &[(&str, SemVer)]) {
+    for (name, version) in spirits {
+        let dir = write_installable_spirit(
+            &temp_path.join(format!("src-{}", version)),
+            name,
+            version.clone(),
+            &[],
+            &[],
+        );
+        let output = run_vudo_with_env(
+            &["install", dir.to_str().unwrap()],
+            temp_path,
+            &[("HOME", home.to_str().unwrap())],
+        );
+        assert_success(&output, "vudo install dependency");
+    }
+}
+
+#[test]
+fn test_install_yanked_dependency_requires_exact_pin() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+    let home = temp_path.join("home");
+    fs::create_dir_all(&home).unwrap();
+    let env = [("HOME", home.to_str().unwrap())];
+
+    install_into_home(
+        temp_path,
+        &home,
+        &[("lib", SemVer::new(0, 1, 0)), ("lib", SemVer::new(0, 2, 0))],
+    );
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut registry = LocalRegistry::with_root(home.join(".vudo").join("registry"));
+        registry.init().await.unwrap();
+        registry.yank("lib", "0.2.0").await.unwrap();
+    });
+
+    let write_app = |name: &str, requirement: &str| {
+        let dir = temp_path.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        let mut manifest = Manifest::new(name, SemVer::new(1, 0, 0), "a".repeat(64));
+        manifest.add_dependency("lib", Dependency::new(requirement));
+        manifest.to_file(dir.join("manifest.json")).unwrap();
+        fs::write(dir.join("spirit.wasm"), EMPTY_WASM).unwrap();
+        dir
+    };
+
+    // A range requirement still resolves (to 0.1.0) without warnings
+    let app = write_app("range-app", "^0.1.0");
+    let output = run_vudo_with_env(&["install", app.to_str().unwrap()], temp_path, &env);
+    assert_success(&output, "vudo install (range dependency)");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("yanked"));
+
+    // An exact pin of the yanked version installs, with a warning
+    let app = write_app("pinned-app", "=0.2.0");
+    let output = run_vudo_with_env(&["install", app.to_str().unwrap()], temp_path, &env);
+    assert_success(&output, "vudo install (pinned yanked dependency)");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("lib@0.2.0 is yanked"),
+        "Expected yank warning: {}",
+        stdout
+    );
+}
+
+// =============================================================================
+// Test 15: vudo publish pre-publish checks
+// =============================================================================
+
+/// An empty but valid WASM module (magic number and version only)
+const EMPTY_WASM: &[u8] = b"\0asm\x01\0\0\0";
+
+/// Writes a `vudo pack`-style package and returns its path.
+fn write_package(base_path: &Path, manifest: &Manifest, wasm: &[u8]) -> std::path::PathBuf {
+    let mut package = b"MANIFEST\n".to_vec();
+    package.extend_from_slice(manifest.to_toml().unwrap().as_bytes());
+    package.extend_from_slice(b"\n\nWASM\n");
+    package.extend_from_slice(wasm);
+
+    let path = base_path.join(format!("{}.spirit", manifest.name));
+    fs::write(&path, package).expect("Failed to write package");
+    path
+}
+
+fn signed_manifest(name: &str) -> Manifest {
+    let key = SigningKey::from_bytes(&[3u8; 32]);
+    let mut manifest = Manifest::new(
+        name,
+        SemVer::new(0, 1, 0),
+        hex::encode(key.verifying_key().as_bytes()),
+    );
+    manifest.signature = Some(manifest.sign(&key).expect("Failed to sign manifest"));
+    manifest
+}
+
+fn assert_publish_rejected(package: &Path, working_dir: &Path, args: &[&str], expected: &str) {
+    let mut full_args = vec!["publish", package.to_str().unwrap()];
+    full_args.extend_from_slice(args);
+
+    let output = run_vudo(&full_args, working_dir);
+    assert_failure(&output, "vudo publish");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(expected),
+        "Expected {:?} in error: {}",
+        expected,
+        stderr
+    );
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Uploading"));
+}
+
+#[test]
+fn test_publish_signed_package() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let package = write_package(temp_dir.path(), &signed_manifest("signed"), EMPTY_WASM);
+
+    let output = run_vudo(
+        &["publish", package.to_str().unwrap(), "--require-signed"],
+        temp_dir.path(),
+    );
+    assert_success(&output, "vudo publish --require-signed");
+}
+
+#[test]
+fn test_publish_require_signed_rejects_unsigned_manifest() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let manifest = Manifest::new("unsigned", SemVer::new(0, 1, 0), "a".repeat(64));
+    let package = write_package(temp_dir.path(), &manifest, EMPTY_WASM);
+
+    assert_publish_rejected(
+        &package,
+        temp_dir.path(),
+        &["--require-signed"],
+        "Manifest is unsigned",
+    );
+
+    // Without the flag an unsigned manifest is only a warning
+    let output = run_vudo(&["publish", package.to_str().unwrap()], temp_dir.path());
+    assert_success(&output, "vudo publish (unsigned)");
+}
+
+#[test]
+fn test_publish_rejects_invalid_manifest() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let manifest = Manifest::new("bad name!", SemVer::new(0, 1, 0), "a".repeat(64));
+    let package = write_package(temp_dir.path(), &manifest, EMPTY_WASM);
+
+    assert_publish_rejected(&package, temp_dir.path(), &[], "Manifest validation failed");
+}
+
+#[test]
+fn test_publish_rejects_tampered_signature() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let mut manifest = signed_manifest("tampered");
+    manifest.description = Some("changed after signing".to_string());
+    let package = write_package(temp_dir.path(), &manifest, EMPTY_WASM);
+
+    assert_publish_rejected(&package, temp_dir.path(), &[], "signature is invalid");
+}
+
+#[test]
+fn test_publish_rejects_non_compiling_wasm() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let mut wasm = EMPTY_WASM.to_vec();
+    wasm.extend_from_slice(&[0xff, 0xff, 0xff]);
+    let package = write_package(temp_dir.path(), &signed_manifest("broken"), &wasm);
+
+    assert_publish_rejected(&package, temp_dir.path(), &[], "failed to compile");
+}
+
+#[test]
+fn test_publish_rejects_undeclared_capability() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let wasm = wat::parse_str(r#"(module (import "vudo" "host_time_now" (func (result i64))))"#)
+        .expect("Invalid WAT");
+    let package = write_package(temp_dir.path(), &signed_manifest("sneaky"), &wasm);
+
+    assert_publish_rejected(&package, temp_dir.path(), &[], "SensorTime");
+}
+
+// =============================================================================
+// Test 16: vudo run limit overrides
+// =============================================================================
+
+/// A Spirit whose `main` counts down from 10,000 before returning
+fn write_counting_spirit(base_path: &Path) -> std::path::PathBuf {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+          (func (export "main") (local i32)
+            (local.set 0 (i32.const 10000))
+            (loop (br_if 0 (local.tee 0 (i32.sub (local.get 0) (i32.const 1)))))))
+        "#,
+    )
+    .expect("Invalid WAT");
+
+    let path = base_path.join("counter.spirit");
+    fs::write(&path, wasm).expect("Failed to write spirit");
+    path
+}
+
+#[test]
+fn test_run_max_fuel_pauses_hungry_spirit() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let spirit = write_counting_spirit(temp_dir.path());
+    let spirit = spirit.to_str().unwrap();
+
+    // The default fuel is plenty for 10,000 iterations
+    let output = run_vudo(&["run", spirit], temp_dir.path());
+    assert_success(&output, "vudo run");
+
+    let output = run_vudo(&["run", spirit, "--max-fuel", "100"], temp_dir.path());
+    assert_failure(&output, "vudo run --max-fuel 100");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Spirit paused: out of fuel after 100 units"),
+        "Expected a fuel pause: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_run_rejects_out_of_range_max_memory() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let spirit = write_counting_spirit(temp_dir.path());
+
+    let output = run_vudo(
+        &["run", spirit.to_str().unwrap(), "--max-memory-mb", "4096"],
+        temp_dir.path(),
+    );
+    assert_failure(&output, "vudo run --max-memory-mb 4096");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Invalid resource limits: Memory limit 4294967296 exceeds maximum"),
+        "Expected a limit validation error: {}",
+        stderr
+    );
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Calling"));
+}
+
+// =============================================================================
+// Test 17: vudo fmt --check
+// =============================================================================
+
+#[test]
+fn test_fmt_check_passes_formatted_input() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let file = temp_dir.path().join("tidy.dol");
+    fs::write(&file, "gene Tidy {\n    field: u64\n}\n").unwrap();
+
+    let output = run_vudo(&["fmt", "--check", "tidy.dol"], temp_dir.path());
+    assert_success(&output, "vudo fmt --check on formatted input");
+}
+
+#[test]
+fn test_fmt_check_reports_unformatted_input() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let file = temp_dir.path().join("messy.dol");
+    let messy = "gene Messy {   \r\n    field: u64\n}\n\n\n";
+    fs::write(&file, messy).unwrap();
+
+    let output = run_vudo(&["fmt", "--check", "messy.dol"], temp_dir.path());
+    assert_failure(&output, "vudo fmt --check on unformatted input");
+    assert_eq!(output.status.code(), Some(2));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("messy.dol"),
+        "File not reported: {}",
+        stdout
+    );
+    assert!(
+        stdout.contains(r#"- "gene Messy {   \r""#) && stdout.contains(r#"+ "gene Messy {""#),
+        "Diff not shown: {}",
+        stdout
+    );
+
+    // Nothing is written in check mode
+    assert_eq!(fs::read_to_string(&file).unwrap(), messy);
+}
+
+// =============================================================================
+// Test 18: vudo run --owner
+// =============================================================================
+
+#[test]
+fn test_run_with_hex_owner() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let project_path = create_compatible_spirit_project(temp_dir.path(), "owner-test");
+
+    let output = run_vudo(&["build"], &project_path);
+    assert_success(&output, "vudo build");
+
+    let owner = "ab".repeat(32);
+    let output = run_vudo(&["run", "--owner", &owner], &project_path);
+    assert_success(&output, "vudo run --owner");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&owner), "Owner not shown: {}", stdout);
+}
+
+#[test]
+fn test_run_rejects_malformed_owner() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+
+    for owner in ["a".repeat(63), "zz".repeat(32)] {
+        let output = run_vudo(&["run", "--owner", &owner], temp_dir.path());
+        assert_failure(&output, "vudo run --owner <malformed>");
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("public key"), "Unclear error: {}", stderr);
+    }
+}
+
+// =============================================================================
+// Test 19: vudo run <file.wat>
+// =============================================================================
+
+#[test]
+fn test_run_wat_file_prints_result() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    fs::write(
+        temp_dir.path().join("answer.wat"),
+        r#"(module
+            (func (export "main") (result i32) i32.const 42)
+            (func (export "double") (result i64) i64.const 84))"#,
+    )
+    .unwrap();
+
+    let output = run_vudo(&["run", "answer.wat"], temp_dir.path());
+    assert_success(&output, "vudo run answer.wat");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Returned: 42"),
+        "Result not shown: {}",
+        stdout
+    );
+
+    let output = run_vudo(&["run", "answer.wat", "--entry", "double"], temp_dir.path());
+    assert_success(&output, "vudo run answer.wat --entry double");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Returned: 84"),
+        "Result not shown: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_run_wat_file_reports_parse_error() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    fs::write(temp_dir.path().join("broken.wat"), "(module (func $oops").unwrap();
+
+    let output = run_vudo(&["run", "broken.wat"], temp_dir.path());
+    assert_failure(&output, "vudo run broken.wat");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Failed to parse WAT file"),
+        "Parse error not reported: {}",
+        stderr
+    );
+}
+
+// =============================================================================
+// Test 20: Manifest export declarations
+// =============================================================================
+
+fn declare_export(project_path: &Path, export: &str) {
+    let manifest_path = project_path.join("manifest.toml");
+    let mut manifest = fs::read_to_string(&manifest_path).unwrap();
+    manifest.push_str(export);
+    fs::write(manifest_path, manifest).unwrap();
+}
+
+#[test]
+fn test_build_verifies_declared_exports() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let project_path = create_compatible_spirit_project(temp_dir.path(), "exports-spirit");
+    declare_export(&project_path, "\n[[exports]]\nname = \"main\"\n");
+
+    let output = run_vudo(&["build"], &project_path);
+    assert_success(&output, "vudo build with matching exports");
+
+    let output = run_vudo(&["check"], &project_path);
+    assert_success(&output, "vudo check with matching exports");
+}
+
+#[test]
+fn test_build_rejects_mismatched_export() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let project_path = create_compatible_spirit_project(temp_dir.path(), "exports-spirit");
+    declare_export(
+        &project_path,
+        "\n[[exports]]\nname = \"main\"\nparams = [\"i32\"]\nresults = [\"i32\"]\n",
+    );
+
+    let output = run_vudo(&["build"], &project_path);
+    assert_failure(&output, "vudo build with mismatched export");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("declared main(i32) -> i32, module has main()"),
+        "Mismatch not reported: {}",
+        stderr
+    );
+    assert!(!project_path.join("exports-spirit.spirit").exists());
+}
+
+// =============================================================================
+// Test 21: vudo check reports every manifest problem
+// =============================================================================
+
+#[test]
+fn test_check_reports_all_manifest_errors() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let project_path = create_compatible_spirit_project(temp_dir.path(), "bad-manifest");
+    let manifest_path = project_path.join("manifest.toml");
+    let manifest = fs::read_to_string(&manifest_path)
+        .unwrap()
+        .replace("name = \"bad-manifest\"", "name = \"bad name\"")
+        .replace(&"a".repeat(64), "not-hex");
+    fs::write(&manifest_path, manifest).unwrap();
+
+    let output = run_vudo(&["check", "--format", "json"], &project_path);
+    assert_failure(&output, "vudo check with invalid manifest");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    assert_eq!(report["errors"], 2);
+    let messages = report["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["file"].as_str().unwrap().ends_with("manifest.toml"))
+        .expect("Manifest not checked")["messages"]
+        .to_string();
+    assert!(messages.contains("Invalid name"), "{}", messages);
+    assert!(messages.contains("Invalid author"), "{}", messages);
+}
+
+// =============================================================================
+// Test 22: vudo run grants manifest capabilities
+// =============================================================================
+
+#[test]
+fn test_run_grants_manifest_capabilities() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let project_path = create_compatible_spirit_project(temp_dir.path(), "caps-spirit");
+    let manifest_path = project_path.join("manifest.toml");
+    let manifest = fs::read_to_string(&manifest_path).unwrap().replace(
+        "[pricing]",
+        "capabilities = [\"sensor_time\", \"actuator_log\"]\n\n[pricing]",
+    );
+    fs::write(&manifest_path, manifest).unwrap();
+
+    let output = run_vudo(&["build"], &project_path);
+    assert_success(&output, "vudo build");
+
+    let output = run_vudo(&["run"], &project_path);
+    assert_success(&output, "vudo run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Capability: SensorTime") && stdout.contains("Capability: ActuatorLog"),
+        "Manifest capabilities not granted: {}",
+        stdout
+    );
+}
+
+// =============================================================================
+// Test 23: vudo run pricing overrides
+// =============================================================================
+
+/// Fuel reported by `vudo run` for a completed Spirit
+fn reported_fuel(stdout: &str) -> u64 {
+    stdout
+        .split("Spirit returned successfully (")
+        .nth(1)
+        .and_then(|rest| rest.split(" fuel)").next())
+        .and_then(|fuel| fuel.parse().ok())
+        .unwrap_or_else(|| panic!("Fuel not reported: {}", stdout))
+}
+
+#[test]
+fn test_run_free_charges_nothing() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let spirit = write_counting_spirit(temp_dir.path());
+
+    let output = run_vudo(
+        &["run", spirit.to_str().unwrap(), "--free"],
+        temp_dir.path(),
+    );
+    assert_success(&output, "vudo run --free");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Charge: 0 microcredits"),
+        "Run was charged: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_run_pricing_overrides_set_charge() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let spirit = write_counting_spirit(temp_dir.path());
+
+    let output = run_vudo(
+        &[
+            "run",
+            spirit.to_str().unwrap(),
+            "--pricing-base",
+            "500",
+            "--pricing-per-fuel",
+            "2000",
+        ],
+        temp_dir.path(),
+    );
+    assert_success(&output, "vudo run with pricing overrides");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fuel = reported_fuel(&stdout);
+    assert!(fuel > 0);
+    let expected = format!("Charge: {} microcredits", 500 + fuel * 2);
+    assert!(
+        stdout.contains(&expected),
+        "Expected {}: {}",
+        expected,
+        stdout
+    );
+}
+
+/// Build a `{name}.spirit` whose `main` calls `host_time_now`
+fn write_clock_spirit(project_path: &Path, name: &str) {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+          (import "vudo" "host_time_now" (func $now (result i64)))
+          (func (export "main") (drop (call $now))))
+        "#,
+    )
+    .expect("Invalid WAT");
+    fs::write(project_path.join(format!("{}.spirit", name)), wasm).unwrap();
+}
+
+#[test]
+fn test_run_charges_surcharge_for_used_capabilities() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let project_path = create_compatible_spirit_project(temp_dir.path(), "clock-spirit");
+    let manifest_path = project_path.join("manifest.toml");
+    let manifest = fs::read_to_string(&manifest_path).unwrap().replace(
+        "[pricing]",
+        "capabilities = [\"sensor_time\", \"actuator_log\"]\n\n[pricing]",
+    );
+    // Only the capability the Spirit invokes is surcharged
+    let manifest = format!(
+        "{}\n[pricing.capability_surcharge]\nsensor_time = 7000\nactuator_log = 300\n",
+        manifest
+    );
+    fs::write(&manifest_path, manifest).unwrap();
+    write_clock_spirit(&project_path, "clock-spirit");
+
+    let output = run_vudo(&["run"], &project_path);
+    assert_success(&output, "vudo run with capability surcharges");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fuel = reported_fuel(&stdout);
+    let expected = format!("Charge: {} microcredits", 100 + fuel / 1000 + 7000);
+    assert!(
+        stdout.contains(&expected),
+        "Expected {}: {}",
+        expected,
+        stdout
+    );
+}
+
+#[test]
+fn test_run_rejects_negative_pricing() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let spirit = write_counting_spirit(temp_dir.path());
+
+    let output = run_vudo(
+        &["run", spirit.to_str().unwrap(), "--pricing-base", "-5"],
+        temp_dir.path(),
+    );
+    assert_failure(&output, "vudo run --pricing-base -5");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Price must be non-negative"),
+        "Negative price not rejected: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_run_accepts_prices_above_i64_max() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let spirit = write_counting_spirit(temp_dir.path());
+
+    let output = run_vudo(
+        &[
+            "run",
+            spirit.to_str().unwrap(),
+            "--pricing-base",
+            &u64::MAX.to_string(),
+        ],
+        temp_dir.path(),
+    );
+    assert_success(&output, "vudo run --pricing-base u64::MAX");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let expected = format!("Charge: {} microcredits", u64::MAX);
+    assert!(
+        stdout.contains(&expected),
+        "Expected {}: {}",
+        expected,
+        stdout
+    );
+}
+
+// =============================================================================
+// Test 24: vudo check --fix
+// =============================================================================
+
+/// Declare `capabilities` in the project's manifest and build a module that
+/// imports `host_storage_write`
+fn write_storage_spirit(project_path: &Path, name: &str, capabilities: &str) {
+    let manifest_path = project_path.join("manifest.toml");
+    let manifest = fs::read_to_string(&manifest_path).unwrap().replace(
+        "[pricing]",
+        &format!("capabilities = {}\n\n[pricing]", capabilities),
+    );
+    fs::write(&manifest_path, manifest).unwrap();
+
+    let wasm = wat::parse_str(
+        r#"
+        (module
+          (import "vudo" "host_storage_write" (func (param i32 i32 i32 i32) (result i32))))
+        "#,
+    )
+    .expect("Invalid WAT");
+    fs::write(project_path.join(format!("{}.spirit", name)), wasm).unwrap();
+}
+
+fn manifest_capabilities(project_path: &Path) -> Vec<String> {
+    let manifest = fs::read_to_string(project_path.join("manifest.toml")).unwrap();
+    let manifest: toml::Value = toml::from_str(&manifest).expect("Rewritten manifest is invalid");
+    manifest["capabilities"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c.as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_check_fix_adds_imported_capability() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let project_path = create_compatible_spirit_project(temp_dir.path(), "fix-spirit");
+    write_storage_spirit(&project_path, "fix-spirit", "[\"storage_read\"]");
+
+    // Without --fix the problem is only reported
+    let output = run_vudo(&["check"], &project_path);
+    assert_failure(&output, "vudo check with undeclared capability");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("undeclared capability storage_write"),
+        "Undeclared capability not reported: {}",
+        stdout
+    );
+    assert_eq!(manifest_capabilities(&project_path), ["storage_read"]);
+
+    let output = run_vudo(&["check", "--fix"], &project_path);
+    assert_success(&output, "vudo check --fix");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Added capability storage_write"),
+        "Fix not reported: {}",
+        stdout
+    );
+    assert_eq!(
+        manifest_capabilities(&project_path),
+        ["storage_read", "storage_write"]
+    );
+}
+
+#[test]
+fn test_check_fix_deduplicates_and_sorts_capabilities() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let project_path = create_compatible_spirit_project(temp_dir.path(), "dup-spirit");
+    write_storage_spirit(
+        &project_path,
+        "dup-spirit",
+        "[\"sensor_time\", \"storage_write\", \"sensor_time\"]",
+    );
+
+    let output = run_vudo(&["check", "--fix", "--format", "json"], &project_path);
+    assert_success(&output, "vudo check --fix");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("Invalid JSON output");
+    assert_eq!(
+        report["fixes"],
+        serde_json::json!([
+            "Removed duplicate capability sensor_time",
+            "Sorted capabilities"
+        ])
+    );
+    assert_eq!(
+        manifest_capabilities(&project_path),
+        ["storage_write", "sensor_time"]
+    );
+
+    // A fixed manifest needs no further changes
+    let output = run_vudo(&["check", "--fix", "--format", "json"], &project_path);
+    assert_success(&output, "vudo check --fix again");
+    let report: serde_json::Value =
+        serde_json::from_str(&String::from_utf8_lossy(&output.stdout)).unwrap();
+    assert_eq!(report["fixes"], serde_json::json!([]));
+}
+
+// =============================================================================
+// Test 25: vudo summon --verify-only
+// =============================================================================
+
+/// Publish a `vudo sign`-style package of `signed_manifest(name)` into a
+/// local registry directory, optionally altering the WASM after signing.
+fn write_registry_package(registry: &Path, name: &str, tamper: bool) {
+    use ed25519_dalek::Signer;
+    use sha2::{Digest, Sha256};
+
+    let key = SigningKey::from_bytes(&[3u8; 32]);
+    let manifest = signed_manifest(name);
+    let mut body = fs::read(write_package(registry, &manifest, EMPTY_WASM)).unwrap();
+    fs::remove_file(registry.join(format!("{}.spirit", name))).unwrap();
+    let signature = key.sign(&Sha256::digest(&body));
+    if tamper {
+        body.push(0x00);
+    }
+
+    let mut package = b"SIGNED\n".to_vec();
+    package.extend_from_slice(key.verifying_key().as_bytes());
+    package.push(b'\n');
+    package.extend_from_slice(&signature.to_bytes());
+    package.push(b'\n');
+    package.extend_from_slice(&body);
+    fs::write(
+        registry.join(format!("{}-{}.spirit", name, manifest.version)),
+        package,
+    )
+    .unwrap();
+}
+
+fn summon_verify_only(temp_path: &Path, name: &str) -> Output {
+    let registry = temp_path.join("registry");
+    let home = temp_path.join("home");
+    fs::create_dir_all(&home).unwrap();
+    run_vudo_with_env(
+        &[
+            "summon",
+            name,
+            "--verify-only",
+            "--registry",
+            registry.to_str().unwrap(),
+        ],
+        temp_path,
+        &[("HOME", home.to_str().unwrap())],
+    )
+}
+
+#[test]
+fn test_summon_verify_only_accepts_signed_spirit() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+    fs::create_dir_all(temp_path.join("registry")).unwrap();
+    write_registry_package(&temp_path.join("registry"), "trusted", false);
+
+    let output = summon_verify_only(temp_path, "trusted");
+    assert_success(&output, "vudo summon --verify-only");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let author = hex::encode(
+        SigningKey::from_bytes(&[3u8; 32])
+            .verifying_key()
+            .as_bytes(),
+    );
+    assert!(stdout.contains(&author), "Author not reported: {}", stdout);
+    assert!(stdout.contains("manifest signature verified"), "{}", stdout);
+    assert!(stdout.contains("artifact signature verified"), "{}", stdout);
+
+    // Nothing was installed or downloaded into the home directory
+    assert_eq!(fs::read_dir(temp_path.join("home")).unwrap().count(), 0);
+}
+
+#[test]
+fn test_summon_verify_only_rejects_tampered_spirit() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+    fs::create_dir_all(temp_path.join("registry")).unwrap();
+    write_registry_package(&temp_path.join("registry"), "tampered", true);
+
+    let output = summon_verify_only(temp_path, "tampered@0.1.0");
+    assert_failure(&output, "vudo summon --verify-only (tampered)");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("manifest signature verified"), "{}", stdout);
+    assert!(stdout.contains("artifact signature invalid"), "{}", stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Verification failed"), "{}", stderr);
+
+    assert_eq!(fs::read_dir(temp_path.join("home")).unwrap().count(), 0);
+}
@@ -3,7 +3,7 @@
 // Based on: docs/ontology/prospective/vudo-vm/genes/capability.dol
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 /// Serialize/deserialize wrapper for [u8; 64]
 mod signature_serde {
@@ -45,7 +45,7 @@ mod signature_serde {
 ///
 /// The Unrestricted capability is only granted to system Spirits
 /// and bypasses all capability checks.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum CapabilityType {
     // Network capabilities
     NetworkListen,
@@ -75,6 +75,102 @@ pub enum CapabilityType {
     Unrestricted, // Only for system Spirits
 }
 
+impl CapabilityType {
+    /// Names of the `vudo` host functions this capability gates.
+    ///
+    /// Empty for capabilities no host function checks yet, and for
+    /// `Unrestricted`, which unlocks every gated function instead.
+    pub fn host_functions(&self) -> &'static [&'static str] {
+        match self {
+            CapabilityType::NetworkListen => &["host_network_listen"],
+            CapabilityType::NetworkConnect => &["host_network_connect"],
+            CapabilityType::NetworkBroadcast => &["host_network_broadcast"],
+            CapabilityType::StorageRead => &["host_storage_read", "host_storage_exists"],
+            CapabilityType::StorageWrite => &[
+                "host_storage_write",
+                "host_storage_write_ttl",
+                "host_storage_increment",
+            ],
+            CapabilityType::StorageDelete => &["host_storage_delete"],
+            CapabilityType::CrossSandboxCall => &["host_call_sandbox"],
+            CapabilityType::SensorTime => &["host_time_now", "host_time_sleep"],
+            CapabilityType::SensorRandom => &["host_random_bytes"],
+            CapabilityType::ActuatorLog => &["host_log", "host_metrics_emit"],
+            CapabilityType::ActuatorCredit => &[
+                "host_credit_balance",
+                "host_credit_transfer",
+                "host_credit_reserve",
+                "host_credit_release",
+                "host_credit_consume",
+                "host_credit_available",
+            ],
+            CapabilityType::SpawnSandbox
+            | CapabilityType::SensorEnvironment
+            | CapabilityType::ActuatorNotify
+            | CapabilityType::Unrestricted => &[],
+        }
+    }
+
+    /// How much harm a Spirit holding this capability could do, for
+    /// prompting users before they approve a grant.
+    pub const fn risk_level(&self) -> RiskLevel {
+        match self {
+            CapabilityType::SensorTime
+            | CapabilityType::SensorRandom
+            | CapabilityType::StorageRead
+            | CapabilityType::ActuatorLog => RiskLevel::Low,
+            CapabilityType::NetworkListen
+            | CapabilityType::StorageWrite
+            | CapabilityType::StorageDelete
+            | CapabilityType::CrossSandboxCall
+            | CapabilityType::SensorEnvironment
+            | CapabilityType::ActuatorNotify => RiskLevel::Medium,
+            CapabilityType::NetworkConnect
+            | CapabilityType::NetworkBroadcast
+            | CapabilityType::SpawnSandbox
+            | CapabilityType::ActuatorCredit
+            | CapabilityType::Unrestricted => RiskLevel::High,
+        }
+    }
+
+    /// Whether this capability is classified [`RiskLevel::High`]
+    pub fn is_dangerous(&self) -> bool {
+        self.risk_level() == RiskLevel::High
+    }
+}
+
+/// Risk classification of a capability, ordered from least to most risky.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    /// Weight of this level in [`CapabilitySet::risk_score`]
+    pub const fn weight(&self) -> u32 {
+        match self {
+            RiskLevel::Low => 1,
+            RiskLevel::Medium => 3,
+            RiskLevel::High => 9,
+        }
+    }
+}
+
+/// Capability gating the `vudo` host function `name`.
+///
+/// The reverse of [`CapabilityType::host_functions`]. Returns `None` for
+/// functions that need no capability (`host_last_error`), whose capability
+/// depends on the call (`host_invoke`), and for names that are not host
+/// functions.
+pub fn host_function_capability(name: &str) -> Option<CapabilityType> {
+    ALL_CAPABILITY_TYPES
+        .iter()
+        .copied()
+        .find(|cap| cap.host_functions().contains(&name))
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // CAPABILITY SCOPE
 // ═══════════════════════════════════════════════════════════════════════════
@@ -133,16 +229,30 @@ pub struct CapabilityGrant {
     pub id: u64,
     pub capability: CapabilityType,
     pub scope: CapabilityScope,
+    /// Peer key or address (or its SHA-256) a non-Global grant is bound to
+    #[serde(default)]
+    pub scope_target: Option<Vec<u8>>,
     pub granter: [u8; 32], // Ed25519 public key
     pub grantee: [u8; 32], // Ed25519 public key
     pub granted_at: u64,   // Unix timestamp in seconds
     pub expires_at: Option<u64>,
+    /// Unix timestamp (seconds) before which the grant is not yet valid
+    #[serde(default)]
+    pub not_before: Option<u64>,
     pub revoked: bool,
+    /// Replay-protection nonce, unique per granter (0 for none)
+    #[serde(default)]
+    pub nonce: u64,
     #[serde(with = "signature_serde")]
     pub signature: [u8; 64], // Ed25519 signature
 }
 
 impl CapabilityGrant {
+    /// Start building a grant of `capability` with named setters
+    pub fn builder(capability: CapabilityType) -> CapabilityGrantBuilder {
+        CapabilityGrantBuilder::new(capability)
+    }
+
     /// Create a new capability grant
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -159,26 +269,63 @@ impl CapabilityGrant {
             id,
             capability,
             scope,
+            scope_target: None,
             granter,
             grantee,
             granted_at,
             expires_at,
+            not_before: None,
             revoked: false,
+            nonce: 0,
             signature,
         }
     }
 
-    /// Check if the grant is currently valid (not expired and not revoked)
+    /// Bind the grant to a specific peer key or address
+    pub fn with_scope_target(mut self, target: impl Into<Vec<u8>>) -> Self {
+        self.scope_target = Some(target.into());
+        self
+    }
+
+    /// Check whether the grant applies to `target`
+    ///
+    /// `Global` grants apply to every target. Other grants apply only if
+    /// their `scope_target` equals `target` or its SHA-256 digest.
+    pub fn matches_target(&self, target: &[u8]) -> bool {
+        use sha2::{Digest, Sha256};
+
+        if self.scope == CapabilityScope::Global {
+            return true;
+        }
+        match &self.scope_target {
+            Some(bound) => bound == target || bound[..] == Sha256::digest(target)[..],
+            None => false,
+        }
+    }
+
+    /// Order of grants of one capability within a [`CapabilitySet`]
+    fn sort_key(&self) -> (u64, [u8; 32], u64) {
+        (self.id, self.granter, self.nonce)
+    }
+
+    /// Check if the grant is currently valid (active, not expired and not revoked)
     pub fn is_valid(&self) -> bool {
         self.is_valid_at(current_timestamp())
     }
 
     /// Check if the grant is valid at a specific timestamp
+    ///
+    /// A grant is valid from `not_before` (inclusive) up to `expires_at`
+    /// (exclusive); either bound may be absent.
     pub fn is_valid_at(&self, now: u64) -> bool {
         if self.revoked {
             return false;
         }
 
+        if self.not_before.is_some_and(|start| now < start) {
+            return false;
+        }
+
         match self.expires_at {
             Some(expiry) => now < expiry,
             None => true,
@@ -212,12 +359,30 @@ impl CapabilityGrant {
 
         hasher.update([self.revoked as u8]);
 
+        // Appended only when set so untargeted grants keep their hash
+        if let Some(target) = &self.scope_target {
+            hasher.update((target.len() as u64).to_le_bytes());
+            hasher.update(target);
+        }
+
+        // Likewise for the activation time; the tag keeps it distinct from
+        // a scope target's length prefix
+        if let Some(not_before) = self.not_before {
+            hasher.update(b"nbf");
+            hasher.update(not_before.to_le_bytes());
+        }
+
+        if self.nonce != 0 {
+            hasher.update(b"nonce");
+            hasher.update(self.nonce.to_le_bytes());
+        }
+
         hasher.finalize().into()
     }
 
     /// Verify the signature on this grant (requires ed25519-dalek dependency)
     pub fn verify_signature(&self) -> bool {
-        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+        use ed25519_dalek::{Signature, VerifyingKey};
 
         let public_key = match VerifyingKey::from_bytes(&self.granter) {
             Ok(pk) => pk,
@@ -226,8 +391,175 @@ impl CapabilityGrant {
 
         let signature = Signature::from_bytes(&self.signature);
 
+        // Strict verification rejects small-order keys such as the all-zero
+        // granter of an unsigned grant, which plain `verify` can accept
         let message = self.hash_for_signing();
-        public_key.verify(&message, &signature).is_ok()
+        public_key.verify_strict(&message, &signature).is_ok()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CAPABILITY GRANT BUILDER
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Builds a [`CapabilityGrant`] without positional arguments.
+///
+/// Defaults: id 0, `Global` scope with no target, zero granter and grantee
+/// keys, granted now, no expiry, no nonce, unsigned. [`sign`](Self::sign)
+/// sets the granter to the signing key's public key and, if no nonce was
+/// set, draws a random one.
+///
+/// ```
+/// use vudo_vm::{CapabilityGrant, CapabilityScope, CapabilityType};
+///
+/// let granter = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+/// let grant = CapabilityGrant::builder(CapabilityType::StorageRead)
+///     .id(1)
+///     .scope(CapabilityScope::Sandboxed)
+///     .grantee([9u8; 32])
+///     .sign(&granter);
+///
+/// assert!(grant.verify_signature());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CapabilityGrantBuilder {
+    grant: CapabilityGrant,
+}
+
+impl CapabilityGrantBuilder {
+    /// Start a grant of `capability` with the defaults above
+    pub fn new(capability: CapabilityType) -> Self {
+        Self {
+            grant: CapabilityGrant::new(
+                0,
+                capability,
+                CapabilityScope::Global,
+                [0u8; 32],
+                [0u8; 32],
+                current_timestamp(),
+                None,
+                [0u8; 64],
+            ),
+        }
+    }
+
+    /// Grant ID, unique per granter
+    pub fn id(mut self, id: u64) -> Self {
+        self.grant.id = id;
+        self
+    }
+
+    /// Scope the grant applies to
+    pub fn scope(mut self, scope: CapabilityScope) -> Self {
+        self.grant.scope = scope;
+        self
+    }
+
+    /// Peer key or address a non-Global grant is bound to
+    pub fn scope_target(mut self, target: impl Into<Vec<u8>>) -> Self {
+        self.grant.scope_target = Some(target.into());
+        self
+    }
+
+    /// Public key of the granter; replaced by [`sign`](Self::sign)
+    pub fn granter(mut self, granter: [u8; 32]) -> Self {
+        self.grant.granter = granter;
+        self
+    }
+
+    /// Public key of the Spirit receiving the grant
+    pub fn grantee(mut self, grantee: [u8; 32]) -> Self {
+        self.grant.grantee = grantee;
+        self
+    }
+
+    /// Unix timestamp (seconds) the grant was issued
+    pub fn granted_at(mut self, granted_at: u64) -> Self {
+        self.grant.granted_at = granted_at;
+        self
+    }
+
+    /// Unix timestamp (seconds) the grant stops being valid
+    pub fn expires_at(mut self, expires_at: u64) -> Self {
+        self.grant.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Unix timestamp (seconds) the grant starts being valid
+    pub fn not_before(mut self, not_before: u64) -> Self {
+        self.grant.not_before = Some(not_before);
+        self
+    }
+
+    /// Replay-protection nonce; must not repeat for the same granter
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.grant.nonce = nonce;
+        self
+    }
+
+    /// Finish the grant without a signature
+    pub fn build(self) -> CapabilityGrant {
+        self.grant
+    }
+
+    /// Finish the grant signed by `granter` over [`CapabilityGrant::hash_for_signing`]
+    pub fn sign(self, granter: &ed25519_dalek::SigningKey) -> CapabilityGrant {
+        use ed25519_dalek::Signer;
+
+        let mut grant = self.grant;
+        grant.granter = granter.verifying_key().to_bytes();
+        while grant.nonce == 0 {
+            let mut bytes = [0u8; 8];
+            getrandom::getrandom(&mut bytes).expect("OS randomness unavailable");
+            grant.nonce = u64::from_le_bytes(bytes);
+        }
+        grant.signature = granter.sign(&grant.hash_for_signing()).to_bytes();
+        grant
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// REVOCATION LIST
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Grants revoked centrally, identified by granter key and grant ID.
+///
+/// Lets a granter withdraw a grant it no longer holds a copy of. A grant
+/// listed here is invalid even if its own `revoked` flag is false.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevocationList {
+    revoked: BTreeSet<([u8; 32], u64)>,
+}
+
+impl RevocationList {
+    /// Create an empty revocation list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Revoke grant `grant_id` issued by `granter`
+    pub fn revoke(&mut self, granter: [u8; 32], grant_id: u64) {
+        self.revoked.insert((granter, grant_id));
+    }
+
+    /// Lift a revocation, returning whether it was present
+    pub fn unrevoke(&mut self, granter: [u8; 32], grant_id: u64) -> bool {
+        self.revoked.remove(&(granter, grant_id))
+    }
+
+    /// Check whether a grant has been revoked through this list
+    pub fn is_revoked(&self, grant: &CapabilityGrant) -> bool {
+        self.revoked.contains(&(grant.granter, grant.id))
+    }
+
+    /// Number of revoked grants
+    pub fn len(&self) -> usize {
+        self.revoked.len()
+    }
+
+    /// Check if the list is empty
+    pub fn is_empty(&self) -> bool {
+        self.revoked.is_empty()
     }
 }
 
@@ -243,20 +575,35 @@ impl CapabilityGrant {
 /// - System default capabilities
 ///
 /// Checked before every privileged operation.
+///
+/// Grants are keyed by capability in declaration order and kept sorted by
+/// id, granter and nonce within each capability, so `Debug` and serialized
+/// output do not depend on the order grants were added.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CapabilitySet {
-    pub grants: HashMap<CapabilityType, Vec<CapabilityGrant>>,
+    pub grants: BTreeMap<CapabilityType, Vec<CapabilityGrant>>,
+    /// Central revocations consulted alongside each grant's own state
+    #[serde(default)]
+    pub revocations: RevocationList,
+    /// (granter, nonce) pairs of every grant ever added, so a replayed
+    /// grant is refused even after the original was removed
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    seen_nonces: BTreeSet<([u8; 32], u64)>,
 }
 
 impl CapabilitySet {
     /// Create a new empty capability set
     pub fn new() -> Self {
         Self {
-            grants: HashMap::new(),
+            grants: BTreeMap::new(),
+            revocations: RevocationList::new(),
+            seen_nonces: BTreeSet::new(),
         }
     }
 
     /// Create a capability set from a list of grants
+    ///
+    /// Replayed grants are dropped, as by [`Self::add_grant`].
     pub fn from_grants(grants: Vec<CapabilityGrant>) -> Self {
         let mut capability_set = Self::new();
         for grant in grants {
@@ -265,9 +612,77 @@ impl CapabilitySet {
         capability_set
     }
 
-    /// Add a grant to this capability set
-    pub fn add_grant(&mut self, grant: CapabilityGrant) {
-        self.grants.entry(grant.capability).or_default().push(grant);
+    /// Create a capability set with one unsigned, never-expiring, globally
+    /// scoped grant per capability type. Grant IDs are assigned from 1 in
+    /// slice order.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn with_types(types: &[CapabilityType]) -> Self {
+        let granted_at = current_timestamp();
+        let grants = types
+            .iter()
+            .enumerate()
+            .map(|(i, &capability)| {
+                CapabilityGrant::new(
+                    i as u64 + 1,
+                    capability,
+                    CapabilityScope::Global,
+                    [0u8; 32],
+                    [0u8; 32],
+                    granted_at,
+                    None,
+                    [0u8; 64],
+                )
+            })
+            .collect();
+        Self::from_grants(grants)
+    }
+
+    /// Create a capability set holding the `Unrestricted` capability, which
+    /// passes every check. For tests only.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn all_for_testing() -> Self {
+        Self::with_types(&[CapabilityType::Unrestricted])
+    }
+
+    /// Replace the revocation list consulted by this set
+    pub fn set_revocation_list(&mut self, revocations: RevocationList) {
+        self.revocations = revocations;
+    }
+
+    /// Check a grant is valid and not revoked through the revocation list
+    fn is_usable(&self, grant: &CapabilityGrant) -> bool {
+        grant.is_valid() && !self.revocations.is_revoked(grant)
+    }
+
+    /// Add a grant to this capability set, in (id, granter, nonce) order
+    /// after any grants with the same key
+    ///
+    /// Returns false and leaves the set unchanged if a grant with the same
+    /// granter and nonce was added before, even if it has since been
+    /// removed: the grant is a replay. Grants without a nonce are always
+    /// added.
+    pub fn add_grant(&mut self, grant: CapabilityGrant) -> bool {
+        if grant.nonce != 0 {
+            let key = (grant.granter, grant.nonce);
+            let held = self.iter().any(|g| (g.granter, g.nonce) == key);
+            if held || !self.seen_nonces.insert(key) {
+                return false;
+            }
+        }
+
+        let grants = self.grants.entry(grant.capability).or_default();
+        let key = grant.sort_key();
+        let pos = grants.partition_point(|g| g.sort_key() <= key);
+        grants.insert(pos, grant);
+        true
+    }
+
+    /// Iterate over every grant, valid or not, ordered by id, capability,
+    /// granter, then nonce
+    pub fn iter(&self) -> impl Iterator<Item = &CapabilityGrant> {
+        let mut grants: Vec<_> = self.grants.values().flatten().collect();
+        grants.sort_by_key(|g| (g.id, g.capability, g.granter, g.nonce));
+        grants.into_iter()
     }
 
     /// Remove a grant by ID
@@ -285,7 +700,7 @@ impl CapabilitySet {
     pub fn has_capability(&self, cap: CapabilityType, scope: CapabilityScope) -> bool {
         // Unrestricted capability bypasses all checks
         if let Some(grants) = self.grants.get(&CapabilityType::Unrestricted) {
-            if grants.iter().any(|g| g.is_valid()) {
+            if grants.iter().any(|g| self.is_usable(g)) {
                 return true;
             }
         }
@@ -294,7 +709,31 @@ impl CapabilitySet {
         match self.grants.get(&cap) {
             Some(grants) => grants
                 .iter()
-                .any(|grant| grant.scope.covers(&scope) && grant.is_valid()),
+                .any(|grant| grant.scope.covers(&scope) && self.is_usable(grant)),
+            None => false,
+        }
+    }
+
+    /// Check if this set has a capability at `scope` that applies to `target`
+    ///
+    /// See [`CapabilityGrant::matches_target`]. `Unrestricted` applies to
+    /// every target.
+    pub fn has_capability_for(
+        &self,
+        cap: CapabilityType,
+        scope: CapabilityScope,
+        target: &[u8],
+    ) -> bool {
+        if let Some(grants) = self.grants.get(&CapabilityType::Unrestricted) {
+            if grants.iter().any(|g| self.is_usable(g)) {
+                return true;
+            }
+        }
+
+        match self.grants.get(&cap) {
+            Some(grants) => grants.iter().any(|grant| {
+                grant.scope.covers(&scope) && grant.matches_target(target) && self.is_usable(grant)
+            }),
             None => false,
         }
     }
@@ -303,14 +742,14 @@ impl CapabilitySet {
     pub fn effective_scope(&self, cap: CapabilityType) -> Option<CapabilityScope> {
         // Unrestricted capability gives global scope for everything
         if let Some(grants) = self.grants.get(&CapabilityType::Unrestricted) {
-            if grants.iter().any(|g| g.is_valid()) {
+            if grants.iter().any(|g| self.is_usable(g)) {
                 return Some(CapabilityScope::Global);
             }
         }
 
         match self.grants.get(&cap) {
             Some(grants) => {
-                let valid_grants: Vec<_> = grants.iter().filter(|g| g.is_valid()).collect();
+                let valid_grants: Vec<_> = grants.iter().filter(|g| self.is_usable(g)).collect();
 
                 if valid_grants.is_empty() {
                     return None;
@@ -332,6 +771,31 @@ impl CapabilitySet {
         }
     }
 
+    /// Get the domain `cap` is confined to, if it is only granted per domain
+    ///
+    /// Returns the `scope_target` of the first usable `Domain`-scoped grant
+    /// that has one. Returns `None` when no such grant exists, or when an
+    /// `Unrestricted` grant or a usable grant of `cap` at any other scope
+    /// applies, since those are not partitioned.
+    pub fn domain_target(&self, cap: CapabilityType) -> Option<&[u8]> {
+        if let Some(grants) = self.grants.get(&CapabilityType::Unrestricted) {
+            if grants.iter().any(|g| self.is_usable(g)) {
+                return None;
+            }
+        }
+
+        let usable: Vec<_> = self
+            .grants
+            .get(&cap)?
+            .iter()
+            .filter(|g| self.is_usable(g))
+            .collect();
+        if usable.iter().any(|g| g.scope != CapabilityScope::Domain) {
+            return None;
+        }
+        usable.iter().find_map(|g| g.scope_target.as_deref())
+    }
+
     /// Remove expired grants
     pub fn clean_expired(&mut self) {
         for grants in self.grants.values_mut() {
@@ -339,19 +803,107 @@ impl CapabilitySet {
         }
     }
 
-    /// Get all valid grants
+    /// Get all valid grants, ordered by id then capability
     pub fn valid_grants(&self) -> Vec<&CapabilityGrant> {
-        self.grants
-            .values()
-            .flat_map(|grants| grants.iter())
-            .filter(|g| g.is_valid())
-            .collect()
+        self.iter().filter(|g| self.is_usable(g)).collect()
     }
 
     /// Check if the set is empty (has no valid grants)
     pub fn is_empty(&self) -> bool {
         self.valid_grants().is_empty()
     }
+
+    /// Highest risk level among the capabilities with a valid grant, or
+    /// `None` if the set grants nothing
+    pub fn max_risk(&self) -> Option<RiskLevel> {
+        self.granted_types().map(|cap| cap.risk_level()).max()
+    }
+
+    /// Sum of [`RiskLevel::weight`] over each capability with a valid grant.
+    ///
+    /// Several grants of one capability count once. A usable `Unrestricted`
+    /// grant unlocks everything, so it scores [`Self::MAX_RISK_SCORE`].
+    pub fn risk_score(&self) -> u32 {
+        Self::score(self.granted_types())
+    }
+
+    /// [`Self::risk_score`] of a set granting exactly `granted`
+    fn score(granted: impl IntoIterator<Item = CapabilityType>) -> u32 {
+        let granted: Vec<_> = granted.into_iter().collect();
+        if granted.contains(&CapabilityType::Unrestricted) {
+            return Self::MAX_RISK_SCORE;
+        }
+        granted.iter().map(|cap| cap.risk_level().weight()).sum()
+    }
+
+    /// [`Self::risk_score`] of a set granting every capability except
+    /// `Unrestricted`, and of any set granting `Unrestricted`
+    pub const MAX_RISK_SCORE: u32 = {
+        let mut score = 0;
+        let mut i = 0;
+        while i < ALL_CAPABILITY_TYPES.len() {
+            score += match ALL_CAPABILITY_TYPES[i] {
+                CapabilityType::Unrestricted => 0,
+                cap => cap.risk_level().weight(),
+            };
+            i += 1;
+        }
+        score
+    };
+
+    /// Capability types with at least one valid grant, in declaration order
+    fn granted_types(&self) -> impl Iterator<Item = CapabilityType> + '_ {
+        self.grants
+            .iter()
+            .filter(|(_, grants)| grants.iter().any(|g| self.is_usable(g)))
+            .map(|(cap, _)| *cap)
+    }
+
+    /// Summarise the set as of `now` for reporting
+    ///
+    /// Unlike the other queries, which use the current time, every section
+    /// is evaluated at `now`. See [`CapabilityAudit`].
+    pub fn audit(&self, now: u64) -> CapabilityAudit {
+        let mut audit = CapabilityAudit::default();
+        for grant in self.iter() {
+            *audit.counts.entry(grant.capability).or_default() += 1;
+
+            if grant.revoked || self.revocations.is_revoked(grant) {
+                audit.revoked.push(grant.id);
+            } else if grant.is_valid_at(now) {
+                audit.active.insert(grant.capability);
+                if grant
+                    .expires_at
+                    .is_some_and(|expiry| expiry - now <= EXPIRY_WARNING_SECS)
+                {
+                    audit.expiring_soon.push(grant.id);
+                }
+            }
+        }
+        audit.risk_score = Self::score(audit.active.iter().copied());
+        audit
+    }
+}
+
+/// How close to expiry (seconds) a grant must be for
+/// [`CapabilitySet::audit`] to list it as expiring soon
+pub const EXPIRY_WARNING_SECS: u64 = 24 * 60 * 60;
+
+/// Snapshot of a [`CapabilitySet`] produced by [`CapabilitySet::audit`]
+///
+/// Grant ids are listed in [`CapabilitySet::iter`] order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityAudit {
+    /// Number of grants held per capability, whatever their state
+    pub counts: BTreeMap<CapabilityType, usize>,
+    /// Capabilities with at least one valid, unrevoked grant
+    pub active: BTreeSet<CapabilityType>,
+    /// Valid grants expiring within [`EXPIRY_WARNING_SECS`]
+    pub expiring_soon: Vec<u64>,
+    /// Grants revoked by their own flag or through the revocation list
+    pub revoked: Vec<u64>,
+    /// [`CapabilitySet::risk_score`] of the active capabilities
+    pub risk_score: u32,
 }
 
 impl Default for CapabilitySet {
@@ -381,9 +933,150 @@ pub const NETWORK_SPIRIT_CAPABILITIES: &[CapabilityType] = &[
     CapabilityType::StorageWrite,
 ];
 
+/// Standard capabilities for storage-backed spirits
+pub const STORAGE_SPIRIT_CAPABILITIES: &[CapabilityType] = &[
+    CapabilityType::SensorTime,
+    CapabilityType::SensorRandom,
+    CapabilityType::ActuatorLog,
+    CapabilityType::StorageRead,
+    CapabilityType::StorageWrite,
+    CapabilityType::StorageDelete,
+];
+
 /// System spirit capabilities (unrestricted access)
 pub const SYSTEM_SPIRIT_CAPABILITIES: &[CapabilityType] = &[CapabilityType::Unrestricted];
 
+// ═══════════════════════════════════════════════════════════════════════════
+// CAPABILITY PROFILES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A named bundle of capabilities that can be granted in one step.
+///
+/// Profiles parse from and display as `minimal`, `network`, `storage`,
+/// `system`, or `custom:NetworkConnect,StorageRead` for an explicit list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CapabilityProfile {
+    /// [`MINIMAL_CAPABILITIES`]
+    Minimal,
+    /// [`NETWORK_SPIRIT_CAPABILITIES`]
+    Network,
+    /// [`STORAGE_SPIRIT_CAPABILITIES`]
+    Storage,
+    /// [`SYSTEM_SPIRIT_CAPABILITIES`]
+    System,
+    /// An explicit list of capabilities
+    Custom(Vec<CapabilityType>),
+}
+
+impl CapabilityProfile {
+    /// The capability types this profile grants
+    pub fn to_capability_types(&self) -> Vec<CapabilityType> {
+        match self {
+            CapabilityProfile::Minimal => MINIMAL_CAPABILITIES.to_vec(),
+            CapabilityProfile::Network => NETWORK_SPIRIT_CAPABILITIES.to_vec(),
+            CapabilityProfile::Storage => STORAGE_SPIRIT_CAPABILITIES.to_vec(),
+            CapabilityProfile::System => SYSTEM_SPIRIT_CAPABILITIES.to_vec(),
+            CapabilityProfile::Custom(caps) => caps.clone(),
+        }
+    }
+
+    /// Build a capability set with one signed, globally scoped grant per
+    /// capability in the profile
+    pub fn to_capability_set(
+        &self,
+        granter: &ed25519_dalek::SigningKey,
+        grantee: [u8; 32],
+        expires_at: Option<u64>,
+    ) -> CapabilitySet {
+        let granted_at = current_timestamp();
+        let grants = self
+            .to_capability_types()
+            .into_iter()
+            .enumerate()
+            .map(|(i, capability)| {
+                let builder = CapabilityGrant::builder(capability)
+                    .id(i as u64 + 1)
+                    .grantee(grantee)
+                    .granted_at(granted_at);
+                match expires_at {
+                    Some(expiry) => builder.expires_at(expiry),
+                    None => builder,
+                }
+                .sign(granter)
+            })
+            .collect();
+
+        CapabilitySet::from_grants(grants)
+    }
+}
+
+impl std::fmt::Display for CapabilityProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CapabilityProfile::Minimal => write!(f, "minimal"),
+            CapabilityProfile::Network => write!(f, "network"),
+            CapabilityProfile::Storage => write!(f, "storage"),
+            CapabilityProfile::System => write!(f, "system"),
+            CapabilityProfile::Custom(caps) => {
+                let names: Vec<String> = caps.iter().map(|c| format!("{:?}", c)).collect();
+                write!(f, "custom:{}", names.join(","))
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for CapabilityProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "minimal" => return Ok(CapabilityProfile::Minimal),
+            "network" => return Ok(CapabilityProfile::Network),
+            "storage" => return Ok(CapabilityProfile::Storage),
+            "system" => return Ok(CapabilityProfile::System),
+            _ => {}
+        }
+
+        let list = s
+            .trim()
+            .strip_prefix("custom:")
+            .ok_or_else(|| format!("Unknown capability profile: {}", s))?;
+
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                ALL_CAPABILITY_TYPES
+                    .iter()
+                    .copied()
+                    .find(|c| format!("{:?}", c).eq_ignore_ascii_case(name))
+                    .ok_or_else(|| format!("Unknown capability: {}", name))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(CapabilityProfile::Custom)
+    }
+}
+
+/// Every capability type, used to parse custom profiles by name and to
+/// look up host functions
+pub(crate) const ALL_CAPABILITY_TYPES: &[CapabilityType] = &[
+    CapabilityType::NetworkListen,
+    CapabilityType::NetworkConnect,
+    CapabilityType::NetworkBroadcast,
+    CapabilityType::StorageRead,
+    CapabilityType::StorageWrite,
+    CapabilityType::StorageDelete,
+    CapabilityType::SpawnSandbox,
+    CapabilityType::CrossSandboxCall,
+    CapabilityType::SensorTime,
+    CapabilityType::SensorRandom,
+    CapabilityType::SensorEnvironment,
+    CapabilityType::ActuatorLog,
+    CapabilityType::ActuatorNotify,
+    CapabilityType::ActuatorCredit,
+    CapabilityType::Unrestricted,
+];
+
 // ═══════════════════════════════════════════════════════════════════════════
 // UTILITY FUNCTIONS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -417,6 +1110,94 @@ mod tests {
         assert!(!CapabilityScope::Sandboxed.covers(&CapabilityScope::Peer));
     }
 
+    #[test]
+    fn test_host_function_capability_inverts_host_functions() {
+        for &cap in ALL_CAPABILITY_TYPES {
+            for name in cap.host_functions() {
+                assert_eq!(host_function_capability(name), Some(cap), "{}", name);
+            }
+        }
+        assert_eq!(
+            host_function_capability("host_storage_exists"),
+            Some(CapabilityType::StorageRead)
+        );
+        assert_eq!(host_function_capability("host_last_error"), None);
+        assert_eq!(host_function_capability("host_invoke"), None);
+        assert_eq!(host_function_capability("not_a_host_fn"), None);
+    }
+
+    #[test]
+    fn test_with_types_grants_exactly_those() {
+        let caps =
+            CapabilitySet::with_types(&[CapabilityType::StorageRead, CapabilityType::StorageWrite]);
+
+        assert_eq!(caps.valid_grants().len(), 2);
+        for &cap in ALL_CAPABILITY_TYPES {
+            let expected = matches!(
+                cap,
+                CapabilityType::StorageRead | CapabilityType::StorageWrite
+            );
+            assert_eq!(
+                caps.has_capability(cap, CapabilityScope::Sandboxed),
+                expected,
+                "{:?}",
+                cap
+            );
+        }
+    }
+
+    #[test]
+    fn test_all_for_testing_is_unrestricted() {
+        let caps = CapabilitySet::all_for_testing();
+
+        for &cap in ALL_CAPABILITY_TYPES {
+            assert!(caps.has_capability(cap, CapabilityScope::Global));
+        }
+    }
+
+    #[test]
+    fn test_revocation_list_invalidates_grant() {
+        let mut caps = CapabilitySet::with_types(&[CapabilityType::StorageRead]);
+        assert!(caps.has_capability(CapabilityType::StorageRead, CapabilityScope::Sandboxed));
+
+        let mut crl = RevocationList::new();
+        crl.revoke([0u8; 32], 1);
+        caps.set_revocation_list(crl);
+
+        assert!(!caps.has_capability(CapabilityType::StorageRead, CapabilityScope::Sandboxed));
+        assert_eq!(caps.effective_scope(CapabilityType::StorageRead), None);
+        assert!(caps.is_empty());
+        // The grant itself is untouched
+        assert!(caps.grants[&CapabilityType::StorageRead][0].is_valid());
+
+        assert!(caps.revocations.unrevoke([0u8; 32], 1));
+        assert!(caps.has_capability(CapabilityType::StorageRead, CapabilityScope::Sandboxed));
+    }
+
+    #[test]
+    fn test_revocation_list_matches_granter() {
+        let mut caps = CapabilitySet::with_types(&[CapabilityType::Unrestricted]);
+
+        // Same id from a different granter does not apply
+        caps.revocations.revoke([9u8; 32], 1);
+        assert!(caps.has_capability(CapabilityType::NetworkConnect, CapabilityScope::Global));
+
+        caps.revocations.revoke([0u8; 32], 1);
+        assert!(!caps.has_capability(CapabilityType::NetworkConnect, CapabilityScope::Global));
+    }
+
+    #[test]
+    fn test_revocation_list_serialization() {
+        let mut crl = RevocationList::new();
+        crl.revoke([7u8; 32], 42);
+
+        let json = serde_json::to_string(&crl).unwrap();
+        let decoded: RevocationList = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded, crl);
+        assert_eq!(decoded.len(), 1);
+    }
+
     #[test]
     fn test_capability_grant_validity() {
         let now = current_timestamp();
@@ -525,6 +1306,88 @@ mod tests {
         assert_eq!(cap_set.effective_scope(CapabilityType::NetworkListen), None);
     }
 
+    #[test]
+    fn test_add_grant_rejects_replayed_nonce() {
+        let granter = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let grant = CapabilityGrant::builder(CapabilityType::StorageRead)
+            .id(1)
+            .nonce(42)
+            .sign(&granter);
+        assert!(grant.verify_signature());
+
+        let mut cap_set = CapabilitySet::new();
+        assert!(cap_set.add_grant(grant.clone()));
+        assert!(!cap_set.add_grant(grant.clone()));
+        assert_eq!(cap_set.valid_grants().len(), 1);
+
+        // Removing the original does not let it be replayed
+        assert!(cap_set.remove_grant(1));
+        assert!(!cap_set.add_grant(grant.clone()));
+
+        // The nonce is signed, so it cannot be changed to dodge the check
+        let mut altered = grant;
+        altered.nonce = 43;
+        assert!(!altered.verify_signature());
+    }
+
+    #[test]
+    fn test_add_grant_accepts_distinct_nonces() {
+        let granter = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let first = CapabilityGrant::builder(CapabilityType::StorageRead)
+            .id(1)
+            .nonce(1)
+            .sign(&granter);
+        let second = CapabilityGrant::builder(CapabilityType::StorageRead)
+            .id(1)
+            .nonce(2)
+            .sign(&granter);
+
+        let mut cap_set = CapabilitySet::new();
+        assert!(cap_set.add_grant(first));
+        assert!(cap_set.add_grant(second));
+        assert_eq!(cap_set.valid_grants().len(), 2);
+
+        // Signing without a nonce draws a fresh one each time
+        let unset = CapabilityGrant::builder(CapabilityType::StorageRead).id(2);
+        let (a, b) = (unset.clone().sign(&granter), unset.sign(&granter));
+        assert_ne!(a.nonce, 0);
+        assert!(cap_set.add_grant(a));
+        assert!(cap_set.add_grant(b));
+    }
+
+    #[test]
+    fn test_domain_target() {
+        let domain = |id, target: &[u8]| {
+            CapabilityGrant::builder(CapabilityType::StorageWrite)
+                .id(id)
+                .scope(CapabilityScope::Domain)
+                .scope_target(target.to_vec())
+                .build()
+        };
+
+        let mut cap_set = CapabilitySet::from_grants(vec![domain(1, b"alpha"), domain(2, b"beta")]);
+        assert_eq!(
+            cap_set.domain_target(CapabilityType::StorageWrite),
+            Some(&b"alpha"[..])
+        );
+        assert_eq!(cap_set.domain_target(CapabilityType::StorageRead), None);
+
+        // A broader grant lifts the partition
+        cap_set.add_grant(
+            CapabilityGrant::builder(CapabilityType::StorageWrite)
+                .id(3)
+                .build(),
+        );
+        assert_eq!(cap_set.domain_target(CapabilityType::StorageWrite), None);
+
+        // A Domain grant without a target confines nothing
+        let untargeted = CapabilityGrant::builder(CapabilityType::StorageWrite)
+            .scope(CapabilityScope::Domain)
+            .build();
+        let cap_set = CapabilitySet::from_grants(vec![untargeted]);
+        assert_eq!(cap_set.domain_target(CapabilityType::StorageWrite), None);
+    }
+
     #[test]
     fn test_minimal_capabilities() {
         assert_eq!(MINIMAL_CAPABILITIES.len(), 3);
@@ -540,4 +1403,360 @@ mod tests {
         assert!(NETWORK_SPIRIT_CAPABILITIES.contains(&CapabilityType::StorageRead));
         assert!(NETWORK_SPIRIT_CAPABILITIES.contains(&CapabilityType::StorageWrite));
     }
+
+    #[test]
+    fn test_capability_profiles_expand() {
+        assert_eq!(
+            CapabilityProfile::Minimal.to_capability_types(),
+            MINIMAL_CAPABILITIES
+        );
+        assert_eq!(
+            CapabilityProfile::Network.to_capability_types(),
+            NETWORK_SPIRIT_CAPABILITIES
+        );
+        assert_eq!(
+            CapabilityProfile::System.to_capability_types(),
+            vec![CapabilityType::Unrestricted]
+        );
+
+        let storage = CapabilityProfile::Storage.to_capability_types();
+        assert_eq!(storage.len(), 6);
+        assert!(storage.contains(&CapabilityType::StorageDelete));
+        assert!(!storage.contains(&CapabilityType::NetworkConnect));
+    }
+
+    #[test]
+    fn test_capability_profile_custom_roundtrip() {
+        let profile = CapabilityProfile::Custom(vec![
+            CapabilityType::NetworkConnect,
+            CapabilityType::StorageRead,
+        ]);
+
+        let text = profile.to_string();
+        assert_eq!(text, "custom:NetworkConnect,StorageRead");
+        assert_eq!(text.parse::<CapabilityProfile>().unwrap(), profile);
+        assert_eq!(
+            profile.to_capability_types(),
+            vec![CapabilityType::NetworkConnect, CapabilityType::StorageRead]
+        );
+
+        for named in ["minimal", "network", "storage", "system"] {
+            let parsed: CapabilityProfile = named.parse().unwrap();
+            assert_eq!(parsed.to_string(), named);
+        }
+        assert!("custom:Teleport".parse::<CapabilityProfile>().is_err());
+        assert!("everything".parse::<CapabilityProfile>().is_err());
+    }
+
+    #[test]
+    fn test_grant_builder_defaults() {
+        let grant = CapabilityGrant::builder(CapabilityType::SensorTime).build();
+
+        assert_eq!(grant.capability, CapabilityType::SensorTime);
+        assert_eq!(grant.scope, CapabilityScope::Global);
+        assert_eq!(grant.expires_at, None);
+        assert_eq!(grant.signature, [0u8; 64]);
+        assert!(grant.granted_at.abs_diff(current_timestamp()) <= 1);
+        assert!(grant.is_valid());
+        assert!(!grant.verify_signature());
+    }
+
+    #[test]
+    fn test_grant_builder_sign_verifies() {
+        let granter = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+
+        let grant = CapabilityGrant::builder(CapabilityType::NetworkConnect)
+            .id(42)
+            .scope(CapabilityScope::Peer)
+            .scope_target(b"peer-a:9000".to_vec())
+            .grantee([9u8; 32])
+            .granted_at(1_000)
+            .expires_at(2_000)
+            .sign(&granter);
+
+        assert_eq!(grant.id, 42);
+        assert_eq!(grant.granter, granter.verifying_key().to_bytes());
+        assert_eq!(grant.grantee, [9u8; 32]);
+        assert_eq!(grant.expires_at, Some(2_000));
+        assert!(grant.verify_signature());
+
+        // Any change to the signed payload invalidates the signature
+        let mut tampered = grant.clone();
+        tampered.scope_target = Some(b"peer-b:9000".to_vec());
+        assert!(!tampered.verify_signature());
+    }
+
+    #[test]
+    fn test_grant_not_before_window() {
+        let granter = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let now = current_timestamp();
+
+        let pending = CapabilityGrant::builder(CapabilityType::StorageRead)
+            .granted_at(now)
+            .not_before(now + 600)
+            .expires_at(now + 3600)
+            .sign(&granter);
+
+        assert!(!pending.is_valid());
+        assert!(!pending.is_valid_at(now + 599));
+        assert!(pending.is_valid_at(now + 600)); // Activates at not_before
+        assert!(pending.is_valid_at(now + 1800));
+        assert!(!pending.is_valid_at(now + 3600)); // Expiry still applies
+        assert!(pending.verify_signature());
+
+        let mut backdated = pending.clone();
+        backdated.not_before = Some(now);
+        assert!(!backdated.verify_signature());
+
+        let mut set = CapabilitySet::new();
+        set.add_grant(pending);
+        assert!(!set.has_capability(CapabilityType::StorageRead, CapabilityScope::Global));
+
+        // A not_before already in the past does not affect validity
+        let active = CapabilityGrant::builder(CapabilityType::StorageRead)
+            .granted_at(now - 600)
+            .not_before(now - 60)
+            .sign(&granter);
+        assert!(active.is_valid());
+        set.add_grant(active);
+        assert!(set.has_capability(CapabilityType::StorageRead, CapabilityScope::Global));
+    }
+
+    #[test]
+    fn test_same_id_grants_order_by_granter_and_nonce() {
+        let grants = [
+            CapabilityGrant::builder(CapabilityType::StorageRead)
+                .id(1)
+                .granter([2u8; 32])
+                .build(),
+            CapabilityGrant::builder(CapabilityType::StorageRead)
+                .id(1)
+                .granter([1u8; 32])
+                .nonce(7)
+                .build(),
+            CapabilityGrant::builder(CapabilityType::StorageRead)
+                .id(1)
+                .granter([1u8; 32])
+                .nonce(3)
+                .build(),
+        ];
+
+        let mut forward = CapabilitySet::new();
+        grants
+            .iter()
+            .cloned()
+            .for_each(|g| assert!(forward.add_grant(g)));
+        let mut reverse = CapabilitySet::new();
+        grants
+            .iter()
+            .rev()
+            .cloned()
+            .for_each(|g| assert!(reverse.add_grant(g)));
+
+        assert_eq!(
+            serde_json::to_string(&forward).unwrap(),
+            serde_json::to_string(&reverse).unwrap()
+        );
+        let order: Vec<_> = forward.iter().map(|g| (g.granter[0], g.nonce)).collect();
+        assert_eq!(order, vec![(1, 3), (1, 7), (2, 0)]);
+    }
+
+    #[test]
+    fn test_capability_set_order_is_deterministic() {
+        let grants = [
+            CapabilityGrant::builder(CapabilityType::StorageWrite)
+                .id(3)
+                .build(),
+            CapabilityGrant::builder(CapabilityType::NetworkConnect)
+                .id(1)
+                .build(),
+            CapabilityGrant::builder(CapabilityType::StorageRead)
+                .id(2)
+                .build(),
+            CapabilityGrant::builder(CapabilityType::StorageRead)
+                .id(1)
+                .build(),
+        ];
+
+        let mut forward = CapabilitySet::new();
+        grants
+            .iter()
+            .cloned()
+            .for_each(|g| assert!(forward.add_grant(g)));
+        let mut reverse = CapabilitySet::new();
+        grants
+            .iter()
+            .rev()
+            .cloned()
+            .for_each(|g| assert!(reverse.add_grant(g)));
+
+        assert_eq!(
+            serde_json::to_string(&forward).unwrap(),
+            serde_json::to_string(&reverse).unwrap()
+        );
+        assert_eq!(format!("{:?}", forward), format!("{:?}", reverse));
+
+        let order: Vec<_> = forward.iter().map(|g| (g.id, g.capability)).collect();
+        assert_eq!(
+            order,
+            vec![
+                (1, CapabilityType::NetworkConnect),
+                (1, CapabilityType::StorageRead),
+                (2, CapabilityType::StorageRead),
+                (3, CapabilityType::StorageWrite),
+            ]
+        );
+        assert!(reverse.iter().map(|g| (g.id, g.capability)).eq(order));
+    }
+
+    #[test]
+    fn test_capability_profile_to_set() {
+        let granter = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let expires = current_timestamp() + 3600;
+
+        let set = CapabilityProfile::Network.to_capability_set(&granter, [9u8; 32], Some(expires));
+
+        let grants = set.valid_grants();
+        assert_eq!(grants.len(), NETWORK_SPIRIT_CAPABILITIES.len());
+        assert!(grants.iter().all(|g| g.verify_signature()));
+        assert!(grants
+            .iter()
+            .all(|g| g.grantee == [9u8; 32] && g.expires_at == Some(expires)));
+        assert!(set.has_capability(CapabilityType::NetworkConnect, CapabilityScope::Global));
+        assert!(!set.has_capability(CapabilityType::StorageDelete, CapabilityScope::Global));
+    }
+
+    #[test]
+    fn test_capability_risk_levels() {
+        use CapabilityType::*;
+        let expected = [
+            (NetworkListen, RiskLevel::Medium),
+            (NetworkConnect, RiskLevel::High),
+            (NetworkBroadcast, RiskLevel::High),
+            (StorageRead, RiskLevel::Low),
+            (StorageWrite, RiskLevel::Medium),
+            (StorageDelete, RiskLevel::Medium),
+            (SpawnSandbox, RiskLevel::High),
+            (CrossSandboxCall, RiskLevel::Medium),
+            (SensorTime, RiskLevel::Low),
+            (SensorRandom, RiskLevel::Low),
+            (SensorEnvironment, RiskLevel::Medium),
+            (ActuatorLog, RiskLevel::Low),
+            (ActuatorNotify, RiskLevel::Medium),
+            (ActuatorCredit, RiskLevel::High),
+            (Unrestricted, RiskLevel::High),
+        ];
+        assert_eq!(expected.len(), ALL_CAPABILITY_TYPES.len());
+
+        for (cap, level) in expected {
+            assert_eq!(cap.risk_level(), level, "{:?}", cap);
+            assert_eq!(cap.is_dangerous(), level == RiskLevel::High, "{:?}", cap);
+        }
+        assert!(RiskLevel::Low < RiskLevel::Medium && RiskLevel::Medium < RiskLevel::High);
+    }
+
+    #[test]
+    fn test_capability_set_risk_aggregation() {
+        let empty = CapabilitySet::new();
+        assert_eq!(empty.max_risk(), None);
+        assert_eq!(empty.risk_score(), 0);
+
+        let minimal = CapabilitySet::with_types(MINIMAL_CAPABILITIES);
+        assert_eq!(minimal.max_risk(), Some(RiskLevel::Low));
+        assert_eq!(minimal.risk_score(), 3);
+
+        // A second grant of the same capability does not add to the score
+        let mut storage =
+            CapabilitySet::with_types(&[CapabilityType::StorageRead, CapabilityType::StorageWrite]);
+        storage.add_grant(
+            CapabilityGrant::builder(CapabilityType::StorageWrite)
+                .id(9)
+                .build(),
+        );
+        assert_eq!(storage.max_risk(), Some(RiskLevel::Medium));
+        assert_eq!(storage.risk_score(), 4);
+
+        // Revoked grants are not counted
+        storage
+            .grants
+            .get_mut(&CapabilityType::StorageWrite)
+            .unwrap()
+            .iter_mut()
+            .for_each(CapabilityGrant::revoke);
+        assert_eq!(storage.max_risk(), Some(RiskLevel::Low));
+        assert_eq!(storage.risk_score(), 1);
+
+        let every: Vec<_> = ALL_CAPABILITY_TYPES
+            .iter()
+            .copied()
+            .filter(|c| *c != CapabilityType::Unrestricted)
+            .collect();
+        assert_eq!(
+            CapabilitySet::with_types(&every).risk_score(),
+            CapabilitySet::MAX_RISK_SCORE
+        );
+
+        let unrestricted = CapabilitySet::all_for_testing();
+        assert_eq!(unrestricted.max_risk(), Some(RiskLevel::High));
+        assert_eq!(unrestricted.risk_score(), CapabilitySet::MAX_RISK_SCORE);
+        let mixed =
+            CapabilitySet::with_types(&[CapabilityType::Unrestricted, CapabilityType::SensorTime]);
+        assert_eq!(mixed.risk_score(), CapabilitySet::MAX_RISK_SCORE);
+    }
+
+    #[test]
+    fn test_capability_set_audit() {
+        let now = 1_700_000_000;
+        let grant = |id, capability, expires_at| {
+            CapabilityGrant::new(
+                id,
+                capability,
+                CapabilityScope::Global,
+                [0u8; 32],
+                [0u8; 32],
+                now - 100,
+                expires_at,
+                [0u8; 64],
+            )
+        };
+        let mut flagged = grant(5, CapabilityType::NetworkConnect, None);
+        flagged.revoke();
+        let mut caps = CapabilitySet::from_grants(vec![
+            grant(1, CapabilityType::SensorTime, None),
+            grant(2, CapabilityType::StorageRead, Some(now + 3600)),
+            grant(3, CapabilityType::StorageRead, Some(now + 30 * 86400)),
+            grant(4, CapabilityType::StorageWrite, Some(now - 1)),
+            flagged,
+            grant(6, CapabilityType::StorageDelete, None),
+        ]);
+        caps.revocations.revoke([0u8; 32], 6);
+
+        let audit = caps.audit(now);
+
+        assert_eq!(
+            audit.counts,
+            BTreeMap::from([
+                (CapabilityType::NetworkConnect, 1),
+                (CapabilityType::StorageRead, 2),
+                (CapabilityType::StorageWrite, 1),
+                (CapabilityType::StorageDelete, 1),
+                (CapabilityType::SensorTime, 1),
+            ])
+        );
+        assert_eq!(
+            audit.active,
+            BTreeSet::from([CapabilityType::StorageRead, CapabilityType::SensorTime])
+        );
+        assert_eq!(audit.expiring_soon, vec![2]);
+        assert_eq!(audit.revoked, vec![5, 6]);
+        assert_eq!(
+            audit.risk_score,
+            CapabilityType::StorageRead.risk_level().weight()
+                + CapabilityType::SensorTime.risk_level().weight()
+        );
+
+        let json = serde_json::to_string(&audit).unwrap();
+        let parsed: CapabilityAudit = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, audit);
+    }
 }
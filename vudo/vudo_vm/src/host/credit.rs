@@ -4,7 +4,7 @@
 //! Credits are used for resource consumption metering in the VUDO system.
 //! Credits are tied to Ed25519 public keys (32 bytes).
 
-use super::{CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
+use super::{unsupported, CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
@@ -17,9 +17,42 @@ pub const MAX_TRANSFER_AMOUNT: u64 = 1_000_000_000_000; // 1 trillion
 /// Maximum amount of credits that can be reserved in a single operation
 pub const MAX_RESERVE_AMOUNT: u64 = 100_000_000_000; // 100 billion
 
+/// Ledger error for an operation whose result would not fit in a `u64`
+pub const CREDIT_OVERFLOW: &str = "Credit overflow";
+
 /// Ed25519 public key type alias
 pub type PublicKey = [u8; PUBLIC_KEY_SIZE];
 
+/// Error parsing a hex-encoded public key
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PublicKeyHexError {
+    #[error("public key must be {expected} hex characters, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+
+    #[error("public key is not valid hex: {0}")]
+    InvalidHex(String),
+}
+
+/// Parse a public key from 64 hex characters (either case)
+pub fn public_key_from_hex(hex: &str) -> Result<PublicKey, PublicKeyHexError> {
+    if hex.len() != PUBLIC_KEY_SIZE * 2 {
+        return Err(PublicKeyHexError::InvalidLength {
+            expected: PUBLIC_KEY_SIZE * 2,
+            actual: hex.len(),
+        });
+    }
+
+    let mut key = [0u8; PUBLIC_KEY_SIZE];
+    hex::decode_to_slice(hex, &mut key)
+        .map_err(|e| PublicKeyHexError::InvalidHex(e.to_string()))?;
+    Ok(key)
+}
+
+/// Encode a public key as 64 lowercase hex characters
+pub fn public_key_to_hex(key: &PublicKey) -> String {
+    hex::encode(key)
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // CREDIT BACKEND TRAIT
 // ═══════════════════════════════════════════════════════════════════════════
@@ -87,6 +120,14 @@ pub trait CreditBackend: Send + Sync {
     /// - Ok(()) - On successful credit
     /// - Err(msg) - On ledger error
     fn credit(&self, account: &PublicKey, amount: u64) -> Result<(), String>;
+
+    /// Credit capabilities this backend can actually carry out
+    ///
+    /// A read-only view of a ledger can leave `ActuatorCredit` out so
+    /// Spirits get a not-supported error. Defaults to `ActuatorCredit`.
+    fn capabilities_supported(&self) -> &'static [CapabilityType] {
+        &[CapabilityType::ActuatorCredit]
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -185,15 +226,18 @@ impl CreditBackend for InMemoryCreditLedger {
             ));
         }
 
-        // Deduct from sender
-        *balances.entry(*from).or_insert(0) -= amount;
+        // Compute both balances before touching either, so an overflowing
+        // receiver leaves the ledger unchanged
+        let new_from = from_balance - amount;
+        let to_balance = if from == to {
+            new_from
+        } else {
+            *balances.get(to).unwrap_or(&0)
+        };
+        let new_to = to_balance.checked_add(amount).ok_or(CREDIT_OVERFLOW)?;
 
-        // Add to receiver
-        *balances.entry(*to).or_insert(0) = balances
-            .get(to)
-            .unwrap_or(&0)
-            .checked_add(amount)
-            .ok_or("Credit overflow")?;
+        balances.insert(*from, new_from);
+        balances.insert(*to, new_to);
 
         Ok(())
     }
@@ -203,7 +247,8 @@ impl CreditBackend for InMemoryCreditLedger {
             return Err("Reserve amount must be greater than zero".to_string());
         }
 
-        // Check available balance
+        // Check available balance; the reserved total then stays within
+        // the account balance and cannot overflow
         let available = self.available_balance(account)?;
         if available < amount {
             return Err(format!(
@@ -312,7 +357,7 @@ impl CreditBackend for InMemoryCreditLedger {
             .get(account)
             .unwrap_or(&0)
             .checked_add(amount)
-            .ok_or("Credit overflow")?;
+            .ok_or(CREDIT_OVERFLOW)?;
         Ok(())
     }
 }
@@ -324,12 +369,11 @@ impl InMemoryCreditLedger {
             .reservations
             .read()
             .map_err(|e| format!("Lock error: {}", e))?;
-        let reserved: u64 = reservations
+        reservations
             .values()
             .filter(|r| r.active && &r.account == account)
-            .map(|r| r.amount)
-            .sum();
-        Ok(reserved)
+            .try_fold(0u64, |total, r| total.checked_add(r.amount))
+            .ok_or_else(|| CREDIT_OVERFLOW.to_string())
     }
 }
 
@@ -337,6 +381,13 @@ impl InMemoryCreditLedger {
 // HOST CREDIT FUNCTIONS
 // ═══════════════════════════════════════════════════════════════════════════
 
+/// Returns true if `result` failed because a ledger balance would overflow.
+///
+/// Transfer and reserve report that error as `CREDIT_OVERFLOW` itself.
+pub fn is_credit_overflow(result: &HostCallResult) -> bool {
+    result.error.as_deref() == Some(CREDIT_OVERFLOW)
+}
+
 /// Get current credit balance for the calling account
 ///
 /// Requires ActuatorCredit capability.
@@ -358,6 +409,14 @@ pub fn host_credit_balance(
         return HostCallResult::capability_denied(CapabilityType::ActuatorCredit);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        ledger.capabilities_supported(),
+        CapabilityType::ActuatorCredit,
+    ) {
+        return result;
+    }
+
     // Get balance
     match ledger.balance(account) {
         Ok(balance) => {
@@ -393,6 +452,14 @@ pub fn host_credit_transfer(
         return HostCallResult::capability_denied(CapabilityType::ActuatorCredit);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        ledger.capabilities_supported(),
+        CapabilityType::ActuatorCredit,
+    ) {
+        return result;
+    }
+
     // Validate amount
     if amount == 0 {
         return HostCallResult::error("Transfer amount must be greater than zero");
@@ -413,6 +480,7 @@ pub fn host_credit_transfer(
     // Perform transfer
     match ledger.transfer(from, to, amount) {
         Ok(()) => HostCallResult::success(),
+        Err(e) if e == CREDIT_OVERFLOW => HostCallResult::error(CREDIT_OVERFLOW),
         Err(e) => HostCallResult::error(format!("Credit transfer error: {}", e)),
     }
 }
@@ -443,6 +511,14 @@ pub fn host_credit_reserve(
         return HostCallResult::capability_denied(CapabilityType::ActuatorCredit);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        ledger.capabilities_supported(),
+        CapabilityType::ActuatorCredit,
+    ) {
+        return result;
+    }
+
     // Validate amount
     if amount == 0 {
         return HostCallResult::error("Reserve amount must be greater than zero");
@@ -461,6 +537,7 @@ pub fn host_credit_reserve(
             let bytes = reservation_id.to_le_bytes().to_vec();
             HostCallResult::success_with_value(bytes)
         }
+        Err(e) if e == CREDIT_OVERFLOW => HostCallResult::error(CREDIT_OVERFLOW),
         Err(e) => HostCallResult::error(format!("Credit reserve error: {}", e)),
     }
 }
@@ -488,6 +565,14 @@ pub fn host_credit_release(
         return HostCallResult::capability_denied(CapabilityType::ActuatorCredit);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        ledger.capabilities_supported(),
+        CapabilityType::ActuatorCredit,
+    ) {
+        return result;
+    }
+
     // Release reservation
     match ledger.release_reservation(reservation_id) {
         Ok(()) => HostCallResult::success(),
@@ -518,6 +603,14 @@ pub fn host_credit_consume(
         return HostCallResult::capability_denied(CapabilityType::ActuatorCredit);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        ledger.capabilities_supported(),
+        CapabilityType::ActuatorCredit,
+    ) {
+        return result;
+    }
+
     // Consume reservation
     match ledger.consume_reservation(reservation_id) {
         Ok(()) => HostCallResult::success(),
@@ -546,6 +639,14 @@ pub fn host_credit_available(
         return HostCallResult::capability_denied(CapabilityType::ActuatorCredit);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        ledger.capabilities_supported(),
+        CapabilityType::ActuatorCredit,
+    ) {
+        return result;
+    }
+
     // Get available balance
     match ledger.available_balance(account) {
         Ok(available) => {
@@ -585,6 +686,33 @@ mod tests {
         key
     }
 
+    #[test]
+    fn test_public_key_hex_round_trip() {
+        let hex = public_key_to_hex(&alice_key());
+        assert_eq!(hex.len(), 64);
+        assert!(hex.starts_with("aa00"));
+        assert_eq!(public_key_from_hex(&hex), Ok(alice_key()));
+        assert_eq!(public_key_from_hex(&hex.to_uppercase()), Ok(alice_key()));
+    }
+
+    #[test]
+    fn test_public_key_hex_rejects_bad_input() {
+        let short = "a".repeat(63);
+        assert_eq!(
+            public_key_from_hex(&short),
+            Err(PublicKeyHexError::InvalidLength {
+                expected: 64,
+                actual: 63,
+            })
+        );
+
+        let not_hex = "zz".repeat(32);
+        assert!(matches!(
+            public_key_from_hex(&not_hex),
+            Err(PublicKeyHexError::InvalidHex(_))
+        ));
+    }
+
     fn create_credit_caps() -> CapabilitySet {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -676,6 +804,49 @@ mod tests {
         assert!(result.unwrap_err().contains("Insufficient"));
     }
 
+    #[test]
+    fn test_ledger_credit_overflow_rejected() {
+        let ledger = InMemoryCreditLedger::with_balances(vec![(alice_key(), u64::MAX - 10)]);
+
+        let result = ledger.credit(&alice_key(), 11);
+        assert_eq!(result.unwrap_err(), CREDIT_OVERFLOW);
+        assert_eq!(ledger.balance(&alice_key()).unwrap(), u64::MAX - 10);
+
+        ledger.credit(&alice_key(), 10).unwrap();
+        assert_eq!(ledger.balance(&alice_key()).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_ledger_transfer_overflow_leaves_balances_unchanged() {
+        let ledger =
+            InMemoryCreditLedger::with_balances(vec![(alice_key(), 1000), (bob_key(), u64::MAX)]);
+
+        let result = ledger.transfer(&alice_key(), &bob_key(), 1);
+        assert_eq!(result.unwrap_err(), CREDIT_OVERFLOW);
+
+        assert_eq!(ledger.balance(&alice_key()).unwrap(), 1000);
+        assert_eq!(ledger.balance(&bob_key()).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_host_credit_transfer_overflow() {
+        let caps = create_credit_caps();
+        let ledger =
+            InMemoryCreditLedger::with_balances(vec![(alice_key(), 1000), (bob_key(), u64::MAX)]);
+
+        let result = host_credit_transfer(&caps, &ledger, &alice_key(), &bob_key(), 500);
+        assert!(!result.success);
+        assert!(is_credit_overflow(&result));
+        assert_eq!(result.error.as_deref(), Some(CREDIT_OVERFLOW));
+
+        let result = host_credit_transfer(&caps, &ledger, &alice_key(), &bob_key(), 5000);
+        assert!(!result.success);
+        assert!(!is_credit_overflow(&result));
+
+        assert_eq!(ledger.balance(&alice_key()).unwrap(), 1000);
+        assert_eq!(ledger.balance(&bob_key()).unwrap(), u64::MAX);
+    }
+
     #[test]
     fn test_ledger_transfer_zero_amount() {
         let ledger = InMemoryCreditLedger::with_balances(vec![(alice_key(), 1000)]);
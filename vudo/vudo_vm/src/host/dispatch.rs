@@ -0,0 +1,110 @@
+//! Host Method Dispatch
+//!
+//! Lets embedders add host functions without touching the linker. Handlers
+//! implementing [`HostMethod`] are registered by name on `HostState` and
+//! reached from WASM through the single `host_invoke` import.
+
+use super::{CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
+
+/// A host function installed at runtime and dispatched by name.
+///
+/// Arguments and results are opaque byte strings; their encoding is a
+/// contract between the handler and the Spirits that call it.
+pub trait HostMethod: Send + Sync {
+    /// Capability the caller must hold to invoke this method.
+    fn required_capability(&self) -> CapabilityType;
+
+    /// Scope the capability must cover. Defaults to `Sandboxed`.
+    fn required_scope(&self) -> CapabilityScope {
+        CapabilityScope::Sandboxed
+    }
+
+    /// Handle a call, returning the response bytes or an error message.
+    fn call(&self, args: &[u8]) -> Result<Vec<u8>, String>;
+}
+
+/// Invoke a registered host method
+///
+/// Requires the capability declared by `method`.
+///
+/// # Arguments
+/// * `caps` - Capability set to check permissions
+/// * `method` - The handler to dispatch to
+/// * `args` - Opaque argument bytes passed through to the handler
+///
+/// # Returns
+/// HostCallResult carrying the handler's response bytes
+pub fn host_invoke(caps: &CapabilitySet, method: &dyn HostMethod, args: &[u8]) -> HostCallResult {
+    let capability = method.required_capability();
+    if !caps.has_capability(capability, method.required_scope()) {
+        return HostCallResult::capability_denied(capability);
+    }
+
+    match method.call(args) {
+        Ok(response) => HostCallResult::success_with_value(response),
+        Err(e) => HostCallResult::error(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capability::CapabilityGrant;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    struct Reverse;
+
+    impl HostMethod for Reverse {
+        fn required_capability(&self) -> CapabilityType {
+            CapabilityType::SensorTime
+        }
+
+        fn call(&self, args: &[u8]) -> Result<Vec<u8>, String> {
+            if args.is_empty() {
+                return Err("nothing to reverse".to_string());
+            }
+            Ok(args.iter().rev().copied().collect())
+        }
+    }
+
+    fn capset(cap_type: CapabilityType) -> CapabilitySet {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
+
+        let mut cap_set = CapabilitySet::new();
+        cap_set.add_grant(CapabilityGrant::new(
+            1,
+            cap_type,
+            CapabilityScope::Global,
+            [0u8; 32],
+            [1u8; 32],
+            now,
+            None,
+            [0u8; 64],
+        ));
+        cap_set
+    }
+
+    #[test]
+    fn test_host_invoke_dispatches() {
+        let result = host_invoke(&capset(CapabilityType::SensorTime), &Reverse, b"abc");
+        assert!(result.success);
+        assert_eq!(result.return_value, Some(b"cba".to_vec()));
+    }
+
+    #[test]
+    fn test_host_invoke_requires_declared_capability() {
+        let result = host_invoke(&capset(CapabilityType::SensorRandom), &Reverse, b"abc");
+        assert!(!result.success);
+        assert_eq!(result.denied, Some(CapabilityType::SensorTime));
+    }
+
+    #[test]
+    fn test_host_invoke_handler_error() {
+        let result = host_invoke(&capset(CapabilityType::SensorTime), &Reverse, b"");
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("nothing to reverse"));
+    }
+}
@@ -0,0 +1,284 @@
+//! Filesystem Storage Backend
+//!
+//! Stores each key as a file under a root directory. Keys are relative
+//! paths (`profile/avatar` lives at `<root>/profile/avatar`) and are
+//! confined to the root chroot-style: absolute keys, `.` and `..`
+//! components, and paths that pass through a symbolic link are rejected.
+//!
+//! Keys partitioned by a storage namespace or a domain-scoped grant (see
+//! `HostState::storage_key`) are stored under `@ns-<hex>` and
+//! `@domain-<hex>` directories. Plain keys may therefore not start with `@`.
+//!
+//! Enabled by the `fs-storage` feature.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use super::storage::{add_to_counter, decode_counter, StorageBackend};
+
+/// Storage backend confined to a directory
+///
+/// The symlink check runs before every access, so a link swapped in
+/// between the check and the access is not caught; the root must not be
+/// writable by anyone the Spirit should be isolated from.
+#[derive(Debug)]
+pub struct FsStorage {
+    root: PathBuf,
+    /// Serializes `increment` so concurrent sandboxes don't lose updates
+    increment_lock: Mutex<()>,
+}
+
+impl FsStorage {
+    /// Open (creating if needed) a store rooted at `root`
+    pub fn new(root: impl AsRef<Path>) -> Result<Self, String> {
+        let root = root.as_ref();
+        fs::create_dir_all(root)
+            .map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+        let root = root
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve {}: {}", root.display(), e))?;
+        Ok(Self {
+            root,
+            increment_lock: Mutex::new(()),
+        })
+    }
+
+    /// Directory the store is confined to
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Map `key` to its file, rejecting keys that could leave the root.
+    fn path_for(&self, key: &[u8]) -> Result<PathBuf, String> {
+        let mut relative = PathBuf::new();
+        let mut key = key;
+        if let Some((namespace, rest)) = split_partition(b"", key) {
+            relative.push(format!("{}ns-{}", PARTITION_MARKER, hex::encode(namespace)));
+            key = rest;
+        }
+        if let Some((domain, rest)) = split_partition(b"domain/", key) {
+            relative.push(format!(
+                "{}domain-{}",
+                PARTITION_MARKER,
+                hex::encode(domain)
+            ));
+            key = rest;
+        }
+
+        let key = std::str::from_utf8(key).map_err(|_| "Key must be valid UTF-8".to_string())?;
+        if key.is_empty() || key.contains('\0') {
+            return Err("Key must be a non-empty path".to_string());
+        }
+        if key.starts_with(PARTITION_MARKER) {
+            return Err(format!(
+                "Key '{}' may not start with '{}'",
+                key, PARTITION_MARKER
+            ));
+        }
+        relative.push(key);
+
+        let mut path = self.root.clone();
+        let mut may_be_link = true;
+        for component in relative.components() {
+            let Component::Normal(part) = component else {
+                return Err(format!("Key '{}' escapes the storage root", key));
+            };
+            path.push(part);
+            if !may_be_link {
+                continue;
+            }
+            match fs::symlink_metadata(&path) {
+                Ok(meta) if meta.file_type().is_symlink() => {
+                    return Err(format!("Key '{}' passes through a symbolic link", key))
+                }
+                Ok(_) => {}
+                // Nothing below a missing entry can exist yet
+                Err(e) if e.kind() == ErrorKind::NotFound => may_be_link = false,
+                Err(e) => return Err(format!("Failed to inspect key '{}': {}", key, e)),
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// Starts the directory names of namespace and domain partitions
+const PARTITION_MARKER: char = '@';
+
+/// Split `tag ‖ len (u32 LE) ‖ prefix ‖ rest`, as written by the linker's
+/// key partitioning, into `prefix` and `rest`.
+///
+/// Only matches when the length field contains a NUL byte, which plain
+/// keys never do, so a plain key is not mistaken for a partitioned one.
+fn split_partition<'a>(tag: &[u8], key: &'a [u8]) -> Option<(&'a [u8], &'a [u8])> {
+    let rest = key.strip_prefix(tag)?;
+    let (len, rest) = rest.split_first_chunk::<4>()?;
+    if !len.contains(&0) {
+        return None;
+    }
+    let len = u32::from_le_bytes(*len) as usize;
+    (len <= rest.len()).then(|| rest.split_at(len))
+}
+
+/// Count the regular files below `dir`.
+fn count_files(dir: &Path) -> Result<usize, String> {
+    let mut count = 0;
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let file_type = entry.file_type().map_err(|e| e.to_string())?;
+        if file_type.is_dir() {
+            count += count_files(&entry.path())?;
+        } else if file_type.is_file() {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+impl StorageBackend for FsStorage {
+    fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        let path = self.path_for(key)?;
+        match fs::read(&path) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    fn write(&self, key: &[u8], value: &[u8]) -> Result<(), String> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, value).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    fn delete(&self, key: &[u8]) -> Result<bool, String> {
+        let path = self.path_for(key)?;
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(format!("Failed to delete {}: {}", path.display(), e)),
+        }
+    }
+
+    fn exists(&self, key: &[u8]) -> Result<bool, String> {
+        Ok(self.path_for(key)?.is_file())
+    }
+
+    fn increment(&self, key: &[u8], delta: i64) -> Result<i64, String> {
+        let _guard = self
+            .increment_lock
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        let current = decode_counter(self.read(key)?.as_deref())?;
+        let next = add_to_counter(current, delta)?;
+        self.write(key, &next.to_le_bytes())?;
+        Ok(next)
+    }
+
+    fn count(&self) -> Result<usize, String> {
+        count_files(&self.root)
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        for entry in fs::read_dir(&self.root).map_err(|e| e.to_string())? {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let path = entry.path();
+            let removed = if entry.file_type().map_err(|e| e.to_string())?.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            removed.map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{host_storage_read, CapabilitySet, CapabilityType};
+
+    #[test]
+    fn test_key_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FsStorage::new(dir.path()).unwrap();
+
+        storage.write(b"profile/name", b"ada").unwrap();
+        assert_eq!(
+            storage.read(b"profile/name").unwrap(),
+            Some(b"ada".to_vec())
+        );
+        assert_eq!(
+            fs::read(dir.path().join("profile").join("name")).unwrap(),
+            b"ada"
+        );
+        assert_eq!(storage.count().unwrap(), 1);
+        assert!(storage.delete(b"profile/name").unwrap());
+        assert_eq!(storage.read(b"profile/name").unwrap(), None);
+    }
+
+    #[test]
+    fn test_traversal_keys_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        let storage = FsStorage::new(&root).unwrap();
+
+        for key in [&b"../escape"[..], b"a/../../escape", b"/etc/passwd", b"./a"] {
+            assert!(storage.write(key, b"x").is_err(), "{:?}", key);
+            assert!(storage.read(key).is_err(), "{:?}", key);
+        }
+        assert!(!dir.path().join("escape").exists());
+
+        let caps = CapabilitySet::with_types(&[CapabilityType::StorageRead]);
+        assert!(!host_storage_read(&caps, &storage, b"../escape").success);
+    }
+
+    #[test]
+    fn test_partitioned_keys_map_to_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FsStorage::new(dir.path()).unwrap();
+
+        // len ‖ namespace ‖ "domain/" ‖ len ‖ domain ‖ key
+        let mut key = vec![2, 0, 0, 0, 0xab, 0xcd];
+        key.extend_from_slice(b"domain/\x05\0\0\0alphaprofile/name");
+        storage.write(&key, b"ada").unwrap();
+        assert_eq!(storage.read(&key).unwrap(), Some(b"ada".to_vec()));
+        let file = dir
+            .path()
+            .join("@ns-abcd")
+            .join(format!("@domain-{}", hex::encode("alpha")))
+            .join("profile")
+            .join("name");
+        assert_eq!(fs::read(file).unwrap(), b"ada");
+
+        // Plain keys cannot reach a partition
+        assert!(storage.read(b"@ns-abcd/x").is_err());
+        // "domain/" without a length field is an ordinary key
+        storage.write(b"domain/plain", b"x").unwrap();
+        assert!(dir.path().join("domain").join("plain").is_file());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_out_of_root_not_followed() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = dir.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        fs::write(outside.join("secret"), b"hidden").unwrap();
+        let storage = FsStorage::new(dir.path().join("root")).unwrap();
+        std::os::unix::fs::symlink(&outside, storage.root().join("link")).unwrap();
+
+        assert!(storage.read(b"link/secret").is_err());
+        assert!(storage.write(b"link/planted", b"x").is_err());
+        assert!(!outside.join("planted").exists());
+    }
+}
@@ -3,7 +3,13 @@
 //! Provides logging capabilities for WASM sandboxes.
 
 use super::{CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
+use std::ffi::OsString;
 use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Log level for host logging
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -61,6 +67,20 @@ const MAX_LOG_MESSAGE_LENGTH: usize = 64 * 1024; // 64KB
 /// # Returns
 /// HostCallResult indicating success or error
 pub fn host_log(caps: &CapabilitySet, level: LogLevel, message: &str) -> HostCallResult {
+    host_log_with(caps, level, message, print_record)
+}
+
+/// Log a message, handing the validated record to `deliver` instead of
+/// the console.
+///
+/// Performs the same capability and length checks as [`host_log`];
+/// `deliver` is only called if they pass.
+pub fn host_log_with(
+    caps: &CapabilitySet,
+    level: LogLevel,
+    message: &str,
+    deliver: impl FnOnce(LogLevel, &str),
+) -> HostCallResult {
     // Check capability
     if !caps.has_capability(CapabilityType::ActuatorLog, CapabilityScope::Global) {
         return HostCallResult::capability_denied(CapabilityType::ActuatorLog);
@@ -74,31 +94,144 @@ pub fn host_log(caps: &CapabilitySet, level: LogLevel, message: &str) -> HostCal
         ));
     }
 
-    // Truncate message if it's too long for display (but this shouldn't happen due to above check)
-    let truncated = if message.len() > MAX_LOG_MESSAGE_LENGTH {
-        &message[..MAX_LOG_MESSAGE_LENGTH]
-    } else {
-        message
-    };
+    deliver(level, message);
+
+    HostCallResult::success()
+}
+
+/// Accept a record that falls below the sandbox's minimum level.
+///
+/// Denial semantics match [`host_log`], but nothing is delivered, so the
+/// caller can skip reading the message out of guest memory.
+pub fn host_log_filtered(caps: &CapabilitySet) -> HostCallResult {
+    if !caps.has_capability(CapabilityType::ActuatorLog, CapabilityScope::Global) {
+        return HostCallResult::capability_denied(CapabilityType::ActuatorLog);
+    }
+
+    HostCallResult::success()
+}
 
-    // Log the message using the appropriate level
+/// Write a record to the console.
+pub fn print_record(level: LogLevel, message: &str) {
     // In a real implementation, this would integrate with a proper logging framework
     match level {
-        LogLevel::Trace => eprintln!("[VUDO:TRACE] {}", truncated),
-        LogLevel::Debug => eprintln!("[VUDO:DEBUG] {}", truncated),
-        LogLevel::Info => println!("[VUDO:INFO] {}", truncated),
-        LogLevel::Warn => println!("[VUDO:WARN] {}", truncated),
-        LogLevel::Error => eprintln!("[VUDO:ERROR] {}", truncated),
+        LogLevel::Trace => eprintln!("[VUDO:TRACE] {}", message),
+        LogLevel::Debug => eprintln!("[VUDO:DEBUG] {}", message),
+        LogLevel::Info => println!("[VUDO:INFO] {}", message),
+        LogLevel::Warn => println!("[VUDO:WARN] {}", message),
+        LogLevel::Error => eprintln!("[VUDO:ERROR] {}", message),
     }
+}
 
-    HostCallResult::success()
+// ═══════════════════════════════════════════════════════════════════════════
+// LOG SINKS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Destination for delivered log records.
+///
+/// Sinks may be shared by many sandboxes, so `write` takes `&self`.
+pub trait LogSink: Send + Sync {
+    /// Persist one record.
+    fn write(&self, level: LogLevel, message: &str) -> io::Result<()>;
+}
+
+/// Appends records to a file, rotating it once it reaches a size limit.
+///
+/// On rotation `app.log` becomes `app.log.1`, `app.log.1` becomes
+/// `app.log.2` and so on; files past the retention count are deleted.
+/// A single record larger than the limit is still written whole.
+pub struct FileLogSink {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    state: Mutex<FileLogState>,
+}
+
+struct FileLogState {
+    file: File,
+    written: u64,
+}
+
+impl FileLogSink {
+    /// Append to `path`, rotating after `max_bytes` and keeping `keep`
+    /// rotated files.
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        let path = path.into();
+        let file = open_append(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            keep,
+            state: Mutex::new(FileLogState { file, written }),
+        })
+    }
+
+    /// Path of the file currently being written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of the `index`th most recent rotated file (starting at 1).
+    pub fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = OsString::from(self.path.as_os_str());
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&self, state: &mut FileLogState) -> io::Result<()> {
+        state.file.flush()?;
+
+        let oldest = self.rotated_path(self.keep.max(1));
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for index in (1..self.keep).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        state.file = open_append(&self.path)?;
+        state.written = 0;
+        Ok(())
+    }
+}
+
+impl LogSink for FileLogSink {
+    fn write(&self, level: LogLevel, message: &str) -> io::Result<()> {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let line = format!("{} [VUDO:{}] {}\n", millis, level, message);
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.written > 0 && state.written + line.len() as u64 > self.max_bytes {
+            self.rotate(&mut state)?;
+        }
+        state.file.write_all(line.as_bytes())?;
+        state.written += line.len() as u64;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::capability::{CapabilityGrant, MINIMAL_CAPABILITIES};
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::sync::Arc;
+    use std::thread;
 
     fn create_test_capset() -> CapabilitySet {
         let now = SystemTime::now()
@@ -225,6 +358,77 @@ mod tests {
         assert!(result.success);
     }
 
+    #[test]
+    fn test_file_log_sink_rotates_past_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = FileLogSink::new(dir.path().join("spirit.log"), 100, 3).unwrap();
+
+        sink.write(LogLevel::Info, &"a".repeat(60)).unwrap();
+        assert!(!sink.rotated_path(1).exists());
+
+        sink.write(LogLevel::Info, &"b".repeat(60)).unwrap();
+        let rotated = fs::read_to_string(sink.rotated_path(1)).unwrap();
+        let current = fs::read_to_string(sink.path()).unwrap();
+        assert!(rotated.contains("[VUDO:INFO] aaaa"));
+        assert!(current.contains("[VUDO:INFO] bbbb"));
+        assert!(!current.contains("aaaa"));
+    }
+
+    #[test]
+    fn test_file_log_sink_deletes_beyond_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = FileLogSink::new(dir.path().join("spirit.log"), 10, 2).unwrap();
+
+        // Every record is over the limit, so each write after the first rotates
+        for i in 0..5 {
+            sink.write(LogLevel::Warn, &format!("record {}", i))
+                .unwrap();
+        }
+
+        assert!(fs::read_to_string(sink.path())
+            .unwrap()
+            .contains("record 4"));
+        assert!(fs::read_to_string(sink.rotated_path(1))
+            .unwrap()
+            .contains("record 3"));
+        assert!(fs::read_to_string(sink.rotated_path(2))
+            .unwrap()
+            .contains("record 2"));
+        assert!(!sink.rotated_path(3).exists());
+    }
+
+    #[test]
+    fn test_file_log_sink_concurrent_writers() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = Arc::new(FileLogSink::new(dir.path().join("spirit.log"), 512, 1000).unwrap());
+
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let sink = Arc::clone(&sink);
+                thread::spawn(move || {
+                    for i in 0..50 {
+                        sink.write(LogLevel::Info, &format!("thread {} line {}", t, i))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let mut lines = fs::read_to_string(sink.path()).unwrap().lines().count();
+        let mut index = 1;
+        while sink.rotated_path(index).exists() {
+            let rotated = fs::read_to_string(sink.rotated_path(index)).unwrap();
+            // Records are never split across files
+            assert!(rotated.lines().all(|line| line.contains(" line ")));
+            lines += rotated.lines().count();
+            index += 1;
+        }
+        assert_eq!(lines, 200);
+    }
+
     #[test]
     fn test_host_log_with_unrestricted() {
         let caps = create_unrestricted_capset();
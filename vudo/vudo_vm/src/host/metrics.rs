@@ -0,0 +1,228 @@
+//! Spirit Metrics Host Functions
+//!
+//! Lets a Spirit publish its own application metrics alongside the VM's
+//! `SandboxMetrics`. Metrics are named counters (values add up) or gauges
+//! (the latest value wins), held in a per-sandbox [`MetricsSink`].
+//!
+//! Emitting requires `ActuatorLog`, the same capability as logging.
+
+use std::collections::HashMap;
+use zeroize::Zeroize;
+
+use super::{CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
+
+/// Maximum metric name length in bytes
+pub const MAX_METRIC_NAME_LENGTH: usize = 128;
+
+/// Maximum number of distinct metric names a Spirit may emit
+pub const MAX_SPIRIT_METRICS: usize = 1024;
+
+/// How an emitted value combines with the metric's current value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum MetricKind {
+    /// Add the value to the running total
+    Counter = 0,
+    /// Replace the current value
+    Gauge = 1,
+}
+
+impl MetricKind {
+    /// Convert from the `kind` argument of `host_metrics_emit`
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(MetricKind::Counter),
+            1 => Some(MetricKind::Gauge),
+            _ => None,
+        }
+    }
+}
+
+/// Counters and gauges emitted by a Spirit
+///
+/// A name is either a counter or a gauge; emitting it as the other kind
+/// is rejected.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSink {
+    counters: HashMap<String, f64>,
+    gauges: HashMap<String, f64>,
+}
+
+impl MetricsSink {
+    /// Create an empty sink
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply one emitted value
+    pub fn record(&mut self, kind: MetricKind, name: &str, value: f64) -> Result<(), String> {
+        let (target, other) = match kind {
+            MetricKind::Counter => (&mut self.counters, &self.gauges),
+            MetricKind::Gauge => (&mut self.gauges, &self.counters),
+        };
+        if other.contains_key(name) {
+            return Err(format!(
+                "Metric '{}' was already emitted as another kind",
+                name
+            ));
+        }
+        if !target.contains_key(name) && target.len() + other.len() >= MAX_SPIRIT_METRICS {
+            return Err(format!(
+                "Spirit may emit at most {} distinct metrics",
+                MAX_SPIRIT_METRICS
+            ));
+        }
+
+        let entry = target.entry(name.to_string()).or_insert(0.0);
+        match kind {
+            MetricKind::Counter => *entry += value,
+            MetricKind::Gauge => *entry = value,
+        }
+        Ok(())
+    }
+
+    /// Current total of counter `name`
+    pub fn counter(&self, name: &str) -> Option<f64> {
+        self.counters.get(name).copied()
+    }
+
+    /// Latest value of gauge `name`
+    pub fn gauge(&self, name: &str) -> Option<f64> {
+        self.gauges.get(name).copied()
+    }
+
+    /// All counters by name
+    pub fn counters(&self) -> &HashMap<String, f64> {
+        &self.counters
+    }
+
+    /// All gauges by name
+    pub fn gauges(&self) -> &HashMap<String, f64> {
+        &self.gauges
+    }
+
+    /// Number of distinct metrics
+    pub fn len(&self) -> usize {
+        self.counters.len() + self.gauges.len()
+    }
+
+    /// Check whether nothing has been emitted
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Zero every metric name and empty the sink
+    pub fn scrub(&mut self) {
+        for (mut name, _) in self.counters.drain().chain(self.gauges.drain()) {
+            name.zeroize();
+        }
+    }
+}
+
+/// Record a Spirit-defined metric
+///
+/// `kind` is a [`MetricKind`] discriminant. Values must be finite, and
+/// counter increments must not be negative.
+///
+/// # Capability Required
+/// - `ActuatorLog` with `Global` scope
+pub fn host_metrics_emit(
+    caps: &CapabilitySet,
+    sink: &mut MetricsSink,
+    name: &str,
+    value: f64,
+    kind: i32,
+) -> HostCallResult {
+    if !caps.has_capability(CapabilityType::ActuatorLog, CapabilityScope::Global) {
+        return HostCallResult::capability_denied(CapabilityType::ActuatorLog);
+    }
+
+    let kind = match MetricKind::from_i32(kind) {
+        Some(kind) => kind,
+        None => return HostCallResult::error(format!("Unknown metric kind {}", kind)),
+    };
+    if name.is_empty() || name.len() > MAX_METRIC_NAME_LENGTH {
+        return HostCallResult::error(format!(
+            "Metric name must be 1 to {} bytes",
+            MAX_METRIC_NAME_LENGTH
+        ));
+    }
+    if !value.is_finite() {
+        return HostCallResult::error("Metric value must be finite");
+    }
+    if kind == MetricKind::Counter && value < 0.0 {
+        return HostCallResult::error("Counter increments must not be negative");
+    }
+
+    match sink.record(kind, name, value) {
+        Ok(()) => HostCallResult::success(),
+        Err(e) => HostCallResult::error(e),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_caps() -> CapabilitySet {
+        CapabilitySet::with_types(&[CapabilityType::ActuatorLog])
+    }
+
+    #[test]
+    fn test_counter_sums_and_gauge_keeps_latest() {
+        let caps = log_caps();
+        let mut sink = MetricsSink::new();
+
+        for _ in 0..3 {
+            assert!(host_metrics_emit(&caps, &mut sink, "requests", 2.5, 0).success);
+        }
+        assert!(host_metrics_emit(&caps, &mut sink, "queue_depth", 7.0, 1).success);
+        assert!(host_metrics_emit(&caps, &mut sink, "queue_depth", 4.0, 1).success);
+
+        assert_eq!(sink.counter("requests"), Some(7.5));
+        assert_eq!(sink.gauge("queue_depth"), Some(4.0));
+        assert_eq!(sink.len(), 2);
+    }
+
+    #[test]
+    fn test_scrub_empties_sink() {
+        let caps = log_caps();
+        let mut sink = MetricsSink::new();
+        assert!(host_metrics_emit(&caps, &mut sink, "requests", 1.0, 0).success);
+        assert!(host_metrics_emit(&caps, &mut sink, "queue_depth", 3.0, 1).success);
+
+        sink.scrub();
+        assert!(sink.is_empty());
+    }
+
+    #[test]
+    fn test_metrics_emit_requires_actuator_log() {
+        let mut sink = MetricsSink::new();
+        let result = host_metrics_emit(&CapabilitySet::new(), &mut sink, "requests", 1.0, 0);
+
+        assert_eq!(result.denied, Some(CapabilityType::ActuatorLog));
+        assert!(sink.is_empty());
+    }
+
+    #[test]
+    fn test_metrics_emit_rejects_invalid_input() {
+        let caps = log_caps();
+        let mut sink = MetricsSink::new();
+        let long_name = "m".repeat(MAX_METRIC_NAME_LENGTH + 1);
+
+        assert!(!host_metrics_emit(&caps, &mut sink, "m", 1.0, 2).success);
+        assert!(!host_metrics_emit(&caps, &mut sink, "", 1.0, 0).success);
+        assert!(!host_metrics_emit(&caps, &mut sink, &long_name, 1.0, 0).success);
+        assert!(!host_metrics_emit(&caps, &mut sink, "m", f64::NAN, 1).success);
+        assert!(!host_metrics_emit(&caps, &mut sink, "m", -1.0, 0).success);
+        assert!(sink.is_empty());
+
+        // A name keeps the kind it was first emitted as
+        assert!(host_metrics_emit(&caps, &mut sink, "m", 1.0, 0).success);
+        assert!(!host_metrics_emit(&caps, &mut sink, "m", 1.0, 1).success);
+    }
+}
@@ -4,7 +4,11 @@
 //! All host functions are capability-gated and return HostCallResult.
 
 pub mod credit;
+pub mod dispatch;
+#[cfg(feature = "fs-storage")]
+pub mod fs_storage;
 pub mod log;
+pub mod metrics;
 pub mod network;
 pub mod random;
 pub mod storage;
@@ -16,18 +20,32 @@ pub use crate::capability::{CapabilityGrant, CapabilityScope, CapabilitySet, Cap
 // Re-exports for convenience
 pub use credit::{
     host_credit_available, host_credit_balance, host_credit_consume, host_credit_release,
-    host_credit_reserve, host_credit_transfer, CreditBackend, InMemoryCreditLedger, PublicKey,
+    host_credit_reserve, host_credit_transfer, is_credit_overflow, public_key_from_hex,
+    public_key_to_hex, CreditBackend, InMemoryCreditLedger, PublicKey, PublicKeyHexError,
+    CREDIT_OVERFLOW,
+};
+pub use dispatch::{host_invoke, HostMethod};
+#[cfg(feature = "fs-storage")]
+pub use fs_storage::FsStorage;
+pub use log::{
+    host_log, host_log_filtered, host_log_with, print_record, FileLogSink, LogLevel, LogSink,
+};
+pub use metrics::{
+    host_metrics_emit, MetricKind, MetricsSink, MAX_METRIC_NAME_LENGTH, MAX_SPIRIT_METRICS,
 };
-pub use log::{host_log, LogLevel};
 pub use network::{
     host_network_broadcast, host_network_connect, host_network_listen, ConnectionHandle,
-    ListenerHandle, MockNetworkBackend, NetworkBackend,
+    ListenerHandle, MockNetworkBackend, NetworkBackend, ALL_NETWORK_CAPABILITIES,
+};
+pub use random::{
+    host_random_bytes, host_random_bytes_limited, DEFAULT_MAX_RANDOM_BYTES_PER_CALL,
+    MAX_RANDOM_BYTES,
 };
-pub use random::host_random_bytes;
 pub use storage::{
-    host_storage_delete, host_storage_read, host_storage_write, InMemoryStorage, StorageBackend,
+    host_storage_delete, host_storage_exists, host_storage_increment, host_storage_read,
+    host_storage_write, host_storage_write_ttl, InMemoryStorage, StorageBackend, StorageClock,
 };
-pub use time::host_time_now;
+pub use time::{host_time_now, host_time_sleep, sleep_fuel_cost, SLEEP_FUEL_PER_MILLI};
 
 // ═══════════════════════════════════════════════════════════════════════════
 // HOST CALL RESULT
@@ -39,6 +57,10 @@ pub struct HostCallResult {
     pub success: bool,
     pub return_value: Option<Vec<u8>>,
     pub error: Option<String>,
+    /// Capability whose absence caused the call to be rejected, if any
+    pub denied: Option<CapabilityType>,
+    /// Granted capability the backend cannot carry out, if any
+    pub unsupported: Option<CapabilityType>,
 }
 
 impl HostCallResult {
@@ -48,6 +70,8 @@ impl HostCallResult {
             success: true,
             return_value: None,
             error: None,
+            denied: None,
+            unsupported: None,
         }
     }
 
@@ -57,6 +81,8 @@ impl HostCallResult {
             success: true,
             return_value: Some(value),
             error: None,
+            denied: None,
+            unsupported: None,
         }
     }
 
@@ -66,15 +92,37 @@ impl HostCallResult {
             success: false,
             return_value: None,
             error: Some(message.into()),
+            denied: None,
+            unsupported: None,
         }
     }
 
     /// Create a capability denied error
     pub fn capability_denied(capability: CapabilityType) -> Self {
-        Self::error(format!("Capability denied: {:?}", capability))
+        Self {
+            denied: Some(capability),
+            ..Self::error(format!("Capability denied: {:?}", capability))
+        }
+    }
+
+    /// Create an error for a granted operation the backend does not support
+    pub fn not_supported(capability: CapabilityType) -> Self {
+        Self {
+            unsupported: Some(capability),
+            ..Self::error(format!("Not supported by backend: {:?}", capability))
+        }
     }
 }
 
+/// A `not_supported` result if a backend reporting `supported` cannot carry
+/// out `capability`
+pub(crate) fn unsupported(
+    supported: &[CapabilityType],
+    capability: CapabilityType,
+) -> Option<HostCallResult> {
+    (!supported.contains(&capability)).then(|| HostCallResult::not_supported(capability))
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // HOST INTERFACE TRAIT
 // ═══════════════════════════════════════════════════════════════════════════
@@ -99,6 +147,9 @@ pub trait HostInterface {
     /// Delete from storage
     fn host_storage_delete(&self, caps: &CapabilitySet, key: &[u8]) -> HostCallResult;
 
+    /// Check whether a storage key exists
+    fn host_storage_exists(&self, caps: &CapabilitySet, key: &[u8]) -> HostCallResult;
+
     /// Connect to a network address
     fn host_network_connect(&self, caps: &CapabilitySet, address: &str) -> HostCallResult;
 
@@ -3,7 +3,7 @@
 //! Provides network-related host functions for WASM sandboxes.
 //! All network operations are capability-gated to ensure secure sandbox execution.
 
-use super::{CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
+use super::{unsupported, CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
@@ -117,8 +117,24 @@ pub trait NetworkBackend: Send + Sync {
 
     /// Get number of active listeners
     fn listener_count(&self) -> usize;
+
+    /// Network capabilities this backend can actually carry out
+    ///
+    /// A backend that cannot, say, broadcast leaves `NetworkBroadcast` out
+    /// so Spirits get a not-supported error rather than a fake result.
+    /// Defaults to connect, listen and broadcast.
+    fn capabilities_supported(&self) -> &'static [CapabilityType] {
+        ALL_NETWORK_CAPABILITIES
+    }
 }
 
+/// Every capability a `NetworkBackend` can carry out
+pub const ALL_NETWORK_CAPABILITIES: &[CapabilityType] = &[
+    CapabilityType::NetworkConnect,
+    CapabilityType::NetworkListen,
+    CapabilityType::NetworkBroadcast,
+];
+
 // ═══════════════════════════════════════════════════════════════════════════
 // MOCK NETWORK BACKEND (FOR TESTING)
 // ═══════════════════════════════════════════════════════════════════════════
@@ -140,6 +156,8 @@ pub struct MockNetworkBackend {
     pub listen_error: Arc<RwLock<Option<String>>>,
     /// If set, broadcast operations will fail with this error
     pub broadcast_error: Arc<RwLock<Option<String>>>,
+    /// Operations reported by `capabilities_supported`
+    supported: &'static [CapabilityType],
 }
 
 impl MockNetworkBackend {
@@ -154,9 +172,17 @@ impl MockNetworkBackend {
             connect_error: Arc::new(RwLock::new(None)),
             listen_error: Arc::new(RwLock::new(None)),
             broadcast_error: Arc::new(RwLock::new(None)),
+            supported: ALL_NETWORK_CAPABILITIES,
         }
     }
 
+    /// Report only `supported` from `capabilities_supported`, simulating a
+    /// backend that cannot carry out the other operations
+    pub fn with_supported(mut self, supported: &'static [CapabilityType]) -> Self {
+        self.supported = supported;
+        self
+    }
+
     /// Get list of addresses that have been connected to
     pub fn connected_addresses(&self) -> Vec<String> {
         self.connections.read().unwrap().values().cloned().collect()
@@ -305,6 +331,10 @@ impl NetworkBackend for MockNetworkBackend {
     fn listener_count(&self) -> usize {
         self.listeners.read().map(|l| l.len()).unwrap_or(0)
     }
+
+    fn capabilities_supported(&self) -> &'static [CapabilityType] {
+        self.supported
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -335,11 +365,25 @@ pub fn host_network_connect(
     network: &dyn NetworkBackend,
     address: &str,
 ) -> HostCallResult {
-    // Check capability
-    if !caps.has_capability(CapabilityType::NetworkConnect, CapabilityScope::Global) {
+    // Check capability: a Global grant, or a Peer grant bound to this address
+    if !caps.has_capability(CapabilityType::NetworkConnect, CapabilityScope::Global)
+        && !caps.has_capability_for(
+            CapabilityType::NetworkConnect,
+            CapabilityScope::Peer,
+            address.as_bytes(),
+        )
+    {
         return HostCallResult::capability_denied(CapabilityType::NetworkConnect);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        network.capabilities_supported(),
+        CapabilityType::NetworkConnect,
+    ) {
+        return result;
+    }
+
     // Validate address
     if address.is_empty() {
         return HostCallResult::error("Address cannot be empty");
@@ -388,6 +432,14 @@ pub fn host_network_listen(
         return HostCallResult::capability_denied(CapabilityType::NetworkListen);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        network.capabilities_supported(),
+        CapabilityType::NetworkListen,
+    ) {
+        return result;
+    }
+
     // Note: Port validation is implicit via u16 type (max 65535)
     // Port 0 is typically for dynamic allocation but we allow it
 
@@ -429,6 +481,14 @@ pub fn host_network_broadcast(
         return HostCallResult::capability_denied(CapabilityType::NetworkBroadcast);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        network.capabilities_supported(),
+        CapabilityType::NetworkBroadcast,
+    ) {
+        return result;
+    }
+
     // Validate message
     if message.is_empty() {
         return HostCallResult::error("Message cannot be empty");
@@ -622,6 +682,34 @@ mod tests {
         assert_eq!(messages[0], b"Hello, peers!");
     }
 
+    #[test]
+    fn test_mock_network_supported_operations() {
+        let full = MockNetworkBackend::new();
+        assert_eq!(full.capabilities_supported(), ALL_NETWORK_CAPABILITIES);
+
+        let no_broadcast = MockNetworkBackend::new().with_supported(&[
+            CapabilityType::NetworkConnect,
+            CapabilityType::NetworkListen,
+        ]);
+        assert!(!no_broadcast
+            .capabilities_supported()
+            .contains(&CapabilityType::NetworkBroadcast));
+
+        let caps = create_network_caps();
+        let result = host_network_broadcast(&caps, &no_broadcast, b"hello");
+        assert!(!result.success);
+        assert_eq!(result.unsupported, Some(CapabilityType::NetworkBroadcast));
+        assert_eq!(result.denied, None);
+        assert!(no_broadcast.broadcast_messages().is_empty());
+
+        assert!(host_network_connect(&caps, &no_broadcast, "peer:8080").success);
+
+        // A missing grant is reported as denied, not unsupported
+        let result = host_network_broadcast(&CapabilitySet::new(), &no_broadcast, b"hello");
+        assert_eq!(result.denied, Some(CapabilityType::NetworkBroadcast));
+        assert_eq!(result.unsupported, None);
+    }
+
     #[test]
     fn test_mock_network_close_connection() {
         let network = MockNetworkBackend::new();
@@ -760,6 +848,99 @@ mod tests {
         assert!(result.return_value.is_some());
     }
 
+    fn create_peer_connect_caps(target: &[u8]) -> CapabilitySet {
+        let mut cap_set = CapabilitySet::new();
+        let grant = CapabilityGrant::new(
+            1,
+            CapabilityType::NetworkConnect,
+            CapabilityScope::Peer,
+            [0u8; 32],
+            [1u8; 32],
+            current_timestamp(),
+            None,
+            [0u8; 64],
+        )
+        .with_scope_target(target);
+        cap_set.add_grant(grant);
+        cap_set
+    }
+
+    #[test]
+    fn test_host_network_connect_peer_scope_matching_address() {
+        let caps = create_peer_connect_caps(b"peer-a:9000");
+        let network = MockNetworkBackend::new();
+
+        let result = host_network_connect(&caps, &network, "peer-a:9000");
+
+        assert!(result.success);
+        assert_eq!(network.connection_count(), 1);
+    }
+
+    #[test]
+    fn test_host_network_connect_peer_scope_matching_address_hash() {
+        use sha2::{Digest, Sha256};
+
+        let caps = create_peer_connect_caps(&Sha256::digest(b"peer-a:9000"));
+        let network = MockNetworkBackend::new();
+
+        let result = host_network_connect(&caps, &network, "peer-a:9000");
+
+        assert!(result.success);
+    }
+
+    #[test]
+    fn test_host_network_connect_peer_scope_other_address_denied() {
+        let caps = create_peer_connect_caps(b"peer-a:9000");
+        let network = MockNetworkBackend::new();
+
+        let result = host_network_connect(&caps, &network, "peer-b:9000");
+
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Capability denied"));
+        assert_eq!(network.connection_count(), 0);
+    }
+
+    #[test]
+    fn test_host_network_connect_peer_scope_without_target_denied() {
+        let mut caps = CapabilitySet::new();
+        caps.add_grant(CapabilityGrant::new(
+            1,
+            CapabilityType::NetworkConnect,
+            CapabilityScope::Peer,
+            [0u8; 32],
+            [1u8; 32],
+            current_timestamp(),
+            None,
+            [0u8; 64],
+        ));
+        let network = MockNetworkBackend::new();
+
+        let result = host_network_connect(&caps, &network, "peer-a:9000");
+
+        assert!(!result.success);
+    }
+
+    #[test]
+    fn test_host_network_connect_global_scope_ignores_target() {
+        let mut cap_set = CapabilitySet::new();
+        let grant = CapabilityGrant::new(
+            1,
+            CapabilityType::NetworkConnect,
+            CapabilityScope::Global,
+            [0u8; 32],
+            [1u8; 32],
+            current_timestamp(),
+            None,
+            [0u8; 64],
+        )
+        .with_scope_target(b"peer-a:9000".to_vec());
+        cap_set.add_grant(grant);
+        let network = MockNetworkBackend::new();
+
+        assert!(host_network_connect(&cap_set, &network, "peer-a:9000").success);
+        assert!(host_network_connect(&cap_set, &network, "peer-b:9000").success);
+    }
+
     // ═══════════════════════════════════════════════════════════════════════
     // HOST_NETWORK_LISTEN TESTS
     // ═══════════════════════════════════════════════════════════════════════
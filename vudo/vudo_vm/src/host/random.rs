@@ -4,8 +4,11 @@
 
 use super::{CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
 
-/// Maximum number of random bytes that can be requested in a single call
-const MAX_RANDOM_BYTES: u32 = 1024 * 1024; // 1MB
+/// Largest per-call limit a sandbox may configure
+pub const MAX_RANDOM_BYTES: u32 = 1024 * 1024; // 1MB
+
+/// Per-call limit used unless `ResourceLimits` says otherwise
+pub const DEFAULT_MAX_RANDOM_BYTES_PER_CALL: u32 = 64 * 1024; // 64KB
 
 /// Generate cryptographically secure random bytes
 ///
@@ -13,11 +16,23 @@ const MAX_RANDOM_BYTES: u32 = 1024 * 1024; // 1MB
 ///
 /// # Arguments
 /// * `caps` - Capability set to check permissions
-/// * `count` - Number of random bytes to generate (max 1MB)
+/// * `count` - Number of random bytes to generate (max 64KB)
 ///
 /// # Returns
 /// HostCallResult with random bytes or error
 pub fn host_random_bytes(caps: &CapabilitySet, count: u32) -> HostCallResult {
+    host_random_bytes_limited(caps, count, DEFAULT_MAX_RANDOM_BYTES_PER_CALL)
+}
+
+/// Generate random bytes, rejecting requests larger than `max_per_call`.
+///
+/// The limit is checked before anything is allocated. It is itself capped
+/// at [`MAX_RANDOM_BYTES`].
+pub fn host_random_bytes_limited(
+    caps: &CapabilitySet,
+    count: u32,
+    max_per_call: u32,
+) -> HostCallResult {
     // Check capability
     if !caps.has_capability(CapabilityType::SensorRandom, CapabilityScope::Global) {
         return HostCallResult::capability_denied(CapabilityType::SensorRandom);
@@ -28,11 +43,9 @@ pub fn host_random_bytes(caps: &CapabilitySet, count: u32) -> HostCallResult {
         return HostCallResult::error("Count must be greater than 0");
     }
 
-    if count > MAX_RANDOM_BYTES {
-        return HostCallResult::error(format!(
-            "Count exceeds maximum of {} bytes",
-            MAX_RANDOM_BYTES
-        ));
+    let max = max_per_call.min(MAX_RANDOM_BYTES);
+    if count > max {
+        return HostCallResult::error(format!("Count exceeds maximum of {} bytes", max));
     }
 
     // Generate random bytes
@@ -143,6 +156,39 @@ mod tests {
         assert!(result.error.unwrap().contains("exceeds maximum"));
     }
 
+    #[test]
+    fn test_host_random_bytes_default_cap() {
+        let caps = create_test_capset();
+
+        let at_cap = host_random_bytes(&caps, DEFAULT_MAX_RANDOM_BYTES_PER_CALL);
+        assert!(at_cap.success);
+        assert_eq!(
+            at_cap.return_value.unwrap().len(),
+            DEFAULT_MAX_RANDOM_BYTES_PER_CALL as usize
+        );
+
+        let over_cap = host_random_bytes(&caps, DEFAULT_MAX_RANDOM_BYTES_PER_CALL + 1);
+        assert!(!over_cap.success);
+        assert!(over_cap.return_value.is_none());
+    }
+
+    #[test]
+    fn test_host_random_bytes_limited_rejects_before_allocating() {
+        let caps = create_test_capset();
+
+        // A 4 GB buffer would abort the test if it were allocated
+        let result = host_random_bytes_limited(&caps, u32::MAX, 16);
+        assert!(!result.success);
+        assert!(result
+            .error
+            .unwrap()
+            .contains("exceeds maximum of 16 bytes"));
+
+        // The configured limit cannot raise the hard maximum
+        let result = host_random_bytes_limited(&caps, MAX_RANDOM_BYTES + 1, u32::MAX);
+        assert!(!result.success);
+    }
+
     #[test]
     fn test_host_random_bytes_with_unrestricted() {
         let caps = create_unrestricted_capset();
@@ -2,9 +2,11 @@
 //!
 //! Provides persistent storage capabilities for WASM sandboxes.
 
-use super::{CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
+use super::{unsupported, CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Maximum key size in bytes
 const MAX_KEY_SIZE: usize = 1024; // 1KB
@@ -26,9 +28,29 @@ pub trait StorageBackend: Send + Sync {
 
     /// Write a key-value pair
     ///
-    /// Overwrites existing value if key already exists.
+    /// Overwrites existing value if key already exists, clearing any TTL.
     fn write(&self, key: &[u8], value: &[u8]) -> Result<(), String>;
 
+    /// Write a key-value pair that expires `ttl_secs` seconds from now
+    ///
+    /// Once expired, `read` and `exists` treat the key as absent until it
+    /// is overwritten or reclaimed by `sweep_expired`.
+    ///
+    /// The default implementation reports TTLs as unsupported.
+    fn write_with_ttl(&self, key: &[u8], value: &[u8], ttl_secs: u64) -> Result<(), String> {
+        let _ = (key, value, ttl_secs);
+        Err("Storage backend does not support TTLs".to_string())
+    }
+
+    /// Remove entries that expired at or before `now` (Unix seconds)
+    ///
+    /// Returns the number of entries removed. The default implementation
+    /// has nothing to sweep.
+    fn sweep_expired(&self, now: u64) -> Result<usize, String> {
+        let _ = now;
+        Ok(0)
+    }
+
     /// Delete a key-value pair
     ///
     /// Returns:
@@ -37,31 +59,128 @@ pub trait StorageBackend: Send + Sync {
     /// - Err(msg) on storage error
     fn delete(&self, key: &[u8]) -> Result<bool, String>;
 
+    /// Check whether a key exists without fetching its value
+    ///
+    /// The default implementation falls back to `read`; backends that can
+    /// answer more cheaply should override it.
+    fn exists(&self, key: &[u8]) -> Result<bool, String> {
+        Ok(self.read(key)?.is_some())
+    }
+
+    /// Add `delta` to the counter stored under `key` and return its new value
+    ///
+    /// Counters are 8-byte little-endian `i64` values; an absent key starts
+    /// at 0. Fails if the existing value is not 8 bytes or the sum overflows.
+    ///
+    /// The default implementation is a plain read followed by a write.
+    /// Backends shared between sandboxes must override it to make the
+    /// update atomic.
+    fn increment(&self, key: &[u8], delta: i64) -> Result<i64, String> {
+        let current = decode_counter(self.read(key)?.as_deref())?;
+        let next = add_to_counter(current, delta)?;
+        self.write(key, &next.to_le_bytes())?;
+        Ok(next)
+    }
+
     /// Get number of stored key-value pairs
     fn count(&self) -> Result<usize, String>;
 
+    /// Storage capabilities this backend can actually carry out
+    ///
+    /// Host functions gated on a capability missing here fail with a
+    /// not-supported error even when the Spirit holds the grant. Defaults
+    /// to read, write and delete.
+    fn capabilities_supported(&self) -> &'static [CapabilityType] {
+        &[
+            CapabilityType::StorageRead,
+            CapabilityType::StorageWrite,
+            CapabilityType::StorageDelete,
+        ]
+    }
+
     /// Clear all stored data
     fn clear(&self) -> Result<(), String>;
 }
 
+/// Decode a stored counter; an absent value counts as 0.
+pub(super) fn decode_counter(value: Option<&[u8]>) -> Result<i64, String> {
+    match value {
+        None => Ok(0),
+        Some(bytes) => bytes
+            .try_into()
+            .map(i64::from_le_bytes)
+            .map_err(|_| format!("Counter value must be 8 bytes, found {}", bytes.len())),
+    }
+}
+
+/// Add `delta` to a counter. `i64::MIN` is excluded so that
+/// `host_storage_increment` can use it as its error value.
+pub(super) fn add_to_counter(current: i64, delta: i64) -> Result<i64, String> {
+    current
+        .checked_add(delta)
+        .filter(|next| *next != i64::MIN)
+        .ok_or_else(|| "Counter overflow".to_string())
+}
+
+/// Source of the current Unix time in seconds, used to expire TTL entries
+pub type StorageClock = Arc<dyn Fn() -> u64 + Send + Sync>;
+
+fn system_clock() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A stored value and the Unix time (seconds) it expires at, if any
+#[derive(Debug, Clone)]
+struct Entry {
+    value: Vec<u8>,
+    expires_at: Option<u64>,
+}
+
+impl Entry {
+    fn is_live(&self, now: u64) -> bool {
+        self.expires_at.is_none_or(|expiry| now < expiry)
+    }
+}
+
 /// In-memory storage implementation
 ///
 /// This is a simple HashMap-based storage for testing and development.
 /// For production, use a persistent storage backend.
-#[derive(Debug, Clone)]
+///
+/// Expired TTL entries stay in memory, invisible to reads and `count`,
+/// until `sweep_expired` removes them or the key is written again.
+#[derive(Clone)]
 pub struct InMemoryStorage {
-    data: Arc<RwLock<HashMap<Vec<u8>, Vec<u8>>>>,
+    data: Arc<RwLock<HashMap<Vec<u8>, Entry>>>,
+    clock: StorageClock,
 }
 
 impl InMemoryStorage {
     /// Create a new in-memory storage
     pub fn new() -> Self {
+        Self::with_clock(system_clock)
+    }
+
+    /// Create a new in-memory storage that reads the time from `clock`
+    pub fn with_clock(clock: impl Fn() -> u64 + Send + Sync + 'static) -> Self {
         Self {
             data: Arc::new(RwLock::new(HashMap::new())),
+            clock: Arc::new(clock),
         }
     }
 }
 
+impl fmt::Debug for InMemoryStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InMemoryStorage")
+            .field("data", &self.data)
+            .finish_non_exhaustive()
+    }
+}
+
 impl Default for InMemoryStorage {
     fn default() -> Self {
         Self::new()
@@ -70,8 +189,12 @@ impl Default for InMemoryStorage {
 
 impl StorageBackend for InMemoryStorage {
     fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        let now = (self.clock)();
         let data = self.data.read().map_err(|e| format!("Lock error: {}", e))?;
-        Ok(data.get(key).cloned())
+        Ok(data
+            .get(key)
+            .filter(|entry| entry.is_live(now))
+            .map(|entry| entry.value.clone()))
     }
 
     fn write(&self, key: &[u8], value: &[u8]) -> Result<(), String> {
@@ -79,21 +202,83 @@ impl StorageBackend for InMemoryStorage {
             .data
             .write()
             .map_err(|e| format!("Lock error: {}", e))?;
-        data.insert(key.to_vec(), value.to_vec());
+        data.insert(
+            key.to_vec(),
+            Entry {
+                value: value.to_vec(),
+                expires_at: None,
+            },
+        );
+        Ok(())
+    }
+
+    fn write_with_ttl(&self, key: &[u8], value: &[u8], ttl_secs: u64) -> Result<(), String> {
+        let expires_at = (self.clock)().saturating_add(ttl_secs);
+        let mut data = self
+            .data
+            .write()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        data.insert(
+            key.to_vec(),
+            Entry {
+                value: value.to_vec(),
+                expires_at: Some(expires_at),
+            },
+        );
         Ok(())
     }
 
     fn delete(&self, key: &[u8]) -> Result<bool, String> {
+        let now = (self.clock)();
+        let mut data = self
+            .data
+            .write()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        Ok(data.remove(key).is_some_and(|entry| entry.is_live(now)))
+    }
+
+    fn exists(&self, key: &[u8]) -> Result<bool, String> {
+        let now = (self.clock)();
+        let data = self.data.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(data.get(key).is_some_and(|entry| entry.is_live(now)))
+    }
+
+    fn increment(&self, key: &[u8], delta: i64) -> Result<i64, String> {
+        let now = (self.clock)();
+        // Holding the write lock across read and write makes this atomic
+        let mut data = self
+            .data
+            .write()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        // A live counter keeps its expiry; an expired one starts over at 0
+        let live = data.get(key).filter(|entry| entry.is_live(now));
+        let current = decode_counter(live.map(|entry| entry.value.as_slice()))?;
+        let next = add_to_counter(current, delta)?;
+        let expires_at = live.and_then(|entry| entry.expires_at);
+        data.insert(
+            key.to_vec(),
+            Entry {
+                value: next.to_le_bytes().to_vec(),
+                expires_at,
+            },
+        );
+        Ok(next)
+    }
+
+    fn sweep_expired(&self, now: u64) -> Result<usize, String> {
         let mut data = self
             .data
             .write()
             .map_err(|e| format!("Lock error: {}", e))?;
-        Ok(data.remove(key).is_some())
+        let before = data.len();
+        data.retain(|_, entry| entry.is_live(now));
+        Ok(before - data.len())
     }
 
     fn count(&self) -> Result<usize, String> {
+        let now = (self.clock)();
         let data = self.data.read().map_err(|e| format!("Lock error: {}", e))?;
-        Ok(data.len())
+        Ok(data.values().filter(|entry| entry.is_live(now)).count())
     }
 
     fn clear(&self) -> Result<(), String> {
@@ -110,6 +295,14 @@ impl StorageBackend for InMemoryStorage {
 // HOST STORAGE FUNCTIONS
 // ═══════════════════════════════════════════════════════════════════════════
 
+/// Check `cap` is granted for sandbox storage, or confined to a domain
+///
+/// Domain-scoped grants don't cover `Sandboxed`, but the linker partitions
+/// their keys by domain (see `HostState::storage_key`), so they are allowed.
+fn storage_permitted(caps: &CapabilitySet, cap: CapabilityType) -> bool {
+    caps.has_capability(cap, CapabilityScope::Sandboxed) || caps.domain_target(cap).is_some()
+}
+
 /// Read from storage
 ///
 /// Requires StorageRead capability.
@@ -127,10 +320,18 @@ pub fn host_storage_read(
     key: &[u8],
 ) -> HostCallResult {
     // Check capability
-    if !caps.has_capability(CapabilityType::StorageRead, CapabilityScope::Sandboxed) {
+    if !storage_permitted(caps, CapabilityType::StorageRead) {
         return HostCallResult::capability_denied(CapabilityType::StorageRead);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        storage.capabilities_supported(),
+        CapabilityType::StorageRead,
+    ) {
+        return result;
+    }
+
     // Validate key size
     if key.is_empty() {
         return HostCallResult::error("Key cannot be empty");
@@ -170,10 +371,18 @@ pub fn host_storage_write(
     value: &[u8],
 ) -> HostCallResult {
     // Check capability
-    if !caps.has_capability(CapabilityType::StorageWrite, CapabilityScope::Sandboxed) {
+    if !storage_permitted(caps, CapabilityType::StorageWrite) {
         return HostCallResult::capability_denied(CapabilityType::StorageWrite);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        storage.capabilities_supported(),
+        CapabilityType::StorageWrite,
+    ) {
+        return result;
+    }
+
     // Validate key size
     if key.is_empty() {
         return HostCallResult::error("Key cannot be empty");
@@ -218,10 +427,18 @@ pub fn host_storage_delete(
     key: &[u8],
 ) -> HostCallResult {
     // Check capability
-    if !caps.has_capability(CapabilityType::StorageDelete, CapabilityScope::Sandboxed) {
+    if !storage_permitted(caps, CapabilityType::StorageDelete) {
         return HostCallResult::capability_denied(CapabilityType::StorageDelete);
     }
 
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        storage.capabilities_supported(),
+        CapabilityType::StorageDelete,
+    ) {
+        return result;
+    }
+
     // Validate key size
     if key.is_empty() {
         return HostCallResult::error("Key cannot be empty");
@@ -244,6 +461,166 @@ pub fn host_storage_delete(
     }
 }
 
+/// Check whether a key exists in storage
+///
+/// Requires StorageRead capability. Unlike [`host_storage_read`], the value
+/// is never copied.
+///
+/// # Arguments
+/// * `caps` - Capability set to check permissions
+/// * `storage` - Storage backend to query
+/// * `key` - Key to look up
+///
+/// # Returns
+/// HostCallResult with success (return_value contains 1 byte: 1 if present, 0 if absent)
+pub fn host_storage_exists(
+    caps: &CapabilitySet,
+    storage: &dyn StorageBackend,
+    key: &[u8],
+) -> HostCallResult {
+    // Check capability
+    if !storage_permitted(caps, CapabilityType::StorageRead) {
+        return HostCallResult::capability_denied(CapabilityType::StorageRead);
+    }
+
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        storage.capabilities_supported(),
+        CapabilityType::StorageRead,
+    ) {
+        return result;
+    }
+
+    // Validate key size
+    if key.is_empty() {
+        return HostCallResult::error("Key cannot be empty");
+    }
+
+    if key.len() > MAX_KEY_SIZE {
+        return HostCallResult::error(format!(
+            "Key size exceeds maximum of {} bytes",
+            MAX_KEY_SIZE
+        ));
+    }
+
+    match storage.exists(key) {
+        Ok(exists) => HostCallResult::success_with_value(vec![exists as u8]),
+        Err(e) => HostCallResult::error(format!("Storage exists error: {}", e)),
+    }
+}
+
+/// Write to storage with an expiry
+///
+/// Requires StorageWrite capability.
+///
+/// # Arguments
+/// * `caps` - Capability set to check permissions
+/// * `storage` - Storage backend to write to
+/// * `key` - Key to write
+/// * `value` - Value to write
+/// * `ttl_secs` - Seconds until the entry expires (must be positive)
+///
+/// # Returns
+/// HostCallResult indicating success or error
+pub fn host_storage_write_ttl(
+    caps: &CapabilitySet,
+    storage: &dyn StorageBackend,
+    key: &[u8],
+    value: &[u8],
+    ttl_secs: u64,
+) -> HostCallResult {
+    // Check capability
+    if !storage_permitted(caps, CapabilityType::StorageWrite) {
+        return HostCallResult::capability_denied(CapabilityType::StorageWrite);
+    }
+
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        storage.capabilities_supported(),
+        CapabilityType::StorageWrite,
+    ) {
+        return result;
+    }
+
+    // Validate key size
+    if key.is_empty() {
+        return HostCallResult::error("Key cannot be empty");
+    }
+
+    if key.len() > MAX_KEY_SIZE {
+        return HostCallResult::error(format!(
+            "Key size exceeds maximum of {} bytes",
+            MAX_KEY_SIZE
+        ));
+    }
+
+    // Validate value size
+    if value.len() > MAX_VALUE_SIZE {
+        return HostCallResult::error(format!(
+            "Value size exceeds maximum of {} bytes",
+            MAX_VALUE_SIZE
+        ));
+    }
+
+    if ttl_secs == 0 {
+        return HostCallResult::error("TTL must be greater than 0");
+    }
+
+    match storage.write_with_ttl(key, value, ttl_secs) {
+        Ok(()) => HostCallResult::success(),
+        Err(e) => HostCallResult::error(format!("Storage write error: {}", e)),
+    }
+}
+
+/// Atomically add to a counter in storage
+///
+/// Requires StorageWrite capability.
+///
+/// # Arguments
+/// * `caps` - Capability set to check permissions
+/// * `storage` - Storage backend holding the counter
+/// * `key` - Key of the counter (8-byte little-endian i64, 0 if absent)
+/// * `delta` - Amount to add (may be negative)
+///
+/// # Returns
+/// HostCallResult with the new value (i64 in little-endian) or error
+pub fn host_storage_increment(
+    caps: &CapabilitySet,
+    storage: &dyn StorageBackend,
+    key: &[u8],
+    delta: i64,
+) -> HostCallResult {
+    // Check capability
+    if !storage_permitted(caps, CapabilityType::StorageWrite) {
+        return HostCallResult::capability_denied(CapabilityType::StorageWrite);
+    }
+
+    // Check the backend can carry it out
+    if let Some(result) = unsupported(
+        storage.capabilities_supported(),
+        CapabilityType::StorageWrite,
+    ) {
+        return result;
+    }
+
+    // Validate key size
+    if key.is_empty() {
+        return HostCallResult::error("Key cannot be empty");
+    }
+
+    if key.len() > MAX_KEY_SIZE {
+        return HostCallResult::error(format!(
+            "Key size exceeds maximum of {} bytes",
+            MAX_KEY_SIZE
+        ));
+    }
+
+    match storage.increment(key, delta) {
+        Ok(value) => HostCallResult::success_with_value(value.to_le_bytes().to_vec()),
+        Err(e) => HostCallResult::error(format!("Storage increment error: {}", e)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -446,6 +823,32 @@ mod tests {
         assert!(result.error.unwrap().contains("Capability denied"));
     }
 
+    #[test]
+    fn test_host_storage_exists() {
+        let caps = create_storage_caps();
+        let storage = InMemoryStorage::new();
+        storage.write(b"present", b"value").unwrap();
+
+        let result = host_storage_exists(&caps, &storage, b"present");
+        assert!(result.success);
+        assert_eq!(result.return_value, Some(vec![1]));
+
+        let result = host_storage_exists(&caps, &storage, b"missing");
+        assert!(result.success);
+        assert_eq!(result.return_value, Some(vec![0]));
+    }
+
+    #[test]
+    fn test_host_storage_exists_without_capability() {
+        let caps = CapabilitySet::new();
+        let storage = InMemoryStorage::new();
+        storage.write(b"present", b"value").unwrap();
+
+        let result = host_storage_exists(&caps, &storage, b"present");
+        assert!(!result.success);
+        assert_eq!(result.denied, Some(CapabilityType::StorageRead));
+    }
+
     #[test]
     fn test_host_storage_empty_key() {
         let caps = create_storage_caps();
@@ -493,4 +896,128 @@ mod tests {
         let delete_result = host_storage_delete(&caps, &storage, b"key");
         assert!(delete_result.success);
     }
+
+    #[test]
+    fn test_host_storage_increment() {
+        let caps = create_storage_caps();
+        let storage = InMemoryStorage::new();
+
+        let result = host_storage_increment(&caps, &storage, b"counter", 5);
+        assert_eq!(result.return_value.unwrap(), 5i64.to_le_bytes());
+
+        let result = host_storage_increment(&caps, &storage, b"counter", -7);
+        assert_eq!(result.return_value.unwrap(), (-2i64).to_le_bytes());
+        assert_eq!(
+            storage.read(b"counter").unwrap().unwrap(),
+            (-2i64).to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn test_host_storage_increment_requires_write() {
+        let caps = CapabilitySet::new();
+        let storage = InMemoryStorage::new();
+
+        let result = host_storage_increment(&caps, &storage, b"counter", 1);
+        assert_eq!(result.denied, Some(CapabilityType::StorageWrite));
+        assert!(!storage.exists(b"counter").unwrap());
+    }
+
+    #[test]
+    fn test_storage_increment_rejects_bad_counter() {
+        let storage = InMemoryStorage::new();
+
+        storage.write(b"text", b"abc").unwrap();
+        assert!(storage
+            .increment(b"text", 1)
+            .unwrap_err()
+            .contains("8 bytes"));
+
+        storage.write(b"max", &i64::MAX.to_le_bytes()).unwrap();
+        assert!(storage
+            .increment(b"max", 1)
+            .unwrap_err()
+            .contains("overflow"));
+        assert_eq!(
+            storage.read(b"max").unwrap().unwrap(),
+            i64::MAX.to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn test_storage_increment_concurrent() {
+        const THREADS: i64 = 8;
+        const PER_THREAD: i64 = 500;
+        const DELTA: i64 = 3;
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let storage = Arc::clone(&storage);
+                std::thread::spawn(move || {
+                    for _ in 0..PER_THREAD {
+                        storage.increment(b"counter", DELTA).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(
+            storage.increment(b"counter", 0).unwrap(),
+            THREADS * PER_THREAD * DELTA
+        );
+    }
+
+    #[test]
+    fn test_in_memory_storage_ttl_expiry() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let now = Arc::new(AtomicU64::new(1_000));
+        let clock = Arc::clone(&now);
+        let storage = InMemoryStorage::with_clock(move || clock.load(Ordering::SeqCst));
+
+        storage.write_with_ttl(b"cached", b"value", 10).unwrap();
+        storage.write(b"kept", b"value").unwrap();
+        storage
+            .write_with_ttl(b"hits", &5i64.to_le_bytes(), 10)
+            .unwrap();
+        assert_eq!(storage.increment(b"hits", 1).unwrap(), 6);
+
+        now.store(1_009, Ordering::SeqCst);
+        assert_eq!(storage.read(b"cached").unwrap(), Some(b"value".to_vec()));
+        assert_eq!(storage.count().unwrap(), 3);
+
+        // Expired entries read as absent but still occupy memory
+        now.store(1_010, Ordering::SeqCst);
+        assert_eq!(storage.read(b"cached").unwrap(), None);
+        assert!(!storage.exists(b"cached").unwrap());
+        assert!(!storage.exists(b"hits").unwrap()); // Increment kept the TTL
+        assert!(storage.exists(b"kept").unwrap());
+        assert_eq!(storage.count().unwrap(), 1);
+        assert_eq!(storage.data.read().unwrap().len(), 3);
+
+        assert_eq!(storage.sweep_expired(1_010).unwrap(), 2);
+        assert_eq!(storage.data.read().unwrap().len(), 1);
+        assert_eq!(storage.sweep_expired(1_010).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_host_storage_write_ttl() {
+        let caps = create_storage_caps();
+        let storage = InMemoryStorage::with_clock(|| 500);
+
+        let result = host_storage_write_ttl(&caps, &storage, b"key", b"value", 60);
+        assert!(result.success);
+        assert_eq!(storage.read(b"key").unwrap(), Some(b"value".to_vec()));
+        assert_eq!(storage.sweep_expired(560).unwrap(), 1);
+
+        let result = host_storage_write_ttl(&caps, &storage, b"key", b"value", 0);
+        assert!(!result.success);
+
+        let result = host_storage_write_ttl(&CapabilitySet::new(), &storage, b"key", b"value", 60);
+        assert_eq!(result.denied, Some(CapabilityType::StorageWrite));
+    }
 }
@@ -5,6 +5,9 @@
 use super::{CapabilityScope, CapabilitySet, CapabilityType, HostCallResult};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Fuel charged for each millisecond a Spirit sleeps
+pub const SLEEP_FUEL_PER_MILLI: u64 = 1_000;
+
 /// Get current Unix timestamp in nanoseconds
 ///
 /// Requires SensorTime capability.
@@ -31,6 +34,41 @@ pub fn host_time_now(caps: &CapabilitySet) -> HostCallResult {
     }
 }
 
+/// Fuel charged for sleeping `millis` milliseconds, saturating at `u64::MAX`
+pub fn sleep_fuel_cost(millis: u64) -> u64 {
+    millis.saturating_mul(SLEEP_FUEL_PER_MILLI)
+}
+
+/// Sleep for `millis` milliseconds of fuel rather than wall-clock time
+///
+/// Requires SensorTime capability. The host thread never blocks, so a
+/// sleeping Spirit stays within its timeout; instead the caller deducts
+/// the returned fuel cost from the Spirit.
+///
+/// # Arguments
+/// * `caps` - Capability set to check permissions
+/// * `millis` - Requested sleep duration in milliseconds
+/// * `fuel_remaining` - Fuel the Spirit has left
+///
+/// # Returns
+/// HostCallResult with the fuel to charge (u64 in little-endian), or an
+/// error if the sleep would cost more than `fuel_remaining`
+pub fn host_time_sleep(caps: &CapabilitySet, millis: u64, fuel_remaining: u64) -> HostCallResult {
+    if !caps.has_capability(CapabilityType::SensorTime, CapabilityScope::Global) {
+        return HostCallResult::capability_denied(CapabilityType::SensorTime);
+    }
+
+    let cost = sleep_fuel_cost(millis);
+    if cost > fuel_remaining {
+        return HostCallResult::error(format!(
+            "Insufficient fuel to sleep {}ms: needs {}, has {}",
+            millis, cost, fuel_remaining
+        ));
+    }
+
+    HostCallResult::success_with_value(cost.to_le_bytes().to_vec())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,6 +147,41 @@ mod tests {
         assert!(result.error.unwrap().contains("Capability denied"));
     }
 
+    #[test]
+    fn test_host_time_sleep_charges_proportional_fuel() {
+        let caps = create_test_capset();
+
+        let short = host_time_sleep(&caps, 2, u64::MAX);
+        let long = host_time_sleep(&caps, 20, u64::MAX);
+
+        let cost = |result: HostCallResult| {
+            assert!(result.success);
+            u64::from_le_bytes(result.return_value.unwrap().try_into().unwrap())
+        };
+        assert_eq!(cost(short), 2 * SLEEP_FUEL_PER_MILLI);
+        assert_eq!(cost(long), 20 * SLEEP_FUEL_PER_MILLI);
+    }
+
+    #[test]
+    fn test_host_time_sleep_insufficient_fuel() {
+        let caps = create_test_capset();
+
+        let result = host_time_sleep(&caps, 10, 10 * SLEEP_FUEL_PER_MILLI - 1);
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Insufficient fuel"));
+
+        // Overflowing durations saturate rather than wrapping to a cheap sleep
+        assert!(!host_time_sleep(&caps, u64::MAX, u64::MAX - 1).success);
+    }
+
+    #[test]
+    fn test_host_time_sleep_without_capability() {
+        let result = host_time_sleep(&CapabilitySet::new(), 1, u64::MAX);
+
+        assert!(!result.success);
+        assert_eq!(result.denied, Some(CapabilityType::SensorTime));
+    }
+
     #[test]
     fn test_host_time_now_with_unrestricted() {
         let caps = create_unrestricted_capset();
@@ -29,18 +29,33 @@ pub mod host;
 pub mod limits;
 pub mod linker;
 pub mod sandbox;
+pub mod trace;
 
 pub use error::SandboxError;
 pub use limits::ResourceLimits;
 
 // Re-export capability types for convenience
 pub use capability::{
-    CapabilityGrant, CapabilityScope, CapabilitySet, CapabilityType, MINIMAL_CAPABILITIES,
-    NETWORK_SPIRIT_CAPABILITIES, SYSTEM_SPIRIT_CAPABILITIES,
+    host_function_capability, CapabilityAudit, CapabilityGrant, CapabilityGrantBuilder,
+    CapabilityProfile, CapabilityScope, CapabilitySet, CapabilityType, RevocationList, RiskLevel,
+    MINIMAL_CAPABILITIES, NETWORK_SPIRIT_CAPABILITIES, STORAGE_SPIRIT_CAPABILITIES,
+    SYSTEM_SPIRIT_CAPABILITIES,
 };
 
 // Re-export host interface types for convenience
-pub use host::{HostCallResult, HostInterface, InMemoryStorage, LogLevel, StorageBackend};
+pub use host::{
+    public_key_from_hex, public_key_to_hex, FileLogSink, HostCallResult, HostInterface, HostMethod,
+    InMemoryStorage, LogLevel, LogSink, MetricKind, MetricsSink, PublicKeyHexError, StorageBackend,
+};
+
+#[cfg(feature = "fs-storage")]
+pub use host::FsStorage;
 
 // Re-export linker types for convenience
-pub use linker::{create_linker, HostState, HOST_ERROR, HOST_SUCCESS};
+pub use linker::{
+    create_linker, create_linker_for, create_linker_in, CapabilityDeniedHook, HostState, LogHook,
+    HOST_ERROR, HOST_NAMESPACE, HOST_SUCCESS,
+};
+
+// Re-export tracing types for convenience
+pub use trace::{TraceEntry, TraceRecorder};
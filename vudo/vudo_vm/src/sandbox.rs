@@ -5,13 +5,24 @@
 //!
 //! Based on: ontology/prospective/vudo-vm/genes/sandbox.dol v0.1.0
 
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use wasmtime::*;
 
-use crate::capability::CapabilitySet;
-use crate::host::{CreditBackend, NetworkBackend, StorageBackend};
-use crate::linker::{create_linker, HostState};
+use crate::capability::{CapabilitySet, RevocationList};
+use crate::host::{
+    CreditBackend, HostMethod, LogLevel, LogSink, MetricsSink, NetworkBackend, StorageBackend,
+    DEFAULT_MAX_RANDOM_BYTES_PER_CALL, MAX_RANDOM_BYTES,
+};
+use crate::linker::{
+    create_linker, create_linker_in, CapabilityDeniedHook, CapabilityViolation, HostState, LogHook,
+    MemoryLimitExceeded, ProcExit, SpiritAbort, HOST_NAMESPACE,
+};
+use crate::trace::TraceEntry;
 
 // ═══════════════════════════════════════════════════════════════════════════
 // CONSTANTS
@@ -21,8 +32,41 @@ pub const DEFAULT_MEMORY_BYTES: u64 = 67_108_864; // 64 MB
 pub const DEFAULT_CPU_QUOTA: f64 = 0.1; // 10%
 pub const DEFAULT_MAX_FUEL: u64 = 1_000_000_000; // 1 billion
 pub const DEFAULT_MAX_DURATION_SECS: u64 = 30; // 30 seconds
+pub const DEFAULT_MAX_DURATION_CEILING_SECS: u64 = 300; // 5 minutes
+pub const EPOCH_TICK_MILLIS: u64 = 10;
 pub const MAX_SANDBOX_MEMORY: u64 = 1_073_741_824; // 1 GB
 pub const MAX_MODULE_SIZE: usize = 104_857_600; // 100 MB
+pub const DEFAULT_MAX_STACK_BYTES: usize = 2_097_152; // 2 MB
+pub const MAX_STACK_BYTES: usize = 16_777_216; // 16 MB
+pub const ASYNC_HOST_STACK_BYTES: usize = 1_048_576; // 1 MB above the WASM stack
+pub const MAX_TABLE_ELEMENTS: u32 = 1_000_000;
+pub const DEFAULT_MAX_FUNCTIONS: u32 = 10_000;
+pub const DEFAULT_MAX_IMPORTS: u32 = 1_000;
+pub const DEFAULT_MAX_MEMORY_PAGES: u64 = DEFAULT_MEMORY_BYTES / WASM_PAGE_SIZE; // 64 MB
+pub const DEFAULT_MAX_GLOBALS: u32 = 1_000;
+pub const WASM_PAGE_SIZE: u64 = 65_536;
+pub const SHUTDOWN_EXPORT: &str = "__vudo_shutdown";
+pub const SHUTDOWN_FUEL: u64 = 100_000;
+/// Host-function ABI version this runtime implements
+pub const VUDO_ABI_VERSION: i32 = 1;
+pub const ABI_VERSION_EXPORT: &str = "__vudo_abi_version";
+pub const ABI_VERSION_FUEL: u64 = 10_000;
+pub const HEALTH_EXPORT: &str = "__vudo_health";
+pub const HEALTH_FUEL: u64 = 10_000;
+pub const HEALTH_TIMEOUT_MILLIS: u64 = 100;
+
+/// Id given to the next sandbox created in this process.
+static NEXT_SANDBOX_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Make the next sandbox created in this process get id `next`, with
+/// later sandboxes counting up from there.
+///
+/// Ids are otherwise assigned from 1 in creation order. Seeding makes them
+/// reproducible in tests, but seeding below an id already handed out lets
+/// live sandboxes share an id.
+pub fn seed_sandbox_ids(next: u64) {
+    NEXT_SANDBOX_ID.store(next, Ordering::Relaxed);
+}
 
 // ═══════════════════════════════════════════════════════════════════════════
 // SANDBOX STATE
@@ -37,7 +81,14 @@ pub const MAX_MODULE_SIZE: usize = 104_857_600; // 100 MB
 /// - Paused: Fuel exhausted, awaiting refuel
 /// - Terminated: Clean shutdown
 /// - Failed: Unrecoverable error occurred
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// `Terminated` and `Failed` are terminal. Every state but `Running` may be
+/// terminated: a running call has to return to `Ready`, `Paused` or
+/// `Failed` first. `Paused` can also be terminated rather than only
+/// refuelled back to `Ready`, so a stalled Spirit can be shut down. See
+/// `can_transition_to` for the full table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SandboxState {
     Initializing,
     Ready,
@@ -47,6 +98,46 @@ pub enum SandboxState {
     Failed,
 }
 
+impl SandboxState {
+    /// Returns true if the state machine allows moving from `self` to `next`.
+    ///
+    /// - Initializing → Ready | Failed | Terminated
+    /// - Ready → Running | Failed | Terminated
+    /// - Running → Ready | Paused | Failed
+    /// - Paused → Ready | Terminated
+    /// - Terminated, Failed → (nothing)
+    pub fn can_transition_to(&self, next: SandboxState) -> bool {
+        use SandboxState::*;
+
+        matches!(
+            (self, next),
+            (Initializing, Ready | Failed | Terminated)
+                | (Ready, Running | Failed | Terminated)
+                | (Running, Ready | Paused | Failed)
+                | (Paused, Ready | Terminated)
+        )
+    }
+
+    /// Returns true if no further transitions are possible.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, SandboxState::Terminated | SandboxState::Failed)
+    }
+}
+
+impl std::fmt::Display for SandboxState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SandboxState::Initializing => "initializing",
+            SandboxState::Ready => "ready",
+            SandboxState::Running => "running",
+            SandboxState::Paused => "paused",
+            SandboxState::Terminated => "terminated",
+            SandboxState::Failed => "failed",
+        };
+        f.write_str(name)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // ERRORS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -55,11 +146,13 @@ pub enum SandboxState {
 ///
 /// Each error type maps to a specific failure mode:
 /// - OutOfMemory: Exceeded memory_bytes limit
-/// - CpuQuotaExceeded: Exceeded cpu_quota limit
+/// - CpuQuotaExceeded: Exceeded cpu_quota or total_fuel_budget
 /// - CapabilityDenied: Attempted operation without permission
 /// - WasmTrap: WASM runtime trap (invalid memory access, etc.)
 /// - Timeout: Exceeded max_duration limit
 /// - InvalidModule: WASM module failed validation
+/// - StackOverflow: Exceeded max_stack_bytes (e.g. runaway recursion)
+/// - MissingImport: Module imports a function the linker does not provide
 #[derive(Debug, Clone)]
 pub enum SandboxError {
     OutOfMemory,
@@ -70,6 +163,9 @@ pub enum SandboxError {
     InvalidModule(String),
     RuntimeError(String),
     FunctionNotFound(String),
+    StackOverflow,
+    IncompatibleRuntime(String),
+    MissingImport(String),
 }
 
 impl std::fmt::Display for SandboxError {
@@ -83,6 +179,9 @@ impl std::fmt::Display for SandboxError {
             SandboxError::InvalidModule(msg) => write!(f, "Invalid module: {}", msg),
             SandboxError::RuntimeError(msg) => write!(f, "Runtime error: {}", msg),
             SandboxError::FunctionNotFound(msg) => write!(f, "Function not found: {}", msg),
+            SandboxError::StackOverflow => write!(f, "WASM stack overflow"),
+            SandboxError::IncompatibleRuntime(msg) => write!(f, "Incompatible runtime: {}", msg),
+            SandboxError::MissingImport(name) => write!(f, "Missing import: {}", name),
         }
     }
 }
@@ -99,8 +198,32 @@ impl std::error::Error for SandboxError {}
 /// - cpu_quota: Fraction of CPU time (0.0 to 1.0)
 /// - max_fuel: wasmtime fuel units before pause
 /// - max_duration: Wall-clock timeout
+/// - max_duration_ceiling: Longest timeout `invoke_with_timeout` may request
 /// - max_table_elements: WASM table size limit
 /// - max_instances: Number of module instances
+/// - max_stack_bytes: WASM call stack size (bounds recursion depth)
+/// - max_functions: Functions defined by the module
+/// - max_imports: Entries in the import section
+/// - max_memory_pages: Initial pages of any memory, defined or imported
+/// - max_globals: Globals defined by the module
+/// - min_log_level: `host_log` records below this level are dropped
+/// - max_random_bytes_per_call: Largest `host_random_bytes` request
+/// - max_host_calls: Host calls allowed per invoke; later calls return
+///   `error_codes::CALL_LIMIT`, or `i64::MIN` from `host_storage_increment`
+///   (`None` for no cap)
+/// - strict_capabilities: Fail the invoke with `CapabilityDenied` on the
+///   first denied host call instead of returning `error_codes::CAPABILITY_DENIED`
+/// - metering: How fuel and the timeout are enforced (see [`MeteringMode`])
+/// - total_fuel_budget: Fuel allowed across all invokes until the next
+///   refuel or budget reset (`None` for no cap)
+/// - scrub_on_terminate: Zero the Spirit's memory and host-held copies of
+///   its data on `terminate` (see [`Sandbox::terminate`])
+/// - deterministic: Canonicalize the NaNs float operations produce, so
+///   results are bit-identical on every architecture (needed for consensus)
+///
+/// max_functions, max_imports, max_memory_pages and max_globals are checked
+/// against the module's sections before compilation, so pathological
+/// modules are rejected cheaply.
 ///
 /// These limits implement the "capability-bounded substrate"
 /// principle from the VUDO architecture.
@@ -110,8 +233,22 @@ pub struct ResourceLimits {
     pub cpu_quota: f64,
     pub max_fuel: u64,
     pub max_duration: Duration,
+    pub max_duration_ceiling: Duration,
     pub max_table_elements: u32,
     pub max_instances: u32,
+    pub max_stack_bytes: usize,
+    pub max_functions: u32,
+    pub max_imports: u32,
+    pub max_memory_pages: u64,
+    pub max_globals: u32,
+    pub min_log_level: LogLevel,
+    pub max_random_bytes_per_call: u32,
+    pub max_host_calls: Option<u64>,
+    pub strict_capabilities: bool,
+    pub metering: MeteringMode,
+    pub total_fuel_budget: Option<u64>,
+    pub scrub_on_terminate: bool,
+    pub deterministic: bool,
 }
 
 impl Default for ResourceLimits {
@@ -121,8 +258,22 @@ impl Default for ResourceLimits {
             cpu_quota: DEFAULT_CPU_QUOTA,
             max_fuel: DEFAULT_MAX_FUEL,
             max_duration: Duration::from_secs(DEFAULT_MAX_DURATION_SECS),
+            max_duration_ceiling: Duration::from_secs(DEFAULT_MAX_DURATION_CEILING_SECS),
             max_table_elements: 1000,
             max_instances: 1,
+            max_stack_bytes: DEFAULT_MAX_STACK_BYTES,
+            max_functions: DEFAULT_MAX_FUNCTIONS,
+            max_imports: DEFAULT_MAX_IMPORTS,
+            max_memory_pages: DEFAULT_MAX_MEMORY_PAGES,
+            max_globals: DEFAULT_MAX_GLOBALS,
+            min_log_level: LogLevel::Trace,
+            max_random_bytes_per_call: DEFAULT_MAX_RANDOM_BYTES_PER_CALL,
+            max_host_calls: None,
+            strict_capabilities: false,
+            metering: MeteringMode::Fuel,
+            total_fuel_budget: None,
+            scrub_on_terminate: false,
+            deterministic: false,
         }
     }
 }
@@ -149,10 +300,389 @@ impl ResourceLimits {
             ));
         }
 
+        if self.max_duration > self.max_duration_ceiling {
+            return Err(SandboxError::InvalidModule(
+                "max_duration must not exceed max_duration_ceiling".to_string(),
+            ));
+        }
+
+        if self.max_random_bytes_per_call == 0 || self.max_random_bytes_per_call > MAX_RANDOM_BYTES
+        {
+            return Err(SandboxError::InvalidModule(format!(
+                "max_random_bytes_per_call must be between 1 and {}",
+                MAX_RANDOM_BYTES
+            )));
+        }
+
+        if self.total_fuel_budget.is_some() && !self.metering.uses_fuel() {
+            return Err(SandboxError::InvalidModule(
+                "total_fuel_budget requires MeteringMode::Fuel".to_string(),
+            ));
+        }
+
+        if self.total_fuel_budget == Some(0) {
+            return Err(SandboxError::InvalidModule(
+                "total_fuel_budget must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.max_stack_bytes == 0 || self.max_stack_bytes > MAX_STACK_BYTES {
+            return Err(SandboxError::InvalidModule(format!(
+                "max_stack_bytes must be between 1 and {}",
+                MAX_STACK_BYTES
+            )));
+        }
+
+        if self.max_instances == 0 {
+            return Err(SandboxError::InvalidModule(
+                "max_instances must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.max_table_elements > MAX_TABLE_ELEMENTS {
+            return Err(SandboxError::InvalidModule(format!(
+                "max_table_elements {} exceeds maximum {}",
+                self.max_table_elements, MAX_TABLE_ELEMENTS
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// How a sandbox bounds a Spirit's execution.
+///
+/// Turning fuel off saves the per-instruction metering cost for trusted
+/// system Spirits. Without fuel, `max_fuel` is ignored and every
+/// `ExecutionResult` reports `fuel_consumed = 0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MeteringMode {
+    /// Fuel metering plus the wall-clock timeout
+    #[default]
+    Fuel,
+    /// Only the wall-clock timeout, enforced by epoch interruption
+    EpochOnly,
+    /// No enforcement; a long-running call is only classified as timed
+    /// out after it returns
+    None,
+}
+
+impl MeteringMode {
+    /// Returns true if the engine meters fuel.
+    pub fn uses_fuel(&self) -> bool {
+        matches!(self, MeteringMode::Fuel)
+    }
+
+    /// Returns true if running calls are interrupted at their timeout.
+    pub fn uses_epochs(&self) -> bool {
+        !matches!(self, MeteringMode::None)
+    }
+}
+
+/// How a Spirit's WASM is turned into native code.
+///
+/// Every sandbox compiles its module once before it first runs, so the
+/// choice trades startup latency against execution speed:
+/// - `Optimized` runs Cranelift's full optimization pipeline. Its code is
+///   fastest, but compiling a large module can take longer than a
+///   short-lived Spirit spends running.
+/// - `Fast` runs Cranelift with optimizations off, compiling large modules
+///   roughly a third quicker at the cost of slower code. It suits Spirits
+///   that are instantiated often and run briefly. wasmtime's Winch
+///   baseline compiler would start faster still, but it cannot meter fuel
+///   or honor epoch deadlines, so it is not offered.
+/// - `Interpreter` runs the module without native compilation. The
+///   wasmtime version vudo builds against has no interpreter, so building
+///   a sandbox with this mode currently fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompilationMode {
+    /// Optimizing Cranelift: slower to compile, fastest to run
+    #[default]
+    Optimized,
+    /// Unoptimized Cranelift: faster to compile, slower to run
+    Fast,
+    /// No native compilation (not supported by this build)
+    Interpreter,
+}
+
+impl CompilationMode {
+    /// Point `config` at this mode's compiler.
+    fn configure(&self, config: &mut Config) -> Result<(), SandboxError> {
+        match self {
+            CompilationMode::Optimized => {
+                config.strategy(Strategy::Cranelift);
+            }
+            CompilationMode::Fast => {
+                config
+                    .strategy(Strategy::Cranelift)
+                    .cranelift_opt_level(OptLevel::None);
+            }
+            CompilationMode::Interpreter => {
+                return Err(SandboxError::RuntimeError(
+                    "Interpreter compilation mode is not supported by this wasmtime build"
+                        .to_string(),
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// MODULE VALIDATION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Structural counts read from a module's sections without compiling it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModuleStats {
+    /// Functions defined in the function section (imports excluded)
+    pub functions: u32,
+    /// Entries in the import section
+    pub imports: u32,
+    /// Largest initial page count of any defined or imported memory
+    pub memory_pages: u64,
+    /// Memories defined or imported; host functions assume at most one
+    pub memories: u32,
+    /// Globals defined in the global section (imports excluded)
+    pub globals: u32,
+}
+
+impl ModuleStats {
+    /// Scan the section headers of `wasm`.
+    ///
+    /// Only the import, function, memory and global sections are read;
+    /// function bodies are skipped.
+    pub fn parse(wasm: &[u8]) -> Result<Self, SandboxError> {
+        use wasmparser::{Parser, Payload, TypeRef};
+
+        let invalid = |e: wasmparser::BinaryReaderError| {
+            SandboxError::InvalidModule(format!("Failed to parse module: {}", e))
+        };
+
+        let mut stats = Self::default();
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload.map_err(invalid)? {
+                Payload::ImportSection(reader) => {
+                    stats.imports = reader.count();
+                    for import in reader {
+                        if let TypeRef::Memory(memory) = import.map_err(invalid)?.ty {
+                            stats.memory_pages = stats.memory_pages.max(memory.initial);
+                            stats.memories += 1;
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => stats.functions = reader.count(),
+                Payload::MemorySection(reader) => {
+                    for memory in reader {
+                        stats.memory_pages =
+                            stats.memory_pages.max(memory.map_err(invalid)?.initial);
+                        stats.memories += 1;
+                    }
+                }
+                Payload::GlobalSection(reader) => stats.globals = reader.count(),
+                Payload::CodeSectionStart { .. } | Payload::End(_) => break,
+                _ => {}
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Reject the module if any count exceeds its limit in `limits`, or if
+    /// it has more than one memory.
+    ///
+    /// Host functions read and write through the single exported memory,
+    /// so multi-memory modules are refused rather than half-supported.
+    pub fn check(&self, limits: &ResourceLimits) -> Result<(), SandboxError> {
+        if self.memories > 1 {
+            return Err(SandboxError::InvalidModule(
+                "multiple memories unsupported".to_string(),
+            ));
+        }
+
+        let checks = [
+            (
+                "functions",
+                "max_functions",
+                self.functions.into(),
+                limits.max_functions.into(),
+            ),
+            (
+                "imports",
+                "max_imports",
+                self.imports.into(),
+                limits.max_imports.into(),
+            ),
+            (
+                "memory pages",
+                "max_memory_pages",
+                self.memory_pages,
+                limits.max_memory_pages,
+            ),
+            (
+                "globals",
+                "max_globals",
+                self.globals.into(),
+                limits.max_globals.into(),
+            ),
+        ];
+
+        for (what, limit_name, actual, limit) in checks {
+            if actual > limit {
+                return Err(SandboxError::InvalidModule(format!(
+                    "Module has {} {}, exceeding {} ({})",
+                    actual, what, limit_name, limit
+                )));
+            }
+        }
+
         Ok(())
     }
 }
 
+/// Capabilities the module's `vudo` imports will be checked against, in
+/// import order without duplicates.
+///
+/// Imports that need no fixed capability are skipped; see
+/// [`crate::linker::import_capability`].
+pub fn required_capabilities(
+    wasm: &[u8],
+) -> Result<Vec<crate::capability::CapabilityType>, SandboxError> {
+    required_capabilities_in(wasm, HOST_NAMESPACE)
+}
+
+/// Like [`required_capabilities`], for host functions registered under
+/// `namespace`; see [`create_linker_in`].
+pub fn required_capabilities_in(
+    wasm: &[u8],
+    namespace: &str,
+) -> Result<Vec<crate::capability::CapabilityType>, SandboxError> {
+    use wasmparser::{Parser, Payload};
+
+    let invalid = |e: wasmparser::BinaryReaderError| {
+        SandboxError::InvalidModule(format!("Failed to parse module: {}", e))
+    };
+
+    let mut required = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.map_err(invalid)? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(invalid)?;
+                    if import.module != namespace {
+                        continue;
+                    }
+                    if let Some(capability) = crate::linker::import_capability(import.name) {
+                        if !required.contains(&capability) {
+                            required.push(capability);
+                        }
+                    }
+                }
+            }
+            Payload::CodeSectionStart { .. } | Payload::End(_) => break,
+            _ => {}
+        }
+    }
+
+    Ok(required)
+}
+
+/// Reject a module whose `start` function can reach a host import.
+///
+/// The start function runs inside instantiation, before an invocation has
+/// begun and outside the window in which host calls are framed and
+/// metered, so a Spirit must do its I/O from an exported entry point.
+/// Every import the sandbox links is a host function, so the check follows
+/// direct calls from the start function through the module and fails on
+/// the first imported function it reaches. Indirect calls cannot be
+/// followed and are assumed to reach any imported function. Start
+/// functions that only compute (initialising memory or globals) are
+/// accepted.
+pub fn check_start_function(wasm: &[u8]) -> Result<(), SandboxError> {
+    use wasmparser::{Operator, Parser, Payload, TypeRef};
+
+    let invalid = |e: wasmparser::BinaryReaderError| {
+        SandboxError::InvalidModule(format!("Failed to parse module: {}", e))
+    };
+
+    let mut imports = Vec::new();
+    let mut start = None;
+    // Callees of each defined function; `None` marks an indirect call
+    let mut calls: Vec<Vec<Option<u32>>> = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.map_err(invalid)? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(invalid)?;
+                    if let TypeRef::Func(_) = import.ty {
+                        imports.push(format!("{}.{}", import.module, import.name));
+                    }
+                }
+            }
+            Payload::StartSection { func, .. } => start = Some(func),
+            Payload::CodeSectionEntry(body) if start.is_some() => {
+                let mut callees = Vec::new();
+                let mut reader = body.get_operators_reader().map_err(invalid)?;
+                while !reader.eof() {
+                    match reader.read().map_err(invalid)? {
+                        Operator::Call { function_index }
+                        | Operator::ReturnCall { function_index } => {
+                            callees.push(Some(function_index))
+                        }
+                        Operator::CallIndirect { .. }
+                        | Operator::ReturnCallIndirect { .. }
+                        | Operator::CallRef { .. }
+                        | Operator::ReturnCallRef { .. } => callees.push(None),
+                        _ => {}
+                    }
+                }
+                calls.push(callees);
+            }
+            Payload::End(_) => break,
+            _ => {}
+        }
+    }
+
+    let Some(start) = start else {
+        return Ok(());
+    };
+
+    let reject = |call: String| {
+        Err(SandboxError::InvalidModule(format!(
+            "start function {}; host calls must be made from an exported function",
+            call
+        )))
+    };
+
+    let imported = imports.len() as u32;
+    let mut visited = std::collections::HashSet::new();
+    let mut pending = vec![start];
+    while let Some(func) = pending.pop() {
+        if !visited.insert(func) {
+            continue;
+        }
+        if func < imported {
+            return reject(format!("calls host import {}", imports[func as usize]));
+        }
+        let Some(callees) = calls.get((func - imported) as usize) else {
+            continue;
+        };
+        for callee in callees {
+            match callee {
+                Some(callee) => pending.push(*callee),
+                None if !imports.is_empty() => {
+                    return reject(
+                        "makes an indirect call in a module with host imports".to_string(),
+                    )
+                }
+                None => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // CAPABILITY TYPES
 // ═══════════════════════════════════════════════════════════════════════════
@@ -239,19 +769,166 @@ impl CapabilityGrant {
     }
 }
 
+impl From<CapabilityType> for crate::capability::CapabilityType {
+    fn from(capability: CapabilityType) -> Self {
+        use crate::capability::CapabilityType as Host;
+        match capability {
+            CapabilityType::NetworkListen => Host::NetworkListen,
+            CapabilityType::NetworkConnect => Host::NetworkConnect,
+            CapabilityType::NetworkBroadcast => Host::NetworkBroadcast,
+            CapabilityType::StorageRead => Host::StorageRead,
+            CapabilityType::StorageWrite => Host::StorageWrite,
+            CapabilityType::StorageDelete => Host::StorageDelete,
+            CapabilityType::SpawnSandbox => Host::SpawnSandbox,
+            CapabilityType::CrossSandboxCall => Host::CrossSandboxCall,
+            CapabilityType::SensorTime => Host::SensorTime,
+            CapabilityType::SensorRandom => Host::SensorRandom,
+            CapabilityType::SensorEnvironment => Host::SensorEnvironment,
+            CapabilityType::ActuatorLog => Host::ActuatorLog,
+            CapabilityType::ActuatorNotify => Host::ActuatorNotify,
+            CapabilityType::ActuatorCredit => Host::ActuatorCredit,
+            CapabilityType::Unrestricted => Host::Unrestricted,
+        }
+    }
+}
+
+impl From<CapabilityScope> for crate::capability::CapabilityScope {
+    fn from(scope: CapabilityScope) -> Self {
+        use crate::capability::CapabilityScope as Host;
+        match scope {
+            CapabilityScope::Global => Host::Global,
+            CapabilityScope::Sandboxed => Host::Sandboxed,
+            CapabilityScope::Peer => Host::Peer,
+            CapabilityScope::Domain => Host::Domain,
+        }
+    }
+}
+
+impl From<CapabilityGrant> for crate::capability::CapabilityGrant {
+    fn from(grant: CapabilityGrant) -> Self {
+        let mut converted = crate::capability::CapabilityGrant::new(
+            grant.id,
+            grant.capability.into(),
+            grant.scope.into(),
+            grant.granter,
+            grant.grantee,
+            grant.granted_at,
+            grant.expires_at,
+            grant.signature,
+        );
+        converted.revoked = grant.revoked;
+        converted
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// HEALTH
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Outcome of a liveness probe; see [`Sandbox::health`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The probe returned 0
+    Healthy,
+    /// The probe returned this nonzero code
+    Unhealthy(i32),
+    /// The Spirit does not export a probe
+    NoProbe,
+    /// The probe could not run to completion: it trapped, exhausted its
+    /// budget, or the sandbox could not run it
+    Trapped(String),
+}
+
+impl HealthStatus {
+    /// Returns true only for `Healthy`.
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, HealthStatus::Healthy)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // EXECUTION RESULT
 // ═══════════════════════════════════════════════════════════════════════════
 
+/// Classification of a WASM trap.
+///
+/// Mirrors the wasmtime trap codes a Spirit can realistically hit.
+/// `Host` covers errors raised by host functions; anything else
+/// lands in `Other` with wasmtime's description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrapKind {
+    Unreachable,
+    MemoryOutOfBounds,
+    TableOutOfBounds,
+    IndirectCallToNull,
+    BadSignature,
+    IntegerOverflow,
+    IntegerDivisionByZero,
+    BadConversionToInteger,
+    StackOverflow,
+    Host(String),
+    Other(String),
+}
+
+impl TrapKind {
+    fn from_error(error: &Error) -> Self {
+        if let Some(exit) = error.downcast_ref::<ProcExit>() {
+            return TrapKind::Host(exit.to_string());
+        }
+        match error.downcast_ref::<Trap>() {
+            Some(Trap::UnreachableCodeReached) => TrapKind::Unreachable,
+            Some(Trap::MemoryOutOfBounds) => TrapKind::MemoryOutOfBounds,
+            Some(Trap::TableOutOfBounds) => TrapKind::TableOutOfBounds,
+            Some(Trap::IndirectCallToNull) => TrapKind::IndirectCallToNull,
+            Some(Trap::BadSignature) => TrapKind::BadSignature,
+            Some(Trap::IntegerOverflow) => TrapKind::IntegerOverflow,
+            Some(Trap::IntegerDivisionByZero) => TrapKind::IntegerDivisionByZero,
+            Some(Trap::BadConversionToInteger) => TrapKind::BadConversionToInteger,
+            Some(Trap::StackOverflow) => TrapKind::StackOverflow,
+            Some(other) => TrapKind::Other(other.to_string()),
+            None => TrapKind::Host(error.to_string()),
+        }
+    }
+}
+
+/// How an invocation ended.
+///
+/// `ExecutionResult::error` carries the human-readable message; this is
+/// the value callers should match on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionOutcome {
+    /// The function returned normally.
+    Completed,
+    /// Fuel ran out; the sandbox is paused and can be refueled.
+    OutOfFuel,
+    /// Execution failed after exceeding `max_duration`.
+    Timeout,
+    /// The module trapped.
+    Trap(TrapKind),
+    /// Linear memory tried to grow past `memory_bytes`.
+    MemoryLimit,
+}
+
+impl ExecutionOutcome {
+    /// Returns true if the invocation completed normally.
+    pub fn is_completed(&self) -> bool {
+        matches!(self, ExecutionOutcome::Completed)
+    }
+}
+
 /// Result of a sandbox execution
 #[derive(Debug, Clone)]
 pub struct ExecutionResult {
     pub success: bool,
+    pub outcome: ExecutionOutcome,
     pub return_value: Option<Vec<Val>>,
     pub fuel_consumed: u64,
     pub duration: Duration,
     pub memory_used: u64,
     pub error: Option<String>,
+    /// Bytes the Spirit published through `host_set_output` before a call
+    /// that did not complete. `None` for completed calls.
+    pub partial_output: Option<Vec<u8>>,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -269,6 +946,7 @@ pub struct SandboxMetrics {
     pub total_duration: Duration,
     pub peak_memory: u64,
     pub trap_count: u64,
+    pub total_host_calls: u64,
     pub last_updated: u64, // Unix timestamp
 }
 
@@ -281,6 +959,7 @@ impl SandboxMetrics {
             total_duration: Duration::from_secs(0),
             peak_memory: 0,
             trap_count: 0,
+            total_host_calls: 0,
             last_updated: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
@@ -290,7 +969,9 @@ impl SandboxMetrics {
 
     fn update(&mut self, result: &ExecutionResult) {
         self.execution_count += 1;
-        self.total_fuel_consumed += result.fuel_consumed;
+        self.total_fuel_consumed = self
+            .total_fuel_consumed
+            .saturating_add(result.fuel_consumed);
         self.total_duration += result.duration;
         self.peak_memory = self.peak_memory.max(result.memory_used);
         if !result.success {
@@ -351,6 +1032,12 @@ pub struct Sandbox {
     linker: Linker<HostState>,
     instance: Option<Instance>,
 
+    // Fuel counted against `limits.total_fuel_budget`
+    budget_fuel_used: u64,
+
+    // Calls go through `invoke_async` and `host_yield` can yield
+    async_support: bool,
+
     // Metrics tracking
     metrics: SandboxMetrics,
 }
@@ -383,7 +1070,70 @@ impl Sandbox {
         network: Arc<dyn NetworkBackend>,
         capability_set: CapabilitySet,
     ) -> Result<Self, SandboxError> {
-        // Validate module size
+        SandboxBuilder::new(wasm, owner)
+            .limits(limits)
+            .storage(storage)
+            .credit(credit)
+            .network(network)
+            .capabilities(capability_set)
+            .build()
+    }
+
+    /// Start configuring a sandbox for `wasm` owned by `owner`.
+    ///
+    /// Unset backends default to the in-memory implementations, limits to
+    /// `ResourceLimits::default()`, and capabilities to none.
+    pub fn builder(wasm: &[u8], owner: [u8; 32]) -> SandboxBuilder<'_> {
+        SandboxBuilder::new(wasm, owner)
+    }
+
+    fn from_builder(builder: SandboxBuilder<'_>) -> Result<Self, SandboxError> {
+        let SandboxBuilder {
+            wasm,
+            owner,
+            limits,
+            storage,
+            credit,
+            network,
+            capabilities,
+            engine_config,
+            compilation,
+            async_support,
+            host_namespace,
+        } = builder;
+
+        Self::validate_module_size(wasm)?;
+
+        // Validate resource limits
+        limits.validate()?;
+
+        let engine = Self::create_engine(
+            limits.max_stack_bytes,
+            limits.metering,
+            limits.deterministic,
+            compilation,
+            async_support,
+            engine_config.as_ref(),
+        )?;
+
+        // Create linker with host function bindings
+        let linker = create_linker_in(&engine, &host_namespace);
+
+        let host_state =
+            Self::create_host_state(owner, &limits, storage, credit, network, capabilities);
+
+        Self::assemble(
+            engine,
+            linker,
+            wasm,
+            owner,
+            limits,
+            host_state,
+            async_support,
+        )
+    }
+
+    fn validate_module_size(wasm: &[u8]) -> Result<(), SandboxError> {
         if wasm.is_empty() {
             return Err(SandboxError::InvalidModule(
                 "WASM module is empty".to_string(),
@@ -398,26 +1148,60 @@ impl Sandbox {
             )));
         }
 
-        // Validate resource limits
-        limits.validate()?;
+        Ok(())
+    }
 
-        // Configure Wasmtime engine with resource limits
+    fn create_engine(
+        max_stack_bytes: usize,
+        metering: MeteringMode,
+        deterministic: bool,
+        compilation: CompilationMode,
+        async_support: bool,
+        engine_config: Option<&EngineConfigHook>,
+    ) -> Result<Engine, SandboxError> {
         let mut config = Config::new();
-        config.consume_fuel(true);
+        compilation.configure(&mut config)?;
+        if let Some(hook) = engine_config {
+            hook(&mut config);
+        }
 
-        // Set memory limits
-        config.max_wasm_stack(2 * 1024 * 1024); // 2 MB stack
+        // Safety-critical settings are applied after the hook so that it
+        // cannot turn them off
+        config.consume_fuel(metering.uses_fuel());
 
-        // Create engine
-        let engine = Engine::new(&config)
-            .map_err(|e| SandboxError::RuntimeError(format!("Failed to create engine: {}", e)))?;
+        // Lets the store's deadline callback enforce wall-clock timeouts
+        config.epoch_interruption(metering.uses_epochs());
 
-        // Create linker with host function bindings
-        let linker = create_linker(&engine);
+        // Bound the WASM call stack (and therefore recursion depth)
+        config.max_wasm_stack(max_stack_bytes);
+
+        // Every NaN becomes the canonical quiet NaN (0x7fc00000 for f32,
+        // 0x7ff8000000000000 for f64) instead of whatever bits the CPU
+        // produced, which differ between x86 and ARM
+        config.cranelift_nan_canonicalization(deterministic);
+
+        // Async calls run on a separate fiber stack, which must leave room
+        // for the host functions the Spirit calls on top of its WASM stack
+        config.async_support(async_support);
+        if async_support {
+            config.async_stack_size(max_stack_bytes + ASYNC_HOST_STACK_BYTES);
+        }
+
+        Engine::new(&config)
+            .map_err(|e| SandboxError::RuntimeError(format!("Failed to create engine: {}", e)))
+    }
 
+    fn create_host_state(
+        owner: [u8; 32],
+        limits: &ResourceLimits,
+        storage: Arc<dyn StorageBackend>,
+        credit: Arc<dyn CreditBackend>,
+        network: Arc<dyn NetworkBackend>,
+        capability_set: CapabilitySet,
+    ) -> HostState {
         // Create HostState with all backends and capabilities
         // The owner's public key is used as the account for credit operations
-        let host_state = HostState::new(
+        let mut host_state = HostState::new(
             storage,
             credit,
             network,
@@ -426,13 +1210,57 @@ impl Sandbox {
             owner,
         );
 
+        // Enforce memory_bytes through the store's resource limiter
+        host_state.set_memory_limit(limits.memory_bytes as usize);
+        if limits.metering.uses_fuel() {
+            host_state.set_max_fuel(limits.max_fuel);
+        }
+        host_state.set_table_elements_limit(limits.max_table_elements as usize);
+        host_state.set_min_log_level(limits.min_log_level);
+        host_state.set_max_random_bytes_per_call(limits.max_random_bytes_per_call);
+        host_state.set_max_host_calls(limits.max_host_calls);
+        host_state.set_strict_capabilities(limits.strict_capabilities);
+
+        host_state
+    }
+
+    /// Build an uninitialized sandbox around an existing engine and linker.
+    fn assemble(
+        engine: Engine,
+        linker: Linker<HostState>,
+        wasm: &[u8],
+        owner: [u8; 32],
+        limits: ResourceLimits,
+        mut host_state: HostState,
+        async_support: bool,
+    ) -> Result<Self, SandboxError> {
+        host_state.set_yield_enabled(async_support && limits.metering.uses_epochs());
+
         // Create store with HostState
         let mut store = Store::new(&engine, host_state);
+        store.limiter(|state| state);
+
+        // Each epoch tick during a call checks the call's timeout, and
+        // honors a pending host_yield
+        if limits.metering.uses_epochs() {
+            store.set_epoch_deadline(1);
+            store.epoch_deadline_callback(|mut ctx| {
+                if ctx.data().is_timed_out() {
+                    Err(Trap::Interrupt.into())
+                } else if ctx.data_mut().take_yield_request() {
+                    Ok(UpdateDeadline::Yield(1))
+                } else {
+                    Ok(UpdateDeadline::Continue(1))
+                }
+            });
+        }
 
         // Set initial fuel
-        store
-            .set_fuel(limits.max_fuel)
-            .map_err(|e| SandboxError::RuntimeError(format!("Failed to set fuel: {}", e)))?;
+        if limits.metering.uses_fuel() {
+            store
+                .set_fuel(limits.max_fuel)
+                .map_err(|e| SandboxError::RuntimeError(format!("Failed to set fuel: {}", e)))?;
+        }
 
         let sandbox_id = Self::generate_id();
         let now = SystemTime::now()
@@ -456,10 +1284,68 @@ impl Sandbox {
             store,
             linker,
             instance: None,
+            budget_fuel_used: 0,
+            async_support,
             metrics: SandboxMetrics::new(sandbox_id),
         })
     }
 
+    /// Fork this sandbox for `new_owner`.
+    ///
+    /// The fork shares the engine, linker and (if already compiled) module,
+    /// so it starts Ready without recompiling; an uncompiled sandbox forks
+    /// to Initializing. It gets a new id, a fresh store with zeroed metrics
+    /// and fuel, and a `HostState` holding the same backend `Arc`s and
+    /// limits. Capabilities are copied with every grant's `grantee`
+    /// rewritten to `new_owner`, so their signatures no longer verify until
+    /// the granter re-signs them.
+    ///
+    /// Per-sandbox settings such as hooks, host methods, the router, the
+    /// storage namespace and tracing are not carried over.
+    pub fn fork(&self, new_owner: [u8; 32]) -> Result<Sandbox, SandboxError> {
+        let state = self.store.data();
+
+        let mut capability_set = state.capabilities.clone();
+        for grant in capability_set.grants.values_mut().flatten() {
+            grant.grantee = new_owner;
+        }
+
+        let host_state = Self::create_host_state(
+            new_owner,
+            &self.limits,
+            state.storage.clone(),
+            state.credit.clone(),
+            state.network.clone(),
+            capability_set,
+        );
+
+        let mut fork = Self::assemble(
+            self.engine.clone(),
+            self.linker.clone(),
+            &self.wasm_module,
+            new_owner,
+            self.limits.clone(),
+            host_state,
+            self.async_support,
+        )?;
+        fork.capabilities = self
+            .capabilities
+            .iter()
+            .cloned()
+            .map(|mut grant| {
+                grant.grantee = new_owner;
+                grant
+            })
+            .collect();
+
+        if let Some(module) = &self.module {
+            fork.module = Some(module.clone());
+            fork.transition_to(SandboxState::Ready)?;
+        }
+
+        Ok(fork)
+    }
+
     /// Creates a new sandbox with default in-memory backends.
     ///
     /// This is a convenience method that creates a sandbox with:
@@ -475,27 +1361,22 @@ impl Sandbox {
         owner: [u8; 32],
         limits: ResourceLimits,
     ) -> Result<Self, SandboxError> {
-        use crate::host::{InMemoryCreditLedger, InMemoryStorage, MockNetworkBackend};
-
-        let storage = Arc::new(InMemoryStorage::new());
-        let credit = Arc::new(InMemoryCreditLedger::new());
-        let network = Arc::new(MockNetworkBackend::new());
-        let capability_set = CapabilitySet::new();
-
-        Self::new(
-            wasm,
-            owner,
-            limits,
-            storage,
-            credit,
-            network,
-            capability_set,
-        )
-    }
+        SandboxBuilder::new(wasm, owner).limits(limits).build()
+    }
 
     /// Initialize the sandbox by compiling the WASM module.
     ///
     /// This transitions from Initializing -> Ready or Failed.
+    ///
+    /// A Spirit may export `__vudo_abi_version() -> i32` naming the host
+    /// ABI it was built against. If it does, the module is instantiated
+    /// here and the export is called on `ABI_VERSION_FUEL`; a version other
+    /// than `VUDO_ABI_VERSION` fails with `SandboxError::IncompatibleRuntime`.
+    /// Modules without the export are assumed compatible. Async sandboxes
+    /// cannot run WASM outside an executor and skip the handshake.
+    ///
+    /// Modules whose `start` function can reach a host import are rejected
+    /// with `SandboxError::InvalidModule`; see [`check_start_function`].
     pub fn initialize(&mut self) -> Result<(), SandboxError> {
         if self.state != SandboxState::Initializing {
             return Err(SandboxError::RuntimeError(
@@ -503,84 +1384,391 @@ impl Sandbox {
             ));
         }
 
+        // Reject pathological modules before spending time compiling them,
+        // and modules whose start function would make host calls during
+        // instantiation
+        if let Err(e) = ModuleStats::parse(&self.wasm_module)
+            .and_then(|stats| stats.check(&self.limits))
+            .and_then(|()| check_start_function(&self.wasm_module))
+        {
+            self.transition_to(SandboxState::Failed)?;
+            return Err(e);
+        }
+
         // Compile the WASM module
-        let module = Module::new(&self.engine, &self.wasm_module).map_err(|e| {
-            self.state = SandboxState::Failed;
-            SandboxError::InvalidModule(format!("Failed to compile module: {}", e))
-        })?;
+        let module = match Module::new(&self.engine, &self.wasm_module) {
+            Ok(module) => module,
+            Err(e) => {
+                self.transition_to(SandboxState::Failed)?;
+                return Err(SandboxError::InvalidModule(format!(
+                    "Failed to compile module: {}",
+                    e
+                )));
+            }
+        };
+
+        self.adopt_module(module)
+    }
+
+    /// Finish initializing with a compiled `module`: run the ABI handshake,
+    /// then move to Ready, or to Failed if the handshake fails.
+    ///
+    /// Shared by [`Sandbox::initialize`] and [`SpiritImage::instantiate`].
+    fn adopt_module(&mut self, module: Module) -> Result<(), SandboxError> {
+        if let Err(e) = self.negotiate_abi_version(&module) {
+            self.instance = None;
+            if self.state != SandboxState::Failed {
+                self.transition_to(SandboxState::Failed)?;
+            }
+            return Err(e);
+        }
 
         self.module = Some(module);
-        self.state = SandboxState::Ready;
+        self.transition_to(SandboxState::Ready)
+    }
+
+    /// Compare the Spirit's declared ABI version, if any, with ours.
+    fn negotiate_abi_version(&mut self, module: &Module) -> Result<(), SandboxError> {
+        if self.async_support || module.get_export(ABI_VERSION_EXPORT).is_none() {
+            return Ok(());
+        }
+
+        let fuel_before = self.store.get_fuel().unwrap_or(0);
+        let instance = self.linker.instantiate(&mut self.store, module);
+        self.set_instance(instance, fuel_before)?;
+        let version = self
+            .resolve_func(ABI_VERSION_EXPORT)?
+            .typed::<(), i32>(&self.store)
+            .map_err(|e| {
+                SandboxError::InvalidModule(format!(
+                    "{} must take no parameters and return i32: {}",
+                    ABI_VERSION_EXPORT, e
+                ))
+            })?;
+
+        // Run on a small budget of its own, leaving the Spirit's fuel as it was
+        let uses_fuel = self.limits.metering.uses_fuel();
+        let own_fuel = self.store.get_fuel().unwrap_or(0);
+        if uses_fuel {
+            self.store
+                .set_fuel(ABI_VERSION_FUEL)
+                .map_err(|e| SandboxError::RuntimeError(format!("Failed to set fuel: {}", e)))?;
+        }
+        self.store.data_mut().start_execution();
+        if self.limits.metering.uses_epochs() {
+            self.store.set_epoch_deadline(1);
+        }
+        let ticker = self
+            .limits
+            .metering
+            .uses_epochs()
+            .then(|| EpochTicker::start(&self.engine));
+        let result = version.call(&mut self.store, ());
+        drop(ticker);
+        if uses_fuel {
+            self.store.set_fuel(own_fuel).map_err(|e| {
+                SandboxError::RuntimeError(format!("Failed to restore fuel: {}", e))
+            })?;
+        }
+
+        match result {
+            Ok(VUDO_ABI_VERSION) => Ok(()),
+            Ok(requested) => Err(SandboxError::IncompatibleRuntime(format!(
+                "Spirit expects host ABI version {}, runtime supports {}",
+                requested, VUDO_ABI_VERSION
+            ))),
+            Err(e) => Err(SandboxError::RuntimeError(format!(
+                "{} failed: {}",
+                ABI_VERSION_EXPORT, e
+            ))),
+        }
+    }
 
+    /// Move to `next`, rejecting transitions the state machine forbids.
+    fn transition_to(&mut self, next: SandboxState) -> Result<(), SandboxError> {
+        if !self.state.can_transition_to(next) {
+            return Err(SandboxError::RuntimeError(format!(
+                "Invalid state transition: {} -> {}",
+                self.state, next
+            )));
+        }
+        self.state = next;
         Ok(())
     }
 
     /// Invoke a function in the WASM module.
     ///
     /// This executes the function with the given arguments and returns the result.
-    /// The sandbox must be in Ready or Paused state.
+    /// The sandbox must be Ready; a Paused sandbox must be refueled first.
     ///
     /// During execution:
     /// - Fuel consumption is tracked
     /// - Execution time is measured
     /// - Memory usage is monitored
     /// - Timeouts are enforced
+    ///
+    /// Traps are reported through the returned `ExecutionResult`, with two
+    /// exceptions. Stack exhaustion returns `SandboxError::StackOverflow`,
+    /// and `host_abort` returns `SandboxError::WasmTrap` carrying the
+    /// Spirit's message.
+    ///
+    /// A `proc_exit(0)` completes the call with no return values. Any other
+    /// exit code is reported as a host trap.
+    ///
+    /// With `ResourceLimits::total_fuel_budget` set, invokes are refused with
+    /// `SandboxError::CpuQuotaExceeded` once the fuel consumed since the last
+    /// refuel or budget reset reaches the budget. The invoke that crosses
+    /// the budget still runs to completion.
+    ///
+    /// A sandbox built with [`SandboxBuilder::async_support`] must use
+    /// [`Sandbox::invoke_async`] instead.
     pub fn invoke(
         &mut self,
         function: &str,
         args: &[Val],
     ) -> Result<ExecutionResult, SandboxError> {
-        // Check state
-        if self.state != SandboxState::Ready && self.state != SandboxState::Paused {
+        if self.async_support {
+            return Err(SandboxError::RuntimeError(
+                "Sandbox was built with async support; use invoke_async".to_string(),
+            ));
+        }
+        self.check_invocable()?;
+        self.ensure_instance()?;
+
+        let func = self.resolve_func(function)?;
+        self.call_func(func, args)
+    }
+
+    /// Invoke a sequence of functions over the same instance.
+    ///
+    /// Every function is resolved before any runs, so a missing export
+    /// fails the whole batch with `SandboxError::FunctionNotFound`. Calls
+    /// then execute in order, sharing globals, memory and fuel, and the
+    /// batch stops at the first call that does not complete: its result
+    /// (carrying the trap or out-of-fuel outcome) is the last one returned.
+    ///
+    /// The fuel budget is checked once for the whole batch. Failures that
+    /// [`Sandbox::invoke`] returns as errors, such as stack overflow, end
+    /// the batch with that error.
+    pub fn invoke_batch(
+        &mut self,
+        calls: &[(String, Vec<Val>)],
+    ) -> Result<Vec<ExecutionResult>, SandboxError> {
+        if self.async_support {
+            return Err(SandboxError::RuntimeError(
+                "Sandbox was built with async support; use invoke_async".to_string(),
+            ));
+        }
+        self.check_invocable()?;
+        self.ensure_instance()?;
+
+        let funcs = calls
+            .iter()
+            .map(|(function, _)| self.resolve_func(function))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut results = Vec::with_capacity(calls.len());
+        for (func, (_, args)) in funcs.into_iter().zip(calls) {
+            let result = self.call_func(func, args)?;
+            let completed = result.success;
+            results.push(result);
+            if !completed {
+                break;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Invoke a function in the WASM module on an async executor.
+    ///
+    /// Behaves like [`Sandbox::invoke`], but the sandbox must have been
+    /// built with [`SandboxBuilder::async_support`]. While the Spirit runs,
+    /// `host_yield` hands control back to the executor so other tasks,
+    /// such as other sandboxes, can make progress.
+    pub async fn invoke_async(
+        &mut self,
+        function: &str,
+        args: &[Val],
+    ) -> Result<ExecutionResult, SandboxError> {
+        if !self.async_support {
+            return Err(SandboxError::RuntimeError(
+                "Sandbox was not built with async support; use invoke".to_string(),
+            ));
+        }
+        self.check_invocable()?;
+
+        if self.instance.is_none() {
+            let module = self.compiled_module()?;
+            let fuel_before = self.store.get_fuel().unwrap_or(0);
+            let instance = self
+                .linker
+                .instantiate_async(&mut self.store, &module)
+                .await;
+            self.set_instance(instance, fuel_before)?;
+        }
+
+        let func = self.resolve_func(function)?;
+        let call = self.begin_call()?;
+        let mut results = vec![Val::I32(0); func.ty(&self.store).results().len()];
+        let ticker = call.start_ticker(&self.engine);
+        let execution_result = func.call_async(&mut self.store, args, &mut results).await;
+        drop(ticker);
+
+        self.finish_call(call, execution_result, results)
+    }
+
+    /// Refuse a call the sandbox's state or fuel budget does not allow.
+    fn check_invocable(&self) -> Result<(), SandboxError> {
+        if !self.state.can_transition_to(SandboxState::Running) {
             return Err(SandboxError::RuntimeError(format!(
-                "Cannot invoke from state {:?}",
+                "Cannot invoke from state {}",
                 self.state
             )));
         }
 
-        // Get or create instance using the linker
+        if self.fuel_budget_remaining() == Some(0) {
+            return Err(SandboxError::CpuQuotaExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Instantiate the module if no instance is live. Linking resolves the
+    /// host function imports.
+    fn ensure_instance(&mut self) -> Result<(), SandboxError> {
         if self.instance.is_none() {
-            let module = self
-                .module
-                .as_ref()
-                .ok_or_else(|| SandboxError::RuntimeError("Module not initialized".to_string()))?;
+            let module = self.compiled_module()?;
+            let fuel_before = self.store.get_fuel().unwrap_or(0);
+            let instance = self.linker.instantiate(&mut self.store, &module);
+            self.set_instance(instance, fuel_before)?;
+        }
+        Ok(())
+    }
 
-            // Use linker to instantiate the module - this resolves host function imports
-            let instance = self
-                .linker
-                .instantiate(&mut self.store, module)
-                .map_err(|e| {
-                    self.state = SandboxState::Failed;
-                    SandboxError::RuntimeError(format!("Failed to instantiate module: {}", e))
-                })?;
+    fn compiled_module(&self) -> Result<Module, SandboxError> {
+        self.module
+            .clone()
+            .ok_or_else(|| SandboxError::RuntimeError("Module not initialized".to_string()))
+    }
+
+    /// Keep a fresh instance, failing the sandbox if instantiation failed.
+    ///
+    /// Fuel the module's start function burned since `fuel_before` is
+    /// counted like a call's, whether or not instantiation succeeded. An
+    /// import the linker does not define fails with
+    /// `SandboxError::MissingImport` naming it.
+    fn set_instance(
+        &mut self,
+        instance: wasmtime::Result<Instance>,
+        fuel_before: u64,
+    ) -> Result<(), SandboxError> {
+        let fuel_after = self.store.get_fuel().unwrap_or(0);
+        self.record_fuel(fuel_before.saturating_sub(fuel_after));
 
-            self.instance = Some(instance);
+        match instance {
+            Ok(instance) => {
+                self.instance = Some(instance);
+                Ok(())
+            }
+            Err(e) => {
+                self.transition_to(SandboxState::Failed)?;
+                if let Some(missing) = e.downcast_ref::<UnknownImportError>() {
+                    return Err(SandboxError::MissingImport(missing.name().to_string()));
+                }
+                Err(SandboxError::RuntimeError(format!(
+                    "Failed to instantiate module: {}",
+                    e
+                )))
+            }
         }
+    }
 
+    /// Look up the exported `function` on the live instance.
+    fn resolve_func(&mut self, function: &str) -> Result<Func, SandboxError> {
         let instance = self.instance.as_ref().unwrap();
-
-        // Get the function
-        let func = instance
+        instance
             .get_func(&mut self.store, function)
-            .ok_or_else(|| SandboxError::FunctionNotFound(function.to_string()))?;
+            .ok_or_else(|| SandboxError::FunctionNotFound(function.to_string()))
+    }
+
+    /// Run `func` to completion on the calling thread.
+    fn call_func(&mut self, func: Func, args: &[Val]) -> Result<ExecutionResult, SandboxError> {
+        let call = self.begin_call()?;
+        let mut results = vec![Val::I32(0); func.ty(&self.store).results().len()];
+        let ticker = call.start_ticker(&self.engine);
+        let execution_result = func.call(&mut self.store, args, &mut results);
+        drop(ticker);
 
+        self.finish_call(call, execution_result, results)
+    }
+
+    /// Count fuel burned outside any call, such as by a start function,
+    /// towards the lifetime total, the metrics and the fuel budget.
+    fn record_fuel(&mut self, fuel: u64) {
+        self.fuel_consumed = self.fuel_consumed.saturating_add(fuel);
+        self.budget_fuel_used = self.budget_fuel_used.saturating_add(fuel);
+        self.metrics.total_fuel_consumed = self.metrics.total_fuel_consumed.saturating_add(fuel);
+    }
+
+    /// Move to Running with a fresh deadline.
+    fn begin_call(&mut self) -> Result<CallStart, SandboxError> {
         // Set up execution context
-        self.state = SandboxState::Running;
+        self.transition_to(SandboxState::Running)?;
         self.store.data_mut().start_execution();
+        let metering = self.limits.metering;
+        if metering.uses_epochs() {
+            self.store.set_epoch_deadline(1);
+        }
 
-        let fuel_before = self.store.get_fuel().unwrap_or(0);
-        let start = Instant::now();
+        // Without fuel metering get_fuel errors, so both readings are 0
+        let call = CallStart {
+            metering,
+            fuel_before: self.store.get_fuel().unwrap_or(0),
+            start: Instant::now(),
+        };
+        Ok(call)
+    }
 
-        // Execute the function
-        let mut results = vec![Val::I32(0); func.ty(&self.store).results().len()];
-        let execution_result = func.call(&mut self.store, args, &mut results);
+    /// Account for a finished call and classify how it ended.
+    fn finish_call(
+        &mut self,
+        call: CallStart,
+        execution_result: wasmtime::Result<()>,
+        mut results: Vec<Val>,
+    ) -> Result<ExecutionResult, SandboxError> {
+        let CallStart {
+            metering,
+            fuel_before,
+            start,
+        } = call;
+
+        let execution_result = match execution_result {
+            // A WASI-style exit with code 0 is a normal return
+            Err(e) if e.downcast_ref::<ProcExit>() == Some(&ProcExit(0)) => {
+                results.clear();
+                Ok(())
+            }
+            other => other,
+        };
+
+        let abort = execution_result
+            .as_ref()
+            .err()
+            .and_then(|e| e.downcast_ref::<SpiritAbort>())
+            .map(ToString::to_string);
+        let violation = execution_result
+            .as_ref()
+            .err()
+            .and_then(|e| e.downcast_ref::<CapabilityViolation>())
+            .map(ToString::to_string);
 
         let duration = start.elapsed();
         let fuel_after = self.store.get_fuel().unwrap_or(0);
         let fuel_consumed = fuel_before.saturating_sub(fuel_after);
 
-        // Update tracking
-        self.fuel_consumed += fuel_consumed;
+        // Update tracking; metrics are updated from the result below
+        self.fuel_consumed = self.fuel_consumed.saturating_add(fuel_consumed);
+        self.budget_fuel_used = self.budget_fuel_used.saturating_add(fuel_consumed);
         self.last_executed = Some(
             SystemTime::now()
                 .duration_since(UNIX_EPOCH)
@@ -588,61 +1776,80 @@ impl Sandbox {
                 .as_secs(),
         );
 
-        // Get memory usage (approximate)
-        let memory_used = self.estimate_memory_usage();
+        let memory_used = self.linear_memory_size();
         self.memory_peak = self.memory_peak.max(memory_used);
 
+        let output = self.store.data_mut().take_output();
+
         // Build result
         let exec_result = match execution_result {
             Ok(_) => {
-                self.state = SandboxState::Ready;
+                self.transition_to(SandboxState::Ready)?;
                 ExecutionResult {
                     success: true,
+                    outcome: ExecutionOutcome::Completed,
                     return_value: Some(results),
                     fuel_consumed,
                     duration,
                     memory_used,
                     error: None,
+                    partial_output: None,
                 }
             }
             Err(e) => {
-                // Check if it's a timeout or trap
-                if duration >= self.limits.max_duration {
-                    self.state = SandboxState::Failed;
-                    ExecutionResult {
-                        success: false,
-                        return_value: None,
-                        fuel_consumed,
-                        duration,
-                        memory_used,
-                        error: Some(format!("Timeout: {}", e)),
-                    }
-                } else if fuel_after == 0 {
-                    self.state = SandboxState::Paused;
-                    ExecutionResult {
-                        success: false,
-                        return_value: None,
-                        fuel_consumed,
-                        duration,
-                        memory_used,
-                        error: Some("Out of fuel".to_string()),
-                    }
+                // Classify the failure: timeout, memory limit, fuel, or trap
+                let (outcome, error) = if duration >= self.store.data().timeout() {
+                    (ExecutionOutcome::Timeout, format!("Timeout: {}", e))
+                } else if let Some(exceeded) = e.downcast_ref::<MemoryLimitExceeded>() {
+                    (
+                        ExecutionOutcome::MemoryLimit,
+                        format!("Memory limit: {}", exceeded),
+                    )
+                } else if metering.uses_fuel()
+                    && (fuel_after == 0 || e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel))
+                {
+                    (ExecutionOutcome::OutOfFuel, "Out of fuel".to_string())
                 } else {
-                    self.state = SandboxState::Failed;
-                    ExecutionResult {
-                        success: false,
-                        return_value: None,
-                        fuel_consumed,
-                        duration,
-                        memory_used,
-                        error: Some(format!("WASM trap: {}", e)),
-                    }
+                    (
+                        ExecutionOutcome::Trap(TrapKind::from_error(&e)),
+                        format!("WASM trap: {}", e),
+                    )
+                };
+
+                self.transition_to(match outcome {
+                    ExecutionOutcome::OutOfFuel => SandboxState::Paused,
+                    _ => SandboxState::Failed,
+                })?;
+
+                ExecutionResult {
+                    success: false,
+                    outcome,
+                    return_value: None,
+                    fuel_consumed,
+                    duration,
+                    memory_used,
+                    error: Some(error),
+                    partial_output: output,
                 }
             }
         };
 
         // Update metrics
         self.metrics.update(&exec_result);
+        self.metrics.total_host_calls = self
+            .metrics
+            .total_host_calls
+            .saturating_add(self.store.data().host_calls());
+
+        if exec_result.outcome == ExecutionOutcome::Trap(TrapKind::StackOverflow) {
+            return Err(SandboxError::StackOverflow);
+        }
+        if let Some(message) = abort {
+            return Err(SandboxError::WasmTrap(message));
+        }
+        if let Some(message) = violation {
+            return Err(SandboxError::CapabilityDenied(message));
+        }
 
         Ok(exec_result)
     }
@@ -657,127 +1864,1013 @@ impl Sandbox {
         self.metrics.clone()
     }
 
-    /// Add a capability grant to the sandbox.
-    pub fn grant_capability(&mut self, grant: CapabilityGrant) {
-        self.capabilities.push(grant);
+    /// Get the counters and gauges the Spirit has emitted through
+    /// `host_metrics_emit`.
+    pub fn spirit_metrics(&self) -> &MetricsSink {
+        self.store.data().spirit_metrics()
     }
 
-    /// Check if the sandbox has a specific capability.
-    pub fn has_capability(&self, cap_type: CapabilityType) -> bool {
-        self.capabilities
-            .iter()
-            .any(|grant| grant.capability == cap_type && grant.is_valid())
+    /// Get the capabilities the Spirit has actually exercised.
+    pub fn capability_usage(&self) -> HashSet<crate::capability::CapabilityType> {
+        self.store.data().capability_usage()
     }
 
-    /// Refuel the sandbox (add more fuel).
-    pub fn refuel(&mut self, additional_fuel: u64) -> Result<(), SandboxError> {
-        let current = self.store.get_fuel().unwrap_or(0);
-        let new_fuel = current.saturating_add(additional_fuel);
+    /// Register a hook to be notified when the Spirit attempts an operation
+    /// it holds no capability for.
+    pub fn set_capability_denied_hook(&mut self, hook: CapabilityDeniedHook) {
+        self.store.data_mut().set_capability_denied_hook(hook);
+    }
+
+    /// Let the Spirit reach sandboxes registered on `router` through
+    /// `host_call_sandbox` (requires `CrossSandboxCall`).
+    pub fn attach_router(&mut self, router: SandboxRouter) {
+        self.store.data_mut().set_router(router);
+    }
+
+    /// Invoke `function` on behalf of another sandbox, running on `fuel`
+    /// lent by the caller instead of this sandbox's own budget.
+    ///
+    /// The sandbox's own fuel is restored afterwards, so exhausting the
+    /// lent fuel leaves it Ready rather than Paused. Without fuel metering
+    /// this is a plain `invoke`.
+    pub fn invoke_with_fuel(
+        &mut self,
+        function: &str,
+        args: &[Val],
+        fuel: u64,
+    ) -> Result<ExecutionResult, SandboxError> {
+        if !self.limits.metering.uses_fuel() {
+            return self.invoke(function, args);
+        }
 
+        let own_fuel = self.store.get_fuel().unwrap_or(0);
         self.store
-            .set_fuel(new_fuel)
-            .map_err(|e| SandboxError::RuntimeError(format!("Failed to refuel: {}", e)))?;
+            .set_fuel(fuel)
+            .map_err(|e| SandboxError::RuntimeError(format!("Failed to set fuel: {}", e)))?;
 
+        let result = self.invoke(function, args);
+
+        self.store
+            .set_fuel(own_fuel)
+            .map_err(|e| SandboxError::RuntimeError(format!("Failed to restore fuel: {}", e)))?;
         if self.state == SandboxState::Paused {
-            self.state = SandboxState::Ready;
+            self.transition_to(SandboxState::Ready)?;
         }
 
-        Ok(())
+        result
     }
 
-    /// Terminate the sandbox cleanly.
-    pub fn terminate(&mut self) {
-        self.state = SandboxState::Terminated;
-        self.instance = None;
-    }
+    /// Invoke `function` with a wall-clock timeout of `timeout` instead of
+    /// `limits.max_duration`.
+    ///
+    /// The timeout is capped at `limits.max_duration_ceiling`. The default
+    /// applies again to later invokes.
+    pub fn invoke_with_timeout(
+        &mut self,
+        function: &str,
+        args: &[Val],
+        timeout: Duration,
+    ) -> Result<ExecutionResult, SandboxError> {
+        let timeout = timeout.min(self.limits.max_duration_ceiling);
+        self.store.data_mut().set_timeout(timeout);
 
-    // Helper methods
+        let result = self.invoke(function, args);
 
-    fn generate_id() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64
+        self.store.data_mut().set_timeout(self.limits.max_duration);
+
+        result
     }
 
-    fn estimate_memory_usage(&self) -> u64 {
-        // This is a simple estimate based on module size
-        // In a real implementation, we would query actual memory usage from wasmtime
-        self.wasm_module.len() as u64
+    /// Run the module's entrypoint: `_start`, or `main` if there is none.
+    ///
+    /// `main` receives zero for each of its parameters (e.g. `argc`/`argv`).
+    /// Returns `FunctionNotFound("_start")` if neither is exported.
+    pub fn run_start(&mut self) -> Result<ExecutionResult, SandboxError> {
+        let name = ["_start", "main"]
+            .into_iter()
+            .find(|name| self.export_func_type(name).is_some())
+            .ok_or_else(|| SandboxError::FunctionNotFound("_start".to_string()))?;
+
+        self.run_entry(name)
     }
-}
 
-// ═══════════════════════════════════════════════════════════════════════════
-// TESTS
-// ═══════════════════════════════════════════════════════════════════════════
+    /// Invoke the exported function `name` with zero for each parameter.
+    pub fn run_entry(&mut self, name: &str) -> Result<ExecutionResult, SandboxError> {
+        let ty = self
+            .export_func_type(name)
+            .ok_or_else(|| SandboxError::FunctionNotFound(name.to_string()))?;
+
+        let args: Vec<Val> = ty
+            .params()
+            .map(|param| {
+                Val::default_for_ty(&param).ok_or_else(|| {
+                    SandboxError::RuntimeError(format!("Unsupported {} parameter: {}", name, param))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        self.invoke(name, &args)
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Parameter and result types of the exported function `name`.
+    pub fn export_func_type(&self, name: &str) -> Option<FuncType> {
+        match self.module.as_ref()?.get_export(name)? {
+            ExternType::Func(ty) => Some(ty),
+            _ => None,
+        }
+    }
 
-    #[test]
-    fn test_sandbox_state_transitions() {
-        // Create a minimal WASM module (empty module for testing)
-        let wasm = wat::parse_str(
-            r#"
-            (module
-                (func (export "test") (result i32)
-                    i32.const 42
-                )
-            )
-        "#,
-        )
-        .unwrap();
+    /// Make `method` callable from the Spirit via `host_invoke` under `name`.
+    pub fn register_host_method(&mut self, name: impl Into<String>, method: Box<dyn HostMethod>) {
+        self.store.data_mut().register_method(name, method);
+    }
 
-        let owner = [0u8; 32];
-        let limits = ResourceLimits::default();
+    /// Send the Spirit's delivered log records to `hook` instead of the console.
+    pub fn set_log_hook(&mut self, hook: LogHook) {
+        self.store.data_mut().set_log_hook(hook);
+    }
 
-        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
-        assert_eq!(sandbox.get_state(), SandboxState::Initializing);
+    /// Send the Spirit's delivered log records to `sink` instead of the console.
+    pub fn set_log_sink(&mut self, sink: Arc<dyn LogSink>) {
+        self.store.data_mut().set_log_sink(sink);
+    }
 
-        sandbox.initialize().unwrap();
-        assert_eq!(sandbox.get_state(), SandboxState::Ready);
+    /// Isolate this sandbox's storage keys under `namespace`.
+    ///
+    /// See [`HostState::set_storage_namespace`] for the key encoding.
+    pub fn set_storage_namespace(&mut self, namespace: impl Into<Vec<u8>>) {
+        self.store.data_mut().set_storage_namespace(namespace);
     }
 
-    #[test]
-    fn test_sandbox_execution() {
-        let wasm = wat::parse_str(
-            r#"
-            (module
-                (func (export "add") (param i32 i32) (result i32)
-                    local.get 0
-                    local.get 1
-                    i32.add
-                )
-            )
-        "#,
-        )
-        .unwrap();
+    /// Isolate storage under a namespace derived from this sandbox's owner
+    /// and id, so sandboxes sharing a backend cannot see each other's keys.
+    pub fn isolate_storage(&mut self) {
+        let mut namespace = self.owner.to_vec();
+        namespace.extend_from_slice(&self.id.to_le_bytes());
+        self.set_storage_namespace(namespace);
+    }
 
-        let owner = [0u8; 32];
-        let limits = ResourceLimits::default();
+    /// Record the Spirit's host calls, keeping the most recent `capacity`.
+    ///
+    /// Tracing is off by default; see [`Sandbox::trace`].
+    pub fn enable_trace(&mut self, capacity: usize) {
+        self.store.data_mut().enable_trace(capacity);
+    }
 
-        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
-        sandbox.initialize().unwrap();
+    /// Stop recording host calls and discard the trace.
+    pub fn disable_trace(&mut self) {
+        self.store.data_mut().disable_trace();
+    }
 
-        let args = vec![Val::I32(10), Val::I32(32)];
-        let result = sandbox.invoke("add", &args).unwrap();
+    /// Host calls recorded since tracing was enabled, oldest first.
+    ///
+    /// Empty when tracing is off.
+    pub fn trace(&self) -> Vec<TraceEntry> {
+        self.store
+            .data()
+            .trace()
+            .map(|trace| trace.entries().cloned().collect())
+            .unwrap_or_default()
+    }
 
-        assert!(result.success);
-        assert_eq!(result.return_value.as_ref().unwrap()[0].unwrap_i32(), 42);
+    /// Replace the revocation list checked by the Spirit's host calls.
+    pub fn set_revocation_list(&mut self, revocations: RevocationList) {
+        self.store
+            .data_mut()
+            .capabilities
+            .set_revocation_list(revocations);
     }
 
-    #[test]
-    fn test_resource_limits_validation() {
-        let limits = ResourceLimits {
-            memory_bytes: MAX_SANDBOX_MEMORY + 1,
-            ..Default::default()
-        };
+    /// Add a capability grant to the sandbox.
+    ///
+    /// The grant reaches the Spirit's host calls from the next call on.
+    /// Taking `&mut self` means no call can be in flight.
+    pub fn grant_capability(&mut self, grant: CapabilityGrant) {
+        self.store
+            .data_mut()
+            .capabilities
+            .add_grant(grant.clone().into());
+        self.capabilities.push(grant);
+    }
 
-        assert!(limits.validate().is_err());
+    /// Add every grant in `capabilities` to those the Spirit's host calls
+    /// check, from the next call on. Replayed grants are dropped (see
+    /// [`CapabilitySet::add_grant`]); the revocation list is left as is.
+    pub fn grant_all(&mut self, capabilities: CapabilitySet) {
+        let held = &mut self.store.data_mut().capabilities;
+        for grant in capabilities.iter().cloned() {
+            held.add_grant(grant);
+        }
+    }
 
-        let limits = ResourceLimits {
-            cpu_quota: 1.5,
+    /// Check if the sandbox has a usable grant of a capability, at any scope.
+    pub fn has_capability(&self, cap_type: CapabilityType) -> bool {
+        self.store
+            .data()
+            .capabilities
+            .effective_scope(cap_type.into())
+            .is_some()
+    }
+
+    /// Fuel left in the cross-invoke budget, or `None` if no budget is set.
+    pub fn fuel_budget_remaining(&self) -> Option<u64> {
+        self.limits
+            .total_fuel_budget
+            .map(|budget| budget.saturating_sub(self.budget_fuel_used))
+    }
+
+    /// Start a new `total_fuel_budget` window without adding fuel.
+    pub fn reset_fuel_budget(&mut self) {
+        self.budget_fuel_used = 0;
+    }
+
+    /// Refuel the sandbox (add more fuel).
+    ///
+    /// Also starts a new `total_fuel_budget` window. Does nothing without
+    /// fuel metering.
+    pub fn refuel(&mut self, additional_fuel: u64) -> Result<(), SandboxError> {
+        if !self.limits.metering.uses_fuel() {
+            return Ok(());
+        }
+
+        let current = self.store.get_fuel().unwrap_or(0);
+        let new_fuel = current.saturating_add(additional_fuel);
+
+        self.store
+            .set_fuel(new_fuel)
+            .map_err(|e| SandboxError::RuntimeError(format!("Failed to refuel: {}", e)))?;
+        self.reset_fuel_budget();
+
+        if self.state == SandboxState::Paused {
+            self.transition_to(SandboxState::Ready)?;
+        }
+
+        Ok(())
+    }
+
+    /// Probe the Spirit's liveness.
+    ///
+    /// A Spirit may export `__vudo_health() -> i32`, returning 0 when
+    /// healthy. The probe runs on `HEALTH_FUEL` and `HEALTH_TIMEOUT_MILLIS`
+    /// of its own, leaving the Spirit's fuel, metrics and state as they
+    /// were, so a probe that hangs is cut off as `Trapped`. The Spirit is
+    /// instantiated first if no call has done so yet.
+    ///
+    /// Only Ready and Paused sandboxes are probed; any other state, like an
+    /// async sandbox (which can only run WASM from an executor), reports
+    /// `Trapped`.
+    pub fn health(&mut self) -> HealthStatus {
+        if !matches!(self.state, SandboxState::Ready | SandboxState::Paused) {
+            return HealthStatus::Trapped(format!("Cannot probe from state {}", self.state));
+        }
+        if self.async_support {
+            return HealthStatus::Trapped("Async sandboxes cannot be probed".to_string());
+        }
+        if let Err(e) = self.ensure_instance() {
+            return HealthStatus::Trapped(e.to_string());
+        }
+
+        let instance = self.instance.as_ref().unwrap();
+        let Some(probe) = instance.get_func(&mut self.store, HEALTH_EXPORT) else {
+            return HealthStatus::NoProbe;
+        };
+        let probe = match probe.typed::<(), i32>(&self.store) {
+            Ok(probe) => probe,
+            Err(e) => {
+                return HealthStatus::Trapped(format!(
+                    "{} must take no parameters and return i32: {}",
+                    HEALTH_EXPORT, e
+                ))
+            }
+        };
+
+        let uses_fuel = self.limits.metering.uses_fuel();
+        let own_fuel = self.store.get_fuel().unwrap_or(0);
+        if uses_fuel && self.store.set_fuel(HEALTH_FUEL).is_err() {
+            return HealthStatus::Trapped("Failed to set fuel".to_string());
+        }
+        let own_timeout = std::mem::replace(
+            &mut self.store.data_mut().timeout,
+            Duration::from_millis(HEALTH_TIMEOUT_MILLIS),
+        );
+        self.store.data_mut().start_execution();
+        if self.limits.metering.uses_epochs() {
+            self.store.set_epoch_deadline(1);
+        }
+        let ticker = self
+            .limits
+            .metering
+            .uses_epochs()
+            .then(|| EpochTicker::start(&self.engine));
+        let result = probe.call(&mut self.store, ());
+        drop(ticker);
+        self.store.data_mut().timeout = own_timeout;
+        if uses_fuel && self.store.set_fuel(own_fuel).is_err() {
+            return HealthStatus::Trapped("Failed to restore fuel".to_string());
+        }
+
+        match result {
+            Ok(0) => HealthStatus::Healthy,
+            Ok(code) => HealthStatus::Unhealthy(code),
+            Err(e) => HealthStatus::Trapped(format!("{} failed: {}", HEALTH_EXPORT, e)),
+        }
+    }
+
+    /// Terminate the sandbox cleanly.
+    ///
+    /// If the Spirit has been instantiated, is Ready or Paused, and exports
+    /// `__vudo_shutdown: fn()`, the hook runs first with `SHUTDOWN_FUEL`
+    /// (or, without fuel, within `max_duration`) so it can flush state. A
+    /// failing hook is logged; termination proceeds regardless. A Failed
+    /// sandbox stays Failed, since that state is terminal, but its instance
+    /// is still dropped.
+    ///
+    /// With `ResourceLimits::scrub_on_terminate`, every exported linear
+    /// memory is then overwritten with zeros, and the host's copies of
+    /// Spirit-supplied data (the last error message and metric names) are
+    /// zeroed and cleared. Data the Spirit wrote to its storage backend is
+    /// persistent by design and left alone.
+    pub fn terminate(&mut self) {
+        if matches!(self.state, SandboxState::Ready | SandboxState::Paused) {
+            if let Err(e) = self.run_shutdown_hook() {
+                eprintln!(
+                    "[VUDO:WARN] sandbox {}: {} failed: {}",
+                    self.id, SHUTDOWN_EXPORT, e
+                );
+            }
+        }
+        if self.state.can_transition_to(SandboxState::Terminated) {
+            self.state = SandboxState::Terminated;
+        }
+        if self.limits.scrub_on_terminate {
+            self.scrub();
+        }
+        self.instance = None;
+    }
+
+    /// Zero the instance's memories and the host's Spirit data.
+    fn scrub(&mut self) {
+        if let Some(instance) = self.instance {
+            let memories: Vec<Memory> = instance
+                .exports(&mut self.store)
+                .filter_map(|export| export.into_memory())
+                .collect();
+            for memory in memories {
+                memory.data_mut(&mut self.store).fill(0);
+            }
+        }
+        self.store.data_mut().scrub_spirit_data();
+    }
+
+    fn run_shutdown_hook(&mut self) -> wasmtime::Result<()> {
+        // Async stores can only run WASM from an executor
+        if self.async_support {
+            return Ok(());
+        }
+        let Some(instance) = self.instance.as_ref() else {
+            return Ok(());
+        };
+        let Some(hook) = instance.get_func(&mut self.store, SHUTDOWN_EXPORT) else {
+            return Ok(());
+        };
+        let hook = hook.typed::<(), ()>(&self.store)?;
+
+        if self.limits.metering.uses_fuel() {
+            self.store.set_fuel(SHUTDOWN_FUEL)?;
+        }
+        self.store.data_mut().start_execution();
+        if self.limits.metering.uses_epochs() {
+            self.store.set_epoch_deadline(1);
+        }
+        let ticker = self
+            .limits
+            .metering
+            .uses_epochs()
+            .then(|| EpochTicker::start(&self.engine));
+        let result = hook.call(&mut self.store, ());
+        drop(ticker);
+        result
+    }
+
+    // Helper methods
+
+    fn generate_id() -> u64 {
+        NEXT_SANDBOX_ID.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Bytes of linear memory the instance's exported memories hold.
+    ///
+    /// Linear memory never shrinks, so after a call this is also the
+    /// peak the call reached.
+    fn linear_memory_size(&mut self) -> u64 {
+        let Some(instance) = self.instance else {
+            return 0;
+        };
+        let memories: Vec<Memory> = instance
+            .exports(&mut self.store)
+            .filter_map(|export| export.into_memory())
+            .collect();
+        memories
+            .iter()
+            .map(|memory| memory.data_size(&self.store) as u64)
+            .sum()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// EPOCH TICKER
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Readings taken as a call starts, settled by `Sandbox::finish_call`.
+struct CallStart {
+    metering: MeteringMode,
+    fuel_before: u64,
+    start: Instant,
+}
+
+impl CallStart {
+    /// Tick the engine's epoch for the duration of the call, if the
+    /// timeout is enforced by epochs.
+    fn start_ticker(&self, engine: &Engine) -> Option<EpochTicker> {
+        self.metering
+            .uses_epochs()
+            .then(|| EpochTicker::start(engine))
+    }
+}
+
+/// Advances an engine's epoch every `EPOCH_TICK_MILLIS` until dropped, so
+/// the store's deadline callback gets a chance to check the timeout while
+/// WASM code runs.
+struct EpochTicker {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl EpochTicker {
+    fn start(engine: &Engine) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let engine = engine.clone();
+        let flag = Arc::clone(&stop);
+        let handle = std::thread::spawn(move || loop {
+            std::thread::park_timeout(Duration::from_millis(EPOCH_TICK_MILLIS));
+            if flag.load(Ordering::Acquire) {
+                break;
+            }
+            engine.increment_epoch();
+        });
+
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for EpochTicker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SANDBOX BUILDER
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Adjusts the wasmtime `Config` before a sandbox's engine is built.
+pub type EngineConfigHook = Box<dyn Fn(&mut Config) + Send + Sync>;
+
+/// Step-by-step construction of a [`Sandbox`].
+///
+/// `Sandbox::new` covers the common case; the builder additionally exposes
+/// wasmtime engine tuning through [`SandboxBuilder::with_engine_config`].
+pub struct SandboxBuilder<'a> {
+    wasm: &'a [u8],
+    owner: [u8; 32],
+    limits: ResourceLimits,
+    storage: Arc<dyn StorageBackend>,
+    credit: Arc<dyn CreditBackend>,
+    network: Arc<dyn NetworkBackend>,
+    capabilities: CapabilitySet,
+    engine_config: Option<EngineConfigHook>,
+    compilation: CompilationMode,
+    async_support: bool,
+    host_namespace: String,
+}
+
+impl<'a> SandboxBuilder<'a> {
+    /// Start a builder with default limits, in-memory backends and no
+    /// capabilities.
+    pub fn new(wasm: &'a [u8], owner: [u8; 32]) -> Self {
+        use crate::host::{InMemoryCreditLedger, InMemoryStorage, MockNetworkBackend};
+
+        Self {
+            wasm,
+            owner,
+            limits: ResourceLimits::default(),
+            storage: Arc::new(InMemoryStorage::new()),
+            credit: Arc::new(InMemoryCreditLedger::new()),
+            network: Arc::new(MockNetworkBackend::new()),
+            capabilities: CapabilitySet::new(),
+            engine_config: None,
+            compilation: CompilationMode::default(),
+            async_support: false,
+            host_namespace: HOST_NAMESPACE.to_string(),
+        }
+    }
+
+    /// Set the resource limits.
+    pub fn limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Register host functions under `namespace` instead of `"vudo"`.
+    ///
+    /// See [`create_linker_in`].
+    pub fn host_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.host_namespace = namespace.into();
+        self
+    }
+
+    /// Set the storage backend.
+    pub fn storage(mut self, storage: Arc<dyn StorageBackend>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Set the credit backend.
+    pub fn credit(mut self, credit: Arc<dyn CreditBackend>) -> Self {
+        self.credit = credit;
+        self
+    }
+
+    /// Set the network backend.
+    pub fn network(mut self, network: Arc<dyn NetworkBackend>) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Set the capabilities granted to the Spirit.
+    pub fn capabilities(mut self, capabilities: CapabilitySet) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Choose the compiler; see [`CompilationMode`] for the tradeoffs.
+    pub fn compilation(mut self, mode: CompilationMode) -> Self {
+        self.compilation = mode;
+        self
+    }
+
+    /// Run calls on an async executor through [`Sandbox::invoke_async`].
+    ///
+    /// Such a sandbox refuses the synchronous `invoke` family, is not
+    /// reachable through a [`SandboxRouter`], and skips the shutdown hook
+    /// on `terminate`. `host_yield` only yields with epoch interruption,
+    /// i.e. under `MeteringMode::Fuel` or `MeteringMode::EpochOnly`.
+    pub fn async_support(mut self, enabled: bool) -> Self {
+        self.async_support = enabled;
+        self
+    }
+
+    /// Tune the wasmtime `Config`, e.g. to toggle SIMD or reference types.
+    /// Its strategy setting, if any, overrides [`SandboxBuilder::compilation`].
+    ///
+    /// The hook runs on a fresh `Config`; fuel metering and the stack limit
+    /// from `ResourceLimits::max_stack_bytes` are re-applied afterwards and
+    /// cannot be overridden here.
+    pub fn with_engine_config(
+        mut self,
+        hook: impl Fn(&mut Config) + Send + Sync + 'static,
+    ) -> Self {
+        self.engine_config = Some(Box::new(hook));
+        self
+    }
+
+    /// Create the sandbox in the Initializing state.
+    pub fn build(self) -> Result<Sandbox, SandboxError> {
+        Sandbox::from_builder(self)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SANDBOX ROUTER
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Why a routed call produced no result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrossCallError {
+    /// The call never started: unknown or busy target, target not Ready,
+    /// missing export, or arguments that don't fit the signature.
+    Rejected(String),
+    /// The target started executing but did not complete.
+    Failed(String),
+}
+
+impl std::fmt::Display for CrossCallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrossCallError::Rejected(msg) => write!(f, "Cross-sandbox call rejected: {}", msg),
+            CrossCallError::Failed(msg) => write!(f, "Cross-sandbox call failed: {}", msg),
+        }
+    }
+}
+
+/// Outcome of a call routed to another sandbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossCallResult {
+    /// Encoded return values, or why the call failed
+    pub output: Result<Vec<u8>, CrossCallError>,
+    /// Fuel the target burned, to be charged to the caller
+    pub fuel_consumed: u64,
+}
+
+impl CrossCallResult {
+    fn rejected(message: impl Into<String>) -> Self {
+        Self {
+            output: Err(CrossCallError::Rejected(message.into())),
+            fuel_consumed: 0,
+        }
+    }
+}
+
+/// Registry of sandboxes addressable by id for cross-sandbox calls.
+///
+/// Cloning is cheap; clones share the same registry. Arguments and results
+/// cross the boundary as the little-endian encoding of each value in the
+/// target function's signature (i32/f32: 4 bytes, i64/f64: 8 bytes),
+/// concatenated in order.
+#[derive(Clone, Default)]
+pub struct SandboxRouter {
+    sandboxes: Arc<Mutex<HashMap<u64, Arc<Mutex<Sandbox>>>>>,
+}
+
+impl SandboxRouter {
+    /// Create an empty router.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `sandbox` reachable under its id, returning a shared handle.
+    pub fn register(&self, sandbox: Sandbox) -> Result<Arc<Mutex<Sandbox>>, SandboxError> {
+        let mut sandboxes = self.sandboxes.lock().unwrap();
+        if sandboxes.contains_key(&sandbox.id) {
+            return Err(SandboxError::RuntimeError(format!(
+                "Sandbox {} is already registered",
+                sandbox.id
+            )));
+        }
+        let id = sandbox.id;
+        let handle = Arc::new(Mutex::new(sandbox));
+        sandboxes.insert(id, Arc::clone(&handle));
+        Ok(handle)
+    }
+
+    /// Get the handle for sandbox `id`.
+    pub fn get(&self, id: u64) -> Option<Arc<Mutex<Sandbox>>> {
+        self.sandboxes.lock().unwrap().get(&id).cloned()
+    }
+
+    /// Stop routing calls to sandbox `id`.
+    pub fn remove(&self, id: u64) -> Option<Arc<Mutex<Sandbox>>> {
+        self.sandboxes.lock().unwrap().remove(&id)
+    }
+
+    /// Call `function` on sandbox `target` with `fuel` lent by the caller.
+    ///
+    /// The target must be Ready. A target that is already executing (for
+    /// example, the caller calling itself) is rejected rather than waited on.
+    pub fn call(&self, target: u64, function: &str, args: &[u8], fuel: u64) -> CrossCallResult {
+        let Some(handle) = self.get(target) else {
+            return CrossCallResult::rejected(format!("no sandbox with id {}", target));
+        };
+        let Ok(mut sandbox) = handle.try_lock() else {
+            return CrossCallResult::rejected(format!("sandbox {} is busy", target));
+        };
+
+        if sandbox.get_state() != SandboxState::Ready {
+            return CrossCallResult::rejected(format!(
+                "sandbox {} is {}, not ready",
+                target,
+                sandbox.get_state()
+            ));
+        }
+        let Some(ty) = sandbox.export_func_type(function) else {
+            return CrossCallResult::rejected(format!(
+                "sandbox {} exports no function {}",
+                target, function
+            ));
+        };
+        let args = match decode_vals(ty.params(), args) {
+            Ok(args) => args,
+            Err(e) => return CrossCallResult::rejected(e),
+        };
+
+        match sandbox.invoke_with_fuel(function, &args, fuel) {
+            Ok(result) => CrossCallResult {
+                output: match (&result.return_value, &result.error) {
+                    (Some(values), _) => encode_vals(values).map_err(CrossCallError::Failed),
+                    (None, error) => Err(CrossCallError::Failed(error.clone().unwrap_or_default())),
+                },
+                fuel_consumed: result.fuel_consumed,
+            },
+            Err(e) => CrossCallResult {
+                output: Err(CrossCallError::Failed(e.to_string())),
+                fuel_consumed: 0,
+            },
+        }
+    }
+}
+
+fn decode_vals(
+    types: impl ExactSizeIterator<Item = ValType>,
+    bytes: &[u8],
+) -> Result<Vec<Val>, String> {
+    let mut vals = Vec::with_capacity(types.len());
+    let mut rest = bytes;
+    for ty in types {
+        let width = match ty {
+            ValType::I32 | ValType::F32 => 4,
+            ValType::I64 | ValType::F64 => 8,
+            other => return Err(format!("unsupported parameter type {}", other)),
+        };
+        if rest.len() < width {
+            return Err("arguments shorter than the function signature".to_string());
+        }
+        let (head, tail) = rest.split_at(width);
+        rest = tail;
+        vals.push(match ty {
+            ValType::I32 => Val::I32(i32::from_le_bytes(head.try_into().unwrap())),
+            ValType::I64 => Val::I64(i64::from_le_bytes(head.try_into().unwrap())),
+            ValType::F32 => Val::F32(u32::from_le_bytes(head.try_into().unwrap())),
+            _ => Val::F64(u64::from_le_bytes(head.try_into().unwrap())),
+        });
+    }
+    if !rest.is_empty() {
+        return Err("arguments longer than the function signature".to_string());
+    }
+    Ok(vals)
+}
+
+fn encode_vals(vals: &[Val]) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for val in vals {
+        match val {
+            Val::I32(v) => bytes.extend_from_slice(&v.to_le_bytes()),
+            Val::I64(v) => bytes.extend_from_slice(&v.to_le_bytes()),
+            Val::F32(v) => bytes.extend_from_slice(&v.to_le_bytes()),
+            Val::F64(v) => bytes.extend_from_slice(&v.to_le_bytes()),
+            _ => return Err("unsupported result type".to_string()),
+        }
+    }
+    Ok(bytes)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// SPIRIT IMAGE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A compiled Spirit that can be instantiated into many sandboxes.
+///
+/// `Sandbox::new` creates an engine and compiles the module per sandbox.
+/// An image compiles once; every sandbox it instantiates shares the same
+/// `Engine`, `Module` and host `Linker` (all reference-counted internally)
+/// while getting its own store, so linear memory, globals, fuel and
+/// capabilities stay isolated.
+///
+/// The WASM stack limit is an engine setting, so it is fixed per image;
+/// `instantiate` rejects limits whose `max_stack_bytes` differ from it.
+/// Images always meter fuel, so `instantiate` also requires
+/// `MeteringMode::Fuel`, and never canonicalize NaNs, so it rejects
+/// deterministic limits.
+#[derive(Clone)]
+pub struct SpiritImage {
+    wasm: Arc<[u8]>,
+    stats: ModuleStats,
+    engine: Engine,
+    module: Module,
+    linker: Linker<HostState>,
+    max_stack_bytes: usize,
+}
+
+impl SpiritImage {
+    /// Compile `wasm` with the default stack limit.
+    pub fn new(wasm: &[u8]) -> Result<Self, SandboxError> {
+        Self::with_max_stack(wasm, DEFAULT_MAX_STACK_BYTES)
+    }
+
+    /// Compile `wasm` with an explicit WASM stack limit.
+    pub fn with_max_stack(wasm: &[u8], max_stack_bytes: usize) -> Result<Self, SandboxError> {
+        Sandbox::validate_module_size(wasm)?;
+
+        if max_stack_bytes == 0 || max_stack_bytes > MAX_STACK_BYTES {
+            return Err(SandboxError::InvalidModule(format!(
+                "max_stack_bytes must be between 1 and {}",
+                MAX_STACK_BYTES
+            )));
+        }
+
+        let stats = ModuleStats::parse(wasm)?;
+        check_start_function(wasm)?;
+        let engine = Sandbox::create_engine(
+            max_stack_bytes,
+            MeteringMode::Fuel,
+            false,
+            CompilationMode::Optimized,
+            false,
+            None,
+        )?;
+        let module = Module::new(&engine, wasm)
+            .map_err(|e| SandboxError::InvalidModule(format!("Failed to compile module: {}", e)))?;
+        let linker = create_linker(&engine);
+
+        Ok(Self {
+            wasm: wasm.into(),
+            stats,
+            engine,
+            module,
+            linker,
+            max_stack_bytes,
+        })
+    }
+
+    /// Structural counts of the compiled module.
+    pub fn stats(&self) -> ModuleStats {
+        self.stats
+    }
+
+    /// The WASM stack limit baked into this image's engine.
+    pub fn max_stack_bytes(&self) -> usize {
+        self.max_stack_bytes
+    }
+
+    /// Register host functions under `namespace` instead of `"vudo"` in
+    /// every sandbox this image instantiates.
+    ///
+    /// See [`SandboxBuilder::host_namespace`].
+    pub fn with_host_namespace(mut self, namespace: &str) -> Self {
+        self.linker = create_linker_in(&self.engine, namespace);
+        self
+    }
+
+    /// Create a Ready sandbox running this image.
+    ///
+    /// No compilation happens here; the sandbox reuses the image's module.
+    /// The ABI version handshake still runs per sandbox, as in
+    /// [`Sandbox::initialize`].
+    pub fn instantiate(
+        &self,
+        owner: [u8; 32],
+        limits: ResourceLimits,
+        storage: Arc<dyn StorageBackend>,
+        credit: Arc<dyn CreditBackend>,
+        network: Arc<dyn NetworkBackend>,
+        capability_set: CapabilitySet,
+    ) -> Result<Sandbox, SandboxError> {
+        limits.validate()?;
+
+        if limits.max_stack_bytes != self.max_stack_bytes {
+            return Err(SandboxError::InvalidModule(format!(
+                "max_stack_bytes {} does not match image stack limit {}",
+                limits.max_stack_bytes, self.max_stack_bytes
+            )));
+        }
+
+        if limits.metering != MeteringMode::Fuel {
+            return Err(SandboxError::InvalidModule(format!(
+                "metering {:?} does not match image metering Fuel",
+                limits.metering
+            )));
+        }
+
+        if limits.deterministic {
+            return Err(SandboxError::InvalidModule(
+                "deterministic limits need NaN canonicalization, which images do not enable"
+                    .to_string(),
+            ));
+        }
+
+        self.stats.check(&limits)?;
+
+        let host_state =
+            Sandbox::create_host_state(owner, &limits, storage, credit, network, capability_set);
+
+        let mut sandbox = Sandbox::assemble(
+            self.engine.clone(),
+            self.linker.clone(),
+            &self.wasm,
+            owner,
+            limits,
+            host_state,
+            false,
+        )?;
+        sandbox.adopt_module(self.module.clone())?;
+
+        Ok(sandbox)
+    }
+
+    /// Create a Ready sandbox with default in-memory backends and no
+    /// capabilities, mirroring `Sandbox::new_with_defaults`.
+    pub fn instantiate_with_defaults(
+        &self,
+        owner: [u8; 32],
+        limits: ResourceLimits,
+    ) -> Result<Sandbox, SandboxError> {
+        use crate::host::{InMemoryCreditLedger, InMemoryStorage, MockNetworkBackend};
+
+        self.instantiate(
+            owner,
+            limits,
+            Arc::new(InMemoryStorage::new()),
+            Arc::new(InMemoryCreditLedger::new()),
+            Arc::new(MockNetworkBackend::new()),
+            CapabilitySet::new(),
+        )
+    }
+
+    /// Returns true if `sandbox` was instantiated from this image and
+    /// therefore shares its engine and compiled module.
+    pub fn is_image_of(&self, sandbox: &Sandbox) -> bool {
+        Engine::same(&self.engine, &sandbox.engine)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linker::limit_fields;
+
+    #[test]
+    fn test_sandbox_state_transitions() {
+        // Create a minimal WASM module (empty module for testing)
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (export "test") (result i32)
+                    i32.const 42
+                )
+            )
+        "#,
+        )
+        .unwrap();
+
+        let owner = [0u8; 32];
+        let limits = ResourceLimits::default();
+
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        assert_eq!(sandbox.get_state(), SandboxState::Initializing);
+
+        sandbox.initialize().unwrap();
+        assert_eq!(sandbox.get_state(), SandboxState::Ready);
+    }
+
+    #[test]
+    fn test_sandbox_execution() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (export "add") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.add
+                )
+            )
+        "#,
+        )
+        .unwrap();
+
+        let owner = [0u8; 32];
+        let limits = ResourceLimits::default();
+
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        let args = vec![Val::I32(10), Val::I32(32)];
+        let result = sandbox.invoke("add", &args).unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.return_value.as_ref().unwrap()[0].unwrap_i32(), 42);
+    }
+
+    #[test]
+    fn test_resource_limits_validation() {
+        let limits = ResourceLimits {
+            memory_bytes: MAX_SANDBOX_MEMORY + 1,
+            ..Default::default()
+        };
+
+        assert!(limits.validate().is_err());
+
+        let limits = ResourceLimits {
+            cpu_quota: 1.5,
             ..Default::default()
         };
 
@@ -884,6 +2977,27 @@ mod tests {
     // HELPER FUNCTIONS FOR COMPREHENSIVE TESTS
     // ═══════════════════════════════════════════════════════════════════════════
 
+    fn create_recursive_wasm() -> Vec<u8> {
+        wat::parse_str(
+            r#"
+            (module
+                (func $recurse (export "recurse") (param i32) (result i32)
+                    (if (result i32) (i32.eqz (local.get 0))
+                        (then (i32.const 0))
+                        (else
+                            (i32.add
+                                (call $recurse (i32.sub (local.get 0) (i32.const 1)))
+                                (i32.const 1)
+                            )
+                        )
+                    )
+                )
+            )
+        "#,
+        )
+        .expect("Failed to parse WAT")
+    }
+
     fn create_loop_wasm() -> Vec<u8> {
         wat::parse_str(
             r#"
@@ -959,74 +3073,504 @@ mod tests {
         let owner = [0u8; 32];
         let limits = ResourceLimits::default();
 
-        let sandbox1 = Sandbox::new_with_defaults(&wasm, owner, limits.clone()).unwrap();
-        std::thread::sleep(std::time::Duration::from_nanos(1));
-        let sandbox2 = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        let sandbox1 = Sandbox::new_with_defaults(&wasm, owner, limits.clone()).unwrap();
+        let sandbox2 = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+
+        assert_ne!(sandbox1.id, sandbox2.id);
+    }
+
+    #[test]
+    fn test_sandbox_ids_distinct_in_tight_loop() {
+        let wasm =
+            wat::parse_str(r#"(module (func (export "test") (result i32) i32.const 1))"#).unwrap();
+        let image = SpiritImage::new(&wasm).unwrap();
+
+        let ids: HashSet<u64> = (0..1000)
+            .map(|_| {
+                image
+                    .instantiate_with_defaults([0u8; 32], ResourceLimits::default())
+                    .unwrap()
+                    .id
+            })
+            .collect();
+
+        assert_eq!(ids.len(), 1000);
+    }
+
+    #[test]
+    fn test_seeded_sandbox_ids() {
+        let wasm =
+            wat::parse_str(r#"(module (func (export "test") (result i32) i32.const 1))"#).unwrap();
+
+        // Far above anything other tests in this process will reach
+        let seed = 1 << 48;
+        seed_sandbox_ids(seed);
+        let sandbox =
+            Sandbox::new_with_defaults(&wasm, [0u8; 32], ResourceLimits::default()).unwrap();
+
+        assert!(sandbox.id >= seed);
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // MULTIPLE EXECUTION AND METRICS TESTS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    #[test]
+    fn test_sandbox_multiple_executions() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (export "add") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.add
+                )
+            )
+        "#,
+        )
+        .unwrap();
+        let owner = [0u8; 32];
+        let limits = ResourceLimits::default();
+
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        // Execute multiple times
+        for i in 0..5 {
+            let args = vec![Val::I32(i), Val::I32(i)];
+            let result = sandbox.invoke("add", &args).unwrap();
+            assert!(result.success);
+            assert_eq!(result.return_value.as_ref().unwrap()[0].unwrap_i32(), i * 2);
+        }
+
+        // Check metrics
+        let metrics = sandbox.metrics();
+        assert_eq!(metrics.execution_count, 5);
+        assert!(metrics.total_fuel_consumed > 0);
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // FUNCTION NOT FOUND TEST
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    #[test]
+    fn test_sandbox_function_not_found() {
+        let wasm =
+            wat::parse_str(r#"(module (func (export "test") (result i32) i32.const 42))"#).unwrap();
+        let owner = [0u8; 32];
+        let limits = ResourceLimits::default();
+
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        let result = sandbox.invoke("nonexistent", &[]);
+        assert!(result.is_err());
+
+        match result.unwrap_err() {
+            SandboxError::FunctionNotFound(name) => {
+                assert_eq!(name, "nonexistent");
+            }
+            _ => panic!("Expected FunctionNotFound error"),
+        }
+    }
+
+    #[test]
+    fn test_sandbox_missing_import() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "nonexistent_function" (func $missing))
+                (func (export "run") call $missing)
+            )
+        "#,
+        )
+        .unwrap();
+
+        let mut sandbox =
+            Sandbox::new_with_defaults(&wasm, [0u8; 32], ResourceLimits::default()).unwrap();
+        sandbox.initialize().unwrap();
+
+        match sandbox.invoke("run", &[]) {
+            Err(SandboxError::MissingImport(name)) => assert_eq!(name, "nonexistent_function"),
+            other => panic!("Expected MissingImport error, got {:?}", other),
+        }
+        assert_eq!(sandbox.get_state(), SandboxState::Failed);
+    }
+
+    #[test]
+    fn test_storage_increment_past_call_limit() {
+        // Decrements a counter from 0 until the call fails, returning the
+        // last result
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_storage_increment"
+                    (func $incr (param i32 i32 i64) (result i64)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "counter")
+                (func (export "count_down") (result i64)
+                    (local $value i64)
+                    (loop $again
+                        (local.set $value
+                            (call $incr (i32.const 0) (i32.const 7) (i64.const -1)))
+                        (br_if $again (i64.ne (local.get $value) (i64.const 0x8000000000000000))))
+                    local.get $value)
+            )
+        "#,
+        )
+        .unwrap();
+        let caps = CapabilitySet::from_grants(vec![
+            crate::capability::CapabilityGrant::builder(
+                crate::capability::CapabilityType::StorageRead,
+            )
+            .build(),
+            crate::capability::CapabilityGrant::builder(
+                crate::capability::CapabilityType::StorageWrite,
+            )
+            .build(),
+        ]);
+        let storage = Arc::new(crate::host::InMemoryStorage::new());
+        let limits = ResourceLimits {
+            max_host_calls: Some(12),
+            ..Default::default()
+        };
+
+        let mut sandbox = Sandbox::builder(&wasm, [0u8; 32])
+            .limits(limits)
+            .storage(storage.clone())
+            .capabilities(caps)
+            .build()
+            .unwrap();
+        sandbox.initialize().unwrap();
+        let result = sandbox.invoke("count_down", &[]).unwrap();
+        assert_eq!(result.return_value.unwrap()[0].unwrap_i64(), i64::MIN);
+
+        // The counter passed -10 and stopped at the limit, not before
+        assert_eq!(
+            storage.read(b"counter").unwrap(),
+            Some((-12i64).to_le_bytes().to_vec())
+        );
+        assert_eq!(sandbox.metrics().total_host_calls, 13);
+    }
+
+    #[test]
+    fn test_host_call_limit() {
+        // Calls host_time_now until it reports the call limit, returning
+        // how many calls succeeded
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_time_now" (func $now (result i64)))
+                (func (export "spin") (result i32)
+                    (local $ok i32)
+                    (block $done
+                        (loop $again
+                            (br_if $done (i64.eq (call $now) (i64.const -10)))
+                            (local.set $ok (i32.add (local.get $ok) (i32.const 1)))
+                            (br $again)))
+                    local.get $ok)
+            )
+        "#,
+        )
+        .unwrap();
+        let caps = CapabilitySet::from_grants(vec![crate::capability::CapabilityGrant::builder(
+            crate::capability::CapabilityType::SensorTime,
+        )
+        .build()]);
+        let limits = ResourceLimits {
+            max_host_calls: Some(5),
+            ..Default::default()
+        };
+
+        let mut sandbox = Sandbox::builder(&wasm, [0u8; 32])
+            .limits(limits)
+            .capabilities(caps)
+            .build()
+            .unwrap();
+        sandbox.initialize().unwrap();
+        let result = sandbox.invoke("spin", &[]).unwrap();
+        assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 5);
+        assert_eq!(crate::linker::error_codes::CALL_LIMIT, -10);
+
+        // Five calls answered plus the one refused
+        assert_eq!(sandbox.metrics().total_host_calls, 6);
+
+        // The count starts over with each invoke
+        let result = sandbox.invoke("spin", &[]).unwrap();
+        assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 5);
+        assert_eq!(sandbox.metrics().total_host_calls, 12);
+    }
+
+    #[test]
+    fn test_strict_capabilities_trap_on_denial() {
+        // Returns the storage read's code plus 100, so a result shows the
+        // Spirit kept running after the denial
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_storage_read" (func $read (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "key")
+                (func (export "run") (result i32)
+                    (i32.add
+                        (call $read (i32.const 0) (i32.const 3) (i32.const 16) (i32.const 16))
+                        (i32.const 100)))
+            )
+        "#,
+        )
+        .unwrap();
+        let sandbox_with = |strict_capabilities| {
+            let limits = ResourceLimits {
+                strict_capabilities,
+                ..Default::default()
+            };
+            let mut sandbox = Sandbox::builder(&wasm, [0u8; 32])
+                .limits(limits)
+                .capabilities(CapabilitySet::new())
+                .build()
+                .unwrap();
+            sandbox.initialize().unwrap();
+            sandbox
+        };
+
+        // Lenient (default): the Spirit sees -1 and carries on
+        assert!(!ResourceLimits::default().strict_capabilities);
+        let mut lenient = sandbox_with(false);
+        let result = lenient.invoke("run", &[]).unwrap();
+        assert!(result.success);
+        assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 99);
+        assert_eq!(lenient.get_state(), SandboxState::Ready);
+
+        // Strict: the denied read ends the invoke
+        let mut strict = sandbox_with(true);
+        match strict.invoke("run", &[]) {
+            Err(SandboxError::CapabilityDenied(message)) => {
+                assert!(message.contains("StorageRead"), "{}", message);
+                assert!(message.contains("host_storage_read"), "{}", message);
+            }
+            other => panic!(
+                "expected CapabilityDenied, got {:?}",
+                other.map(|r| r.outcome)
+            ),
+        }
+        assert_eq!(strict.get_state(), SandboxState::Failed);
+    }
+
+    #[test]
+    fn test_start_function_host_calls_rejected() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_log" (func $log (param i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "boot")
+                (func $boot (drop (call $log (i32.const 1) (i32.const 0) (i32.const 4))))
+                (func $init call $boot)
+                (start $init)
+                (func (export "run") (result i32) i32.const 1)
+            )
+        "#,
+        )
+        .unwrap();
+        let mut sandbox = Sandbox::builder(&wasm, [0u8; 32]).build().unwrap();
+        match sandbox.initialize() {
+            Err(SandboxError::InvalidModule(msg)) => {
+                assert!(msg.contains("vudo.host_log"), "{}", msg)
+            }
+            other => panic!("expected InvalidModule, got {:?}", other),
+        }
+        assert_eq!(sandbox.state, SandboxState::Failed);
+        assert!(SpiritImage::new(&wasm).is_err());
+
+        // A start function that only computes is fine
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_log" (func $log (param i32 i32 i32) (result i32)))
+                (global $ready (mut i32) (i32.const 0))
+                (func $init (global.set $ready (i32.const 1)))
+                (start $init)
+                (func (export "ready") (result i32) global.get $ready)
+            )
+        "#,
+        )
+        .unwrap();
+        let mut sandbox = Sandbox::builder(&wasm, [0u8; 32]).build().unwrap();
+        sandbox.initialize().unwrap();
+        let result = sandbox.invoke("ready", &[]).unwrap();
+        assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 1);
+    }
+
+    #[test]
+    fn test_custom_host_namespace() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo_v1" "host_time_now" (func $now (result i64)))
+                (func (export "now") (result i64) call $now)
+            )
+        "#,
+        )
+        .unwrap();
+        let caps = CapabilitySet::from_grants(vec![crate::capability::CapabilityGrant::builder(
+            crate::capability::CapabilityType::SensorTime,
+        )
+        .build()]);
+
+        let mut sandbox = Sandbox::builder(&wasm, [0u8; 32])
+            .host_namespace("vudo_v1")
+            .capabilities(caps.clone())
+            .build()
+            .unwrap();
+        sandbox.initialize().unwrap();
+        let result = sandbox.invoke("now", &[]).unwrap();
+        assert!(result.return_value.unwrap()[0].unwrap_i64() > 0);
+
+        // Images take the namespace too
+        let image = SpiritImage::new(&wasm)
+            .unwrap()
+            .with_host_namespace("vudo_v1");
+        let mut sandbox = image
+            .instantiate(
+                [0u8; 32],
+                ResourceLimits::default(),
+                Arc::new(crate::host::InMemoryStorage::new()),
+                Arc::new(crate::host::InMemoryCreditLedger::new()),
+                Arc::new(crate::host::MockNetworkBackend::new()),
+                caps.clone(),
+            )
+            .unwrap();
+        let result = sandbox.invoke("now", &[]).unwrap();
+        assert!(result.return_value.unwrap()[0].unwrap_i64() > 0);
 
-        assert_ne!(sandbox1.id, sandbox2.id);
+        // The default namespace does not provide it
+        let mut sandbox = Sandbox::builder(&wasm, [0u8; 32])
+            .capabilities(caps)
+            .build()
+            .unwrap();
+        sandbox.initialize().unwrap();
+        assert!(matches!(
+            sandbox.invoke("now", &[]),
+            Err(SandboxError::MissingImport(name)) if name == "host_time_now"
+        ));
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // MULTIPLE EXECUTION AND METRICS TESTS
-    // ═══════════════════════════════════════════════════════════════════════════
-
     #[test]
-    fn test_sandbox_multiple_executions() {
+    fn test_deterministic_mode_canonicalizes_nans() {
+        // 0/0 and sqrt(-1) yield a NaN whose sign and payload depend on the
+        // CPU (x86 sets the sign bit, ARM does not). Taking the operands as
+        // parameters keeps the compiler from folding them.
         let wasm = wat::parse_str(
             r#"
             (module
-                (func (export "add") (param i32 i32) (result i32)
-                    local.get 0
-                    local.get 1
-                    i32.add
+                (func (export "nan32") (param f32) (result i32)
+                    (i32.reinterpret_f32
+                        (f32.add
+                            (f32.div (local.get 0) (local.get 0))
+                            (f32.const 1)))
+                )
+                (func (export "nan64") (param f64) (result i64)
+                    (i64.reinterpret_f64 (f64.sqrt (f64.neg (local.get 0))))
                 )
             )
         "#,
         )
         .unwrap();
-        let owner = [0u8; 32];
-        let limits = ResourceLimits::default();
+        let limits = ResourceLimits {
+            deterministic: true,
+            ..Default::default()
+        };
 
-        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
         sandbox.initialize().unwrap();
 
-        // Execute multiple times
-        for i in 0..5 {
-            let args = vec![Val::I32(i), Val::I32(i)];
-            let result = sandbox.invoke("add", &args).unwrap();
-            assert!(result.success);
-            assert_eq!(result.return_value.as_ref().unwrap()[0].unwrap_i32(), i * 2);
-        }
-
-        // Check metrics
-        let metrics = sandbox.metrics();
-        assert_eq!(metrics.execution_count, 5);
-        assert!(metrics.total_fuel_consumed > 0);
-    }
+        let result = sandbox.invoke("nan32", &[Val::F32(0)]).unwrap();
+        assert_eq!(
+            result.return_value.unwrap()[0].unwrap_i32() as u32,
+            0x7fc0_0000
+        );
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // FUNCTION NOT FOUND TEST
-    // ═══════════════════════════════════════════════════════════════════════════
+        let result = sandbox
+            .invoke("nan64", &[Val::F64(1f64.to_bits())])
+            .unwrap();
+        assert_eq!(
+            result.return_value.unwrap()[0].unwrap_i64() as u64,
+            0x7ff8_0000_0000_0000
+        );
 
-    #[test]
-    fn test_sandbox_function_not_found() {
-        let wasm =
-            wat::parse_str(r#"(module (func (export "test") (result i32) i32.const 42))"#).unwrap();
-        let owner = [0u8; 32];
-        let limits = ResourceLimits::default();
+        // Images skip canonicalization, so they refuse deterministic limits
+        let image = SpiritImage::new(&wasm).unwrap();
+        assert!(image
+            .instantiate_with_defaults(
+                [0u8; 32],
+                ResourceLimits {
+                    deterministic: true,
+                    ..Default::default()
+                }
+            )
+            .is_err());
+    }
 
-        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+    fn probed(wat: &str, limits: ResourceLimits) -> HealthStatus {
+        let wasm = wat::parse_str(wat).unwrap();
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
         sandbox.initialize().unwrap();
+        let fuel_before = sandbox.store.get_fuel().ok();
+        let status = sandbox.health();
+        assert_eq!(sandbox.store.get_fuel().ok(), fuel_before);
+        assert_eq!(sandbox.get_state(), SandboxState::Ready);
+        status
+    }
 
-        let result = sandbox.invoke("nonexistent", &[]);
-        assert!(result.is_err());
+    #[test]
+    fn test_health_probe_statuses() {
+        let defaults = ResourceLimits::default;
+        assert_eq!(
+            probed(
+                r#"(module (func (export "__vudo_health") (result i32) i32.const 0))"#,
+                defaults()
+            ),
+            HealthStatus::Healthy
+        );
+        assert_eq!(
+            probed(
+                r#"(module (func (export "__vudo_health") (result i32) i32.const 3))"#,
+                defaults()
+            ),
+            HealthStatus::Unhealthy(3)
+        );
+        assert_eq!(
+            probed(r#"(module (func (export "main")))"#, defaults()),
+            HealthStatus::NoProbe
+        );
+        assert!(matches!(
+            probed(
+                r#"(module (func (export "__vudo_health") (result i32) unreachable))"#,
+                defaults()
+            ),
+            HealthStatus::Trapped(_)
+        ));
+    }
 
-        match result.unwrap_err() {
-            SandboxError::FunctionNotFound(name) => {
-                assert_eq!(name, "nonexistent");
-            }
-            _ => panic!("Expected FunctionNotFound error"),
+    #[test]
+    fn test_health_probe_budget_stops_hung_probe() {
+        let spin =
+            r#"(module (func (export "__vudo_health") (result i32) (loop (br 0)) i32.const 0))"#;
+
+        // Cut off by its fuel...
+        match probed(spin, ResourceLimits::default()) {
+            HealthStatus::Trapped(msg) => assert!(msg.contains(HEALTH_EXPORT), "{}", msg),
+            other => panic!("Expected Trapped, got {:?}", other),
         }
+
+        // ...or, without fuel, by its timeout
+        let started = Instant::now();
+        let epochs = ResourceLimits {
+            metering: MeteringMode::EpochOnly,
+            ..Default::default()
+        };
+        assert!(matches!(probed(spin, epochs), HealthStatus::Trapped(_)));
+        assert!(started.elapsed() < Duration::from_secs(DEFAULT_MAX_DURATION_SECS));
     }
 
     // ═══════════════════════════════════════════════════════════════════════════
@@ -1098,95 +3642,370 @@ mod tests {
     }
 
     #[test]
-    fn test_sandbox_fuel_exhaustion() {
-        let wasm = create_loop_wasm();
-        let owner = [0u8; 32];
+    fn test_sandbox_fuel_exhaustion() {
+        let wasm = create_loop_wasm();
+        let owner = [0u8; 32];
+        let limits = ResourceLimits {
+            max_fuel: 100, // Very low fuel
+            ..Default::default()
+        };
+
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        // Run a loop that will exhaust fuel
+        let result = sandbox.invoke("loop", &[Val::I32(1000000)]).unwrap();
+
+        assert!(!result.success);
+        assert!(result.error.is_some());
+        assert_eq!(sandbox.get_state(), SandboxState::Paused);
+    }
+
+    #[test]
+    fn test_sandbox_refuel() {
+        let wasm = create_loop_wasm();
+        let owner = [0u8; 32];
+        let limits = ResourceLimits {
+            max_fuel: 100, // Very low fuel
+            ..Default::default()
+        };
+
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        // Exhaust fuel
+        let _ = sandbox.invoke("loop", &[Val::I32(1000000)]);
+        assert_eq!(sandbox.get_state(), SandboxState::Paused);
+
+        // Refuel
+        sandbox.refuel(1_000_000).unwrap();
+        assert_eq!(sandbox.get_state(), SandboxState::Ready);
+
+        // Should be able to execute again
+        let result = sandbox.invoke("loop", &[Val::I32(10)]).unwrap();
+        assert!(result.success);
+    }
+
+    #[test]
+    fn test_fuel_accounting_reconciles_across_refuels() {
+        let wasm = create_loop_wasm();
+        let limits = ResourceLimits {
+            max_fuel: 500,
+            ..Default::default()
+        };
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        let mut supplied = 500;
+        let mut invoked = 0;
+
+        let result = sandbox.invoke("loop", &[Val::I32(10)]).unwrap();
+        assert!(result.success);
+        invoked += result.fuel_consumed;
+
+        // Run out partway, then refuel and carry on
+        let result = sandbox.invoke("loop", &[Val::I32(1_000_000)]).unwrap();
+        assert_eq!(result.outcome, ExecutionOutcome::OutOfFuel);
+        invoked += result.fuel_consumed;
+
+        sandbox.refuel(2_000).unwrap();
+        supplied += 2_000;
+        let result = sandbox.invoke("loop", &[Val::I32(20)]).unwrap();
+        assert!(result.success);
+        invoked += result.fuel_consumed;
+
+        // Refuelling a Ready sandbox with fuel left over
+        sandbox.refuel(300).unwrap();
+        supplied += 300;
+        let result = sandbox.invoke("loop", &[Val::I32(5)]).unwrap();
+        assert!(result.success);
+        invoked += result.fuel_consumed;
+
+        let remaining = sandbox.store.get_fuel().unwrap();
+        assert_eq!(sandbox.fuel_consumed, invoked);
+        assert_eq!(sandbox.fuel_consumed, supplied - remaining);
+        assert_eq!(sandbox.metrics().total_fuel_consumed, sandbox.fuel_consumed);
+    }
+
+    #[test]
+    fn test_start_function_fuel_is_counted() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (global $ready (mut i32) (i32.const 0))
+                (func $init
+                    (local $i i32)
+                    (local.set $i (i32.const 50))
+                    (loop $l
+                        (local.set $i (i32.sub (local.get $i) (i32.const 1)))
+                        (br_if $l (local.get $i)))
+                    (global.set $ready (i32.const 1)))
+                (start $init)
+                (func (export "ready") (result i32) (global.get $ready))
+            )
+        "#,
+        )
+        .unwrap();
+        let limits = ResourceLimits::default();
+        let max_fuel = limits.max_fuel;
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        let result = sandbox.invoke("ready", &[]).unwrap();
+        assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 1);
+
+        let burned = max_fuel - sandbox.store.get_fuel().unwrap();
+        assert!(burned > result.fuel_consumed);
+        assert_eq!(sandbox.fuel_consumed, burned);
+        assert_eq!(sandbox.metrics().total_fuel_consumed, burned);
+    }
+
+    #[test]
+    fn test_host_limit_query_reports_limits() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_limit_query" (func $query (param i32) (result i64)))
+                (func (export "query") (param i32) (result i64)
+                    (call $query (local.get 0))
+                )
+            )
+        "#,
+        )
+        .unwrap();
+        let limits = ResourceLimits {
+            memory_bytes: 8 * 1024 * 1024,
+            max_fuel: 50_000,
+            ..Default::default()
+        };
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        let mut query = |field: i32| {
+            let result = sandbox.invoke("query", &[Val::I32(field)]).unwrap();
+            result.return_value.unwrap()[0].unwrap_i64()
+        };
+
+        assert_eq!(query(limit_fields::MEMORY_BYTES), 8 * 1024 * 1024);
+        assert_eq!(query(limit_fields::MAX_FUEL), 50_000);
+        let remaining = query(limit_fields::REMAINING_FUEL);
+        assert!(remaining > 0 && remaining < 50_000, "{}", remaining);
+        assert!(query(limit_fields::ELAPSED_NANOS) >= 0);
+        assert_eq!(query(99), -1);
+        assert_eq!(query(-1), -1);
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // ADDITIONAL RESOURCE LIMITS TESTS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    #[test]
+    fn test_resource_limits_negative_cpu_quota() {
+        let limits = ResourceLimits {
+            cpu_quota: -0.5,
+            ..Default::default()
+        };
+
+        let result = limits.validate();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_resource_limits_zero_max_fuel() {
+        let limits = ResourceLimits {
+            max_fuel: 0,
+            ..Default::default()
+        };
+
+        let result = limits.validate();
+        assert!(result.is_err());
+
+        match result.unwrap_err() {
+            SandboxError::InvalidModule(msg) => {
+                assert!(msg.contains("max_fuel"));
+            }
+            _ => panic!("Expected InvalidModule error"),
+        }
+    }
+
+    #[test]
+    fn test_resource_limits_default_values() {
+        let limits = ResourceLimits::default();
+
+        assert_eq!(limits.memory_bytes, DEFAULT_MEMORY_BYTES);
+        assert_eq!(limits.cpu_quota, DEFAULT_CPU_QUOTA);
+        assert_eq!(limits.max_fuel, DEFAULT_MAX_FUEL);
+        assert_eq!(
+            limits.max_duration,
+            Duration::from_secs(DEFAULT_MAX_DURATION_SECS)
+        );
+        assert!(limits.validate().is_ok());
+    }
+
+    #[test]
+    fn test_resource_limits_stack_bounds() {
+        let limits = ResourceLimits {
+            max_stack_bytes: 0,
+            ..Default::default()
+        };
+        assert!(limits.validate().is_err());
+
+        let limits = ResourceLimits {
+            max_stack_bytes: MAX_STACK_BYTES + 1,
+            ..Default::default()
+        };
+        assert!(limits.validate().is_err());
+
+        let limits = ResourceLimits {
+            max_stack_bytes: MAX_STACK_BYTES,
+            ..Default::default()
+        };
+        assert!(limits.validate().is_ok());
+    }
+
+    #[test]
+    fn test_resource_limits_duration_above_ceiling() {
+        let limits = ResourceLimits {
+            max_duration: Duration::from_secs(10),
+            max_duration_ceiling: Duration::from_secs(5),
+            ..Default::default()
+        };
+
+        match limits.validate().unwrap_err() {
+            SandboxError::InvalidModule(msg) => assert!(msg.contains("max_duration_ceiling")),
+            _ => panic!("Expected InvalidModule error"),
+        }
+    }
+
+    #[test]
+    fn test_resource_limits_zero_instances() {
+        let limits = ResourceLimits {
+            max_instances: 0,
+            ..Default::default()
+        };
+        match limits.validate().unwrap_err() {
+            SandboxError::InvalidModule(msg) => assert!(msg.contains("max_instances")),
+            _ => panic!("Expected InvalidModule error"),
+        }
+    }
+
+    #[test]
+    fn test_resource_limits_table_elements_ceiling() {
         let limits = ResourceLimits {
-            max_fuel: 100, // Very low fuel
+            max_table_elements: MAX_TABLE_ELEMENTS + 1,
             ..Default::default()
         };
+        match limits.validate().unwrap_err() {
+            SandboxError::InvalidModule(msg) => assert!(msg.contains("max_table_elements")),
+            _ => panic!("Expected InvalidModule error"),
+        }
 
-        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
-        sandbox.initialize().unwrap();
-
-        // Run a loop that will exhaust fuel
-        let result = sandbox.invoke("loop", &[Val::I32(1000000)]).unwrap();
-
-        assert!(!result.success);
-        assert!(result.error.is_some());
-        assert_eq!(sandbox.get_state(), SandboxState::Paused);
+        let limits = ResourceLimits {
+            max_table_elements: MAX_TABLE_ELEMENTS,
+            ..Default::default()
+        };
+        assert!(limits.validate().is_ok());
     }
 
     #[test]
-    fn test_sandbox_refuel() {
-        let wasm = create_loop_wasm();
-        let owner = [0u8; 32];
+    fn test_table_elements_limit_enforced() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (table 4 funcref)
+                (func (export "grow") (param i32) (result i32)
+                    (table.grow (ref.null func) (local.get 0))
+                )
+            )
+        "#,
+        )
+        .unwrap();
         let limits = ResourceLimits {
-            max_fuel: 100, // Very low fuel
+            max_table_elements: 8,
             ..Default::default()
         };
 
-        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
         sandbox.initialize().unwrap();
 
-        // Exhaust fuel
-        let _ = sandbox.invoke("loop", &[Val::I32(1000000)]);
-        assert_eq!(sandbox.get_state(), SandboxState::Paused);
+        // Growing to the cap succeeds and returns the old size
+        let result = sandbox.invoke("grow", &[Val::I32(4)]).unwrap();
+        assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 4);
+        // One more element is refused
+        let result = sandbox.invoke("grow", &[Val::I32(1)]).unwrap();
+        assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), -1);
 
-        // Refuel
-        sandbox.refuel(1_000_000).unwrap();
-        assert_eq!(sandbox.get_state(), SandboxState::Ready);
+        // A table declared larger than the cap cannot be instantiated
+        let limits = ResourceLimits {
+            max_table_elements: 2,
+            ..Default::default()
+        };
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
+        sandbox.initialize().unwrap();
+        match sandbox.invoke("grow", &[Val::I32(0)]) {
+            Err(SandboxError::RuntimeError(msg)) => assert!(msg.contains("instantiate"), "{}", msg),
+            other => panic!("expected instantiation failure, got {:?}", other),
+        }
+    }
 
-        // Should be able to execute again
-        let result = sandbox.invoke("loop", &[Val::I32(10)]).unwrap();
-        assert!(result.success);
+    #[test]
+    fn test_resource_limits_random_bytes_per_call_bounds() {
+        for max in [0, MAX_RANDOM_BYTES + 1] {
+            let limits = ResourceLimits {
+                max_random_bytes_per_call: max,
+                ..Default::default()
+            };
+            match limits.validate().unwrap_err() {
+                SandboxError::InvalidModule(msg) => {
+                    assert!(msg.contains("max_random_bytes_per_call"))
+                }
+                _ => panic!("Expected InvalidModule error"),
+            }
+        }
+
+        let limits = ResourceLimits {
+            max_random_bytes_per_call: MAX_RANDOM_BYTES,
+            ..Default::default()
+        };
+        assert!(limits.validate().is_ok());
     }
 
     // ═══════════════════════════════════════════════════════════════════════════
-    // ADDITIONAL RESOURCE LIMITS TESTS
+    // STACK LIMIT TESTS
     // ═══════════════════════════════════════════════════════════════════════════
 
     #[test]
-    fn test_resource_limits_negative_cpu_quota() {
+    fn test_sandbox_stack_overflow() {
+        let wasm = create_recursive_wasm();
+        let owner = [0u8; 32];
         let limits = ResourceLimits {
-            cpu_quota: -0.5,
+            max_stack_bytes: 32 * 1024,
             ..Default::default()
         };
 
-        let result = limits.validate();
-        assert!(result.is_err());
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        let result = sandbox.invoke("recurse", &[Val::I32(5_000)]);
+        assert!(matches!(result, Err(SandboxError::StackOverflow)));
+        assert_eq!(sandbox.metrics().trap_count, 1);
     }
 
     #[test]
-    fn test_resource_limits_zero_max_fuel() {
+    fn test_sandbox_deep_recursion_with_larger_stack() {
+        let wasm = create_recursive_wasm();
+        let owner = [0u8; 32];
         let limits = ResourceLimits {
-            max_fuel: 0,
+            max_stack_bytes: 1024 * 1024,
             ..Default::default()
         };
 
-        let result = limits.validate();
-        assert!(result.is_err());
-
-        match result.unwrap_err() {
-            SandboxError::InvalidModule(msg) => {
-                assert!(msg.contains("max_fuel"));
-            }
-            _ => panic!("Expected InvalidModule error"),
-        }
-    }
-
-    #[test]
-    fn test_resource_limits_default_values() {
-        let limits = ResourceLimits::default();
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        sandbox.initialize().unwrap();
 
-        assert_eq!(limits.memory_bytes, DEFAULT_MEMORY_BYTES);
-        assert_eq!(limits.cpu_quota, DEFAULT_CPU_QUOTA);
-        assert_eq!(limits.max_fuel, DEFAULT_MAX_FUEL);
-        assert_eq!(
-            limits.max_duration,
-            Duration::from_secs(DEFAULT_MAX_DURATION_SECS)
-        );
-        assert!(limits.validate().is_ok());
+        let result = sandbox.invoke("recurse", &[Val::I32(5_000)]).unwrap();
+        assert!(result.success);
+        assert_eq!(result.return_value.as_ref().unwrap()[0].unwrap_i32(), 5_000);
     }
 
     // ═══════════════════════════════════════════════════════════════════════════
@@ -1226,6 +4045,54 @@ mod tests {
         assert!(sandbox.has_capability(CapabilityType::NetworkConnect));
     }
 
+    #[test]
+    fn test_granted_capability_reaches_host_calls() {
+        use crate::capability::{CapabilityGrant as HostGrant, CapabilityType as HostType};
+        use crate::linker::{HOST_ERROR, HOST_SUCCESS};
+
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_storage_write" (func $write (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "key")
+                (func (export "write") (result i32)
+                    (call $write (i32.const 0) (i32.const 3) (i32.const 0) (i32.const 3))
+                )
+            )
+        "#,
+        )
+        .unwrap();
+        let owner = [0u8; 32];
+
+        let mut sandbox =
+            Sandbox::new_with_defaults(&wasm, owner, ResourceLimits::default()).unwrap();
+        sandbox.initialize().unwrap();
+
+        let denied = sandbox.invoke("write", &[]).unwrap();
+        assert_eq!(denied.return_value.unwrap()[0].unwrap_i32(), HOST_ERROR);
+
+        sandbox.grant_capability(CapabilityGrant {
+            id: 1,
+            capability: CapabilityType::StorageWrite,
+            scope: CapabilityScope::Global,
+            granter: [0u8; 32],
+            grantee: owner,
+            granted_at: 1000,
+            expires_at: None,
+            revoked: false,
+            signature: [0u8; 64],
+        });
+        let allowed = sandbox.invoke("write", &[]).unwrap();
+        assert_eq!(allowed.return_value.unwrap()[0].unwrap_i32(), HOST_SUCCESS);
+
+        // A whole set applies the same way
+        assert!(!sandbox.has_capability(CapabilityType::StorageRead));
+        let read = HostGrant::builder(HostType::StorageRead).id(2).build();
+        sandbox.grant_all(CapabilitySet::from_grants(vec![read]));
+        assert!(sandbox.has_capability(CapabilityType::StorageRead));
+    }
+
     #[test]
     fn test_sandbox_expired_capability() {
         let wasm =
@@ -1299,6 +4166,30 @@ mod tests {
         assert_eq!(metrics.execution_count, 1);
         assert!(metrics.total_fuel_consumed > 0);
         assert!(metrics.total_duration > Duration::from_secs(0));
+        assert_eq!(metrics.peak_memory, 0);
+    }
+
+    #[test]
+    fn test_memory_used_is_linear_memory_size() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "grow") (result i32)
+                    (memory.grow (i32.const 2))
+                )
+            )
+        "#,
+        )
+        .unwrap();
+        let mut sandbox =
+            Sandbox::new_with_defaults(&wasm, [0u8; 32], ResourceLimits::default()).unwrap();
+        sandbox.initialize().unwrap();
+
+        let result = sandbox.invoke("grow", &[]).unwrap();
+        assert!(result.success);
+        assert_eq!(result.memory_used, 3 * 65536);
+        assert_eq!(sandbox.metrics().peak_memory, 3 * 65536);
     }
 
     #[test]
@@ -1320,6 +4211,76 @@ mod tests {
         assert_eq!(metrics.trap_count, 1);
     }
 
+    #[test]
+    fn test_sandbox_host_abort_returns_wasm_trap() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_abort" (func $abort (param i32 i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "boom")
+                (func (export "run")
+                    (call $abort (i32.const 0) (i32.const 4))
+                )
+            )
+        "#,
+        )
+        .unwrap();
+        let owner = [0u8; 32];
+
+        let mut sandbox =
+            Sandbox::new_with_defaults(&wasm, owner, ResourceLimits::default()).unwrap();
+        sandbox.initialize().unwrap();
+
+        match sandbox.invoke("run", &[]) {
+            Err(SandboxError::WasmTrap(message)) => assert!(message.contains("boom")),
+            other => panic!("expected WasmTrap, got {:?}", other),
+        }
+        assert_eq!(sandbox.get_state(), SandboxState::Failed);
+        assert_eq!(sandbox.metrics().trap_count, 1);
+    }
+
+    #[test]
+    fn test_sandbox_trap_returns_partial_output() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_set_output" (func $set_output (param i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "row1;row2;row3")
+                (func (export "rows") (result i32)
+                    (drop (call $set_output (i32.const 0) (i32.const 5)))
+                    (drop (call $set_output (i32.const 0) (i32.const 10)))
+                    unreachable
+                )
+                (func (export "done") (result i32)
+                    (drop (call $set_output (i32.const 0) (i32.const 14)))
+                    i32.const 3
+                )
+            )
+        "#,
+        )
+        .unwrap();
+        let owner = [0u8; 32];
+
+        let mut sandbox =
+            Sandbox::new_with_defaults(&wasm, owner, ResourceLimits::default()).unwrap();
+        sandbox.initialize().unwrap();
+
+        let result = sandbox.invoke("done", &[]).unwrap();
+        assert!(result.success);
+        assert_eq!(result.partial_output, None);
+
+        // The last published output survives the trap
+        let result = sandbox.invoke("rows", &[]).unwrap();
+        assert_eq!(
+            result.outcome,
+            ExecutionOutcome::Trap(TrapKind::Unreachable)
+        );
+        assert!(result.error.is_some());
+        assert_eq!(result.partial_output.as_deref(), Some(&b"row1;row2;"[..]));
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // TERMINATION TESTS
     // ═══════════════════════════════════════════════════════════════════════════
@@ -1339,39 +4300,204 @@ mod tests {
         assert_eq!(sandbox.get_state(), SandboxState::Terminated);
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════
-    // INITIALIZATION TESTS
-    // ═══════════════════════════════════════════════════════════════════════════
+    // ═══════════════════════════════════════════════════════════════════════════
+    // INITIALIZATION TESTS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    #[test]
+    fn test_sandbox_double_initialize() {
+        let wasm =
+            wat::parse_str(r#"(module (func (export "test") (result i32) i32.const 42))"#).unwrap();
+        let owner = [0u8; 32];
+        let limits = ResourceLimits::default();
+
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        // Second initialization should fail
+        let result = sandbox.initialize();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_sandbox_invalid_wasm_module() {
+        // Invalid WASM bytes (not a valid module)
+        let wasm = vec![0x00, 0x01, 0x02, 0x03];
+        let owner = [0u8; 32];
+        let limits = ResourceLimits::default();
+
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
+        let result = sandbox.initialize();
+
+        assert!(result.is_err());
+        assert_eq!(sandbox.get_state(), SandboxState::Failed);
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // MODULE VALIDATION TESTS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    const SECTIONS_WAT: &str = r#"
+        (module
+            (import "vudo" "host_time_now" (func (result i64)))
+            (import "vudo" "host_log" (func (param i32 i32 i32) (result i32)))
+            (memory 3)
+            (global (mut i32) (i32.const 0))
+            (global (mut i32) (i32.const 0))
+            (func (export "a") (result i32) i32.const 1)
+            (func (export "b") (result i32) i32.const 2)
+            (func (export "c") (result i32) i32.const 3)
+        )
+    "#;
+
+    fn initialize_with(limits: ResourceLimits) -> Result<(), SandboxError> {
+        let wasm = wat::parse_str(SECTIONS_WAT).unwrap();
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
+        let result = sandbox.initialize();
+        if result.is_err() {
+            assert_eq!(sandbox.get_state(), SandboxState::Failed);
+        }
+        result
+    }
+
+    fn rejected_for(limits: ResourceLimits) -> String {
+        match initialize_with(limits) {
+            Err(SandboxError::InvalidModule(msg)) => msg,
+            other => panic!("expected InvalidModule, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_module_stats_parse() {
+        let wasm = wat::parse_str(SECTIONS_WAT).unwrap();
+        let stats = ModuleStats::parse(&wasm).unwrap();
+
+        assert_eq!(
+            stats,
+            ModuleStats {
+                functions: 3,
+                imports: 2,
+                memory_pages: 3,
+                memories: 1,
+                globals: 2,
+            }
+        );
+        assert!(ModuleStats::parse(&[0x00, 0x61, 0x73]).is_err());
+    }
+
+    #[test]
+    fn test_required_capabilities() {
+        use crate::capability::CapabilityType;
+
+        let wasm = wat::parse_str(SECTIONS_WAT).unwrap();
+        assert_eq!(
+            required_capabilities(&wasm).unwrap(),
+            vec![CapabilityType::SensorTime, CapabilityType::ActuatorLog]
+        );
+        assert!(required_capabilities(&[0x00, 0x61, 0x73]).is_err());
+    }
+
+    #[test]
+    fn test_required_capabilities_in_namespace() {
+        use crate::capability::CapabilityType;
+
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo_v1" "host_time_now" (func (result i64)))
+                (import "vudo" "host_log" (func (param i32 i32 i32) (result i32)))
+            )
+        "#,
+        )
+        .unwrap();
+        assert_eq!(
+            required_capabilities_in(&wasm, "vudo_v1").unwrap(),
+            vec![CapabilityType::SensorTime]
+        );
+        assert_eq!(
+            required_capabilities(&wasm).unwrap(),
+            vec![CapabilityType::ActuatorLog]
+        );
+    }
+
+    #[test]
+    fn test_module_within_limits_initializes() {
+        let limits = ResourceLimits {
+            max_functions: 3,
+            max_imports: 2,
+            max_memory_pages: 3,
+            max_globals: 2,
+            ..Default::default()
+        };
+        assert!(initialize_with(limits).is_ok());
+    }
+
+    #[test]
+    fn test_module_exceeding_max_functions() {
+        let msg = rejected_for(ResourceLimits {
+            max_functions: 2,
+            ..Default::default()
+        });
+        assert!(msg.contains("max_functions"), "{}", msg);
+    }
 
     #[test]
-    fn test_sandbox_double_initialize() {
-        let wasm =
-            wat::parse_str(r#"(module (func (export "test") (result i32) i32.const 42))"#).unwrap();
-        let owner = [0u8; 32];
-        let limits = ResourceLimits::default();
+    fn test_module_exceeding_max_imports() {
+        let msg = rejected_for(ResourceLimits {
+            max_imports: 1,
+            ..Default::default()
+        });
+        assert!(msg.contains("max_imports"), "{}", msg);
+    }
 
-        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
-        sandbox.initialize().unwrap();
+    #[test]
+    fn test_module_exceeding_max_memory_pages() {
+        let msg = rejected_for(ResourceLimits {
+            max_memory_pages: 2,
+            ..Default::default()
+        });
+        assert!(msg.contains("max_memory_pages"), "{}", msg);
 
-        // Second initialization should fail
-        let result = sandbox.initialize();
-        assert!(result.is_err());
+        // Imported memories count too
+        let wasm = wat::parse_str(r#"(module (import "env" "memory" (memory 5)))"#).unwrap();
+        let stats = ModuleStats::parse(&wasm).unwrap();
+        assert_eq!(stats.memory_pages, 5);
     }
 
     #[test]
-    fn test_sandbox_invalid_wasm_module() {
-        // Invalid WASM bytes (not a valid module)
-        let wasm = vec![0x00, 0x01, 0x02, 0x03];
-        let owner = [0u8; 32];
-        let limits = ResourceLimits::default();
-
-        let mut sandbox = Sandbox::new_with_defaults(&wasm, owner, limits).unwrap();
-        let result = sandbox.initialize();
+    fn test_module_with_multiple_memories_rejected() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "env" "memory" (memory 1))
+                (memory (export "memory") 1)
+                (func (export "run"))
+            )
+        "#,
+        )
+        .unwrap();
+        assert_eq!(ModuleStats::parse(&wasm).unwrap().memories, 2);
 
-        assert!(result.is_err());
+        let mut sandbox =
+            Sandbox::new_with_defaults(&wasm, [0u8; 32], ResourceLimits::default()).unwrap();
+        match sandbox.initialize() {
+            Err(SandboxError::InvalidModule(msg)) => {
+                assert_eq!(msg, "multiple memories unsupported")
+            }
+            other => panic!("expected InvalidModule, got {:?}", other),
+        }
         assert_eq!(sandbox.get_state(), SandboxState::Failed);
     }
 
+    #[test]
+    fn test_module_exceeding_max_globals() {
+        let msg = rejected_for(ResourceLimits {
+            max_globals: 1,
+            ..Default::default()
+        });
+        assert!(msg.contains("max_globals"), "{}", msg);
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // ERROR DISPLAY TESTS
     // ═══════════════════════════════════════════════════════════════════════════
@@ -1402,6 +4528,15 @@ mod tests {
                 SandboxError::FunctionNotFound("fn".to_string()),
                 "Function not found: fn",
             ),
+            (SandboxError::StackOverflow, "WASM stack overflow"),
+            (
+                SandboxError::IncompatibleRuntime("abi".to_string()),
+                "Incompatible runtime: abi",
+            ),
+            (
+                SandboxError::MissingImport("host_fn".to_string()),
+                "Missing import: host_fn",
+            ),
         ];
 
         for (error, expected_msg) in errors {
@@ -1427,6 +4562,262 @@ mod tests {
         assert_eq!(debug_str, "Ready");
     }
 
+    const ALL_STATES: [SandboxState; 6] = [
+        SandboxState::Initializing,
+        SandboxState::Ready,
+        SandboxState::Running,
+        SandboxState::Paused,
+        SandboxState::Terminated,
+        SandboxState::Failed,
+    ];
+
+    #[test]
+    fn test_sandbox_state_transition_table() {
+        use SandboxState::*;
+
+        let legal = [
+            (Initializing, Ready),
+            (Initializing, Failed),
+            (Initializing, Terminated),
+            (Ready, Running),
+            (Ready, Failed),
+            (Ready, Terminated),
+            (Running, Ready),
+            (Running, Paused),
+            (Running, Failed),
+            (Paused, Ready),
+            (Paused, Terminated),
+        ];
+
+        for from in ALL_STATES {
+            for to in ALL_STATES {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    legal.contains(&(from, to)),
+                    "{} -> {}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_sandbox_state_rejected_transitions() {
+        use SandboxState::*;
+
+        let rejected = [
+            (Initializing, Initializing),
+            (Initializing, Running),
+            (Initializing, Paused),
+            (Ready, Initializing),
+            (Ready, Ready),
+            (Ready, Paused),
+            (Running, Initializing),
+            (Running, Running),
+            (Running, Terminated),
+            (Paused, Initializing),
+            (Paused, Running),
+            (Paused, Paused),
+            (Paused, Failed),
+        ];
+        for (from, to) in rejected {
+            assert!(!from.can_transition_to(to), "{} -> {}", from, to);
+        }
+        for to in ALL_STATES {
+            assert!(!Terminated.can_transition_to(to), "terminated -> {}", to);
+            assert!(!Failed.can_transition_to(to), "failed -> {}", to);
+        }
+    }
+
+    #[test]
+    fn test_sandbox_state_terminal() {
+        for state in ALL_STATES {
+            let has_exit = ALL_STATES.iter().any(|&next| state.can_transition_to(next));
+            assert_eq!(state.is_terminal(), !has_exit, "{}", state);
+        }
+        assert!(SandboxState::Failed.is_terminal());
+        assert!(SandboxState::Terminated.is_terminal());
+    }
+
+    #[test]
+    fn test_sandbox_state_display_and_serialize() {
+        for state in ALL_STATES {
+            let json = serde_json::to_string(&state).unwrap();
+            assert_eq!(json, format!("\"{}\"", state));
+            let back: SandboxState = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, state);
+        }
+        assert_eq!(SandboxState::Initializing.to_string(), "initializing");
+    }
+
+    #[test]
+    fn test_paused_sandbox_requires_refuel() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (export "spin") (loop $l (br $l)))
+            )
+        "#,
+        )
+        .unwrap();
+        let limits = ResourceLimits {
+            max_fuel: 1000,
+            ..Default::default()
+        };
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
+        sandbox.initialize().unwrap();
+
+        sandbox.invoke("spin", &[]).unwrap();
+        assert_eq!(sandbox.get_state(), SandboxState::Paused);
+
+        // Paused -> Running is not a legal transition
+        assert!(sandbox.invoke("spin", &[]).is_err());
+
+        sandbox.refuel(1000).unwrap();
+        assert_eq!(sandbox.get_state(), SandboxState::Ready);
+    }
+
+    #[test]
+    fn test_failed_sandbox_stays_failed_on_terminate() {
+        let mut sandbox =
+            Sandbox::new_with_defaults(&[0x00, 0x01], [0u8; 32], ResourceLimits::default())
+                .unwrap();
+        assert!(sandbox.initialize().is_err());
+        assert_eq!(sandbox.get_state(), SandboxState::Failed);
+
+        sandbox.terminate();
+        assert_eq!(sandbox.get_state(), SandboxState::Failed);
+        assert!(sandbox.initialize().is_err());
+    }
+
+    fn abi_sandbox(version_export: &str) -> Sandbox {
+        let wasm = wat::parse_str(format!(
+            r#"
+            (module
+                {}
+                (func (export "answer") (result i32) (i32.const 42))
+            )
+        "#,
+            version_export
+        ))
+        .unwrap();
+        Sandbox::new_with_defaults(&wasm, [0u8; 32], ResourceLimits::default()).unwrap()
+    }
+
+    #[test]
+    fn test_matching_abi_version_initializes() {
+        let mut sandbox = abi_sandbox(&format!(
+            r#"(func (export "__vudo_abi_version") (result i32) (i32.const {}))"#,
+            VUDO_ABI_VERSION
+        ));
+        let fuel_before = sandbox.store.get_fuel().unwrap();
+
+        sandbox.initialize().unwrap();
+        assert_eq!(sandbox.get_state(), SandboxState::Ready);
+        assert_eq!(sandbox.store.get_fuel().unwrap(), fuel_before);
+        let result = sandbox.invoke("answer", &[]).unwrap();
+        assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 42);
+    }
+
+    #[test]
+    fn test_mismatched_abi_version_fails() {
+        let mut sandbox = abi_sandbox(&format!(
+            r#"(func (export "__vudo_abi_version") (result i32) (i32.const {}))"#,
+            VUDO_ABI_VERSION + 1
+        ));
+
+        let err = sandbox.initialize().unwrap_err();
+        assert!(
+            matches!(err, SandboxError::IncompatibleRuntime(_)),
+            "{}",
+            err
+        );
+        assert_eq!(sandbox.get_state(), SandboxState::Failed);
+    }
+
+    #[test]
+    fn test_image_checks_abi_version() {
+        let wasm = |version: i32| {
+            wat::parse_str(format!(
+                r#"(module (func (export "__vudo_abi_version") (result i32) (i32.const {})))"#,
+                version
+            ))
+            .unwrap()
+        };
+
+        let image = SpiritImage::new(&wasm(VUDO_ABI_VERSION + 1)).unwrap();
+        let err = image
+            .instantiate_with_defaults([0u8; 32], ResourceLimits::default())
+            .unwrap_err();
+        assert!(
+            matches!(err, SandboxError::IncompatibleRuntime(_)),
+            "{}",
+            err
+        );
+
+        let image = SpiritImage::new(&wasm(VUDO_ABI_VERSION)).unwrap();
+        let sandbox = image
+            .instantiate_with_defaults([0u8; 32], ResourceLimits::default())
+            .unwrap();
+        assert_eq!(sandbox.get_state(), SandboxState::Ready);
+    }
+
+    #[test]
+    fn test_absent_abi_version_is_compatible() {
+        let mut sandbox = abi_sandbox("");
+
+        sandbox.initialize().unwrap();
+        assert_eq!(sandbox.get_state(), SandboxState::Ready);
+        assert!(sandbox.instance.is_none());
+    }
+
+    /// Run a module that writes a marker into memory, then terminate it.
+    /// Returns whether the marker survived in the (still live) store.
+    fn marker_survives_terminate(scrub: bool) -> bool {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "fill")
+                    (i64.store (i32.const 4096) (i64.const 0x5EC2E75EC2E75EC2))
+                )
+            )
+        "#,
+        )
+        .unwrap();
+        let limits = ResourceLimits {
+            scrub_on_terminate: scrub,
+            ..Default::default()
+        };
+        let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
+        sandbox.initialize().unwrap();
+        sandbox.invoke("fill", &[]).unwrap();
+        sandbox.store.data_mut().set_last_error("secret");
+
+        let memory = sandbox
+            .instance
+            .unwrap()
+            .get_memory(&mut sandbox.store, "memory")
+            .unwrap();
+        let marker = 0x5EC2E75EC2E75EC2u64.to_le_bytes();
+        assert_eq!(&memory.data(&sandbox.store)[4096..4104], &marker);
+
+        sandbox.terminate();
+        assert_eq!(sandbox.get_state(), SandboxState::Terminated);
+        assert_eq!(sandbox.store.data().last_error().is_none(), scrub);
+        memory
+            .data(&sandbox.store)
+            .windows(marker.len())
+            .any(|window| window == marker)
+    }
+
+    #[test]
+    fn test_terminate_scrubs_memory_when_enabled() {
+        assert!(marker_survives_terminate(false));
+        assert!(!marker_survives_terminate(true));
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // EXECUTION RESULT TESTS
     // ═══════════════════════════════════════════════════════════════════════════
@@ -1460,6 +4851,72 @@ mod tests {
         assert_eq!(result.return_value.as_ref().unwrap()[0].unwrap_i32(), 42);
     }
 
+    #[test]
+    fn test_invoke_batch_stops_at_first_trap() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (global $count (export "count") (mut i32) (i32.const 0))
+                (func (export "bump") (param i32) (result i32)
+                    global.get $count
+                    local.get 0
+                    i32.add
+                    global.set $count
+                    global.get $count
+                )
+                (func (export "boom") unreachable)
+            )
+        "#,
+        )
+        .unwrap();
+        let mut sandbox =
+            Sandbox::new_with_defaults(&wasm, [0u8; 32], ResourceLimits::default()).unwrap();
+        sandbox.initialize().unwrap();
+
+        let calls = vec![
+            ("bump".to_string(), vec![Val::I32(5)]),
+            ("boom".to_string(), vec![]),
+            ("bump".to_string(), vec![Val::I32(5)]),
+        ];
+        let results = sandbox.invoke_batch(&calls).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].success);
+        assert_eq!(results[0].return_value.as_ref().unwrap()[0].unwrap_i32(), 5);
+        assert_eq!(
+            results[1].outcome,
+            ExecutionOutcome::Trap(TrapKind::Unreachable)
+        );
+        assert_eq!(sandbox.get_state(), SandboxState::Failed);
+
+        // The third call never ran against the shared global
+        let count = sandbox
+            .instance
+            .unwrap()
+            .get_global(&mut sandbox.store, "count")
+            .unwrap();
+        assert_eq!(count.get(&mut sandbox.store).unwrap_i32(), 5);
+        assert_eq!(sandbox.metrics().execution_count, 2);
+    }
+
+    #[test]
+    fn test_invoke_batch_resolves_every_function_first() {
+        let wasm = wat::parse_str(r#"(module (func (export "noop")))"#).unwrap();
+        let mut sandbox =
+            Sandbox::new_with_defaults(&wasm, [0u8; 32], ResourceLimits::default()).unwrap();
+        sandbox.initialize().unwrap();
+
+        let calls = vec![
+            ("noop".to_string(), vec![]),
+            ("missing".to_string(), vec![]),
+        ];
+        assert!(matches!(
+            sandbox.invoke_batch(&calls),
+            Err(SandboxError::FunctionNotFound(name)) if name == "missing"
+        ));
+        assert_eq!(sandbox.metrics().execution_count, 0);
+    }
+
     #[test]
     fn test_execution_result_with_void_function() {
         let wasm = wat::parse_str(
@@ -1488,6 +4945,209 @@ mod tests {
         assert!(result.return_value.as_ref().unwrap().is_empty());
     }
 
+    #[test]
+    fn test_fork_isolates_state() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (global $count (mut i32) (i32.const 0))
+                (func (export "increment") (result i32)
+                    global.get $count
+                    i32.const 1
+                    i32.add
+                    global.set $count
+                    global.get $count
+                )
+                (func (export "count") (result i32)
+                    global.get $count
+                )
+            )
+        "#,
+        )
+        .unwrap();
+        use crate::capability::{self, CapabilityScope};
+
+        let grant = capability::CapabilityGrant::builder(capability::CapabilityType::StorageRead)
+            .grantee([1u8; 32])
+            .build();
+        let mut original = Sandbox::builder(&wasm, [1u8; 32])
+            .capabilities(CapabilitySet::from_grants(vec![grant]))
+            .build()
+            .unwrap();
+        original.initialize().unwrap();
+        original.invoke("increment", &[]).unwrap();
+
+        let mut fork = original.fork([2u8; 32]).unwrap();
+        assert_eq!(fork.get_state(), SandboxState::Ready);
+        assert_ne!(fork.id, original.id);
+        assert_eq!(fork.owner, [2u8; 32]);
+        assert_eq!(fork.metrics().execution_count, 0);
+        assert!(Engine::same(&fork.engine, &original.engine));
+
+        let caps = &fork.store.data().capabilities;
+        assert!(caps.has_capability(
+            capability::CapabilityType::StorageRead,
+            CapabilityScope::Global
+        ));
+        assert!(caps.iter().all(|g| g.grantee == [2u8; 32]));
+
+        // The fork starts from the module's initial globals
+        for _ in 0..3 {
+            fork.invoke("increment", &[]).unwrap();
+        }
+        let forked = fork.invoke("count", &[]).unwrap();
+        assert_eq!(forked.return_value.unwrap()[0].unwrap_i32(), 3);
+
+        let untouched = original.invoke("count", &[]).unwrap();
+        assert_eq!(untouched.return_value.unwrap()[0].unwrap_i32(), 1);
+    }
+
+    #[test]
+    fn test_domain_scoped_storage_is_partitioned() {
+        use crate::capability::{CapabilityGrant, CapabilityScope, CapabilityType};
+
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_storage_write" (func $write (param i32 i32 i32 i32) (result i32)))
+                (import "vudo" "host_storage_read" (func $read (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "key")
+                (func (export "put") (param i32) (result i32)
+                    (i32.store (i32.const 16) (local.get 0))
+                    (call $write (i32.const 0) (i32.const 3) (i32.const 16) (i32.const 4))
+                )
+                (func (export "get") (result i32)
+                    (drop (call $read (i32.const 0) (i32.const 3) (i32.const 32) (i32.const 4)))
+                    (i32.load (i32.const 32))
+                )
+            )
+        "#,
+        )
+        .unwrap();
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let spawn = |scope: CapabilityScope, domain: &[u8]| {
+            let grants = [CapabilityType::StorageRead, CapabilityType::StorageWrite]
+                .into_iter()
+                .map(|cap| {
+                    CapabilityGrant::builder(cap)
+                        .scope(scope)
+                        .scope_target(domain.to_vec())
+                        .build()
+                })
+                .collect();
+            let mut sandbox = Sandbox::builder(&wasm, [1u8; 32])
+                .storage(storage.clone())
+                .capabilities(CapabilitySet::from_grants(grants))
+                .build()
+                .unwrap();
+            sandbox.initialize().unwrap();
+            sandbox
+        };
+        let put = |sandbox: &mut Sandbox, value: i32| {
+            let result = sandbox.invoke("put", &[Val::I32(value)]).unwrap();
+            assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 0);
+        };
+        let get = |sandbox: &mut Sandbox| {
+            let result = sandbox.invoke("get", &[]).unwrap();
+            result.return_value.unwrap()[0].unwrap_i32()
+        };
+
+        let mut alpha = spawn(CapabilityScope::Domain, b"alpha");
+        let mut beta = spawn(CapabilityScope::Domain, b"beta");
+        let mut global = spawn(CapabilityScope::Global, b"alpha");
+
+        put(&mut alpha, 1);
+        put(&mut beta, 2);
+        put(&mut global, 3);
+
+        // The same logical key lands in three places
+        assert_eq!(get(&mut alpha), 1);
+        assert_eq!(get(&mut beta), 2);
+        assert_eq!(get(&mut global), 3);
+        assert_eq!(storage.count().unwrap(), 3);
+
+        // The global grant writes the key unprefixed
+        assert_eq!(
+            storage.read(b"key").unwrap(),
+            Some(3i32.to_le_bytes().to_vec())
+        );
+    }
+
+    #[test]
+    fn test_fs_storage_behind_namespace_and_domain() {
+        use crate::capability::{CapabilityGrant, CapabilityScope, CapabilityType};
+        use crate::host::FsStorage;
+
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "vudo" "host_storage_write" (func $write (param i32 i32 i32 i32) (result i32)))
+                (import "vudo" "host_storage_read" (func $read (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "key")
+                (func (export "put") (param i32) (result i32)
+                    (i32.store (i32.const 16) (local.get 0))
+                    (call $write (i32.const 0) (i32.const 3) (i32.const 16) (i32.const 4))
+                )
+                (func (export "get") (result i32)
+                    (drop (call $read (i32.const 0) (i32.const 3) (i32.const 32) (i32.const 4)))
+                    (i32.load (i32.const 32))
+                )
+            )
+        "#,
+        )
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(FsStorage::new(dir.path()).unwrap());
+        let spawn = |scope: CapabilityScope, isolated: bool| {
+            let grants = [CapabilityType::StorageRead, CapabilityType::StorageWrite]
+                .into_iter()
+                .map(|cap| {
+                    CapabilityGrant::builder(cap)
+                        .scope(scope)
+                        .scope_target(b"alpha".to_vec())
+                        .build()
+                })
+                .collect();
+            let mut sandbox = Sandbox::builder(&wasm, [1u8; 32])
+                .storage(storage.clone())
+                .capabilities(CapabilitySet::from_grants(grants))
+                .build()
+                .unwrap();
+            if isolated {
+                sandbox.isolate_storage();
+            }
+            sandbox.initialize().unwrap();
+            sandbox
+        };
+        let round_trip = |sandbox: &mut Sandbox, value: i32| {
+            let result = sandbox.invoke("put", &[Val::I32(value)]).unwrap();
+            assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 0);
+            let result = sandbox.invoke("get", &[]).unwrap();
+            assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), value);
+        };
+
+        round_trip(&mut spawn(CapabilityScope::Global, true), 1);
+        round_trip(&mut spawn(CapabilityScope::Domain, false), 2);
+        round_trip(&mut spawn(CapabilityScope::Domain, true), 3);
+        round_trip(&mut spawn(CapabilityScope::Global, false), 4);
+        assert_eq!(storage.count().unwrap(), 4);
+
+        let domain_dir = format!("@domain-{}", hex::encode("alpha"));
+        assert!(dir.path().join(&domain_dir).join("key").is_file());
+        assert!(dir.path().join("key").is_file());
+        let namespaces: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name.starts_with("@ns-"))
+            .collect();
+        // Each sandbox has its own id, so its own namespace
+        assert_eq!(namespaces.len(), 2);
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // CONSTANTS TESTS
     // ═══════════════════════════════════════════════════════════════════════════
@@ -0,0 +1,156 @@
+//! Host Call Tracing for VUDO VM
+//!
+//! This module records the host calls a Spirit makes so that nondeterministic
+//! behaviour can be inspected (and replayed) after the fact. Tracing is off
+//! unless enabled on the sandbox, and keeps only the most recent calls.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// CONSTANTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Number of host calls kept when no capacity is given
+pub const DEFAULT_TRACE_CAPACITY: usize = 1024;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TRACE ENTRY
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// One host call made by a Spirit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// Host function name, e.g. `host_storage_read`
+    pub function: String,
+    /// Raw WASM arguments (pointers, lengths and scalars), widened to i64
+    pub args: Vec<i64>,
+    /// Value returned to the Spirit, widened to i64
+    pub result: i64,
+    /// Store fuel when the call started
+    pub fuel_before: u64,
+    /// Store fuel when the call returned
+    pub fuel_after: u64,
+    /// Unix timestamp in nanoseconds when the call started
+    pub timestamp: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TRACE RECORDER
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Ring buffer of the most recent host calls.
+///
+/// Once `capacity` entries are held, recording a call drops the oldest one.
+#[derive(Debug, Clone)]
+pub struct TraceRecorder {
+    entries: VecDeque<TraceEntry>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl TraceRecorder {
+    /// Create a recorder holding at most `capacity` entries (at least one).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            entries: VecDeque::with_capacity(capacity.min(DEFAULT_TRACE_CAPACITY)),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    /// Append `entry`, evicting the oldest entry if the buffer is full.
+    pub fn record(&mut self, entry: TraceEntry) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Recorded entries, oldest first.
+    pub fn entries(&self) -> impl Iterator<Item = &TraceEntry> {
+        self.entries.iter()
+    }
+
+    /// Number of entries currently held.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no calls have been recorded (or all were cleared).
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Maximum number of entries held.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of entries evicted to make room for newer ones.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Discard all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.dropped = 0;
+    }
+}
+
+impl Default for TraceRecorder {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRACE_CAPACITY)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(function: &str, result: i64) -> TraceEntry {
+        TraceEntry {
+            function: function.to_string(),
+            args: vec![],
+            result,
+            fuel_before: 0,
+            fuel_after: 0,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_recorder_keeps_most_recent() {
+        let mut recorder = TraceRecorder::new(2);
+
+        recorder.record(entry("a", 0));
+        recorder.record(entry("b", 1));
+        recorder.record(entry("c", 2));
+
+        let names: Vec<&str> = recorder.entries().map(|e| e.function.as_str()).collect();
+        assert_eq!(names, ["b", "c"]);
+        assert_eq!(recorder.dropped(), 1);
+
+        recorder.clear();
+        assert!(recorder.is_empty());
+        assert_eq!(recorder.dropped(), 0);
+    }
+
+    #[test]
+    fn test_recorder_zero_capacity_holds_one() {
+        let mut recorder = TraceRecorder::new(0);
+
+        recorder.record(entry("a", 0));
+        recorder.record(entry("b", 0));
+
+        assert_eq!(recorder.capacity(), 1);
+        assert_eq!(recorder.len(), 1);
+    }
+}
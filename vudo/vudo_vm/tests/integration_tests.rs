@@ -9,7 +9,7 @@
 
 use std::sync::{Arc, Barrier};
 use std::thread;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use wasmtime::Val;
 
 // Use the capability types from capability.rs module for host functions
@@ -19,11 +19,13 @@ use vudo_vm::{CapabilityGrant, CapabilityScope, CapabilitySet, CapabilityType, S
 use vudo_vm::fuel::FuelManager;
 use vudo_vm::host::{
     host_log, host_random_bytes, host_storage_read, host_storage_write, host_time_now,
-    InMemoryStorage, LogLevel,
+    InMemoryCreditLedger, InMemoryStorage, LogLevel, MockNetworkBackend,
 };
+use vudo_vm::linker::error_codes;
 use vudo_vm::sandbox::{
     CapabilityGrant as SandboxCapabilityGrant, CapabilityType as SandboxCapabilityType,
-    ResourceLimits, Sandbox, SandboxState,
+    CompilationMode, ExecutionOutcome, MeteringMode, ResourceLimits, Sandbox, SandboxError,
+    SandboxRouter, SandboxState, SpiritImage, TrapKind,
 };
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -80,62 +82,28 @@ fn create_sandbox_grant(
 
 /// Create a minimal capability set with basic permissions
 fn create_minimal_capset() -> CapabilitySet {
-    let mut cap_set = CapabilitySet::new();
-    cap_set.add_grant(create_grant(
-        1,
+    CapabilitySet::with_types(&[
         CapabilityType::SensorTime,
-        CapabilityScope::Global,
-        None,
-    ));
-    cap_set.add_grant(create_grant(
-        2,
         CapabilityType::SensorRandom,
-        CapabilityScope::Global,
-        None,
-    ));
-    cap_set.add_grant(create_grant(
-        3,
         CapabilityType::ActuatorLog,
-        CapabilityScope::Global,
-        None,
-    ));
-    cap_set
+    ])
 }
 
 /// Create a capability set with storage permissions
 fn create_storage_capset() -> CapabilitySet {
-    let mut cap_set = create_minimal_capset();
-    cap_set.add_grant(create_grant(
-        4,
+    CapabilitySet::with_types(&[
+        CapabilityType::SensorTime,
+        CapabilityType::SensorRandom,
+        CapabilityType::ActuatorLog,
         CapabilityType::StorageRead,
-        CapabilityScope::Sandboxed,
-        None,
-    ));
-    cap_set.add_grant(create_grant(
-        5,
         CapabilityType::StorageWrite,
-        CapabilityScope::Sandboxed,
-        None,
-    ));
-    cap_set.add_grant(create_grant(
-        6,
         CapabilityType::StorageDelete,
-        CapabilityScope::Sandboxed,
-        None,
-    ));
-    cap_set
+    ])
 }
 
 /// Create an unrestricted capability set (for system spirits)
 fn create_unrestricted_capset() -> CapabilitySet {
-    let mut cap_set = CapabilitySet::new();
-    cap_set.add_grant(create_grant(
-        1,
-        CapabilityType::Unrestricted,
-        CapabilityScope::Global,
-        None,
-    ));
-    cap_set
+    CapabilitySet::all_for_testing()
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -836,14 +804,10 @@ fn test_fuel_exhaustion() {
 
     // Execution failed due to fuel exhaustion
     assert!(!result.success);
-    assert!(
-        result.error.as_ref().unwrap().contains("fuel")
-            || result.error.as_ref().unwrap().contains("trap")
-    );
+    assert_eq!(result.outcome, ExecutionOutcome::OutOfFuel);
 
-    // Sandbox should be in Paused or Failed state
-    let state = sandbox.get_state();
-    assert!(state == SandboxState::Paused || state == SandboxState::Failed);
+    // Out of fuel pauses rather than fails, so the sandbox can be refueled
+    assert_eq!(sandbox.get_state(), SandboxState::Paused);
 }
 
 /// Tests refueling a paused sandbox
@@ -1330,6 +1294,10 @@ fn test_wasm_trap_handling() {
         .expect("invoke should return result");
 
     assert!(!result.success);
+    assert_eq!(
+        result.outcome,
+        ExecutionOutcome::Trap(TrapKind::IntegerDivisionByZero)
+    );
     assert!(result.error.is_some());
     assert_eq!(sandbox.get_state(), SandboxState::Failed);
 }
@@ -1516,3 +1484,1372 @@ fn test_float_operations() {
     let product = result.return_value.as_ref().unwrap()[0].unwrap_f64();
     assert!((product - std::f64::consts::TAU).abs() < 0.0001);
 }
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 6: HOST PANIC SAFETY
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Storage backend whose reads always panic
+struct PanickingStorage;
+
+impl StorageBackend for PanickingStorage {
+    fn read(&self, _key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        panic!("storage backend exploded");
+    }
+
+    fn write(&self, _key: &[u8], _value: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn delete(&self, _key: &[u8]) -> Result<bool, String> {
+        Ok(false)
+    }
+
+    fn count(&self) -> Result<usize, String> {
+        Ok(0)
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Tests that a panicking host backend degrades to an error code
+#[test]
+fn test_host_panic_returns_error() {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+            (import "vudo" "host_storage_read" (func $read (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "key")
+
+            (func (export "read") (result i32)
+                (call $read (i32.const 0) (i32.const 3) (i32.const 64) (i32.const 32))
+            )
+            (func (export "answer") (result i32)
+                i32.const 42
+            )
+        )
+    "#,
+    )
+    .expect("Failed to parse WAT");
+
+    let mut sandbox = Sandbox::new(
+        &wasm,
+        [0u8; 32],
+        ResourceLimits::default(),
+        Arc::new(PanickingStorage),
+        Arc::new(InMemoryCreditLedger::new()),
+        Arc::new(MockNetworkBackend::new()),
+        create_storage_capset(),
+    )
+    .expect("Failed to create sandbox");
+    sandbox.initialize().expect("Failed to initialize");
+
+    let result = sandbox.invoke("read", &[]).expect("Failed to invoke");
+    assert!(result.success);
+    assert_eq!(result.return_value.as_ref().unwrap()[0].unwrap_i32(), -1);
+
+    // The sandbox remains usable after the host panic
+    assert_eq!(sandbox.get_state(), SandboxState::Ready);
+    let result = sandbox.invoke("answer", &[]).expect("Failed to invoke");
+    assert!(result.success);
+    assert_eq!(result.return_value.as_ref().unwrap()[0].unwrap_i32(), 42);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 7: STORAGE NAMESPACING
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Tests that namespaced sandboxes sharing one backend cannot see each
+/// other's keys, even when writing the same logical key concurrently
+#[test]
+fn test_namespaced_storage_isolation() {
+    // Writes the byte passed as argument under "key", then reads it back
+    let wasm = wat::parse_str(
+        r#"
+        (module
+            (import "vudo" "host_storage_write" (func $write (param i32 i32 i32 i32) (result i32)))
+            (import "vudo" "host_storage_read" (func $read (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "key")
+
+            (func (export "write") (param $v i32) (result i32)
+                (i32.store8 (i32.const 16) (local.get $v))
+                (call $write (i32.const 0) (i32.const 3) (i32.const 16) (i32.const 1))
+            )
+            (func (export "read") (result i32)
+                (drop (call $read (i32.const 0) (i32.const 3) (i32.const 32) (i32.const 1)))
+                (i32.load8_u (i32.const 32))
+            )
+        )
+    "#,
+    )
+    .expect("Failed to parse WAT");
+
+    let storage = Arc::new(InMemoryStorage::new());
+    let num_threads = 2;
+    let barrier = Arc::new(Barrier::new(num_threads));
+
+    let handles: Vec<_> = (0..num_threads)
+        .map(|i| {
+            let wasm = wasm.clone();
+            let storage = Arc::clone(&storage);
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                let mut sandbox = Sandbox::new(
+                    &wasm,
+                    [i as u8 + 1; 32],
+                    ResourceLimits::default(),
+                    storage,
+                    Arc::new(InMemoryCreditLedger::new()),
+                    Arc::new(MockNetworkBackend::new()),
+                    create_storage_capset(),
+                )
+                .expect("Failed to create sandbox");
+                sandbox.isolate_storage();
+                sandbox.initialize().expect("Failed to initialize");
+
+                let value = 10 + i as i32;
+                barrier.wait();
+                let result = sandbox
+                    .invoke("write", &[Val::I32(value)])
+                    .expect("Failed to write");
+                assert_eq!(result.return_value.as_ref().unwrap()[0].unwrap_i32(), 0);
+                barrier.wait();
+
+                let result = sandbox.invoke("read", &[]).expect("Failed to read");
+                (value, result.return_value.as_ref().unwrap()[0].unwrap_i32())
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        let (written, read) = handle.join().expect("Thread panicked");
+        assert_eq!(read, written, "Sandbox read another sandbox's value");
+    }
+
+    // Both writes landed in the shared backend under distinct keys
+    assert_eq!(storage.count().unwrap(), 2);
+    assert_eq!(storage.read(b"key").unwrap(), None);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 8: EXECUTION OUTCOMES
+// ═══════════════════════════════════════════════════════════════════════════
+
+const OUTCOME_WAT: &str = r#"
+    (module
+        (memory 1)
+        (func (export "ok") (result i32)
+            i32.const 7
+        )
+        (func (export "spin")
+            (loop $forever
+                (br $forever)
+            )
+        )
+        (func (export "unreachable")
+            unreachable
+        )
+        (func (export "oob") (result i32)
+            i32.const 0x7fffffff
+            i32.load
+        )
+        (func (export "grow") (param i32) (result i32)
+            local.get 0
+            memory.grow
+        )
+    )
+"#;
+
+fn outcome_sandbox(limits: ResourceLimits) -> Sandbox {
+    let wasm = wat::parse_str(OUTCOME_WAT).expect("Failed to parse WAT");
+    let mut sandbox =
+        Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).expect("Failed to create sandbox");
+    sandbox.initialize().expect("Failed to initialize");
+    sandbox
+}
+
+/// Tests that a normal return reports `Completed`
+#[test]
+fn test_outcome_completed() {
+    let mut sandbox = outcome_sandbox(ResourceLimits::default());
+
+    let result = sandbox.invoke("ok", &[]).expect("invoke should succeed");
+
+    assert_eq!(result.outcome, ExecutionOutcome::Completed);
+    assert!(result.outcome.is_completed());
+    assert!(result.success);
+    assert!(result.error.is_none());
+}
+
+/// Tests that fuel exhaustion reports `OutOfFuel`
+#[test]
+fn test_outcome_out_of_fuel() {
+    let mut sandbox = outcome_sandbox(ResourceLimits {
+        max_fuel: 1000,
+        ..Default::default()
+    });
+
+    let result = sandbox.invoke("spin", &[]).expect("invoke should return");
+
+    assert_eq!(result.outcome, ExecutionOutcome::OutOfFuel);
+    assert_eq!(result.error.as_deref(), Some("Out of fuel"));
+}
+
+/// Tests that a failure past `max_duration` reports `Timeout`
+#[test]
+fn test_outcome_timeout() {
+    let mut sandbox = outcome_sandbox(ResourceLimits {
+        max_fuel: 1000,
+        max_duration: Duration::ZERO,
+        ..Default::default()
+    });
+
+    let result = sandbox.invoke("spin", &[]).expect("invoke should return");
+
+    assert_eq!(result.outcome, ExecutionOutcome::Timeout);
+    assert_eq!(sandbox.get_state(), SandboxState::Failed);
+}
+
+/// Tests that traps carry their kind
+#[test]
+fn test_outcome_traps() {
+    let mut sandbox = outcome_sandbox(ResourceLimits::default());
+    let result = sandbox
+        .invoke("unreachable", &[])
+        .expect("invoke should return");
+    assert_eq!(
+        result.outcome,
+        ExecutionOutcome::Trap(TrapKind::Unreachable)
+    );
+
+    let mut sandbox = outcome_sandbox(ResourceLimits::default());
+    let result = sandbox.invoke("oob", &[]).expect("invoke should return");
+    assert_eq!(
+        result.outcome,
+        ExecutionOutcome::Trap(TrapKind::MemoryOutOfBounds)
+    );
+    assert!(result.error.unwrap().starts_with("WASM trap"));
+}
+
+/// Tests that growing memory past `memory_bytes` reports `MemoryLimit`
+#[test]
+fn test_outcome_memory_limit() {
+    // One 64 KiB page is allowed, so the module instantiates but cannot grow
+    let mut sandbox = outcome_sandbox(ResourceLimits {
+        memory_bytes: 65_536,
+        ..Default::default()
+    });
+
+    let result = sandbox
+        .invoke("grow", &[Val::I32(1)])
+        .expect("invoke should return");
+
+    assert_eq!(result.outcome, ExecutionOutcome::MemoryLimit);
+    assert!(!result.success);
+    assert_eq!(sandbox.get_state(), SandboxState::Failed);
+}
+
+/// Tests that growth within `memory_bytes` is unaffected by the limit
+#[test]
+fn test_memory_growth_within_limit() {
+    let mut sandbox = outcome_sandbox(ResourceLimits {
+        memory_bytes: 4 * 65_536,
+        ..Default::default()
+    });
+
+    let result = sandbox
+        .invoke("grow", &[Val::I32(3)])
+        .expect("invoke should succeed");
+
+    assert_eq!(result.outcome, ExecutionOutcome::Completed);
+    assert_eq!(result.return_value.unwrap()[0].i32(), Some(1));
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 9: SHUTDOWN HOOK
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn storage_sandbox(wat: &str, storage: Arc<InMemoryStorage>) -> Sandbox {
+    let wasm = wat::parse_str(wat).expect("Failed to parse WAT");
+    let mut sandbox = Sandbox::new(
+        &wasm,
+        [0u8; 32],
+        ResourceLimits::default(),
+        storage,
+        Arc::new(InMemoryCreditLedger::new()),
+        Arc::new(MockNetworkBackend::new()),
+        create_storage_capset(),
+    )
+    .expect("Failed to create sandbox");
+    sandbox.initialize().expect("Failed to initialize");
+    sandbox
+}
+
+/// Tests that terminate runs `__vudo_shutdown` so the Spirit can flush state
+#[test]
+fn test_shutdown_hook_runs_on_terminate() {
+    let storage = Arc::new(InMemoryStorage::new());
+    let mut sandbox = storage_sandbox(
+        r#"
+        (module
+            (import "vudo" "host_storage_write" (func $write (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "status")
+            (data (i32.const 16) "clean")
+
+            (func (export "work") (result i32)
+                i32.const 1
+            )
+            (func (export "__vudo_shutdown")
+                (drop (call $write (i32.const 0) (i32.const 6) (i32.const 16) (i32.const 5)))
+            )
+        )
+    "#,
+        Arc::clone(&storage),
+    );
+
+    assert!(sandbox.invoke("work", &[]).unwrap().success);
+    assert_eq!(storage.read(b"status").unwrap(), None);
+
+    sandbox.terminate();
+
+    assert_eq!(sandbox.get_state(), SandboxState::Terminated);
+    assert_eq!(storage.read(b"status").unwrap(), Some(b"clean".to_vec()));
+}
+
+/// Tests that a module without a shutdown hook, or with a failing one,
+/// still terminates
+#[test]
+fn test_terminate_without_or_failing_shutdown_hook() {
+    let storage = Arc::new(InMemoryStorage::new());
+    let mut sandbox = storage_sandbox(
+        r#"
+        (module
+            (func (export "work") (result i32)
+                i32.const 1
+            )
+        )
+    "#,
+        Arc::clone(&storage),
+    );
+    assert!(sandbox.invoke("work", &[]).unwrap().success);
+    sandbox.terminate();
+    assert_eq!(sandbox.get_state(), SandboxState::Terminated);
+    assert_eq!(storage.count().unwrap(), 0);
+
+    let mut sandbox = storage_sandbox(
+        r#"
+        (module
+            (func (export "work") (result i32)
+                i32.const 1
+            )
+            (func (export "__vudo_shutdown")
+                (loop $forever
+                    (br $forever)
+                )
+            )
+        )
+    "#,
+        Arc::clone(&storage),
+    );
+    assert!(sandbox.invoke("work", &[]).unwrap().success);
+    sandbox.terminate();
+    assert_eq!(sandbox.get_state(), SandboxState::Terminated);
+}
+
+/// Tests that a hung shutdown hook is cut off by the timeout when the
+/// sandbox runs without fuel
+#[test]
+fn test_hung_shutdown_hook_times_out_without_fuel() {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+            (func (export "work") (result i32)
+                i32.const 1
+            )
+            (func (export "__vudo_shutdown")
+                (loop $forever
+                    (br $forever)
+                )
+            )
+        )
+    "#,
+    )
+    .expect("Failed to parse WAT");
+    let limits = ResourceLimits {
+        metering: MeteringMode::EpochOnly,
+        max_duration: Duration::from_millis(200),
+        ..Default::default()
+    };
+    let mut sandbox = Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).unwrap();
+    sandbox.initialize().unwrap();
+    assert!(sandbox.invoke("work", &[]).unwrap().success);
+
+    let started = Instant::now();
+    sandbox.terminate();
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(sandbox.get_state(), SandboxState::Terminated);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 10: SPIRIT IMAGE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Tests that many sandboxes instantiated from one image share the compiled
+/// module but keep their globals and memory to themselves
+#[test]
+fn test_spirit_image_fan_out() {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+            (global $counter (mut i32) (i32.const 0))
+            (memory 1)
+            (func (export "bump") (result i32)
+                (global.set $counter (i32.add (global.get $counter) (i32.const 1)))
+                (i32.store (i32.const 0) (global.get $counter))
+                (global.get $counter)
+            )
+            (func (export "stored") (result i32)
+                (i32.load (i32.const 0))
+            )
+        )
+    "#,
+    )
+    .expect("Failed to parse WAT");
+
+    let image = SpiritImage::new(&wasm).expect("Failed to compile image");
+    let num_sandboxes = 100;
+
+    let mut sandboxes: Vec<Sandbox> = (0..num_sandboxes)
+        .map(|i| {
+            image
+                .instantiate_with_defaults([i as u8; 32], ResourceLimits::default())
+                .expect("Failed to instantiate image")
+        })
+        .collect();
+
+    // Sandbox i bumps its counter i + 1 times
+    for (i, sandbox) in sandboxes.iter_mut().enumerate() {
+        assert_eq!(sandbox.get_state(), SandboxState::Ready);
+        assert!(image.is_image_of(sandbox));
+        for _ in 0..=i {
+            assert!(sandbox.invoke("bump", &[]).unwrap().success);
+        }
+    }
+
+    for (i, sandbox) in sandboxes.iter_mut().enumerate() {
+        let result = sandbox.invoke("stored", &[]).unwrap();
+        assert_eq!(result.return_value.unwrap()[0].i32(), Some(i as i32 + 1));
+    }
+
+    // A sandbox built the usual way compiles its own module
+    let mut standalone = Sandbox::new_with_defaults(&wasm, [0u8; 32], ResourceLimits::default())
+        .expect("Failed to create sandbox");
+    standalone.initialize().expect("Failed to initialize");
+    assert!(!image.is_image_of(&standalone));
+}
+
+/// Tests that an image rejects limits that would need a different engine
+#[test]
+fn test_spirit_image_stack_limit_mismatch() {
+    let wasm = wat::parse_str(r#"(module (func (export "f")))"#).unwrap();
+    let image = SpiritImage::new(&wasm).unwrap();
+
+    let limits = ResourceLimits {
+        max_stack_bytes: image.max_stack_bytes() / 2,
+        ..Default::default()
+    };
+    assert!(image.instantiate_with_defaults([0u8; 32], limits).is_err());
+
+    assert!(SpiritImage::new(&[]).is_err());
+    assert!(SpiritImage::new(b"not wasm").is_err());
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 11: CROSS-SANDBOX CALLS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Spirit A: calls `add(20, 22)` on the sandbox whose id is passed in
+const CALLER_WAT: &str = r#"
+    (module
+        (import "vudo" "host_call_sandbox"
+            (func $call (param i64 i32 i32 i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 0) "add")
+
+        (func (export "call_add") (param $target i64) (result i32)
+            (i32.store (i32.const 16) (i32.const 20))
+            (i32.store (i32.const 20) (i32.const 22))
+            (call $call
+                (local.get $target)
+                (i32.const 0) (i32.const 3)
+                (i32.const 16) (i32.const 8)
+                (i32.const 64) (i32.const 4))
+        )
+        (func (export "result") (result i32)
+            (i32.load (i32.const 64))
+        )
+    )
+"#;
+
+/// Spirit B: exports `add`
+const CALLEE_WAT: &str = r#"
+    (module
+        (func (export "add") (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            i32.add
+        )
+    )
+"#;
+
+fn cross_call_sandboxes(caller_caps: CapabilitySet) -> (Sandbox, u64, SandboxRouter) {
+    let router = SandboxRouter::new();
+
+    let callee_wasm = wat::parse_str(CALLEE_WAT).expect("Failed to parse WAT");
+    let mut callee = Sandbox::new_with_defaults(&callee_wasm, [2u8; 32], Default::default())
+        .expect("Failed to create sandbox");
+    callee.initialize().expect("Failed to initialize");
+    let callee_id = callee.id;
+    router.register(callee).expect("Failed to register callee");
+
+    let caller_wasm = wat::parse_str(CALLER_WAT).expect("Failed to parse WAT");
+    let mut caller = Sandbox::new(
+        &caller_wasm,
+        [1u8; 32],
+        ResourceLimits::default(),
+        Arc::new(InMemoryStorage::new()),
+        Arc::new(InMemoryCreditLedger::new()),
+        Arc::new(MockNetworkBackend::new()),
+        caller_caps,
+    )
+    .expect("Failed to create sandbox");
+    caller.initialize().expect("Failed to initialize");
+    caller.attach_router(router.clone());
+
+    (caller, callee_id, router)
+}
+
+/// Tests that Spirit A can call Spirit B's `add` and read back the result
+#[test]
+fn test_cross_sandbox_call() {
+    let mut caps = CapabilitySet::new();
+    caps.add_grant(create_grant(
+        1,
+        CapabilityType::CrossSandboxCall,
+        CapabilityScope::Sandboxed,
+        None,
+    ));
+    let (mut caller, callee_id, router) = cross_call_sandboxes(caps);
+
+    let result = caller
+        .invoke("call_add", &[Val::I64(callee_id as i64)])
+        .expect("invoke should succeed");
+    assert_eq!(result.return_value.unwrap()[0].i32(), Some(4));
+
+    let value = caller.invoke("result", &[]).unwrap();
+    assert_eq!(value.return_value.unwrap()[0].i32(), Some(42));
+
+    // The callee ran on the caller's fuel and is still Ready
+    let callee = router.get(callee_id).unwrap();
+    let callee = callee.lock().unwrap();
+    assert_eq!(callee.get_state(), SandboxState::Ready);
+    assert_eq!(callee.metrics().execution_count, 1);
+    assert!(result.fuel_consumed > callee.metrics().total_fuel_consumed);
+    assert!(caller
+        .capability_usage()
+        .contains(&CapabilityType::CrossSandboxCall));
+}
+
+/// Tests that the call is denied without CrossSandboxCall and that
+/// unknown targets are rejected
+#[test]
+fn test_cross_sandbox_call_denied() {
+    let (mut caller, callee_id, router) = cross_call_sandboxes(create_minimal_capset());
+
+    let result = caller
+        .invoke("call_add", &[Val::I64(callee_id as i64)])
+        .expect("invoke should succeed");
+    assert_eq!(
+        result.return_value.unwrap()[0].i32(),
+        Some(error_codes::CAPABILITY_DENIED)
+    );
+
+    let callee = router.get(callee_id).unwrap();
+    assert_eq!(callee.lock().unwrap().metrics().execution_count, 0);
+
+    let mut caps = CapabilitySet::new();
+    caps.add_grant(create_grant(
+        1,
+        CapabilityType::CrossSandboxCall,
+        CapabilityScope::Sandboxed,
+        None,
+    ));
+    let (mut caller, callee_id, _router) = cross_call_sandboxes(caps);
+    let result = caller
+        .invoke("call_add", &[Val::I64(callee_id as i64 + 1)])
+        .expect("invoke should succeed");
+    assert_eq!(
+        result.return_value.unwrap()[0].i32(),
+        Some(error_codes::INVALID_PARAMETER)
+    );
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 12: ENGINE CONFIG HOOK
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Spirit using a v128 instruction: `i32x4.add` of two splats, lane 0
+const SIMD_WAT: &str = r#"
+    (module
+        (func (export "simd_add") (param i32 i32) (result i32)
+            local.get 0
+            i32x4.splat
+            local.get 1
+            i32x4.splat
+            i32x4.add
+            i32x4.extract_lane 0
+        )
+        (func (export "spin")
+            (loop $forever
+                (br $forever)
+            )
+        )
+    )
+"#;
+
+/// Tests that a hook enabling SIMD lets a v128 module run
+#[test]
+fn test_engine_config_enables_simd() {
+    let wasm = wat::parse_str(SIMD_WAT).expect("Failed to parse WAT");
+    let mut sandbox = Sandbox::builder(&wasm, [0u8; 32])
+        .with_engine_config(|config| {
+            config.wasm_simd(true);
+        })
+        .build()
+        .expect("Failed to create sandbox");
+    sandbox.initialize().expect("Failed to initialize");
+
+    let result = sandbox
+        .invoke("simd_add", &[Val::I32(20), Val::I32(22)])
+        .expect("invoke should succeed");
+
+    assert!(result.success);
+    assert_eq!(result.return_value.unwrap()[0].i32(), Some(42));
+}
+
+/// Tests that the hook's settings reach the engine
+#[test]
+fn test_engine_config_disables_simd() {
+    let wasm = wat::parse_str(SIMD_WAT).expect("Failed to parse WAT");
+    let mut sandbox = Sandbox::builder(&wasm, [0u8; 32])
+        .with_engine_config(|config| {
+            config.wasm_relaxed_simd(false).wasm_simd(false);
+        })
+        .build()
+        .expect("Failed to create sandbox");
+
+    assert!(sandbox.initialize().is_err());
+    assert_eq!(sandbox.get_state(), SandboxState::Failed);
+}
+
+/// Tests that the hook cannot turn fuel metering off
+#[test]
+fn test_engine_config_cannot_disable_fuel() {
+    let wasm = wat::parse_str(SIMD_WAT).expect("Failed to parse WAT");
+    let mut sandbox = Sandbox::builder(&wasm, [0u8; 32])
+        .limits(ResourceLimits {
+            max_fuel: 1000,
+            ..ResourceLimits::default()
+        })
+        .with_engine_config(|config| {
+            config.consume_fuel(false);
+        })
+        .build()
+        .expect("Failed to create sandbox");
+    sandbox.initialize().expect("Failed to initialize");
+
+    let result = sandbox.invoke("spin", &[]).expect("invoke should succeed");
+
+    assert_eq!(result.outcome, ExecutionOutcome::OutOfFuel);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 13: CROSS-INVOKE FUEL BUDGET
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Tests that repeated invokes stop once the total fuel budget is spent
+#[test]
+fn test_total_fuel_budget_exhausted() {
+    let mut sandbox = outcome_sandbox(ResourceLimits {
+        total_fuel_budget: Some(10),
+        ..ResourceLimits::default()
+    });
+
+    let mut completed = 0;
+    let error = loop {
+        match sandbox.invoke("ok", &[]) {
+            Ok(result) => {
+                assert!(result.success);
+                completed += 1;
+                assert!(completed < 100, "budget was never enforced");
+            }
+            Err(e) => break e,
+        }
+    };
+
+    assert!(completed > 0);
+    assert!(matches!(error, SandboxError::CpuQuotaExceeded));
+    assert_eq!(sandbox.fuel_budget_remaining(), Some(0));
+    assert_eq!(sandbox.get_state(), SandboxState::Ready);
+
+    // A budget reset allows further invokes
+    sandbox.reset_fuel_budget();
+    assert!(sandbox.invoke("ok", &[]).is_ok());
+
+    // So does refueling
+    while sandbox.invoke("ok", &[]).is_ok() {}
+    sandbox.refuel(1_000).expect("refuel should succeed");
+    assert!(sandbox.invoke("ok", &[]).is_ok());
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 14: PER-INVOKE TIMEOUT
+// ═══════════════════════════════════════════════════════════════════════════
+
+const BUSY_WAT: &str = r#"
+    (module
+        (func (export "busy") (param $n i32) (result i32)
+            (loop $again
+                local.get $n
+                i32.const 1
+                i32.sub
+                local.tee $n
+                br_if $again
+            )
+            local.get $n
+        )
+    )
+"#;
+
+const BUSY_ITERATIONS: i32 = 50_000_000;
+
+fn busy_sandbox(limits: ResourceLimits) -> Sandbox {
+    let wasm = wat::parse_str(BUSY_WAT).expect("Failed to parse WAT");
+    let mut sandbox =
+        Sandbox::new_with_defaults(&wasm, [0u8; 32], limits).expect("Failed to create sandbox");
+    sandbox.initialize().expect("Failed to initialize");
+    sandbox
+}
+
+/// Tests that a long call succeeds under a generous per-call timeout and
+/// that the short default applies again afterwards
+#[test]
+fn test_invoke_with_timeout_overrides_default() {
+    let mut sandbox = busy_sandbox(ResourceLimits {
+        max_duration: Duration::from_millis(5),
+        ..ResourceLimits::default()
+    });
+
+    let result = sandbox
+        .invoke_with_timeout(
+            "busy",
+            &[Val::I32(BUSY_ITERATIONS)],
+            Duration::from_secs(60),
+        )
+        .expect("invoke should return");
+    assert!(result.success, "{:?}", result.error);
+    assert!(result.duration > Duration::from_millis(5));
+    assert_eq!(sandbox.get_state(), SandboxState::Ready);
+
+    let result = sandbox
+        .invoke("busy", &[Val::I32(BUSY_ITERATIONS)])
+        .expect("invoke should return");
+    assert_eq!(result.outcome, ExecutionOutcome::Timeout);
+    assert_eq!(sandbox.get_state(), SandboxState::Failed);
+}
+
+/// Tests that a per-call timeout cannot exceed `max_duration_ceiling`
+#[test]
+fn test_invoke_with_timeout_capped_by_ceiling() {
+    let mut sandbox = busy_sandbox(ResourceLimits {
+        max_duration: Duration::from_millis(5),
+        max_duration_ceiling: Duration::from_millis(5),
+        ..ResourceLimits::default()
+    });
+
+    let result = sandbox
+        .invoke_with_timeout(
+            "busy",
+            &[Val::I32(BUSY_ITERATIONS)],
+            Duration::from_secs(60),
+        )
+        .expect("invoke should return");
+
+    assert_eq!(result.outcome, ExecutionOutcome::Timeout);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 15: HOST CALL TRACE
+// ═══════════════════════════════════════════════════════════════════════════
+
+const TRACED_WAT: &str = r#"
+    (module
+        (import "vudo" "host_log" (func $log (param i32 i32 i32) (result i32)))
+        (import "vudo" "host_storage_read" (func $read (param i32 i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 0) "hello")
+        (data (i32.const 16) "key")
+
+        (func (export "run") (result i32)
+            (drop (call $log (i32.const 2) (i32.const 0) (i32.const 5)))
+            (call $read (i32.const 16) (i32.const 3) (i32.const 64) (i32.const 32))
+        )
+    )
+"#;
+
+fn traced_sandbox() -> Sandbox {
+    let wasm = wat::parse_str(TRACED_WAT).expect("Failed to parse WAT");
+    let storage = Arc::new(InMemoryStorage::new());
+    storage.write(b"key", b"value").unwrap();
+
+    let mut sandbox = Sandbox::new(
+        &wasm,
+        [0u8; 32],
+        ResourceLimits::default(),
+        storage,
+        Arc::new(InMemoryCreditLedger::new()),
+        Arc::new(MockNetworkBackend::new()),
+        create_storage_capset(),
+    )
+    .expect("Failed to create sandbox");
+    sandbox.set_log_hook(Arc::new(|_, _| {}));
+    sandbox.initialize().expect("Failed to initialize");
+    sandbox
+}
+
+/// Tests that an enabled trace records host calls in order with their results
+#[test]
+fn test_trace_records_host_calls() {
+    let mut sandbox = traced_sandbox();
+    sandbox.enable_trace(16);
+
+    let result = sandbox.invoke("run", &[]).expect("Failed to invoke");
+    assert!(result.success);
+
+    let trace = sandbox.trace();
+    assert_eq!(trace.len(), 2);
+
+    assert_eq!(trace[0].function, "host_log");
+    assert_eq!(trace[0].args, vec![2, 0, 5]);
+    assert_eq!(trace[0].result, 0);
+
+    assert_eq!(trace[1].function, "host_storage_read");
+    assert_eq!(trace[1].args, vec![16, 3, 64, 32]);
+    assert_eq!(trace[1].result, 5);
+
+    assert!(trace[0].timestamp <= trace[1].timestamp);
+    assert!(trace[0].fuel_after <= trace[0].fuel_before);
+    assert!(trace[1].fuel_before <= trace[0].fuel_after);
+}
+
+/// Tests that tracing is off by default
+#[test]
+fn test_trace_disabled_by_default() {
+    let mut sandbox = traced_sandbox();
+
+    sandbox.invoke("run", &[]).expect("Failed to invoke");
+
+    assert!(sandbox.trace().is_empty());
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 16: ATOMIC STORAGE COUNTER
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Tests that sandboxes on separate threads incrementing one shared counter
+/// never lose an update
+#[test]
+fn test_concurrent_storage_increment() {
+    const THREADS: usize = 8;
+    const CALLS: usize = 50;
+    const DELTA: i64 = 7;
+
+    let wasm = wat::parse_str(
+        r#"
+        (module
+            (import "vudo" "host_storage_increment" (func $inc (param i32 i32 i64) (result i64)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "hits")
+
+            (func (export "bump") (param $delta i64) (result i64)
+                (call $inc (i32.const 0) (i32.const 4) (local.get $delta))
+            )
+        )
+    "#,
+    )
+    .expect("Failed to parse WAT");
+    let storage = Arc::new(InMemoryStorage::new());
+    let barrier = Arc::new(Barrier::new(THREADS));
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let wasm = wasm.clone();
+            let storage = Arc::clone(&storage);
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                let mut sandbox = Sandbox::new(
+                    &wasm,
+                    [0u8; 32],
+                    ResourceLimits::default(),
+                    storage,
+                    Arc::new(InMemoryCreditLedger::new()),
+                    Arc::new(MockNetworkBackend::new()),
+                    create_storage_capset(),
+                )
+                .expect("Failed to create sandbox");
+                sandbox.initialize().expect("Failed to initialize");
+
+                barrier.wait();
+                for _ in 0..CALLS {
+                    let result = sandbox
+                        .invoke("bump", &[Val::I64(DELTA)])
+                        .expect("Failed to invoke");
+                    assert!(result.return_value.unwrap()[0].unwrap_i64() > 0);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().expect("Thread panicked");
+    }
+
+    let total = storage.read(b"hits").unwrap().expect("counter missing");
+    assert_eq!(
+        i64::from_le_bytes(total.try_into().unwrap()),
+        (THREADS * CALLS) as i64 * DELTA
+    );
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 17: ENTRYPOINTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Builds a Ready sandbox whose module writes "ran" = "yes" through `body`
+fn entrypoint_sandbox(exports: &str) -> (Sandbox, Arc<InMemoryStorage>) {
+    let wasm = wat::parse_str(format!(
+        r#"
+        (module
+            (import "vudo" "host_storage_write" (func $write (param i32 i32 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "proc_exit" (func $exit (param i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "ran")
+            (data (i32.const 16) "yes")
+            (func $mark
+                (drop (call $write (i32.const 0) (i32.const 3) (i32.const 16) (i32.const 3)))
+            )
+            {exports}
+        )
+    "#
+    ))
+    .expect("Failed to parse WAT");
+    let storage = Arc::new(InMemoryStorage::new());
+
+    let mut sandbox = Sandbox::new(
+        &wasm,
+        [0u8; 32],
+        ResourceLimits::default(),
+        storage.clone(),
+        Arc::new(InMemoryCreditLedger::new()),
+        Arc::new(MockNetworkBackend::new()),
+        create_storage_capset(),
+    )
+    .expect("Failed to create sandbox");
+    sandbox.initialize().expect("Failed to initialize");
+    (sandbox, storage)
+}
+
+/// Tests that `_start` runs and its side effects persist
+#[test]
+fn test_run_start_invokes_start() {
+    let (mut sandbox, storage) = entrypoint_sandbox(r#"(func (export "_start") call $mark)"#);
+
+    let result = sandbox.run_start().expect("run_start should succeed");
+
+    assert!(result.success);
+    assert_eq!(storage.read(b"ran").unwrap(), Some(b"yes".to_vec()));
+    assert_eq!(sandbox.get_state(), SandboxState::Ready);
+}
+
+/// Tests that `main` is used when there is no `_start`
+#[test]
+fn test_run_start_falls_back_to_main() {
+    let (mut sandbox, storage) = entrypoint_sandbox(
+        r#"(func (export "main") (param i32 i32) (result i32) call $mark i32.const 0)"#,
+    );
+
+    let result = sandbox.run_start().expect("run_start should succeed");
+
+    assert!(result.success);
+    assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 0);
+    assert_eq!(storage.read(b"ran").unwrap(), Some(b"yes".to_vec()));
+}
+
+/// Tests that `proc_exit(0)` counts as success and other codes do not
+#[test]
+fn test_run_start_proc_exit() {
+    let (mut sandbox, storage) = entrypoint_sandbox(
+        r#"(func (export "_start") call $mark (call $exit (i32.const 0)) unreachable)"#,
+    );
+    let result = sandbox.run_start().expect("run_start should succeed");
+    assert!(result.success);
+    assert_eq!(result.outcome, ExecutionOutcome::Completed);
+    assert_eq!(storage.read(b"ran").unwrap(), Some(b"yes".to_vec()));
+    assert_eq!(sandbox.get_state(), SandboxState::Ready);
+
+    let (mut sandbox, _) =
+        entrypoint_sandbox(r#"(func (export "_start") (call $exit (i32.const 3)))"#);
+    let result = sandbox.run_start().expect("run_start should return");
+    assert!(!result.success);
+    match result.outcome {
+        ExecutionOutcome::Trap(TrapKind::Host(message)) => assert!(message.contains("code 3")),
+        other => panic!("Expected host trap, got {:?}", other),
+    }
+}
+
+/// Tests that a module without an entrypoint is reported as such
+#[test]
+fn test_run_start_without_entrypoint() {
+    let (mut sandbox, storage) = entrypoint_sandbox(r#"(func (export "other") call $mark)"#);
+
+    match sandbox.run_start() {
+        Err(SandboxError::FunctionNotFound(name)) => assert_eq!(name, "_start"),
+        other => panic!(
+            "Expected FunctionNotFound, got {:?}",
+            other.map(|r| r.outcome)
+        ),
+    }
+    assert_eq!(storage.read(b"ran").unwrap(), None);
+}
+
+/// Tests that `run_entry` calls a named export with zeroed arguments
+#[test]
+fn test_run_entry_named_export() {
+    let (mut sandbox, storage) = entrypoint_sandbox(
+        r#"(func (export "init") (param i64) (result i64) call $mark local.get 0)"#,
+    );
+
+    let result = sandbox.run_entry("init").expect("run_entry should succeed");
+    assert_eq!(result.return_value.unwrap()[0].unwrap_i64(), 0);
+    assert_eq!(storage.read(b"ran").unwrap(), Some(b"yes".to_vec()));
+
+    match sandbox.run_entry("missing") {
+        Err(SandboxError::FunctionNotFound(name)) => assert_eq!(name, "missing"),
+        other => panic!(
+            "Expected FunctionNotFound, got {:?}",
+            other.map(|r| r.outcome)
+        ),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 18: METERING MODES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Tests that without fuel a runaway loop is still stopped by the timeout
+#[test]
+fn test_epoch_only_metering_stops_loop_by_timeout() {
+    let mut sandbox = busy_sandbox(ResourceLimits {
+        metering: MeteringMode::EpochOnly,
+        max_fuel: 1,
+        max_duration: Duration::from_millis(50),
+        ..ResourceLimits::default()
+    });
+
+    // A single unit of fuel would not get far if it were metered
+    let result = sandbox
+        .invoke("busy", &[Val::I32(1_000)])
+        .expect("invoke should return");
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(result.fuel_consumed, 0);
+
+    let result = sandbox
+        .invoke("busy", &[Val::I32(i32::MAX)])
+        .expect("invoke should return");
+    assert_eq!(result.outcome, ExecutionOutcome::Timeout);
+    assert_eq!(result.fuel_consumed, 0);
+    assert!(result.duration < Duration::from_secs(5));
+    assert_eq!(sandbox.get_state(), SandboxState::Failed);
+}
+
+/// Tests that MeteringMode::None runs without fuel accounting
+#[test]
+fn test_unmetered_sandbox_reports_no_fuel() {
+    let mut sandbox = busy_sandbox(ResourceLimits {
+        metering: MeteringMode::None,
+        ..ResourceLimits::default()
+    });
+
+    let result = sandbox
+        .invoke("busy", &[Val::I32(100_000)])
+        .expect("invoke should return");
+    assert!(result.success, "{:?}", result.error);
+    assert_eq!(result.fuel_consumed, 0);
+
+    // Fuel-only operations degrade to no-ops
+    sandbox.refuel(1_000).expect("refuel should succeed");
+    assert_eq!(sandbox.get_state(), SandboxState::Ready);
+
+    // A fuel budget cannot be enforced without fuel
+    let limits = ResourceLimits {
+        metering: MeteringMode::EpochOnly,
+        total_fuel_budget: Some(1_000),
+        ..ResourceLimits::default()
+    };
+    assert!(limits.validate().is_err());
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 19: SPIRIT METRICS
+// ═══════════════════════════════════════════════════════════════════════════
+
+const METRICS_WAT: &str = r#"
+    (module
+        (import "vudo" "host_metrics_emit" (func $emit (param i32 i32 f64 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 0) "requests")
+        (data (i32.const 16) "queue_depth")
+
+        (func (export "run") (result i32)
+            (drop (call $emit (i32.const 0) (i32.const 8) (f64.const 1) (i32.const 0)))
+            (drop (call $emit (i32.const 0) (i32.const 8) (f64.const 2) (i32.const 0)))
+            (drop (call $emit (i32.const 0) (i32.const 8) (f64.const 3) (i32.const 0)))
+            (drop (call $emit (i32.const 16) (i32.const 11) (f64.const 9.5) (i32.const 1)))
+            (call $emit (i32.const 16) (i32.const 11) (f64.const 4.25) (i32.const 1))
+        )
+    )
+"#;
+
+fn metrics_sandbox(capabilities: CapabilitySet) -> Sandbox {
+    let wasm = wat::parse_str(METRICS_WAT).expect("Failed to parse WAT");
+    let mut sandbox = Sandbox::new(
+        &wasm,
+        [0u8; 32],
+        ResourceLimits::default(),
+        Arc::new(InMemoryStorage::new()),
+        Arc::new(InMemoryCreditLedger::new()),
+        Arc::new(MockNetworkBackend::new()),
+        capabilities,
+    )
+    .expect("Failed to create sandbox");
+    sandbox.initialize().expect("Failed to initialize");
+    sandbox
+}
+
+/// Tests that counters accumulate and gauges keep the latest value
+#[test]
+fn test_spirit_metrics_counter_and_gauge() {
+    let mut sandbox = metrics_sandbox(create_minimal_capset());
+
+    let result = sandbox.invoke("run", &[]).expect("Failed to invoke");
+    assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), 0);
+
+    let metrics = sandbox.spirit_metrics();
+    assert_eq!(metrics.counter("requests"), Some(6.0));
+    assert_eq!(metrics.gauge("queue_depth"), Some(4.25));
+    assert_eq!(metrics.len(), 2);
+    assert!(sandbox
+        .capability_usage()
+        .contains(&CapabilityType::ActuatorLog));
+}
+
+/// Tests that emitting without ActuatorLog records nothing
+#[test]
+fn test_spirit_metrics_require_actuator_log() {
+    let mut sandbox = metrics_sandbox(CapabilitySet::new());
+
+    let result = sandbox.invoke("run", &[]).expect("Failed to invoke");
+    assert_eq!(result.return_value.unwrap()[0].unwrap_i32(), -1);
+    assert!(sandbox.spirit_metrics().is_empty());
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 20: COMPILATION MODES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Module with `functions` exported arithmetic functions, large enough for
+/// compile time to dominate startup.
+fn large_module(functions: usize) -> Vec<u8> {
+    let mut wat = String::from("(module\n");
+    for i in 0..functions {
+        wat.push_str(&format!(
+            "(func (export \"f{i}\") (param i32) (result i32)
+                (local i32)
+                (local.set 1 (i32.mul (local.get 0) (i32.const {i})))
+                (loop $l
+                    (local.set 1 (i32.add (local.get 1) (i32.xor (local.get 0) (i32.const 7))))
+                    (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+                    (br_if $l (i32.gt_s (local.get 0) (i32.const 0))))
+                (local.get 1))\n"
+        ));
+    }
+    wat.push(')');
+    wat::parse_str(&wat).expect("Failed to parse WAT")
+}
+
+fn sandbox_with_mode(wasm: &[u8], mode: CompilationMode) -> Result<Sandbox, SandboxError> {
+    let mut sandbox = Sandbox::builder(wasm, [0u8; 32])
+        .compilation(mode)
+        .build()?;
+    sandbox.initialize()?;
+    Ok(sandbox)
+}
+
+/// Tests that a module compiled in Fast mode computes the same results
+#[test]
+fn test_fast_compilation_executes_module() {
+    let wasm = large_module(4);
+    let mut optimized = sandbox_with_mode(&wasm, CompilationMode::Optimized).unwrap();
+    let mut fast = sandbox_with_mode(&wasm, CompilationMode::Fast).unwrap();
+
+    for (name, arg) in [("f0", 10), ("f3", 25)] {
+        let expected = optimized.invoke(name, &[Val::I32(arg)]).unwrap();
+        let actual = fast.invoke(name, &[Val::I32(arg)]).unwrap();
+        assert!(actual.success);
+        assert_eq!(
+            actual.return_value.unwrap()[0].i32(),
+            expected.return_value.unwrap()[0].i32()
+        );
+    }
+}
+
+/// Tests that Fast mode still meters fuel
+#[test]
+fn test_fast_compilation_enforces_fuel() {
+    let wasm = wat::parse_str(r#"(module (func (export "spin") (loop $l (br $l))))"#)
+        .expect("Failed to parse WAT");
+    let mut sandbox = Sandbox::builder(&wasm, [0u8; 32])
+        .limits(ResourceLimits {
+            max_fuel: 1000,
+            ..ResourceLimits::default()
+        })
+        .compilation(CompilationMode::Fast)
+        .build()
+        .expect("Failed to create sandbox");
+    sandbox.initialize().expect("Failed to initialize");
+
+    let result = sandbox.invoke("spin", &[]).expect("invoke should succeed");
+
+    assert_eq!(result.outcome, ExecutionOutcome::OutOfFuel);
+}
+
+/// Tests that the unsupported interpreter mode fails instead of silently
+/// falling back to a compiler
+#[test]
+fn test_interpreter_compilation_unsupported() {
+    let wasm = large_module(1);
+    assert!(matches!(
+        sandbox_with_mode(&wasm, CompilationMode::Interpreter),
+        Err(SandboxError::RuntimeError(_))
+    ));
+}
+
+/// Smoke benchmark: Fast mode should compile a large module quicker than
+/// Optimized. Timing-sensitive, so run explicitly with
+/// `cargo test --release -p vudo_vm --test integration_tests -- --ignored`.
+#[test]
+#[ignore = "timing benchmark"]
+fn bench_fast_compilation_startup() {
+    let wasm = large_module(2000);
+    let time = |mode| {
+        let start = Instant::now();
+        sandbox_with_mode(&wasm, mode).unwrap();
+        start.elapsed()
+    };
+
+    let optimized = time(CompilationMode::Optimized);
+    let fast = time(CompilationMode::Fast);
+    println!("optimized: {:?}, fast: {:?}", optimized, fast);
+    assert!(fast < optimized);
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TEST 21: COOPERATIVE YIELDING
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Spirit that logs `tag` then calls host_yield, five times over
+fn yielding_sandbox(tag: &str, log: Arc<std::sync::Mutex<Vec<String>>>) -> Sandbox {
+    let wat = format!(
+        r#"(module
+            (import "vudo" "host_log" (func $log (param i32 i32 i32) (result i32)))
+            (import "vudo" "host_yield" (func $yield (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "{tag}")
+            (func (export "run") (local $i i32)
+                (loop $again
+                    (drop (call $log (i32.const 2) (i32.const 0) (i32.const {len})))
+                    (drop (call $yield))
+                    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                    (br_if $again (i32.lt_u (local.get $i) (i32.const 5))))))"#,
+        tag = tag,
+        len = tag.len(),
+    );
+    let wasm = wat::parse_str(&wat).expect("Failed to parse WAT");
+    let mut sandbox = Sandbox::builder(&wasm, [0u8; 32])
+        .capabilities(CapabilitySet::with_types(&[CapabilityType::ActuatorLog]))
+        .async_support(true)
+        .build()
+        .expect("Failed to create sandbox");
+    sandbox.set_log_hook(Arc::new(move |_, message| {
+        log.lock().unwrap().push(message.to_string());
+    }));
+    sandbox.initialize().expect("Failed to initialize");
+    sandbox
+}
+
+/// Tests that two async sandboxes calling host_yield share one executor
+/// thread instead of the first running to completion
+#[tokio::test(flavor = "current_thread")]
+async fn test_host_yield_interleaves_async_sandboxes() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut first = yielding_sandbox("a", Arc::clone(&log));
+    let mut second = yielding_sandbox("b", Arc::clone(&log));
+
+    let (a, b) = tokio::join!(
+        first.invoke_async("run", &[]),
+        second.invoke_async("run", &[])
+    );
+    assert!(a.unwrap().success);
+    assert!(b.unwrap().success);
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), 10);
+    let first_b = log.iter().position(|m| m == "b").unwrap();
+    let last_a = log.iter().rposition(|m| m == "a").unwrap();
+    assert!(first_b < last_a, "sandboxes did not interleave: {:?}", log);
+}
+
+/// Tests that sync and async sandboxes refuse each other's invoke
+#[test]
+fn test_invoke_matches_async_support() {
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut sandbox = yielding_sandbox("a", log);
+    assert!(matches!(
+        sandbox.invoke("run", &[]),
+        Err(SandboxError::RuntimeError(_))
+    ));
+    assert_eq!(sandbox.get_state(), SandboxState::Ready);
+
+    let wasm = wat::parse_str(r#"(module (func (export "run")))"#).unwrap();
+    let mut sync = Sandbox::builder(&wasm, [0u8; 32]).build().unwrap();
+    sync.initialize().unwrap();
+    let result = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(sync.invoke_async("run", &[]));
+    assert!(matches!(result, Err(SandboxError::RuntimeError(_))));
+}